@@ -0,0 +1,137 @@
+/// The window the user is working in
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ActiveWindow {
+    /// Application or process name, e.g. "Code" or "firefox"
+    pub app: String,
+    pub title: String,
+    /// Covers its whole screen, like a game or a video
+    pub fullscreen: bool,
+}
+
+/// Name of the process with id `pid`, empty if it's gone.
+#[cfg(any(target_os = "linux", target_os = "windows"))]
+fn process_name(pid: u32) -> String {
+    use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System};
+
+    let pid = Pid::from_u32(pid);
+    let mut system = System::new();
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::Some(&[pid]),
+        false,
+        ProcessRefreshKind::nothing(),
+    );
+    system
+        .process(pid)
+        .map(|process| process.name().to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// Run `program` and return its trimmed output, or `None` if it isn't installed or fails.
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn run(program: &str, args: &[&str]) -> Option<String> {
+    let output = std::process::Command::new(program)
+        .args(args)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    let text = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!text.is_empty()).then_some(text)
+}
+
+/// The focused window, or `None` if it can't be told (e.g. on Wayland, which doesn't say).
+#[cfg(target_os = "linux")]
+pub fn current() -> Option<ActiveWindow> {
+    // X11 only, through the usual command-line tools
+    let id = run("xdotool", &["getactivewindow"])?;
+    let title = run("xdotool", &["getwindowname", &id]).unwrap_or_default();
+    let app = run("xdotool", &["getwindowpid", &id])
+        .and_then(|pid| pid.parse().ok())
+        .map(process_name)
+        .unwrap_or_default();
+    let fullscreen = run("xprop", &["-id", &id, "_NET_WM_STATE"])
+        .is_some_and(|state| state.contains("_NET_WM_STATE_FULLSCREEN"));
+    Some(ActiveWindow {
+        app,
+        title,
+        fullscreen,
+    })
+}
+
+/// The focused window, or `None` if it can't be told. The window title and fullscreen state
+/// need the accessibility permission; without it only the app name is known.
+#[cfg(target_os = "macos")]
+pub fn current() -> Option<ActiveWindow> {
+    const SCRIPT: &str = r#"tell application "System Events"
+    set frontApp to first application process whose frontmost is true
+    set appName to name of frontApp
+    set windowTitle to ""
+    set isFullscreen to false
+    try
+        set windowTitle to name of front window of frontApp
+        set isFullscreen to value of attribute "AXFullScreen" of front window of frontApp
+    end try
+end tell
+return appName & tab & windowTitle & tab & isFullscreen"#;
+
+    let output = run("osascript", &["-e", SCRIPT])?;
+    let mut parts = output.split('\t');
+    Some(ActiveWindow {
+        app: parts.next().unwrap_or_default().to_string(),
+        title: parts.next().unwrap_or_default().to_string(),
+        fullscreen: parts.next() == Some("true"),
+    })
+}
+
+/// The focused window, or `None` if nothing has focus.
+#[cfg(target_os = "windows")]
+pub fn current() -> Option<ActiveWindow> {
+    use windows_sys::Win32::Foundation::RECT;
+    use windows_sys::Win32::Graphics::Gdi::{
+        GetMonitorInfoW, MonitorFromWindow, MONITORINFO, MONITOR_DEFAULTTONEAREST,
+    };
+    use windows_sys::Win32::UI::WindowsAndMessaging::{
+        GetDesktopWindow, GetForegroundWindow, GetShellWindow, GetWindowRect, GetWindowTextW,
+        GetWindowThreadProcessId,
+    };
+
+    // SAFETY: plain Win32 queries on the foreground window with buffers sized for them
+    unsafe {
+        let hwnd = GetForegroundWindow();
+        if hwnd.is_null() {
+            return None;
+        }
+        let mut buffer = [0u16; 512];
+        let len = GetWindowTextW(hwnd, buffer.as_mut_ptr(), buffer.len() as i32);
+        let title = String::from_utf16_lossy(&buffer[..len.max(0) as usize]);
+
+        let mut pid = 0u32;
+        GetWindowThreadProcessId(hwnd, &mut pid);
+
+        let mut rect: RECT = std::mem::zeroed();
+        let mut monitor: MONITORINFO = std::mem::zeroed();
+        monitor.cbSize = std::mem::size_of::<MONITORINFO>() as u32;
+        // The desktop covers the screen too, but isn't what fullscreen rules are about
+        let desktop = hwnd == GetShellWindow() || hwnd == GetDesktopWindow();
+        let fullscreen = !desktop
+            && GetWindowRect(hwnd, &mut rect) != 0
+            && GetMonitorInfoW(
+                MonitorFromWindow(hwnd, MONITOR_DEFAULTTONEAREST),
+                &mut monitor,
+            ) != 0
+            && rect.left <= monitor.rcMonitor.left
+            && rect.top <= monitor.rcMonitor.top
+            && rect.right >= monitor.rcMonitor.right
+            && rect.bottom >= monitor.rcMonitor.bottom;
+
+        Some(ActiveWindow {
+            app: process_name(pid),
+            title,
+            fullscreen,
+        })
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+pub fn current() -> Option<ActiveWindow> {
+    None
+}
@@ -0,0 +1,242 @@
+use anyhow::{anyhow, Result};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// The character bundled with the app under `assets/agents/`
+pub const BUILTIN_AGENT: &str = "Clippy";
+/// Sound formats an imported character may carry, tried in this order
+const SOUND_EXTENSIONS: [&str; 3] = ["mp3", "ogg", "wav"];
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+/// Directory imported characters live in, one folder per character holding `agent.png`,
+/// `map.json` and `sounds/<id>.<ext>`, the same layout as the bundled Clippy.
+pub fn agents_dir() -> Result<PathBuf> {
+    let data_dir = dirs::data_dir().ok_or_else(|| anyhow!("Could not find data directory"))?;
+    let dir = data_dir.join("rusty-clippy").join("agents");
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+/// An imported character ready to hand to the page: everything inlined as data URLs, since
+/// the webview can't read the data dir directly.
+#[derive(Debug, Clone, Serialize)]
+pub struct AgentData {
+    pub name: String,
+    pub sprite: String,
+    pub map: String,
+    /// Frame sound id -> data URL
+    pub sounds: HashMap<String, String>,
+}
+
+/// The object inside a clippy.js `agent.js` or `sounds-*.js` file, which wraps plain JSON
+/// in a `clippy.ready('Name', {...})` call.
+fn unwrap_js(content: &str) -> Option<&str> {
+    let start = content.find('{')?;
+    let end = content.rfind('}')?;
+    (start < end).then(|| &content[start..=end])
+}
+
+/// The name passed to `clippy.ready('Name', ...)`, if the file has one.
+fn js_name(content: &str) -> Option<String> {
+    let call = content[content.find("ready(")? + "ready(".len()..].trim_start();
+    let quote = call.chars().next().filter(|c| *c == '\'' || *c == '"')?;
+    let rest = &call[1..];
+    Some(rest[..rest.find(quote)?].to_string())
+}
+
+/// A folder name that is safe to create and can't climb out of the agents dir.
+fn sanitize_name(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_alphanumeric() || matches!(c, ' ' | '-' | '_'))
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+/// The first of `names` that exists in `dir`.
+fn find_file(dir: &Path, names: &[&str]) -> Option<PathBuf> {
+    names.iter().map(|n| dir.join(n)).find(|p| p.is_file())
+}
+
+/// The animation map from `map.json`, or from the object in a clippy.js `agent.js`.
+fn read_map(source: &Path) -> Result<(serde_json::Value, Option<String>)> {
+    let path = find_file(source, &["map.json", "agent.js"])
+        .ok_or_else(|| anyhow!("No agent.js or map.json in {}", source.display()))?;
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+    let json =
+        unwrap_js(&content).ok_or_else(|| anyhow!("{} has no animation data", path.display()))?;
+    let map: serde_json::Value = serde_json::from_str(json)
+        .map_err(|e| anyhow!("{} isn't a valid animation map: {}", path.display(), e))?;
+    let framesize_ok = map
+        .get("framesize")
+        .and_then(|f| f.as_array())
+        .is_some_and(|f| f.len() == 2 && f.iter().all(|n| n.as_u64().is_some()));
+    if !framesize_ok || !map.get("animations").is_some_and(|a| a.is_object()) {
+        return Err(anyhow!(
+            "{} is missing framesize or animations",
+            path.display()
+        ));
+    }
+    Ok((map, js_name(&content)))
+}
+
+/// Frame sounds from a clippy.js `sounds-mp3.js`/`sounds-ogg.js` (id -> data URL) or a
+/// `sounds/` folder of `<id>.<ext>` files, as id -> (extension, bytes).
+fn read_sounds(source: &Path) -> Result<HashMap<String, (String, Vec<u8>)>> {
+    let mut sounds = HashMap::new();
+    if let Some(path) = find_file(source, &["sounds-mp3.js", "sounds-ogg.js"]) {
+        let content = std::fs::read_to_string(&path)
+            .map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+        let urls: HashMap<String, String> = unwrap_js(&content)
+            .and_then(|json| serde_json::from_str(json).ok())
+            .ok_or_else(|| anyhow!("{} has no sound data", path.display()))?;
+        let ext = if path.ends_with("sounds-ogg.js") {
+            "ogg"
+        } else {
+            "mp3"
+        };
+        for (id, url) in urls {
+            let data = url
+                .split_once(";base64,")
+                .map(|(_, data)| data)
+                .ok_or_else(|| anyhow!("Sound {} in {} isn't base64", id, path.display()))?;
+            let bytes = BASE64
+                .decode(data.trim())
+                .map_err(|e| anyhow!("Sound {} in {} is corrupt: {}", id, path.display(), e))?;
+            sounds.insert(id, (ext.to_string(), bytes));
+        }
+    } else if let Ok(entries) = std::fs::read_dir(source.join("sounds")) {
+        for path in entries.flatten().map(|entry| entry.path()) {
+            let (Some(id), Some(ext)) = (
+                path.file_stem().and_then(|s| s.to_str()),
+                path.extension().and_then(|e| e.to_str()),
+            ) else {
+                continue;
+            };
+            let ext = ext.to_lowercase();
+            if SOUND_EXTENSIONS.contains(&ext.as_str()) {
+                sounds.insert(id.to_string(), (ext, std::fs::read(&path)?));
+            }
+        }
+    }
+    Ok(sounds)
+}
+
+/// Import a character from a folder of community-extracted MS Agent art: a clippy.js pack
+/// (`agent.js`, `map.png` and `sounds-mp3.js`) or one already in this app's layout. The
+/// character is converted into the agents dir and its name returned.
+pub fn import(source: &Path) -> Result<String> {
+    if !source.is_dir() {
+        return Err(anyhow!("{} isn't a folder", source.display()));
+    }
+    let (map, js_name) = read_map(source)?;
+    let sprite_path = find_file(source, &["map.png", "agent.png"])
+        .ok_or_else(|| anyhow!("No map.png or agent.png in {}", source.display()))?;
+    let sprite = std::fs::read(&sprite_path)
+        .map_err(|e| anyhow!("Failed to read {}: {}", sprite_path.display(), e))?;
+    if !sprite.starts_with(PNG_SIGNATURE) {
+        return Err(anyhow!("{} isn't a PNG image", sprite_path.display()));
+    }
+    let sounds = read_sounds(source)?;
+
+    let folder_name = source.file_name().map(|n| n.to_string_lossy().to_string());
+    let name = sanitize_name(&js_name.or(folder_name).unwrap_or_default());
+    if name.is_empty() {
+        return Err(anyhow!("Couldn't tell the character's name"));
+    }
+    if name.eq_ignore_ascii_case(BUILTIN_AGENT) {
+        return Err(anyhow!(
+            "{} is built in and can't be replaced",
+            BUILTIN_AGENT
+        ));
+    }
+
+    // Build the folder aside and swap it in, so a failed import never leaves half a character
+    let dir = agents_dir()?;
+    let partial = dir.join(format!("{}.partial", name));
+    if partial.exists() {
+        std::fs::remove_dir_all(&partial)?;
+    }
+    std::fs::create_dir_all(partial.join("sounds"))?;
+    std::fs::write(partial.join("agent.png"), &sprite)?;
+    std::fs::write(partial.join("map.json"), serde_json::to_vec(&map)?)?;
+    for (id, (ext, bytes)) in &sounds {
+        let id = sanitize_name(id);
+        if !id.is_empty() {
+            std::fs::write(
+                partial.join("sounds").join(format!("{}.{}", id, ext)),
+                bytes,
+            )?;
+        }
+    }
+    let target = dir.join(&name);
+    if target.exists() {
+        std::fs::remove_dir_all(&target)?;
+    }
+    std::fs::rename(&partial, &target)?;
+    tracing::info!("Imported character {} with {} sounds", name, sounds.len());
+    Ok(name)
+}
+
+/// Names of every character that can be shown, the built-in one first.
+pub fn list() -> Vec<String> {
+    let mut names: Vec<String> = agents_dir()
+        .and_then(|dir| Ok(std::fs::read_dir(dir)?))
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| path.join("map.json").is_file() && path.join("agent.png").is_file())
+                .filter_map(|path| Some(path.file_name()?.to_string_lossy().to_string()))
+                // Left over from an import that failed midway
+                .filter(|name| !name.ends_with(".partial"))
+                .collect()
+        })
+        .unwrap_or_default();
+    names.sort();
+    names.insert(0, BUILTIN_AGENT.to_string());
+    names
+}
+
+fn data_url(mime: &str, bytes: &[u8]) -> String {
+    format!("data:{};base64,{}", mime, BASE64.encode(bytes))
+}
+
+/// An imported character's art, map and sounds. `None` for the built-in character, which
+/// the page loads from its own assets.
+pub fn load(name: &str) -> Result<Option<AgentData>> {
+    if name.eq_ignore_ascii_case(BUILTIN_AGENT) {
+        return Ok(None);
+    }
+    let dir = agents_dir()?.join(sanitize_name(name));
+    if !dir.join("map.json").is_file() {
+        return Err(anyhow!("Character '{}' isn't installed", name));
+    }
+    let sprite = std::fs::read(dir.join("agent.png"))?;
+    let map = std::fs::read(dir.join("map.json"))?;
+    let mut sounds = HashMap::new();
+    if let Ok(entries) = std::fs::read_dir(dir.join("sounds")) {
+        for path in entries.flatten().map(|entry| entry.path()) {
+            let Some(id) = path.file_stem().and_then(|s| s.to_str()) else {
+                continue;
+            };
+            let mime = match path.extension().and_then(|e| e.to_str()) {
+                Some("mp3") => "audio/mpeg",
+                Some("ogg") => "audio/ogg",
+                Some("wav") => "audio/wav",
+                _ => continue,
+            };
+            sounds.insert(id.to_string(), data_url(mime, &std::fs::read(&path)?));
+        }
+    }
+    Ok(Some(AgentData {
+        name: name.to_string(),
+        sprite: data_url("image/png", &sprite),
+        map: data_url("application/json", &map),
+        sounds,
+    }))
+}
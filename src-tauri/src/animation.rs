@@ -0,0 +1,173 @@
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+/// What Clippy is doing, as far as its animation goes
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum AppState {
+    Idle,
+    /// Do not disturb is on
+    Sleeping,
+    /// The user is typing to Clippy
+    Listening,
+    Thinking,
+    Speaking,
+    Error,
+    Downloading,
+}
+
+impl AppState {
+    /// The agent animation acted out while in this state
+    fn animation(self) -> &'static str {
+        match self {
+            AppState::Idle => "RestPose",
+            AppState::Sleeping => "IdleSnooze",
+            AppState::Listening => "Hearing_1",
+            AppState::Thinking => "Processing",
+            AppState::Speaking => "Explain",
+            AppState::Error => "Alert",
+            AppState::Downloading => "CheckingSomething",
+        }
+    }
+
+    /// When several states are active, the highest priority one is shown.
+    fn priority(self) -> u8 {
+        match self {
+            AppState::Idle => 0,
+            AppState::Sleeping => 1,
+            AppState::Downloading => 2,
+            AppState::Listening => 3,
+            AppState::Thinking | AppState::Speaking => 4,
+            AppState::Error => 5,
+        }
+    }
+
+    /// How long the state lasts on its own; `None` lasts until it's left.
+    fn duration(self) -> Option<Duration> {
+        match self {
+            AppState::Error => Some(Duration::from_secs(3)),
+            _ => None,
+        }
+    }
+
+    fn event(self) -> PlayAnimationEvent {
+        PlayAnimationEvent {
+            state: self,
+            animation: self.animation(),
+            priority: self.priority(),
+            duration_ms: self.duration().map(|d| d.as_millis() as u64),
+        }
+    }
+}
+
+/// Tells every page which animation to play. The page loops it until the next event,
+/// and an `idle` state means its own idle fidgeting.
+#[derive(Debug, Clone, Serialize)]
+pub struct PlayAnimationEvent {
+    pub state: AppState,
+    pub animation: &'static str,
+    pub priority: u8,
+    /// Set for states that end on their own
+    pub duration_ms: Option<u64>,
+}
+
+/// The states currently active, each with the order it was entered in
+#[derive(Default)]
+struct Active {
+    states: Vec<(AppState, u64)>,
+    entered: u64,
+}
+
+impl Active {
+    /// The state to show: highest priority, most recent on a tie.
+    fn shown(&self) -> AppState {
+        self.states
+            .iter()
+            .max_by_key(|(state, order)| (state.priority(), *order))
+            .map(|(state, _)| *state)
+            .unwrap_or(AppState::Idle)
+    }
+}
+
+/// Decides from what the app is doing which animation every page should play, so that logic
+/// lives in one place.
+#[derive(Default)]
+pub struct AnimationController {
+    active: Mutex<Active>,
+}
+
+impl AnimationController {
+    /// Start `state`. A timed state leaves again by itself.
+    pub fn enter(&self, app: &AppHandle, state: AppState) {
+        if state == AppState::Idle {
+            return;
+        }
+        let order = self.update(app, |active| {
+            active.entered += 1;
+            let order = active.entered;
+            active.states.retain(|(s, _)| *s != state);
+            active.states.push((state, order));
+            order
+        });
+        if let Some(duration) = state.duration() {
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                tokio::time::sleep(duration).await;
+                if let Some(controller) = app.try_state::<AnimationController>() {
+                    // Entered again since, which restarted the clock
+                    controller.update(&app, |active| {
+                        active.states.retain(|entry| *entry != (state, order))
+                    });
+                }
+            });
+        }
+    }
+
+    /// End `state`, if it's active.
+    pub fn leave(&self, app: &AppHandle, state: AppState) {
+        self.update(app, |active| active.states.retain(|(s, _)| *s != state));
+    }
+
+    /// What every page should be playing now.
+    pub fn current(&self) -> PlayAnimationEvent {
+        let active = match self.active.lock() {
+            Ok(active) => active,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        active.shown().event()
+    }
+
+    /// Apply `change` and tell the pages if the shown state changed as a result.
+    fn update<T>(&self, app: &AppHandle, change: impl FnOnce(&mut Active) -> T) -> T {
+        let (result, before, after) = {
+            let mut active = match self.active.lock() {
+                Ok(active) => active,
+                Err(poisoned) => poisoned.into_inner(),
+            };
+            let before = active.shown();
+            let result = change(&mut active);
+            (result, before, active.shown())
+        };
+        if before != after {
+            tracing::debug!("Animation state {:?} -> {:?}", before, after);
+            let _ = app.emit("play-animation", after.event());
+        }
+        result
+    }
+}
+
+/// Start `state` on the app's controller.
+pub fn enter(app: &AppHandle, state: AppState) {
+    if let Some(controller) = app.try_state::<AnimationController>() {
+        controller.enter(app, state);
+    }
+}
+
+/// End `state` on the app's controller.
+pub fn leave(app: &AppHandle, state: AppState) {
+    if let Some(controller) = app.try_state::<AnimationController>() {
+        controller.leave(app, state);
+    }
+}
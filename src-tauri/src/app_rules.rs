@@ -0,0 +1,109 @@
+use crate::active_window::ActiveWindow;
+use crate::config::{AppRule, Config};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// How often to look at which window is focused
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// The rule matching the focused window, if any
+static ACTIVE: Mutex<Option<AppRule>> = Mutex::new(None);
+
+fn contains(haystack: &str, needle: &Option<String>) -> bool {
+    needle
+        .as_ref()
+        .is_none_or(|needle| haystack.to_lowercase().contains(&needle.to_lowercase()))
+}
+
+impl AppRule {
+    /// Whether every condition the rule sets holds for `window`.
+    fn matches(&self, window: &ActiveWindow) -> bool {
+        contains(&window.app, &self.app)
+            && contains(&window.title, &self.title)
+            && (!self.fullscreen || window.fullscreen)
+    }
+
+    /// The rule's conditions for the log, e.g. `app "code", fullscreen`.
+    fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(app) = &self.app {
+            parts.push(format!("app \"{}\"", app));
+        }
+        if let Some(title) = &self.title {
+            parts.push(format!("title \"{}\"", title));
+        }
+        if self.fullscreen {
+            parts.push("fullscreen".to_string());
+        }
+        parts.join(", ")
+    }
+}
+
+/// The first of `rules` that matches `window`.
+fn find(rules: &[AppRule], window: &ActiveWindow) -> Option<AppRule> {
+    rules.iter().find(|rule| rule.matches(window)).cloned()
+}
+
+fn active() -> Option<AppRule> {
+    ACTIVE.lock().ok().and_then(|rule| rule.clone())
+}
+
+/// Overlay the rule for the focused app onto `config`, for a reply or a tip. The file on
+/// disk is left alone, so the settings window never sees these values.
+pub fn apply(config: &mut Config) {
+    let Some(rule) = active() else {
+        return;
+    };
+    if let Some(personality) = &rule.personality {
+        config.personality = personality.clone();
+    }
+    if let Some(model) = &rule.model {
+        config.use_model(model);
+    }
+    if let Some(proactive) = rule.proactive {
+        config.proactive.enabled = proactive;
+        config.proactive.peek &= proactive;
+    }
+    if rule.silent {
+        config.proactive.enabled = false;
+        config.proactive.peek = false;
+        config.sound_effects = false;
+        config.tts_enabled = false;
+    }
+}
+
+/// Whether the focused app wants Clippy to make no sound at all.
+pub fn silenced() -> bool {
+    active().is_some_and(|rule| rule.silent)
+}
+
+/// Keep track of which rule matches the focused window. Nothing is polled while there are
+/// no rules.
+pub fn spawn() {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(POLL_INTERVAL).await;
+            let rules = Config::load().map(|c| c.app_rules).unwrap_or_default();
+            let rule = if rules.is_empty() {
+                None
+            } else {
+                tokio::task::spawn_blocking(crate::active_window::current)
+                    .await
+                    .ok()
+                    .flatten()
+                    .and_then(|window| find(&rules, &window))
+            };
+
+            let Ok(mut active) = ACTIVE.lock() else {
+                continue;
+            };
+            if *active != rule {
+                match &rule {
+                    Some(rule) => tracing::info!("App rule now active: {}", rule.describe()),
+                    None => tracing::info!("No app rule active"),
+                }
+                *active = rule;
+            }
+        }
+    });
+}
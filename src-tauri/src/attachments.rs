@@ -0,0 +1,76 @@
+use anyhow::{anyhow, Result};
+use std::path::Path;
+
+/// Files bigger than this aren't read at all
+const MAX_FILE_BYTES: u64 = 5 * 1024 * 1024;
+/// Characters kept from one file; the middle of a longer one is left out
+const MAX_FILE_CHARS: usize = 12_000;
+/// Characters kept across all of a message's files
+const MAX_TOTAL_CHARS: usize = 24_000;
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.display().to_string())
+}
+
+/// The text of `path`, refusing anything that doesn't look like a text file.
+fn read_text(path: &Path) -> Result<String> {
+    let name = file_name(path);
+    let size = std::fs::metadata(path)
+        .map_err(|e| anyhow!("Failed to read {}: {}", name, e))?
+        .len();
+    if size > MAX_FILE_BYTES {
+        return Err(anyhow!(
+            "{} is too big to attach ({} MB; the limit is {} MB)",
+            name,
+            size / (1024 * 1024),
+            MAX_FILE_BYTES / (1024 * 1024)
+        ));
+    }
+    let bytes = std::fs::read(path).map_err(|e| anyhow!("Failed to read {}: {}", name, e))?;
+    if bytes.contains(&0) {
+        return Err(anyhow!("{} isn't a text file", name));
+    }
+    String::from_utf8(bytes).map_err(|_| anyhow!("{} isn't a UTF-8 text file", name))
+}
+
+/// `text` cut down to `max` characters, keeping its start and end.
+fn shorten(text: &str, max: usize) -> String {
+    let total = text.chars().count();
+    if total <= max {
+        return text.to_string();
+    }
+    let head: String = text.chars().take(max * 2 / 3).collect();
+    let tail: String = text.chars().skip(total - max / 3).collect();
+    format!(
+        "{}\n[... {} characters left out ...]\n{}",
+        head,
+        total - head.chars().count() - tail.chars().count(),
+        tail
+    )
+}
+
+/// `message` with each file in `paths` added after it in a delimited block, so the model
+/// can tell the user's words from the files' contents. Long files are shortened.
+pub fn inline(message: &str, paths: &[String]) -> Result<String> {
+    if paths.is_empty() {
+        return Ok(message.to_string());
+    }
+    let per_file = MAX_FILE_CHARS.min(MAX_TOTAL_CHARS / paths.len());
+    let mut turn = message.trim().to_string();
+    for path in paths {
+        let path = Path::new(path);
+        let name = file_name(path);
+        let text = shorten(read_text(path)?.trim(), per_file);
+        tracing::info!("Attaching {} ({} characters)", name, text.chars().count());
+        if !turn.is_empty() {
+            turn.push_str("\n\n");
+        }
+        turn.push_str(&format!(
+            "--- Attached file: {} ---\n{}\n--- End of {} ---",
+            name, text, name
+        ));
+    }
+    Ok(turn)
+}
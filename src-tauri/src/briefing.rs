@@ -0,0 +1,140 @@
+use crate::config::Config;
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Local, NaiveDate, NaiveTime, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::Duration;
+use tauri::AppHandle;
+
+/// How often the scheduler checks whether the briefing is due
+const TICK: Duration = Duration::from_secs(60);
+
+const BRIEFING_REQUEST: &str = "Give the user their morning briefing: greet them, then sum up \
+the day ahead from the notes below in a few short, friendly sentences. Mention every event and \
+reminder with its time and mention the weather if it's given. Don't invent appointments, \
+weather or news that aren't in the notes.";
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// When the briefing last went out, so a restart doesn't repeat it
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct BriefingState {
+    /// Local date, as `YYYY-MM-DD`
+    last_date: Option<String>,
+}
+
+fn state_path() -> Result<PathBuf> {
+    let data_dir = dirs::data_dir().ok_or_else(|| anyhow!("Could not find data directory"))?;
+    let dir = data_dir.join("rusty-clippy");
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir.join("briefing.json"))
+}
+
+fn last_date() -> Option<NaiveDate> {
+    let content = std::fs::read_to_string(state_path().ok()?).ok()?;
+    let last = serde_json::from_str::<BriefingState>(&content)
+        .ok()?
+        .last_date?;
+    NaiveDate::parse_from_str(&last, DATE_FORMAT).ok()
+}
+
+fn mark_delivered(date: NaiveDate) -> Result<()> {
+    let content = serde_json::to_string(&BriefingState {
+        last_date: Some(date.format(DATE_FORMAT).to_string()),
+    })?;
+    crate::config::write_atomic(&state_path()?, content.as_bytes())
+}
+
+/// Start the briefing scheduler. The briefing goes out once a day, at the configured time
+/// or on the first launch after it.
+pub fn spawn(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            if let Ok(config) = Config::load() {
+                if is_due(&config) {
+                    // Mark it first, so a failing provider isn't retried every minute
+                    if let Err(e) = mark_delivered(Local::now().date_naive()) {
+                        tracing::warn!("Failed to save briefing state: {}", e);
+                    }
+                    if let Err(e) = deliver(&app, &config).await {
+                        tracing::warn!("Morning briefing failed: {}", e);
+                    }
+                }
+            }
+            tokio::time::sleep(TICK).await;
+        }
+    });
+}
+
+fn is_due(config: &Config) -> bool {
+    if !config.briefing.enabled {
+        return false;
+    }
+    let Ok(time) = NaiveTime::parse_from_str(&config.briefing.time, "%H:%M") else {
+        return false;
+    };
+    let now = Local::now();
+    now.time() >= time && last_date() != Some(now.date_naive())
+}
+
+/// Compose today's briefing with the model and present it.
+pub async fn deliver(app: &AppHandle, config: &Config) -> Result<(), String> {
+    let mut notes = gather_notes();
+    match crate::tools::weather::report(config, None).await {
+        Ok(weather) => notes.push_str(&format!("\nWeather: {}", weather)),
+        Err(e) => tracing::warn!("No weather for the briefing: {}", e),
+    }
+    if !config.calendar.sources.is_empty() {
+        match crate::calendar::upcoming(&config.calendar, end_of_day()).await {
+            Ok(events) if events.is_empty() => notes.push_str("\nNo calendar events today."),
+            Ok(events) => {
+                let lines: Vec<String> =
+                    events.iter().map(|e| format!("- {}", e.describe())).collect();
+                notes.push_str(&format!("\nCalendar today:\n{}", lines.join("\n")));
+            }
+            Err(e) => tracing::warn!("No calendar for the briefing: {}", e),
+        }
+    }
+    let request = format!("{}\n\n{}", BRIEFING_REQUEST, notes);
+    let text = crate::proactive::generate(app, config, &request).await?;
+    tracing::info!("Morning briefing delivered");
+    crate::proactive::deliver(app, text, config.briefing.speak).await;
+    Ok(())
+}
+
+/// Local midnight tonight
+fn end_of_day() -> DateTime<Local> {
+    let now = Local::now();
+    now.date_naive()
+        .succ_opt()
+        .and_then(|d| Local.from_local_datetime(&d.into()).earliest())
+        .unwrap_or(now)
+}
+
+/// What Clippy knows about the day: the date and today's reminders. The weather and calendar
+/// are added by `deliver`, as they need the network.
+fn gather_notes() -> String {
+    let now = Local::now();
+    let mut notes = vec![format!("Today is {}.", now.format("%A, %-d %B %Y"))];
+
+    let end_of_day = end_of_day().timestamp();
+    let reminders = crate::reminders::pending().unwrap_or_else(|e| {
+        tracing::warn!("Failed to read reminders for the briefing: {}", e);
+        Vec::new()
+    });
+    let today: Vec<String> = reminders
+        .iter()
+        .filter(|r| r.kind == crate::reminders::ReminderKind::Reminder && r.due_at < end_of_day)
+        .filter_map(|r| {
+            let due = Local.timestamp_opt(r.due_at, 0).single()?;
+            Some(format!("- {} at {}", r.text, due.format("%H:%M")))
+        })
+        .collect();
+    if today.is_empty() {
+        notes.push("No reminders today.".to_string());
+    } else {
+        notes.push(format!("Reminders today:\n{}", today.join("\n")));
+    }
+    notes.join("\n")
+}
@@ -0,0 +1,126 @@
+use crate::config::{Config, LlmProviderType, ModelChoice};
+use crate::notifications::Target;
+use serde::Serialize;
+use std::path::Path;
+use tauri::{AppHandle, Emitter};
+
+/// Shares of a monthly limit, in percent, the user is warned at
+const THRESHOLDS: [u8; 3] = [50, 80, 100];
+
+/// Spend with a provider crossed a share of its monthly limit
+#[derive(Debug, Clone, Serialize)]
+pub struct BudgetWarningEvent {
+    pub provider: String,
+    pub percent: u8,
+    pub spent_usd: f64,
+    pub limit_usd: f64,
+    pub text: String,
+}
+
+fn provider_name(config: &Config) -> String {
+    format!("{:?}", config.llm_provider)
+}
+
+/// The monthly limit on the configured provider, if it's paid and has one
+fn limit(config: &Config) -> Option<f64> {
+    if !crate::pricing::is_paid(&config.llm_provider) {
+        return None;
+    }
+    config
+        .budget
+        .monthly_usd
+        .get(&provider_name(config))
+        .copied()
+}
+
+/// The built-in model to answer with once the budget is spent, if one is set up
+fn local_fallback(config: &Config) -> Option<ModelChoice> {
+    if !config.budget.fallback_to_local {
+        return None;
+    }
+    config
+        .builtin_model_path
+        .as_ref()
+        .filter(|path| Path::new(path).is_file())
+        .map(|path| ModelChoice {
+            provider: LlmProviderType::BuiltIn,
+            model: path.clone(),
+        })
+}
+
+/// Whether the configured provider's limit for this month is used up
+pub fn spent(config: &Config) -> bool {
+    limit(config)
+        .is_some_and(|limit| crate::stats::cost_this_month(&provider_name(config)) >= limit)
+}
+
+/// Keep `config` within this month's budget. Once its provider's limit is spent, it's
+/// switched to the built-in model, or the request is refused with what Clippy says instead.
+/// The switch is made to this request's copy only; the saved config keeps the user's model.
+pub fn enforce(config: &mut Config) -> Result<(), String> {
+    let Some(limit) = limit(config) else {
+        return Ok(());
+    };
+    let provider = provider_name(config);
+    if crate::stats::cost_this_month(&provider) < limit {
+        return Ok(());
+    }
+    if let Some(local) = local_fallback(config) {
+        tracing::info!(
+            "{} budget spent, answering with the built-in model",
+            provider
+        );
+        config.use_model(&local);
+        return Ok(());
+    }
+    Err(format!(
+        "I've used up this month's ${:.2} budget for {}, so I'm taking a break from it until \
+         next month. You can raise the limit in Settings.",
+        limit, provider
+    ))
+}
+
+fn warning_text(config: &Config, provider: &str, percent: u8, spent: f64, limit: f64) -> String {
+    if percent < 100 {
+        return format!(
+            "Heads up: that's ${:.2} of this month's ${:.2} budget for {} spent ({}%).",
+            spent, limit, provider, percent
+        );
+    }
+    let next = if local_fallback(config).is_some() {
+        "I'll answer with the built-in model until next month.".to_string()
+    } else {
+        format!("I won't use {} again until next month.", provider)
+    };
+    format!(
+        "That's this month's ${:.2} budget for {} used up. {}",
+        limit, provider, next
+    )
+}
+
+/// Warn about every threshold that spend going from `before` to `after` this month crossed.
+pub fn warn_crossed(app: &AppHandle, config: &Config, before: f64, after: f64) {
+    let Some(limit) = limit(config) else {
+        return;
+    };
+    let provider = provider_name(config);
+    for percent in THRESHOLDS {
+        let at = limit * percent as f64 / 100.0;
+        if before >= at || after < at {
+            continue;
+        }
+        let text = warning_text(config, &provider, percent, after, limit);
+        tracing::info!("{}", text);
+        let _ = app.emit(
+            "budget-warning",
+            BudgetWarningEvent {
+                provider: provider.clone(),
+                percent,
+                spent_usd: after,
+                limit_usd: limit,
+                text: text.clone(),
+            },
+        );
+        crate::notifications::notify(app, "Spending limit", &text, Target::Settings);
+    }
+}
@@ -0,0 +1,611 @@
+use crate::config::{CalendarConfig, Config};
+use anyhow::{anyhow, Result};
+use chrono::{
+    DateTime, Datelike, Duration as ChronoDuration, Local, NaiveDate, NaiveDateTime, TimeZone, Utc,
+    Weekday,
+};
+use chrono_tz::Tz;
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::AppHandle;
+
+/// How often the scheduler checks for events about to start
+const TICK: Duration = Duration::from_secs(60);
+/// Feeds are refetched at most this often
+const CACHE_TTL: Duration = Duration::from_secs(15 * 60);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+/// Most occurrences walked through for one recurring event, about 27 years of a daily one
+const MAX_OCCURRENCES: usize = 10_000;
+
+/// Raw ICS text keyed by source
+static CACHE: Mutex<Option<HashMap<String, (Instant, String)>>> = Mutex::new(None);
+
+/// One occurrence of a calendar event
+#[derive(Debug, Clone)]
+pub struct CalendarEvent {
+    pub summary: String,
+    pub location: Option<String>,
+    pub start: DateTime<Local>,
+    pub end: DateTime<Local>,
+    pub all_day: bool,
+}
+
+impl CalendarEvent {
+    /// The event as one line, e.g. "Mon 16 Oct 09:00–09:30: Standup (Room 4)"
+    pub fn describe(&self) -> String {
+        let when = if self.all_day {
+            format!("{} (all day)", self.start.format("%a %-d %b"))
+        } else {
+            format!(
+                "{}–{}",
+                self.start.format("%a %-d %b %H:%M"),
+                self.end.format("%H:%M")
+            )
+        };
+        match &self.location {
+            Some(location) => format!("{}: {} ({})", when, self.summary, location),
+            None => format!("{}: {}", when, self.summary),
+        }
+    }
+}
+
+/// The time zone a `DTSTART`/`DTEND` is in
+#[derive(Debug, Clone, Copy)]
+enum Zone {
+    Utc,
+    Named(Tz),
+    /// No zone given, or one we don't know (such as a Windows zone name): taken as local time
+    Floating,
+}
+
+impl Zone {
+    fn resolve(self, time: NaiveDateTime) -> Option<DateTime<Local>> {
+        match self {
+            Zone::Utc => Some(Utc.from_utc_datetime(&time).with_timezone(&Local)),
+            Zone::Named(tz) => tz
+                .from_local_datetime(&time)
+                .earliest()
+                .map(|t| t.with_timezone(&Local)),
+            Zone::Floating => Local.from_local_datetime(&time).earliest(),
+        }
+    }
+}
+
+/// A parsed date or date-time property value
+#[derive(Debug, Clone, Copy)]
+struct Stamp {
+    time: NaiveDateTime,
+    zone: Zone,
+    date_only: bool,
+}
+
+impl Stamp {
+    fn resolve(&self) -> Option<DateTime<Local>> {
+        self.zone.resolve(self.time)
+    }
+}
+
+/// A content line, e.g. `DTSTART;TZID=Europe/Lisbon:20261016T090000`
+struct Property<'a> {
+    name: String,
+    params: Vec<(String, &'a str)>,
+    value: &'a str,
+}
+
+impl Property<'_> {
+    fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.trim_matches('"'))
+    }
+}
+
+fn parse_property(line: &str) -> Option<Property<'_>> {
+    // The value starts at the first colon outside a quoted parameter
+    let mut quoted = false;
+    let colon = line.char_indices().find_map(|(i, c)| match c {
+        '"' => {
+            quoted = !quoted;
+            None
+        }
+        ':' if !quoted => Some(i),
+        _ => None,
+    })?;
+    let mut head = line[..colon].split(';');
+    let name = head.next()?.to_ascii_uppercase();
+    let params = head
+        .filter_map(|p| p.split_once('='))
+        .map(|(n, v)| (n.to_ascii_uppercase(), v))
+        .collect();
+    Some(Property {
+        name,
+        params,
+        value: &line[colon + 1..],
+    })
+}
+
+fn parse_stamp(property: &Property) -> Option<Stamp> {
+    let value = property.value.trim();
+    if property.param("VALUE") == Some("DATE") || value.len() == 8 {
+        let date = NaiveDate::parse_from_str(value, "%Y%m%d").ok()?;
+        return Some(Stamp {
+            time: date.and_hms_opt(0, 0, 0)?,
+            zone: Zone::Floating,
+            date_only: true,
+        });
+    }
+    let (value, zone) = match value.strip_suffix('Z') {
+        Some(utc) => (utc, Zone::Utc),
+        None => (
+            value,
+            property
+                .param("TZID")
+                .and_then(|tz| tz.parse::<Tz>().ok())
+                .map_or(Zone::Floating, Zone::Named),
+        ),
+    };
+    Some(Stamp {
+        time: NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?,
+        zone,
+        date_only: false,
+    })
+}
+
+/// `PT1H30M`, `P1D` and the like
+fn parse_duration(value: &str) -> Option<ChronoDuration> {
+    let (negative, value) = match value.trim().strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, value.trim().trim_start_matches('+')),
+    };
+    let mut total = ChronoDuration::zero();
+    let mut number = String::new();
+    for c in value.strip_prefix('P')?.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'T' => {}
+            unit => {
+                let n: i64 = number.parse().ok()?;
+                number.clear();
+                total += match unit {
+                    'W' => ChronoDuration::weeks(n),
+                    'D' => ChronoDuration::days(n),
+                    'H' => ChronoDuration::hours(n),
+                    'M' => ChronoDuration::minutes(n),
+                    'S' => ChronoDuration::seconds(n),
+                    _ => return None,
+                };
+            }
+        }
+    }
+    Some(if negative { -total } else { total })
+}
+
+/// The parts of an `RRULE` we expand. Rules we can't follow (such as "the second Tuesday")
+/// fall back to the first occurrence only.
+#[derive(Debug, Default)]
+struct Recurrence {
+    frequency: Frequency,
+    interval: u32,
+    count: Option<usize>,
+    until: Option<DateTime<Local>>,
+    weekdays: Vec<Weekday>,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum Frequency {
+    #[default]
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+fn parse_rule(value: &str) -> Option<Recurrence> {
+    let mut rule = Recurrence {
+        interval: 1,
+        ..Default::default()
+    };
+    for part in value.split(';') {
+        let (key, value) = part.split_once('=')?;
+        match key.to_ascii_uppercase().as_str() {
+            "FREQ" => {
+                rule.frequency = match value {
+                    "DAILY" => Frequency::Daily,
+                    "WEEKLY" => Frequency::Weekly,
+                    "MONTHLY" => Frequency::Monthly,
+                    "YEARLY" => Frequency::Yearly,
+                    _ => return None,
+                }
+            }
+            "INTERVAL" => rule.interval = value.parse().ok().filter(|&n| n > 0)?,
+            "COUNT" => rule.count = Some(value.parse().ok()?),
+            "UNTIL" => {
+                let until = Property {
+                    name: "UNTIL".to_string(),
+                    params: Vec::new(),
+                    value,
+                };
+                let until = parse_stamp(&until)?;
+                rule.until = if until.date_only {
+                    // An all-day UNTIL includes that whole day
+                    Zone::Floating.resolve(until.time + ChronoDuration::days(1))
+                } else {
+                    until.resolve()
+                };
+            }
+            "BYDAY" => {
+                rule.weekdays = value
+                    .split(',')
+                    .map(|day| match day {
+                        "MO" => Some(Weekday::Mon),
+                        "TU" => Some(Weekday::Tue),
+                        "WE" => Some(Weekday::Wed),
+                        "TH" => Some(Weekday::Thu),
+                        "FR" => Some(Weekday::Fri),
+                        "SA" => Some(Weekday::Sat),
+                        "SU" => Some(Weekday::Sun),
+                        // Ordinals such as "2TU"
+                        _ => None,
+                    })
+                    .collect::<Option<_>>()?;
+            }
+            "WKST" => {}
+            // BYMONTHDAY, BYSETPOS and friends change which days match
+            _ => return None,
+        }
+    }
+    if !rule.weekdays.is_empty() && rule.frequency != Frequency::Weekly {
+        return None;
+    }
+    Some(rule)
+}
+
+/// Start times of a recurring event, in the event's own zone, in order
+fn occurrences(start: NaiveDateTime, rule: &Recurrence) -> impl Iterator<Item = NaiveDateTime> {
+    let interval = rule.interval;
+    let frequency = rule.frequency;
+    let mut weekdays = rule.weekdays.clone();
+    weekdays.sort_by_key(|d| d.num_days_from_monday());
+    let week_start =
+        start.date() - ChronoDuration::days(start.weekday().num_days_from_monday() as i64);
+
+    (0..)
+        .map_while(move |step: u32| {
+            let step = step.checked_mul(interval)?;
+            let dates: Vec<NaiveDate> = match frequency {
+                Frequency::Daily => vec![start.date() + ChronoDuration::days(step as i64)],
+                Frequency::Weekly if weekdays.is_empty() => {
+                    vec![start.date() + ChronoDuration::weeks(step as i64)]
+                }
+                Frequency::Weekly => {
+                    let week = week_start + ChronoDuration::weeks(step as i64);
+                    weekdays
+                        .iter()
+                        .map(|d| week + ChronoDuration::days(d.num_days_from_monday() as i64))
+                        .filter(|&d| d >= start.date())
+                        .collect()
+                }
+                // Months without the day (the 31st, 29 February) are skipped
+                Frequency::Monthly => {
+                    let months = start.month0() + step;
+                    let year = start.year().checked_add((months / 12) as i32)?;
+                    NaiveDate::from_ymd_opt(year, months % 12 + 1, start.day())
+                        .into_iter()
+                        .collect()
+                }
+                Frequency::Yearly => {
+                    let year = start.year().checked_add(step as i32)?;
+                    NaiveDate::from_ymd_opt(year, start.month(), start.day())
+                        .into_iter()
+                        .collect()
+                }
+            };
+            Some(dates.into_iter().map(move |d| d.and_time(start.time())))
+        })
+        .flatten()
+        .take(MAX_OCCURRENCES)
+}
+
+/// A `VEVENT` as written in the feed
+#[derive(Default)]
+struct RawEvent {
+    uid: Option<String>,
+    summary: Option<String>,
+    location: Option<String>,
+    start: Option<Stamp>,
+    end: Option<Stamp>,
+    duration: Option<ChronoDuration>,
+    rule: Option<String>,
+    exceptions: Vec<Stamp>,
+    recurrence_id: Option<Stamp>,
+    cancelled: bool,
+}
+
+/// Undo ICS text escaping
+fn unescape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => out.push(' '),
+            Some(other) => out.push(other),
+            None => {}
+        }
+    }
+    out.trim().to_string()
+}
+
+fn parse_events(ics: &str) -> Vec<RawEvent> {
+    // Long lines are folded onto continuation lines starting with a space or tab
+    let mut lines: Vec<String> = Vec::new();
+    for line in ics.lines() {
+        match line.strip_prefix([' ', '\t']) {
+            Some(rest) if !lines.is_empty() => lines.last_mut().unwrap().push_str(rest),
+            _ => lines.push(line.to_string()),
+        }
+    }
+
+    let mut events = Vec::new();
+    let mut current: Option<RawEvent> = None;
+    // Alarms nested inside an event have properties of their own
+    let mut nested = 0usize;
+    for line in &lines {
+        let Some(property) = parse_property(line) else {
+            continue;
+        };
+        match (property.name.as_str(), property.value.trim()) {
+            ("BEGIN", "VEVENT") => current = Some(RawEvent::default()),
+            ("END", "VEVENT") => events.extend(current.take()),
+            ("BEGIN", _) if current.is_some() => nested += 1,
+            ("END", _) if current.is_some() => nested = nested.saturating_sub(1),
+            _ => {}
+        }
+        let Some(event) = current.as_mut().filter(|_| nested == 0) else {
+            continue;
+        };
+        match property.name.as_str() {
+            "UID" => event.uid = Some(property.value.trim().to_string()),
+            "SUMMARY" => event.summary = Some(unescape(property.value)),
+            "LOCATION" => event.location = Some(unescape(property.value)).filter(|l| !l.is_empty()),
+            "DTSTART" => event.start = parse_stamp(&property),
+            "DTEND" => event.end = parse_stamp(&property),
+            "DURATION" => event.duration = parse_duration(property.value),
+            "RRULE" => event.rule = Some(property.value.trim().to_string()),
+            "EXDATE" => {
+                for value in property.value.split(',') {
+                    let exception = Property {
+                        name: property.name.clone(),
+                        params: property.params.clone(),
+                        value,
+                    };
+                    event.exceptions.extend(parse_stamp(&exception));
+                }
+            }
+            "RECURRENCE-ID" => event.recurrence_id = parse_stamp(&property),
+            "STATUS" => event.cancelled = property.value.trim() == "CANCELLED",
+            _ => {}
+        }
+    }
+    events
+}
+
+/// Occurrences of the feed's events that overlap `from..until`
+fn expand(ics: &str, from: DateTime<Local>, until: DateTime<Local>) -> Vec<CalendarEvent> {
+    let events = parse_events(ics);
+
+    // Moved or cancelled occurrences are separate events carrying a RECURRENCE-ID; the
+    // recurring event must skip the occurrence they replace
+    let replaced: HashSet<(String, DateTime<Local>)> = events
+        .iter()
+        .filter_map(|e| Some((e.uid.clone()?, e.recurrence_id?.resolve()?)))
+        .collect();
+
+    let mut found = Vec::new();
+    for event in &events {
+        let Some(start) = event.start else {
+            continue;
+        };
+        if event.cancelled {
+            continue;
+        }
+        let summary = event
+            .summary
+            .clone()
+            .filter(|s| !s.is_empty())
+            .unwrap_or_else(|| "(untitled event)".to_string());
+        let length = match (event.end, event.duration) {
+            // The end may be in another zone than the start
+            (Some(end), _) => match (start.resolve(), end.resolve()) {
+                (Some(from), Some(to)) => to - from,
+                _ => end.time - start.time,
+            },
+            (None, Some(duration)) => duration,
+            (None, None) if start.date_only => ChronoDuration::days(1),
+            (None, None) => ChronoDuration::zero(),
+        };
+
+        let rule = event.rule.as_deref().and_then(parse_rule);
+        let starts: Box<dyn Iterator<Item = NaiveDateTime>> = match (&rule, event.recurrence_id) {
+            (Some(rule), None) => Box::new(occurrences(start.time, rule)),
+            _ => Box::new(std::iter::once(start.time)),
+        };
+        let exceptions: Vec<DateTime<Local>> =
+            event.exceptions.iter().filter_map(Stamp::resolve).collect();
+
+        for (index, time) in starts.enumerate() {
+            if rule
+                .as_ref()
+                .and_then(|r| r.count)
+                .is_some_and(|count| index >= count)
+            {
+                break;
+            }
+            let Some(begins) = start.zone.resolve(time) else {
+                continue;
+            };
+            let ends = begins + length;
+            if begins >= until
+                || rule
+                    .as_ref()
+                    .and_then(|r| r.until)
+                    .is_some_and(|u| begins > u)
+            {
+                break;
+            }
+            if ends <= from || exceptions.contains(&begins) {
+                continue;
+            }
+            if event.recurrence_id.is_none()
+                && event
+                    .uid
+                    .as_ref()
+                    .is_some_and(|uid| replaced.contains(&(uid.clone(), begins)))
+            {
+                continue;
+            }
+            found.push(CalendarEvent {
+                summary: summary.clone(),
+                location: event.location.clone(),
+                start: begins,
+                end: ends,
+                all_day: start.date_only,
+            });
+        }
+    }
+    found
+}
+
+/// The feed's ICS text, fetched or read and cached for `CACHE_TTL`
+async fn load(source: &str) -> Result<String> {
+    let source = source.trim();
+    if let Some(cached) = CACHE.lock().ok().and_then(|cache| {
+        let (at, ics) = cache.as_ref()?.get(source)?;
+        (at.elapsed() < CACHE_TTL).then(|| ics.clone())
+    }) {
+        return Ok(cached);
+    }
+
+    let ics = if source.contains("://") {
+        // webcal:// is just a hint to open the URL in a calendar app
+        let url = match source.strip_prefix("webcal://") {
+            Some(rest) => format!("https://{}", rest),
+            None => source.to_string(),
+        };
+        // Private feed URLs carry a secret token, so keep them out of errors and logs
+        reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()?
+            .get(&url)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| e.without_url())?
+            .text()
+            .await
+            .map_err(|e| e.without_url())?
+    } else {
+        tokio::fs::read_to_string(source).await?
+    };
+    if !ics.contains("BEGIN:VCALENDAR") {
+        return Err(anyhow!("Not an ICS calendar"));
+    }
+
+    if let Ok(mut cache) = CACHE.lock() {
+        let cache = cache.get_or_insert_with(HashMap::new);
+        cache.retain(|_, (at, _)| at.elapsed() < CACHE_TTL);
+        cache.insert(source.to_string(), (Instant::now(), ics.clone()));
+    }
+    Ok(ics)
+}
+
+/// Events from every configured calendar that haven't ended and start before `until`,
+/// soonest first. A feed that fails is skipped, unless they all do.
+pub async fn upcoming(
+    config: &CalendarConfig,
+    until: DateTime<Local>,
+) -> Result<Vec<CalendarEvent>> {
+    if config.sources.is_empty() {
+        return Err(anyhow!("No calendars are set up; add one in Settings"));
+    }
+    let now = Local::now();
+    let mut events = Vec::new();
+    let mut failure = None;
+    let mut loaded = false;
+    for (index, source) in config.sources.iter().enumerate() {
+        match load(source).await {
+            Ok(ics) => {
+                loaded = true;
+                events.extend(expand(&ics, now, until));
+            }
+            Err(e) => {
+                tracing::warn!("Failed to load calendar {}: {}", index + 1, e);
+                failure = Some(e);
+            }
+        }
+    }
+    if let (false, Some(e)) = (loaded, failure) {
+        return Err(anyhow!("Couldn't load your calendar: {}", e));
+    }
+    events.sort_by_key(|e| e.start);
+    Ok(events)
+}
+
+/// Start the scheduler that warns about meetings shortly before they start
+pub fn spawn(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        // Events already warned about, by summary and start; forgotten once they've started
+        let mut warned: HashSet<(String, DateTime<Local>)> = HashSet::new();
+        loop {
+            if let Ok(config) = Config::load() {
+                if let Some(minutes) = config.calendar.alert_minutes {
+                    if !config.calendar.sources.is_empty() {
+                        check(&app, &config, minutes, &mut warned).await;
+                    }
+                }
+            }
+            tokio::time::sleep(TICK).await;
+        }
+    });
+}
+
+async fn check(
+    app: &AppHandle,
+    config: &Config,
+    minutes: u32,
+    warned: &mut HashSet<(String, DateTime<Local>)>,
+) {
+    let now = Local::now();
+    let horizon = now + ChronoDuration::minutes(minutes as i64);
+    let events = match upcoming(&config.calendar, horizon).await {
+        Ok(events) => events,
+        Err(e) => {
+            tracing::debug!("Calendar check skipped: {}", e);
+            return;
+        }
+    };
+    warned.retain(|(_, start)| *start > now);
+    for event in events {
+        if event.all_day || event.start <= now {
+            continue;
+        }
+        if !warned.insert((event.summary.clone(), event.start)) {
+            continue;
+        }
+        let left = (event.start - now).num_minutes().max(1);
+        let text = format!(
+            "It looks like you have {} in {} minute{}.",
+            event.summary,
+            left,
+            if left == 1 { "" } else { "s" }
+        );
+        tracing::info!("Calendar warning: {}", event.summary);
+        crate::proactive::deliver(app, text, true).await;
+    }
+}
@@ -0,0 +1,121 @@
+use serde::{Deserialize, Serialize};
+
+/// A fenced code block from an assistant reply, exactly as the model wrote it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CodeBlock {
+    /// The first word after the opening fence, e.g. "rust"; `None` for a bare fence
+    #[serde(default)]
+    pub language: Option<String>,
+    pub code: String,
+}
+
+/// What the chat needs to label a block's buttons, sent with `chat-done`
+#[derive(Debug, Clone, Serialize)]
+pub struct CodeBlockInfo {
+    pub index: usize,
+    pub language: Option<String>,
+    /// Suggested when saving, e.g. "snippet-1.rs"
+    pub file_name: String,
+}
+
+impl CodeBlock {
+    pub fn info(&self, index: usize) -> CodeBlockInfo {
+        CodeBlockInfo {
+            index,
+            language: self.language.clone(),
+            file_name: format!("snippet-{}.{}", index + 1, self.extension()),
+        }
+    }
+
+    /// File extension to suggest when saving, from the language tag
+    fn extension(&self) -> &str {
+        let language = self.language.as_deref().unwrap_or_default();
+        match language.to_ascii_lowercase().as_str() {
+            "rust" | "rs" => "rs",
+            "python" | "py" => "py",
+            "javascript" | "js" => "js",
+            "typescript" | "ts" => "ts",
+            "tsx" => "tsx",
+            "jsx" => "jsx",
+            "json" => "json",
+            "toml" => "toml",
+            "yaml" | "yml" => "yml",
+            "html" => "html",
+            "css" => "css",
+            "sql" => "sql",
+            "go" => "go",
+            "java" => "java",
+            "kotlin" | "kt" => "kt",
+            "swift" => "swift",
+            "c" => "c",
+            "cpp" | "c++" => "cpp",
+            "csharp" | "cs" | "c#" => "cs",
+            "ruby" | "rb" => "rb",
+            "php" => "php",
+            "bash" | "sh" | "shell" | "zsh" => "sh",
+            "powershell" | "ps1" => "ps1",
+            "markdown" | "md" => "md",
+            "xml" => "xml",
+            _ => "txt",
+        }
+    }
+}
+
+/// The fence opening a block on `line`: its character, length and indentation, and the
+/// info string after it. Up to three spaces of indentation are allowed, as in CommonMark.
+fn opening_fence(line: &str) -> Option<(char, usize, usize, &str)> {
+    let indent = line.len() - line.trim_start_matches(' ').len();
+    if indent > 3 {
+        return None;
+    }
+    let rest = &line[indent..];
+    let fence = rest.chars().next().filter(|c| *c == '`' || *c == '~')?;
+    let length = rest.len() - rest.trim_start_matches(fence).len();
+    if length < 3 {
+        return None;
+    }
+    let info = rest[length..].trim();
+    // A backtick fence can't have backticks in its info string
+    if fence == '`' && info.contains('`') {
+        return None;
+    }
+    Some((fence, length, indent, info))
+}
+
+fn closes(line: &str, fence: char, length: usize) -> bool {
+    let trimmed = line.trim();
+    let indent = line.len() - line.trim_start_matches(' ').len();
+    indent <= 3 && trimmed.len() >= length && trimmed.chars().all(|c| c == fence)
+}
+
+/// Every fenced code block in `text`, in order. A block the reply never closed runs to the
+/// end, so a reply cut short still offers what it had.
+pub fn extract(text: &str) -> Vec<CodeBlock> {
+    let mut blocks = Vec::new();
+    let mut lines = text.lines();
+    while let Some(line) = lines.next() {
+        let Some((fence, length, indent, info)) = opening_fence(line) else {
+            continue;
+        };
+        let language = info
+            .split_whitespace()
+            .next()
+            .map(|word| word.trim_start_matches('{').trim_start_matches('.'))
+            .filter(|word| !word.is_empty())
+            .map(str::to_string);
+        let mut code = Vec::new();
+        for line in lines.by_ref() {
+            if closes(line, fence, length) {
+                break;
+            }
+            // Lines of an indented block lose as much indentation as the fence had
+            let strip = line.len() - line.trim_start_matches(' ').len();
+            code.push(&line[strip.min(indent)..]);
+        }
+        blocks.push(CodeBlock {
+            language,
+            code: code.join("\n"),
+        });
+    }
+    blocks
+}
@@ -1,15 +1,32 @@
-use crate::config::{Config, LlmProviderType};
-use crate::llm::{openai::OpenAIProvider, local::LocalLLMProvider, LLMProvider, Message};
+use crate::animation::AppState;
+use crate::code_blocks::{CodeBlock, CodeBlockInfo};
+use crate::config::{Config, LlmProviderType, ReasoningDisplay, TtsEngineType};
+use crate::downloads::{DownloadJob, DownloadManager};
+use crate::llm::{openai::OpenAIProvider, local::LocalLLMProvider, mock::MockProvider, LLMProvider, Message};
+use crate::mood::MoodTrigger;
+use crate::notifications::Target;
 use crate::personality;
-use crate::tts::TtsState;
+use crate::redact::redact;
+use crate::request_log::RequestLogEntry;
+use crate::tray::TrayStatus;
+use crate::tts::{TtsEngine, TtsState};
 use serde::{Deserialize, Serialize};
-use tauri::{AppHandle, Emitter, Manager, State, WebviewUrl, WebviewWindowBuilder};
+use tauri::{
+    AppHandle, Emitter, Manager, State, WebviewUrl, WebviewWindow, WebviewWindowBuilder,
+};
 use tokio_stream::StreamExt;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChatMessage {
     pub role: String,
     pub content: String,
+    /// A reply cut off by a stream error, which `continue_response` can pick up
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub interrupted: bool,
+    /// Fenced code blocks of a finished assistant reply, kept so the chat's copy and save
+    /// buttons get them exactly as written (see `code_blocks`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub code_blocks: Vec<CodeBlock>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -20,10 +37,18 @@ pub struct StreamEvent {
 #[derive(Debug, Clone, Serialize)]
 pub struct ErrorEvent {
     pub error: String,
+    /// What arrived of the reply before a stream error; it's kept in the history
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub partial: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize)]
-pub struct DoneEvent {}
+/// Sent as `chat-done` when a reply is complete
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DoneEvent {
+    /// The reply's index in the conversation, for `copy_code_block` and `save_code_block`
+    pub message_id: Option<usize>,
+    pub code_blocks: Vec<CodeBlockInfo>,
+}
 
 #[derive(Debug, Clone, Serialize)]
 pub struct DownloadProgressEvent {
@@ -31,18 +56,110 @@ pub struct DownloadProgressEvent {
     pub status: String,
 }
 
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelReadyEvent {
+    pub model_path: String,
+    pub warmed_up: bool,
+    pub load_ms: u64,
+}
+
+/// Bytes of an update downloaded so far; `total` is unknown for some servers
+#[derive(Debug, Clone, Serialize)]
+pub struct UpdateProgressEvent {
+    pub downloaded: u64,
+    pub total: Option<u64>,
+}
+
+/// A newer release was found, or finished installing and waits for a restart
+#[derive(Debug, Clone, Serialize)]
+pub struct UpdateEvent {
+    pub version: String,
+    pub notes: Option<String>,
+}
+
+/// Older messages were left out of the prompt to fit the model's context window
+#[derive(Debug, Clone, Serialize)]
+pub struct ContextTrimmedEvent {
+    pub dropped_messages: usize,
+    /// Estimated
+    pub dropped_tokens: usize,
+    pub budget_tokens: usize,
+}
+
+/// A reminder came due
+#[derive(Debug, Clone, Serialize)]
+pub struct ReminderEvent {
+    pub id: i64,
+    pub text: String,
+}
+
+/// The focus timer moved to a new phase
+#[derive(Debug, Clone, Serialize)]
+pub struct PomodoroEvent {
+    pub phase: crate::pomodoro::Phase,
+    pub text: String,
+    pub animation: String,
+    pub completed: u32,
+}
+
+/// A tip or quip Clippy offers unprompted after the user has been idle
+#[derive(Debug, Clone, Serialize)]
+pub struct ProactiveEvent {
+    pub text: String,
+}
+
+/// Clippy's answer instead of a reply when a rate limit or the monthly budget is reached
+#[derive(Debug, Clone, Serialize)]
+pub struct RateLimitedEvent {
+    pub text: String,
+}
+
+/// Speech was muted or unmuted from the tray
+#[derive(Debug, Clone, Serialize)]
+pub struct VoiceMutedEvent {
+    pub muted: bool,
+}
+
+/// A saved conversation was loaded into the chat
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionLoadedEvent {
+    pub id: String,
+    pub title: String,
+    pub messages: Vec<ChatMessage>,
+}
+
+/// Passages from the knowledge folders the reply being streamed was given
+#[derive(Debug, Clone, Serialize)]
+pub struct CitationsEvent {
+    pub citations: Vec<crate::knowledge::Citation>,
+}
+
+/// A scripted easter-egg reply, answered without the LLM
+#[derive(Debug, Clone, Serialize)]
+pub struct EasterEggEvent {
+    pub id: String,
+    pub text: String,
+    pub animation: Option<String>,
+}
+
 // Use the ConversationState from lib.rs
-use crate::ConversationState;
+use crate::{ConversationState, Conversations};
+use tokio::sync::RwLock;
 
 /// Build the appropriate LLM provider based on config
-fn build_provider(config: &Config) -> Result<Box<dyn LLMProvider>, String> {
+pub(crate) fn build_provider(config: &Config) -> Result<Box<dyn LLMProvider>, String> {
     match config.llm_provider {
         LlmProviderType::OpenAI => {
             let key = config
                 .openai_api_key
                 .clone()
                 .ok_or_else(|| "OpenAI API key not set. Please configure it in settings.".to_string())?;
-            Ok(Box::new(OpenAIProvider::new(key, config.openai_model.clone())))
+            Ok(Box::new(
+                OpenAIProvider::new(key, config.openai_model.clone())
+                    .with_seed(config.seed)
+                    .with_temperature(config.temperature)
+                    .with_reasoning(config.reasoning != ReasoningDisplay::Discard),
+            ))
         }
         LlmProviderType::LMStudio => {
             let url = config
@@ -57,7 +174,13 @@ fn build_provider(config: &Config) -> Result<Box<dyn LLMProvider>, String> {
                 .custom_api_key
                 .clone()
                 .unwrap_or_else(|| "lm-studio".into());
-            Ok(Box::new(OpenAIProvider::new(key, model).with_base_url(url)))
+            Ok(Box::new(
+                OpenAIProvider::new(key, model)
+                    .with_base_url(url)
+                    .with_seed(config.seed)
+                    .with_temperature(config.temperature)
+                    .with_reasoning(config.reasoning != ReasoningDisplay::Discard),
+            ))
         }
         LlmProviderType::Ollama => {
             let url = config
@@ -69,7 +192,11 @@ fn build_provider(config: &Config) -> Result<Box<dyn LLMProvider>, String> {
                 .clone()
                 .unwrap_or_else(|| "llama3.2".into());
             Ok(Box::new(
-                OpenAIProvider::new("ollama".into(), model).with_base_url(url),
+                OpenAIProvider::new("ollama".into(), model)
+                    .with_base_url(url)
+                    .with_seed(config.seed)
+                    .with_temperature(config.temperature)
+                    .with_reasoning(config.reasoning != ReasoningDisplay::Discard),
             ))
         }
         LlmProviderType::CustomAPI => {
@@ -82,7 +209,13 @@ fn build_provider(config: &Config) -> Result<Box<dyn LLMProvider>, String> {
                 .clone()
                 .unwrap_or_else(|| "default".into());
             let key = config.custom_api_key.clone().unwrap_or_default();
-            Ok(Box::new(OpenAIProvider::new(key, model).with_base_url(url)))
+            Ok(Box::new(
+                OpenAIProvider::new(key, model)
+                    .with_base_url(url)
+                    .with_seed(config.seed)
+                    .with_temperature(config.temperature)
+                    .with_reasoning(config.reasoning != ReasoningDisplay::Discard),
+            ))
         }
         LlmProviderType::BuiltIn => {
             let model_path = config
@@ -90,108 +223,994 @@ fn build_provider(config: &Config) -> Result<Box<dyn LLMProvider>, String> {
                 .clone()
                 .ok_or_else(|| "No local model path configured. Please download or select a model in settings.".to_string())?;
             LocalLLMProvider::new(&model_path)
+                .map(|p| p.with_seed(config.seed).with_temperature(config.temperature))
                 .map(|p| Box::new(p) as Box<dyn LLMProvider>)
                 .map_err(|e| format!("Failed to load local model: {}", e))
         }
+        LlmProviderType::Mock => Ok(Box::new(MockProvider::new(&config.mock))),
     }
 }
 
 #[tauri::command]
 pub async fn send_message(
     app: AppHandle,
+    window: WebviewWindow,
     message: String,
-    state: State<'_, std::sync::Mutex<ConversationState>>,
+    attachments: Option<Vec<String>>,
+    conversations: State<'_, Conversations>,
 ) -> Result<(), String> {
-    // Load config
-    let config = Config::load().map_err(|e| format!("Failed to load config: {}", e))?;
-    
-    // Build the appropriate provider
-    let provider = build_provider(&config)?;
+    let attachments = attachments.unwrap_or_default();
+    // Load config, as the focused app wants it
+    let mut config = Config::load().map_err(|e| format!("Failed to load config: {}", e))?;
+    crate::app_rules::apply(&mut config);
+    crate::ensemble::use_judge(&mut config);
+    // Don't keep reading the last answer over the new one
+    crate::tts::stop();
+    // Replies stream only to the window that asked, which has its own conversation
+    let label = window.label();
+    let state = conversations.get(label).await;
     
+    crate::proactive::touch();
+
+    // "/model gpt-4o" and friends change settings here rather than going to the model
+    if let Some(event) = crate::slash::handle(&app, &state, &message).await {
+        let _ = app.emit_to(label, "slash-command", event);
+        return Ok(());
+    }
+
+    // Classic phrases get a scripted answer without spending any tokens
+    if let Some(egg) = crate::easter_eggs::find(&message).filter(|_| attachments.is_empty()) {
+        let event = play_easter_egg(&egg, Some(&message), &state).await;
+        let token = event.text.clone();
+        let _ = app.emit_to(label, "chat-token", StreamEvent { token });
+        let _ = app.emit_to(label, "chat-done", DoneEvent::default());
+        let _ = app.emit_to(label, "easter-egg", event);
+        persist_conversation(&app, &state).await;
+        return Ok(());
+    }
+
+    // "Remind me at 3pm to stretch" is handled here rather than by the model
+    let reminder = crate::reminders::parse_request(&message, chrono::Local::now())
+        .filter(|_| attachments.is_empty());
+    if let Some((task, due)) = reminder {
+        let reminder = crate::reminders::add(&task, due.timestamp())
+            .map_err(|e| format!("Failed to save reminder: {}", e))?;
+        let token = crate::reminders::confirmation(&reminder);
+        {
+            let mut conv_state = state.write().await;
+            conv_state.history.push(ChatMessage {
+                role: "user".to_string(),
+                content: message.clone(),
+                interrupted: false,
+                code_blocks: Vec::new(),
+            });
+            conv_state.history.push(ChatMessage {
+                role: "assistant".to_string(),
+                content: token.clone(),
+                interrupted: false,
+                code_blocks: Vec::new(),
+            });
+        }
+        let _ = app.emit_to(label, "chat-token", StreamEvent { token });
+        let _ = app.emit_to(label, "chat-done", DoneEvent::default());
+        persist_conversation(&app, &state).await;
+        return Ok(());
+    }
+
+    // Attached text files go into the turn itself
+    let message = crate::attachments::inline(&message, &attachments)
+        .map_err(|e| format!("Failed to attach file: {}", e))?;
+
+    if !admit(&app, label, &mut config) {
+        return Ok(());
+    }
+
     // Add user message to history
     {
-        let mut conv_state = state.lock().unwrap();
+        let mut conv_state = state.write().await;
         conv_state.history.push(ChatMessage {
             role: "user".to_string(),
             content: message.clone(),
+            interrupted: false,
+            code_blocks: Vec::new(),
         });
     }
+    respond(&app, label, &state, &config, &message, None).await
+}
+
+/// Check the budget and rate limits before asking a paid provider, answering `label` with
+/// Clippy's refusal instead when one is reached. Over budget, `config` may be switched to the
+/// built-in model. Returns whether the request may go ahead.
+fn admit(app: &AppHandle, label: &str, config: &mut Config) -> bool {
+    let refusal = match crate::budget::enforce(config) {
+        Ok(()) => crate::rate_limit::admit(config).map_err(|limit| {
+            tracing::info!("Rate limit reached: {:?}", limit);
+            limit.refusal()
+        }),
+        Err(text) => {
+            tracing::info!("Monthly budget spent");
+            Err(text)
+        }
+    };
+    match refusal {
+        Ok(()) => true,
+        Err(text) => {
+            let _ = app.emit_to(label, "rate-limited", RateLimitedEvent { text });
+            false
+        }
+    }
+}
+
+/// Pick up a reply that a stream error cut off, from where it stopped.
+#[tauri::command]
+pub async fn continue_response(
+    app: AppHandle,
+    window: WebviewWindow,
+    conversations: State<'_, Conversations>,
+) -> Result<(), String> {
+    let mut config = Config::load().map_err(|e| format!("Failed to load config: {}", e))?;
+    crate::app_rules::apply(&mut config);
+    crate::ensemble::use_judge(&mut config);
+    crate::proactive::touch();
+    crate::tts::stop();
+    if !admit(&app, window.label(), &mut config) {
+        return Ok(());
+    }
+    let state = conversations.get(window.label()).await;
+
+    let (question, partial) = {
+        let conv_state = state.read().await;
+        let Some(last) = conv_state.history.last().filter(|m| m.interrupted) else {
+            return Err("There's no interrupted reply to continue".to_string());
+        };
+        let question = conv_state
+            .history
+            .iter()
+            .rev()
+            .find(|m| m.role == "user")
+            .map(|m| m.content.clone())
+            .unwrap_or_default();
+        (question, last.content.clone())
+    };
+    respond(&app, window.label(), &state, &config, &question, Some(partial)).await
+}
+
+/// Code block `index` of the reply at `message_id` in this window's conversation.
+async fn find_code_block(
+    window: &WebviewWindow,
+    conversations: &Conversations,
+    message_id: usize,
+    index: usize,
+) -> Result<CodeBlock, String> {
+    let state = conversations.get(window.label()).await;
+    let conv_state = state.read().await;
+    conv_state
+        .history
+        .get(message_id)
+        .and_then(|message| message.code_blocks.get(index))
+        .cloned()
+        .ok_or_else(|| "That code block is no longer in the conversation".to_string())
+}
+
+/// A code block from a reply, exactly as the model wrote it, for the chat's copy button
+#[tauri::command]
+pub async fn copy_code_block(
+    window: WebviewWindow,
+    message_id: usize,
+    index: usize,
+    conversations: State<'_, Conversations>,
+) -> Result<CodeBlock, String> {
+    find_code_block(&window, &conversations, message_id, index).await
+}
+
+/// Write a code block from a reply to `path`, with a trailing newline
+#[tauri::command]
+pub async fn save_code_block(
+    window: WebviewWindow,
+    message_id: usize,
+    index: usize,
+    path: String,
+    conversations: State<'_, Conversations>,
+) -> Result<(), String> {
+    let block = find_code_block(&window, &conversations, message_id, index).await?;
+    std::fs::write(&path, format!("{}\n", block.code))
+        .map_err(|e| format!("Failed to save code block: {}", e))
+}
+
+/// Asked after an interrupted reply, which the model sees as its own last message
+const CONTINUE_REQUEST: &str = "Your last reply was cut off. Continue it from exactly where it \
+stopped, without repeating any of it or starting over.";
+
+/// Answer `message`, already the last user turn in the history, streaming the reply to the
+/// window labelled `label`. With `resume`, the last history entry is that interrupted reply and the model
+/// carries on from it.
+async fn respond(
+    app: &AppHandle,
+    label: &str,
+    state: &RwLock<ConversationState>,
+    config: &Config,
+    message: &str,
+    resume: Option<String>,
+) -> Result<(), String> {
+    // Build the appropriate provider
+    let provider = build_provider(config)?;
+    if config.llm_provider == LlmProviderType::LMStudio && config.lmstudio_load_on_chat {
+        crate::lmstudio::ensure_loaded(config)
+            .await
+            .map_err(|e| format!("Failed to load model: {}", e))?;
+    }
+    let started = std::time::Instant::now();
+    crate::stats::record_message(&format!("{:?}", config.llm_provider));
+
+    // Offer this model in the tray's quick switcher
+    let tray_app = app.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        match crate::runtime_state::remember_current_model() {
+            Ok(true) => crate::tray::refresh(&tray_app),
+            Ok(false) => {}
+            Err(e) => tracing::warn!("Failed to remember recent model: {}", e),
+        }
+    });
+
+    if config.llm_provider == LlmProviderType::BuiltIn {
+        if let Some(path) = &config.builtin_model_path {
+            if let Some(warning) = crate::hardware::check_model_fits_once(path) {
+                tracing::warn!("{}", warning);
+                let _ = app.emit_to(label, "model-memory-warning", ErrorEvent {
+                    error: warning,
+                    partial: None,
+                });
+            }
+        }
+    }
     
     // Prepare messages with system prompt
-    let mut messages = vec![Message {
+    let mut system_prompt = format!(
+        "{}\n\n{}\n\n{}",
+        personality::get_system_prompt(config),
+        personality::datetime_context(),
+        crate::language::instruction(config, Some(message))
+    );
+    if config.knowledge.enabled {
+        match crate::knowledge::search(message, config.knowledge.max_chunks) {
+            Ok(citations) if !citations.is_empty() => {
+                system_prompt.push_str("\n\n");
+                system_prompt.push_str(&crate::knowledge::prompt_context(&citations));
+                let _ = app.emit_to(label, "chat-citations", CitationsEvent { citations });
+            }
+            Ok(_) => {}
+            Err(e) => tracing::warn!("Knowledge search failed: {}", e),
+        }
+    }
+    let tools = crate::tools::ToolRegistry::new(config);
+    let tool_prompt = tools.prompt();
+    if !tool_prompt.is_empty() {
+        system_prompt.push_str("\n\n");
+        system_prompt.push_str(&tool_prompt);
+    }
+    if config.pins_in_context {
+        if let Some(pins) = crate::sessions::pinned_context() {
+            system_prompt.push_str("\n\n");
+            system_prompt.push_str(&pins);
+        }
+    }
+    let system = Message {
         role: "system".to_string(),
-        content: personality::get_system_prompt(),
-    }];
-    
-    // Add conversation history
-    {
-        let conv_state = state.lock().unwrap();
-        for msg in &conv_state.history {
-            messages.push(Message {
+        content: system_prompt,
+    };
+
+    // Add as much of the conversation as fits, newest first
+    let history: Vec<Message> = {
+        let conv_state = state.read().await;
+        conv_state
+            .history
+            .iter()
+            .map(|msg| Message {
                 role: msg.role.clone(),
                 content: msg.content.clone(),
-            });
-        }
+            })
+            .collect()
+    };
+    let context_tokens = config
+        .context_tokens
+        .map(|n| n as usize)
+        .unwrap_or_else(|| provider.context_window());
+    let fitted = crate::llm::budget::fit(
+        system,
+        &history,
+        context_tokens,
+        provider.max_reply_tokens(),
+    );
+    if fitted.dropped_messages > 0 {
+        tracing::info!(
+            "Left {} older messages (~{} tokens) out of a {}-token budget",
+            fitted.dropped_messages,
+            fitted.dropped_tokens,
+            fitted.budget_tokens
+        );
+        let _ = app.emit_to(
+            label,
+            "context-trimmed",
+            ContextTrimmedEvent {
+                dropped_messages: fitted.dropped_messages,
+                dropped_tokens: fitted.dropped_tokens,
+                budget_tokens: fitted.budget_tokens,
+            },
+        );
+    }
+    let mut messages = fitted.messages;
+    if resume.is_some() {
+        messages.push(Message {
+            role: "user".to_string(),
+            content: CONTINUE_REQUEST.to_string(),
+        });
     }
     
-    // Stream response
-    let mut stream = provider
-        .stream_completion(messages, config.temperature)
-        .await
-        .map_err(|e| format!("Failed to get completion: {}", e))?;
-    
+    crate::mood::transition(app, MoodTrigger::MessageSent);
+    crate::tray::set_status(app, TrayStatus::Thinking);
+    crate::animation::enter(app, AppState::Thinking);
+
+    // With the ensemble on, the members answer first and the judge writes the reply from theirs
+    if resume.is_none() && crate::ensemble::active(config) {
+        let answers = crate::ensemble::gather(app, label, config, &messages).await;
+        if let (Some(request), Some(last)) =
+            (crate::ensemble::judge_request(&answers), messages.last_mut())
+        {
+            last.content = format!("{}\n\n{}", last.content, request);
+        }
+    }
+
+    // Stream the response. A reply that is a tool call is held back from the chat, run,
+    // and answered again with its result.
     let mut full_response = String::new();
-    
-    while let Some(result) = stream.next().await {
-        match result {
-            Ok(token) => {
-                full_response.push_str(&token);
-                let _ = app.emit("chat-token", StreamEvent { token });
-            }
+    for round in 0..=crate::tools::MAX_ROUNDS {
+        let log_entry = RequestLogEntry::new(config, "chat", &messages);
+        let round_started = std::time::Instant::now();
+        let mut first_token = None;
+        let mut stream = match provider
+            .stream_completion(messages.clone())
+            .await
+        {
+            Ok(stream) => stream,
             Err(e) => {
-                let _ = app.emit("chat-error", ErrorEvent {
-                    error: format!("Stream error: {}", e),
-                });
-                return Err(format!("Stream error: {}", e));
+                crate::mood::transition(app, MoodTrigger::Error);
+                crate::tray::set_status(app, TrayStatus::Error);
+                crate::animation::leave(app, AppState::Thinking);
+                crate::animation::enter(app, AppState::Error);
+                let error = crate::lmstudio::explain(config, &e)
+                    .unwrap_or_else(|| redact(&format!("Failed to get completion: {}", e)));
+                let log_entry =
+                    log_entry.finish("", None, round_started.elapsed(), Some(&error));
+                crate::request_log::append(config, &log_entry);
+                crate::notifications::notify(app, "Clippy couldn't answer", &error, Target::Chat);
+                return Err(error);
+            }
+        };
+
+        full_response.clear();
+        // The reply as the model sent it, thoughts included, for spend and the request log
+        let mut raw_response = String::new();
+        // Reasoning models think out loud in <think> tags; that goes to the chat separately
+        // and is left out of the history and speech
+        let mut thoughts = crate::reasoning::ReasoningFilter::default();
+        // How much of the reply has gone out as chat-token events
+        let mut emitted = 0;
+        while let Some(result) = stream.next().await {
+            match result {
+                Ok(token) => {
+                    first_token.get_or_insert_with(|| round_started.elapsed());
+                    let split = thoughts.push(&token);
+                    raw_response.push_str(&token);
+                    crate::reasoning::emit(app, label, config, split.reasoning);
+                    full_response.push_str(&split.text);
+                    if !crate::tools::may_be_call(&full_response) {
+                        let token = full_response[emitted..].to_string();
+                        emitted = full_response.len();
+                        let _ = app.emit_to(label, "chat-token", StreamEvent { token });
+                    }
+                }
+                Err(e) => {
+                    // Provider errors can echo the request, key included
+                    let error = redact(&format!("Stream error: {}", e));
+                    let log_entry = log_entry.finish(
+                        logged(config, &raw_response, &full_response),
+                        first_token,
+                        round_started.elapsed(),
+                        Some(&error),
+                    );
+                    crate::request_log::append(config, &log_entry);
+                    crate::mood::transition(app, MoodTrigger::Error);
+                    crate::tray::set_status(app, TrayStatus::Error);
+                    crate::animation::leave(app, AppState::Thinking);
+                    crate::animation::enter(app, AppState::Error);
+                    crate::notifications::notify(
+                        app,
+                        "Clippy couldn't answer",
+                        &error,
+                        Target::Chat,
+                    );
+                    let partial = keep_partial(state, resume.as_deref(), &full_response).await;
+                    if partial.is_some() {
+                        persist_conversation(app, state).await;
+                    }
+                    let _ = app.emit_to(label, "chat-error", ErrorEvent {
+                        error: error.clone(),
+                        partial,
+                    });
+                    return Err(error);
+                }
             }
         }
+
+        let rest = thoughts.finish();
+        crate::reasoning::emit(app, label, config, rest.reasoning);
+        full_response.push_str(&rest.text);
+
+        let logged = logged(config, &raw_response, &full_response);
+        let log_entry = log_entry.finish(logged, first_token, round_started.elapsed(), None);
+        crate::request_log::append(config, &log_entry);
+        crate::pricing::record(app, config, &messages, &raw_response);
+
+        let call = crate::tools::parse_call(&full_response)
+            .filter(|_| round < crate::tools::MAX_ROUNDS);
+        let Some(call) = call else {
+            // Not a call after all (or one too many); show whatever was held back
+            if emitted < full_response.len() {
+                let token = full_response[emitted..].to_string();
+                let _ = app.emit_to(label, "chat-token", StreamEvent { token });
+            }
+            break;
+        };
+        let result = tools.dispatch(app, &call).await;
+        let _ = app.emit_to(label, "tool-result", result.clone());
+        messages.push(Message {
+            role: "assistant".to_string(),
+            content: full_response.clone(),
+        });
+        messages.push(Message {
+            role: "user".to_string(),
+            content: crate::tools::result_message(&result),
+        });
     }
     
     // Add assistant response to history
+    let done = {
+        let mut conv_state = state.write().await;
+        match (resume, conv_state.history.last_mut()) {
+            (Some(partial), Some(last)) => {
+                last.content = format!("{}{}", partial, full_response);
+                last.interrupted = false;
+                last.code_blocks = crate::code_blocks::extract(&last.content);
+            }
+            _ => conv_state.history.push(ChatMessage {
+                role: "assistant".to_string(),
+                code_blocks: crate::code_blocks::extract(&full_response),
+                content: full_response,
+                interrupted: false,
+            }),
+        }
+        DoneEvent {
+            message_id: conv_state.history.len().checked_sub(1),
+            code_blocks: conv_state
+                .history
+                .last()
+                .map(|m| {
+                    let blocks = m.code_blocks.iter().enumerate();
+                    blocks.map(|(i, block)| block.info(i)).collect()
+                })
+                .unwrap_or_default(),
+        }
+    };
+    
+    crate::stats::record_response(started.elapsed());
+    crate::proactive::touch();
+    crate::mood::transition(app, MoodTrigger::ResponseDone);
+    crate::tray::clear_status(app, TrayStatus::Thinking);
+    crate::animation::leave(app, AppState::Thinking);
+    crate::sounds::play(crate::sounds::Effect::Tada);
+    let _ = app.emit_to(label, "chat-done", done);
+    persist_conversation(app, state).await;
+    
+    Ok(())
+}
+
+/// The reply as the request log records it: as sent, unless thoughts are to be discarded
+fn logged<'a>(config: &Config, raw: &'a str, said: &'a str) -> &'a str {
+    if config.reasoning == ReasoningDisplay::Discard {
+        said
+    } else {
+        raw
+    }
+}
+
+/// Keep what arrived of a reply cut off by a stream error, flagged so it can be continued.
+/// Returns the whole partial reply, or `None` when nothing arrived.
+async fn keep_partial(
+    state: &RwLock<ConversationState>,
+    resume: Option<&str>,
+    received: &str,
+) -> Option<String> {
+    // A tool call still being held back isn't worth keeping
+    let received = if crate::tools::may_be_call(received) {
+        ""
+    } else {
+        received
+    };
+    let mut conv_state = state.write().await;
+    match resume {
+        Some(earlier) => {
+            let last = conv_state.history.last_mut()?;
+            last.content = format!("{}{}", earlier, received);
+            Some(last.content.clone())
+        }
+        None if received.trim().is_empty() => None,
+        None => {
+            conv_state.history.push(ChatMessage {
+                role: "assistant".to_string(),
+                content: received.to_string(),
+                interrupted: true,
+                code_blocks: Vec::new(),
+            });
+            Some(received.to_string())
+        }
+    }
+}
+
+#[tauri::command]
+pub fn get_config() -> Result<Config, String> {
+    Config::load().map_err(|e| format!("Failed to load config: {}", e))
+}
+
+#[tauri::command]
+pub fn list_personalities() -> Vec<personality::Personality> {
+    personality::list()
+}
+
+#[tauri::command]
+pub fn list_agents() -> Vec<String> {
+    crate::agents::list()
+}
+
+/// Import an extracted MS Agent character from a folder, returning its name.
+#[tauri::command]
+pub async fn import_agent(path: String) -> Result<String, String> {
+    tokio::task::spawn_blocking(move || crate::agents::import(std::path::Path::new(&path)))
+        .await
+        .map_err(|e| format!("Import task error: {}", e))?
+        .map_err(|e| format!("Failed to import character: {}", e))
+}
+
+/// The character to show: the configured one, else the personality's sprite set. `None`
+/// means the built-in Clippy.
+#[tauri::command]
+pub async fn get_agent() -> Result<Option<crate::agents::AgentData>, String> {
+    let config = Config::load().map_err(|e| format!("Failed to load config: {}", e))?;
+    let name = config
+        .agent
+        .clone()
+        .or_else(|| personality::get(&config.personality).sprite)
+        .unwrap_or_else(|| crate::agents::BUILTIN_AGENT.to_string());
+    tokio::task::spawn_blocking(move || crate::agents::load(&name))
+        .await
+        .map_err(|e| format!("Load task error: {}", e))?
+        .map_err(|e| format!("Failed to load character: {}", e))
+}
+
+#[tauri::command]
+pub fn list_languages() -> Vec<crate::language::ResponseLanguage> {
+    crate::language::all().to_vec()
+}
+
+/// Save the conversation to its session file and list it in the tray.
+pub(crate) async fn persist_conversation(app: &AppHandle, state: &RwLock<ConversationState>) {
+    match crate::sessions::save_conversation(state).await {
+        Ok(()) => crate::tray::refresh(app),
+        Err(e) => tracing::warn!("Failed to save conversation: {}", e),
+    }
+}
+
+#[tauri::command]
+pub fn list_sessions() -> Result<Vec<crate::sessions::SessionSummary>, String> {
+    crate::sessions::list().map_err(|e| format!("Failed to list conversations: {}", e))
+}
+
+#[tauri::command]
+pub async fn load_session(
+    app: AppHandle,
+    window: WebviewWindow,
+    id: String,
+    conversations: State<'_, Conversations>,
+) -> Result<(), String> {
+    let state = conversations.get(window.label()).await;
+    open_session(&app, window.label(), &id, &state).await
+}
+
+/// Fork a saved conversation at `message_index` into a new session and open it,
+/// leaving the original thread as it was.
+#[tauri::command]
+pub async fn branch_conversation(
+    app: AppHandle,
+    session_id: String,
+    message_index: usize,
+    window: WebviewWindow,
+    conversations: State<'_, Conversations>,
+) -> Result<crate::sessions::SessionSummary, String> {
+    let state = conversations.get(window.label()).await;
+    // Save the live conversation first so a branch of it includes the latest messages
+    crate::sessions::save_conversation(&state)
+        .await
+        .map_err(|e| format!("Failed to save conversation: {}", e))?;
+    let session = crate::sessions::branch(&session_id, message_index)
+        .map_err(|e| format!("Failed to branch conversation: {}", e))?;
+    tracing::info!("Branched conversation {} into {}", session_id, session.id);
+    open_session(&app, window.label(), &session.id, &state).await?;
+    crate::tray::refresh(&app);
+    Ok(crate::sessions::SessionSummary::from(&session))
+}
+
+#[tauri::command]
+pub fn pin_message(session_id: String, message_index: usize, pinned: bool) -> Result<(), String> {
+    crate::sessions::set_pinned(&session_id, message_index, pinned)
+        .map_err(|e| format!("Failed to pin message: {}", e))
+}
+
+#[tauri::command]
+pub fn list_pinned() -> Result<Vec<crate::sessions::PinnedMessage>, String> {
+    crate::sessions::pinned().map_err(|e| format!("Failed to list pinned messages: {}", e))
+}
+
+#[tauri::command]
+pub fn index_status() -> crate::knowledge::IndexStatus {
+    crate::knowledge::status()
+}
+
+/// Re-embed every file in the knowledge folders; progress arrives as `knowledge-status` events.
+#[tauri::command]
+pub fn reindex() -> Result<(), String> {
+    crate::knowledge::reindex().map_err(|e| format!("Failed to start reindexing: {}", e))
+}
+
+/// Save the live conversation and start a fresh one; the old one stays in the history.
+/// The local model builds a new context for every reply, so there is no KV cache to reset.
+#[tauri::command]
+pub async fn new_conversation(
+    app: AppHandle,
+    window: WebviewWindow,
+    conversations: State<'_, Conversations>,
+) -> Result<(), String> {
+    let state = conversations.get(window.label()).await;
+    start_new_conversation(&app, &state).await
+}
+
+/// Save `state`'s conversation to the history and empty it.
+pub(crate) async fn start_new_conversation(
+    app: &AppHandle,
+    state: &RwLock<ConversationState>,
+) -> Result<(), String> {
+    crate::sessions::save_conversation(state)
+        .await
+        .map_err(|e| format!("Failed to save conversation: {}", e))?;
     {
-        let mut conv_state = state.lock().unwrap();
+        let mut conv_state = state.write().await;
+        conv_state.history.clear();
+        conv_state.session_id = None;
+    }
+    crate::proactive::touch();
+    crate::tray::refresh(app);
+    tracing::info!("Started a new conversation");
+    Ok(())
+}
+
+/// Forget the live conversation entirely, deleting its saved session too.
+#[tauri::command]
+pub async fn clear_conversation(
+    app: AppHandle,
+    window: WebviewWindow,
+    conversations: State<'_, Conversations>,
+) -> Result<(), String> {
+    let state = conversations.get(window.label()).await;
+    let session_id = {
+        let mut conv_state = state.write().await;
+        conv_state.history.clear();
+        conv_state.session_id.take()
+    };
+    if let Some(id) = session_id {
+        // Never saved if the user hadn't said anything yet
+        if crate::sessions::load(&id).is_ok() {
+            crate::sessions::delete(&id)
+                .map_err(|e| format!("Failed to delete conversation: {}", e))?;
+        }
+    }
+    crate::tray::refresh(&app);
+    tracing::info!("Cleared the conversation");
+    Ok(())
+}
+
+/// Delete saved conversations last updated more than `older_than` days ago; 0 deletes them all.
+/// Returns how many were removed.
+#[tauri::command]
+pub async fn purge_history(
+    app: AppHandle,
+    older_than: u32,
+    conversations: State<'_, Conversations>,
+) -> Result<usize, String> {
+    let deleted = crate::sessions::purge(older_than)
+        .map_err(|e| format!("Failed to delete history: {}", e))?;
+    // A live conversation's file may be gone; its next save starts a fresh one
+    for state in conversations.all().await {
+        let mut conv_state = state.write().await;
+        if let Some(id) = conv_state.session_id.clone() {
+            if crate::sessions::load(&id).is_err() {
+                conv_state.session_id = None;
+            }
+        }
+    }
+    crate::tray::refresh(&app);
+    Ok(deleted)
+}
+
+/// Make a saved session the live conversation of the window labelled `label` and show it there.
+pub(crate) async fn open_session(
+    app: &AppHandle,
+    label: &str,
+    id: &str,
+    state: &RwLock<ConversationState>,
+) -> Result<(), String> {
+    let session =
+        crate::sessions::load(id).map_err(|e| format!("Failed to load conversation: {}", e))?;
+    {
+        let mut conv_state = state.write().await;
+        conv_state.history = session.messages.clone();
+        conv_state.session_id = Some(session.id.clone());
+    }
+
+    if let Some(window) = app.get_webview_window(label) {
+        crate::ghost::wake(app);
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+    let _ = app.emit_to(
+        label,
+        "session-loaded",
+        SessionLoadedEvent {
+            id: session.id,
+            title: session.title,
+            messages: session.messages,
+        },
+    );
+    Ok(())
+}
+
+/// Record an easter egg's reply in the history and start its sound.
+async fn play_easter_egg(
+    egg: &crate::easter_eggs::EasterEgg,
+    user_message: Option<&str>,
+    state: &RwLock<ConversationState>,
+) -> EasterEggEvent {
+    tracing::info!("Easter egg triggered: {}", egg.id);
+    let text = egg.response();
+    {
+        let mut conv_state = state.write().await;
+        if let Some(message) = user_message {
+            conv_state.history.push(ChatMessage {
+                role: "user".to_string(),
+                content: message.to_string(),
+                interrupted: false,
+                code_blocks: Vec::new(),
+            });
+        }
         conv_state.history.push(ChatMessage {
             role: "assistant".to_string(),
-            content: full_response,
+            content: text.clone(),
+            interrupted: false,
+            code_blocks: Vec::new(),
         });
     }
-    
-    let _ = app.emit("chat-done", DoneEvent {});
-    
+
+    if let Some(path) = egg.sound_path() {
+        tauri::async_runtime::spawn_blocking(move || {
+            if let Err(e) = crate::tts::play_file(&path) {
+                tracing::warn!("Easter egg sound failed: {}", e);
+            }
+        });
+    }
+
+    EasterEggEvent {
+        id: egg.id.clone(),
+        text,
+        animation: egg.animation.clone(),
+    }
+}
+
+/// Set off an easter egg by id, for triggers that aren't chat messages (e.g. the Konami code).
+#[tauri::command]
+pub async fn trigger_easter_egg(
+    id: String,
+    window: WebviewWindow,
+    conversations: State<'_, Conversations>,
+) -> Result<EasterEggEvent, String> {
+    let egg = crate::easter_eggs::get(&id).ok_or_else(|| format!("Unknown easter egg '{}'", id))?;
+    crate::proactive::touch();
+    let state = conversations.get(window.label()).await;
+    Ok(play_easter_egg(&egg, None, &state).await)
+}
+
+/// Fade Clippy and let clicks through (the frontend calls this once it has been idle a while).
+#[tauri::command]
+pub fn set_click_through(app: AppHandle, enabled: bool) -> Result<(), String> {
+    crate::ghost::set(&app, enabled).map_err(|e| format!("Failed to set click-through: {}", e))
+}
+
+/// Pin a window above all others, or let it fall behind. The Clippy window's
+/// choice is saved as the `always_on_top` default for the next launch.
+#[tauri::command]
+pub fn set_always_on_top(app: AppHandle, window: String, on_top: bool) -> Result<(), String> {
+    let target = app
+        .get_webview_window(&window)
+        .ok_or_else(|| format!("Window '{}' not found", window))?;
+    target
+        .set_always_on_top(on_top)
+        .map_err(|e| format!("Failed to set always-on-top: {}", e))?;
+
+    if window == "clippy" {
+        let mut config = Config::load().map_err(|e| format!("Failed to load config: {}", e))?;
+        if config.always_on_top != on_top {
+            config.always_on_top = on_top;
+            config
+                .save()
+                .map_err(|e| format!("Failed to save config: {}", e))?;
+        }
+    }
     Ok(())
 }
 
+/// The screen edge or corner Clippy is docked to, so the bubble can open on the free side
 #[tauri::command]
-pub fn get_config() -> Result<Config, String> {
-    Config::load().map_err(|e| format!("Failed to load config: {}", e))
+pub fn get_dock() -> crate::config::Dock {
+    crate::docking::current()
+}
+
+#[tauri::command]
+pub fn get_mood() -> crate::mood::Mood {
+    crate::mood::current()
+}
+
+/// Clippy perks up while the user is typing to it.
+#[tauri::command]
+pub fn set_listening(app: AppHandle, listening: bool) {
+    if listening {
+        crate::animation::enter(&app, AppState::Listening);
+    } else {
+        crate::animation::leave(&app, AppState::Listening);
+    }
+}
+
+/// The animation Clippy should be playing, for a page that missed the last `play-animation`.
+#[tauri::command]
+pub fn get_animation(
+    controller: State<'_, crate::animation::AnimationController>,
+) -> crate::animation::PlayAnimationEvent {
+    controller.current()
+}
+
+/// Turn do not disturb on or off by hand.
+#[tauri::command]
+pub fn set_dnd(app: AppHandle, enabled: bool) -> crate::dnd::DndStatus {
+    crate::dnd::set(&app, enabled)
+}
+
+#[tauri::command]
+pub fn get_dnd() -> crate::dnd::DndStatus {
+    crate::dnd::status()
+}
+
+/// Restart the idle timer for proactive messages (the frontend calls this on clicks).
+#[tauri::command]
+pub fn report_activity(app: AppHandle) {
+    crate::proactive::touch();
+    // Clicking a peeking Clippy is how it's summoned
+    crate::peek::summon(&app);
+}
+
+/// An opening line from the active personality.
+#[tauri::command]
+pub fn get_greeting() -> Result<String, String> {
+    let config = Config::load().map_err(|e| format!("Failed to load config: {}", e))?;
+    Ok(personality::get(&config.personality).greeting())
+}
+
+/// Set (or clear, with `None`) the custom system prompt used by `send_message`.
+#[tauri::command]
+pub fn set_system_prompt(
+    prompt: Option<String>,
+    mode: Option<crate::config::SystemPromptMode>,
+) -> Result<(), String> {
+    let mut config = Config::load().map_err(|e| format!("Failed to load config: {}", e))?;
+    config.custom_system_prompt = prompt.filter(|p| !p.trim().is_empty());
+    if let Some(mode) = mode {
+        config.system_prompt_mode = mode;
+    }
+    config
+        .save()
+        .map_err(|e| format!("Failed to save config: {}", e))
+}
+
+/// Field-level problems with `config`; empty when it is safe to save.
+#[tauri::command]
+pub fn validate_config(config: Config) -> Vec<crate::config::FieldError> {
+    config.validate()
 }
 
 #[tauri::command]
 pub async fn save_config(
-    config: Config,
+    mut config: Config,
+    revoked_tools: Option<Vec<String>>,
     tts_state: State<'_, TtsState>,
 ) -> Result<(), String> {
     tracing::info!("save_config called. Voice in config: {:?}", config.tts_voice);
 
+    // "Always allow" is also granted from the approval prompt, maybe after the settings
+    // form loaded; keep those grants unless the form revoked them
+    if let Ok(current) = Config::load_file() {
+        let revoked = revoked_tools.unwrap_or_default();
+        for (name, tool) in current.tools {
+            if tool.always_allow && !revoked.contains(&name) {
+                config.tools.entry(name).or_default().always_allow = true;
+            }
+        }
+    }
+
     config
         .save()
         .map_err(|e| format!("Failed to save config: {}", e))?;
+    crate::crash::set_enabled(config.crash_reports);
+
+    apply_tts_config(&config, &tts_state).await
+}
 
-    // Reload TTS engine if enabled and voice is ready
-    if config.tts_enabled {
-        if let Some(voice) = &config.tts_voice {
-            if crate::tts::voice_ready(voice) {
-                let config_path = crate::tts::voice_config(voice)
+/// Load, swap, or unload the TTS engine to match `config`.
+pub(crate) async fn apply_tts_config(config: &Config, tts_state: &TtsState) -> Result<(), String> {
+    if config.tts_enabled && config.tts_engine == TtsEngineType::OpenAI {
+        let key = config
+            .openai_api_key
+            .clone()
+            .filter(|k| !k.trim().is_empty())
+            .ok_or_else(|| "OpenAI speech needs an OpenAI API key".to_string())?;
+        let engine = crate::tts::OpenAITtsEngine::new(
+            key,
+            config.openai_tts_model.clone(),
+            config.openai_tts_voice.clone(),
+        );
+        let mut guard = tts_state.0.lock().map_err(|e| format!("TTS lock error: {}", e))?;
+        *guard = Some(std::sync::Arc::new(engine));
+        tracing::info!("TTS engine switched to OpenAI voice: {}", config.openai_tts_voice);
+    } else if config.tts_enabled && config.tts_engine == TtsEngineType::ElevenLabs {
+        let key = config
+            .elevenlabs_api_key
+            .clone()
+            .filter(|k| !k.trim().is_empty())
+            .ok_or_else(|| "ElevenLabs speech needs an ElevenLabs API key".to_string())?;
+        let engine = crate::tts::ElevenLabsTtsEngine::new(
+            key,
+            config.elevenlabs_voice_id.clone(),
+            config.elevenlabs_model.clone(),
+        );
+        let mut guard = tts_state.0.lock().map_err(|e| format!("TTS lock error: {}", e))?;
+        *guard = Some(std::sync::Arc::new(engine));
+        tracing::info!("TTS engine switched to ElevenLabs voice: {}", config.elevenlabs_voice_id);
+    } else if config.tts_enabled {
+        // Reload TTS engine if voice is ready
+        // Fall back to the personality's preferred voice when none is chosen
+        let voice = config
+            .tts_voice
+            .clone()
+            .or_else(|| personality::get(&config.personality).tts_voice);
+        // A fixed reply language needs a voice that can pronounce it
+        let voice = crate::language::voice_for(config, voice);
+        if let Some(wanted) = &voice {
+            // Another quality of the same voice will do if that's what's downloaded
+            if let Some(voice) = crate::tts::resolve_local(wanted) {
+                let config_path = crate::tts::voice_config(&voice)
                     .map_err(|e| format!("Failed to get voice config path: {}", e))?;
 
                 let engine = tokio::task::spawn_blocking(move || {
@@ -201,11 +1220,13 @@ pub async fn save_config(
                 .map_err(|e| format!("TTS reload task failed: {}", e))?
                 .map_err(|e| format!("Failed to reload TTS: {}", e))?;
 
+                let engine = std::sync::Arc::new(engine);
+                crate::tts::warm_in_background(std::sync::Arc::clone(&engine), config);
                 let mut guard = tts_state.0.lock().map_err(|e| format!("TTS lock error: {}", e))?;
-                *guard = Some(std::sync::Arc::new(engine));
+                *guard = Some(engine);
                 tracing::info!("TTS engine reloaded with voice: {}", voice);
             } else {
-                tracing::warn!("Voice '{}' is not ready/downloaded. Skipping TTS reload.", voice);
+                tracing::warn!("Voice '{}' is not ready/downloaded. Skipping TTS reload.", wanted);
             }
         }
     } else {
@@ -217,81 +1238,238 @@ pub async fn save_config(
     Ok(())
 }
 
+#[derive(Debug, Clone, Serialize)]
+pub struct ProfileChangedEvent {
+    pub name: String,
+}
+
+#[tauri::command]
+pub fn list_profiles() -> Result<crate::profiles::ProfileList, String> {
+    crate::profiles::list().map_err(|e| format!("Failed to list profiles: {}", e))
+}
+
 #[tauri::command]
-pub async fn download_model(app: AppHandle) -> Result<String, String> {
-    use hf_hub::api::sync::ApiBuilder;
+pub fn create_profile(app: AppHandle, name: String) -> Result<(), String> {
+    crate::profiles::create(&name).map_err(|e| format!("Failed to create profile: {}", e))?;
+    crate::tray::refresh(&app);
+    Ok(())
+}
 
+#[tauri::command]
+pub async fn switch_profile(app: AppHandle, name: String) -> Result<(), String> {
+    activate_profile(&app, &name).await
+}
+
+#[tauri::command]
+pub fn delete_profile(app: AppHandle, name: String) -> Result<(), String> {
+    crate::profiles::delete(&name).map_err(|e| format!("Failed to delete profile: {}", e))?;
+    crate::tray::refresh(&app);
+    Ok(())
+}
+
+/// Switch to a profile and bring runtime state (TTS, tray, windows) in line with it.
+pub(crate) async fn activate_profile(app: &AppHandle, name: &str) -> Result<(), String> {
+    let config =
+        crate::profiles::switch(name).map_err(|e| format!("Failed to switch profile: {}", e))?;
+
+    if let Some(tts_state) = app.try_state::<TtsState>() {
+        apply_tts_config(&config, &tts_state).await?;
+    }
+
+    crate::tray::refresh(app);
     let _ = app.emit(
-        "model-download-progress",
-        DownloadProgressEvent {
-            percent: 0.0,
-            status: "Starting download...".into(),
+        "profile-changed",
+        ProfileChangedEvent {
+            name: name.to_string(),
         },
     );
+    Ok(())
+}
+
+/// Export settings and profiles to `path`. API keys are only included,
+/// encrypted, when a passphrase is supplied.
+#[tauri::command]
+pub fn export_settings(path: String, passphrase: Option<String>) -> Result<(), String> {
+    crate::settings_bundle::export(std::path::Path::new(&path), passphrase.as_deref())
+        .map_err(|e| format!("Failed to export settings: {}", e))
+}
+
+#[tauri::command]
+pub async fn import_settings(
+    app: AppHandle,
+    path: String,
+    passphrase: Option<String>,
+    tts_state: State<'_, TtsState>,
+) -> Result<crate::settings_bundle::ImportSummary, String> {
+    let (config, summary) =
+        crate::settings_bundle::import(std::path::Path::new(&path), passphrase.as_deref())
+            .map_err(|e| format!("Failed to import settings: {}", e))?;
 
+    apply_tts_config(&config, &tts_state).await?;
+    crate::tray::refresh(&app);
+    Ok(summary)
+}
+
+#[tauri::command]
+pub async fn download_model(
+    app: AppHandle,
+    model_id: Option<String>,
+    downloads: State<'_, DownloadManager>,
+) -> Result<String, String> {
     let data_dir =
         Config::data_dir().map_err(|e| format!("Failed to get data directory: {}", e))?;
 
-    let _ = app.emit(
-        "model-download-progress",
-        DownloadProgressEvent {
-            percent: 10.0,
-            status: "Connecting to HuggingFace...".into(),
-        },
-    );
+    let model = match model_id.as_deref() {
+        Some(id) => crate::models::catalog_model(id)
+            .ok_or_else(|| format!("Unknown model: {}", id))?,
+        None => &crate::models::CATALOG[0],
+    };
 
-    // Download Gemma 3 1B Q4_K_M from HuggingFace
-    let api = ApiBuilder::new()
-        .with_cache_dir(data_dir.clone())
-        .build()
-        .map_err(|e| format!("Failed to create HF API: {}", e))?;
+    let model_path = downloads
+        .download(&app, model.name, &model.url(), &model.dest(&data_dir))
+        .await?;
+
+    let model_path_str = model_path.to_string_lossy().to_string();
 
     let _ = app.emit(
         "model-download-progress",
         DownloadProgressEvent {
-            percent: 20.0,
-            status: "Downloading Gemma 3 1B (Q4_K_M)...".into(),
+            percent: 100.0,
+            status: "Download complete!".into(),
         },
     );
 
-    let model_path = tokio::task::spawn_blocking(move || {
-        api.model("bartowski/google_gemma-3-1b-it-GGUF".to_string())
-            .get("google_gemma-3-1b-it-Q4_K_M.gguf")
-    })
-    .await
-    .map_err(|e| format!("Download task failed: {}", e))?
-    .map_err(|e| format!("Failed to download model: {}", e))?;
+    // Auto-save the model path to config
+    let mut config = Config::load().map_err(|e| format!("Failed to load config: {}", e))?;
+    config.builtin_model_path = Some(model_path_str.clone());
+    config
+        .save()
+        .map_err(|e| format!("Failed to save config: {}", e))?;
+
+    Ok(model_path_str)
+}
+
+/// Use a GGUF from disk as the built-in model, copying it into the data directory first,
+/// for machines that can't reach HuggingFace.
+#[tauri::command]
+pub async fn use_local_model(path: String) -> Result<String, String> {
+    let model_path = tokio::task::spawn_blocking(move || {
+        crate::models::install_model(std::path::Path::new(&path))
+    })
+    .await
+    .map_err(|e| format!("Install task error: {}", e))?
+    .map_err(|e| format!("Failed to install model: {}", e))?;
+    let model_path_str = model_path.to_string_lossy().to_string();
+
+    let mut config = Config::load().map_err(|e| format!("Failed to load config: {}", e))?;
+    config.builtin_model_path = Some(model_path_str.clone());
+    config
+        .save()
+        .map_err(|e| format!("Failed to save config: {}", e))?;
+
+    tracing::info!("Using local model {}", model_path_str);
+    Ok(model_path_str)
+}
+
+#[tauri::command]
+pub fn get_hardware_info() -> crate::hardware::HardwareInfo {
+    crate::hardware::detect()
+}
+
+#[tauri::command]
+pub fn get_model_catalog() -> Vec<crate::models::CatalogEntry> {
+    crate::models::catalog_for(&crate::hardware::detect())
+}
+
+/// Returns a warning if the model at `path` is too big for this machine.
+#[tauri::command]
+pub fn check_model_memory(path: String) -> Option<String> {
+    crate::hardware::check_model_fits(std::path::Path::new(&path), &crate::hardware::detect())
+}
+
+#[tauri::command]
+pub fn list_downloads(downloads: State<'_, DownloadManager>) -> Vec<DownloadJob> {
+    downloads.list()
+}
+
+#[tauri::command]
+pub fn pause_download(id: u64, downloads: State<'_, DownloadManager>) -> Result<(), String> {
+    downloads.pause(id)
+}
+
+#[tauri::command]
+pub fn resume_download(id: u64, downloads: State<'_, DownloadManager>) -> Result<(), String> {
+    downloads.resume(id)
+}
 
-    let model_path_str = model_path.to_string_lossy().to_string();
+#[tauri::command]
+pub fn cancel_download(id: u64, downloads: State<'_, DownloadManager>) -> Result<(), String> {
+    downloads.cancel(id)
+}
 
-    let _ = app.emit(
-        "model-download-progress",
-        DownloadProgressEvent {
-            percent: 100.0,
-            status: "Download complete!".into(),
-        },
-    );
+/// Whether LM Studio's server is running, with its models when it is
+#[tauri::command]
+pub async fn lmstudio_status() -> Result<crate::lmstudio::LmStudioStatus, String> {
+    let config = Config::load().map_err(|e| format!("Failed to load config: {}", e))?;
+    Ok(crate::lmstudio::status(&config).await)
+}
 
-    // Auto-save the model path to config
-    let mut config = Config::load().map_err(|e| format!("Failed to load config: {}", e))?;
-    config.builtin_model_path = Some(model_path_str.clone());
-    config
-        .save()
-        .map_err(|e| format!("Failed to save config: {}", e))?;
+/// Have LM Studio load `model` into memory, so the first reply doesn't wait for it
+#[tauri::command]
+pub async fn lmstudio_load_model(model: String) -> Result<(), String> {
+    let config = Config::load().map_err(|e| format!("Failed to load config: {}", e))?;
+    crate::lmstudio::load(&config, &model)
+        .await
+        .map_err(|e| format!("Failed to load model: {}", e))
+}
 
-    Ok(model_path_str)
+/// Models the Ollama server has pulled
+#[tauri::command]
+pub async fn ollama_list_models() -> Result<Vec<crate::ollama::OllamaModel>, String> {
+    let config = Config::load().map_err(|e| format!("Failed to load config: {}", e))?;
+    crate::ollama::list(&config)
+        .await
+        .map_err(|e| format!("Failed to list Ollama models: {}", e))
+}
+
+/// Download a model into Ollama, emitting `ollama-pull-progress` until it's ready
+#[tauri::command]
+pub async fn ollama_pull_model(app: AppHandle, name: String) -> Result<(), String> {
+    let config = Config::load().map_err(|e| format!("Failed to load config: {}", e))?;
+    crate::ollama::pull(&app, &config, &name)
+        .await
+        .map_err(|e| format!("Failed to pull model: {}", e))
 }
 
+#[tauri::command]
+pub async fn ollama_delete_model(name: String) -> Result<(), String> {
+    let config = Config::load().map_err(|e| format!("Failed to load config: {}", e))?;
+    crate::ollama::delete(&config, &name)
+        .await
+        .map_err(|e| format!("Failed to delete model: {}", e))
+}
 
 #[tauri::command]
 pub async fn speak_text(
+    app: AppHandle,
     text: String,
     tts_state: State<'_, TtsState>,
 ) -> Result<(), String> {
     tracing::info!("speak_text called: \"{}\"", text);
+    if crate::app_rules::silenced() {
+        tracing::debug!("Not speaking: the focused app's rule is silent");
+        return Ok(());
+    }
+    if crate::dnd::active() {
+        tracing::debug!("Not speaking: do not disturb is on");
+        return Ok(());
+    }
+    let config = Config::load().unwrap_or_default();
+    let text =
+        crate::tts::limit_sentences(&crate::reasoning::strip(&text), config.tts_max_sentences);
 
     // Clone Arc handle out of the lock so we can run synthesis on a blocking thread
-    let engine: std::sync::Arc<crate::tts::PiperTTSEngine> = {
+    let engine: std::sync::Arc<dyn TtsEngine> = {
         let guard = tts_state.0.lock().map_err(|e| format!("TTS lock error: {}", e))?;
         match guard.as_ref() {
             Some(e) => std::sync::Arc::clone(e),
@@ -300,20 +1478,32 @@ pub async fn speak_text(
     };
 
     // Piper synthesis is synchronous (uses rayon internally) — run on a blocking thread
-    tokio::task::spawn_blocking(move || engine.speak(&text))
-        .await
+    crate::tray::set_status(&app, TrayStatus::Speaking);
+    crate::animation::enter(&app, AppState::Speaking);
+    let result = tokio::task::spawn_blocking(move || {
+        crate::tts::speak_queued(engine, &text, config.tts_queue_size, config.tts_queue_policy)
+    })
+    .await;
+    crate::tray::clear_status(&app, TrayStatus::Speaking);
+    crate::animation::leave(&app, AppState::Speaking);
+    result
         .map_err(|e| format!("TTS task error: {}", e))?
         .map_err(|e| format!("TTS error: {}", e))?;
     tracing::info!("speak_text completed successfully");
+    crate::stats::record_tts_utterance();
     Ok(())
 }
 
+/// Say `text` in `voice`. A voice that isn't downloaded plays its published sample clip
+/// instead, so it can be heard before committing to the download.
 #[tauri::command]
 pub async fn preview_voice(text: String, voice: String) -> Result<(), String> {
     tracing::info!("preview_voice called: \"{}\" with voice \"{}\"", text, voice);
 
     if !crate::tts::voice_ready(&voice) {
-        return Err(format!("Voice '{}' is not downloaded. Please download it first.", voice));
+        return crate::tts::play_sample(&voice)
+            .await
+            .map_err(|e| format!("Failed to play voice sample: {}", e));
     }
 
     let config_path = crate::tts::voice_config(&voice)
@@ -336,6 +1526,76 @@ pub async fn preview_voice(text: String, voice: String) -> Result<(), String> {
     Ok(())
 }
 
+/// Synthesize the configured phrases and the personality's greetings into the loaded voice's
+/// cache now. Returns how many weren't cached yet.
+#[tauri::command]
+pub async fn warm_tts_cache(tts_state: State<'_, TtsState>) -> Result<usize, String> {
+    let config = Config::load().map_err(|e| format!("Failed to load config: {}", e))?;
+    let engine = {
+        let guard = tts_state.0.lock().map_err(|e| format!("TTS lock error: {}", e))?;
+        match guard.as_ref() {
+            Some(e) => std::sync::Arc::clone(e),
+            None => return Err("TTS not initialized. Download a voice model first.".into()),
+        }
+    };
+    let phrases = crate::tts::phrases_to_cache(&config);
+    tokio::task::spawn_blocking(move || engine.warm(&phrases))
+        .await
+        .map_err(|e| format!("TTS task error: {}", e))
+}
+
+/// Words Clippy respells before speaking, and how each is said
+#[tauri::command]
+pub fn list_pronunciations() -> Result<std::collections::BTreeMap<String, String>, String> {
+    crate::pronunciation::list().map_err(|e| format!("Failed to load pronunciations: {}", e))
+}
+
+/// Speak `word` as `replacement`, e.g. "nginx" as "engine x".
+#[tauri::command]
+pub fn add_pronunciation(word: String, replacement: String) -> Result<(), String> {
+    crate::pronunciation::add(&word, &replacement)
+        .map_err(|e| format!("Failed to save pronunciation: {}", e))
+}
+
+#[tauri::command]
+pub fn remove_pronunciation(word: String) -> Result<bool, String> {
+    crate::pronunciation::remove(&word)
+        .map_err(|e| format!("Failed to remove pronunciation: {}", e))
+}
+
+#[tauri::command]
+pub fn list_prompt_templates() -> Result<Vec<crate::prompt_templates::PromptTemplate>, String> {
+    crate::prompt_templates::list().map_err(|e| format!("Failed to load prompt templates: {}", e))
+}
+
+/// Save a new prompt template, or replace the one with `id`.
+#[tauri::command]
+pub fn save_prompt_template(
+    id: Option<String>,
+    name: String,
+    text: String,
+) -> Result<crate::prompt_templates::PromptTemplate, String> {
+    crate::prompt_templates::save(id.as_deref(), &name, &text)
+        .map_err(|e| format!("Failed to save prompt template: {}", e))
+}
+
+#[tauri::command]
+pub fn delete_prompt_template(id: String) -> Result<bool, String> {
+    crate::prompt_templates::delete(&id)
+        .map_err(|e| format!("Failed to delete prompt template: {}", e))
+}
+
+/// The prompt template `id` makes with its placeholders set to `values`, ready to send.
+#[tauri::command]
+pub fn fill_prompt_template(
+    id: String,
+    values: std::collections::BTreeMap<String, String>,
+) -> Result<String, String> {
+    crate::prompt_templates::get(&id)
+        .and_then(|template| crate::prompt_templates::fill(&template, &values))
+        .map_err(|e| format!("Failed to fill in prompt template: {}", e))
+}
+
 #[tauri::command]
 pub fn is_tts_initialized(tts_state: State<'_, TtsState>) -> bool {
     tts_state
@@ -350,36 +1610,47 @@ pub fn is_voice_downloaded(voice: String) -> bool {
     crate::tts::voice_ready(&voice)
 }
 
+/// Download a Piper voice and load it. When HuggingFace doesn't have that quality, the same
+/// voice at the best quality it does have is downloaded instead; returns the voice used.
 #[tauri::command]
-pub async fn download_tts_model(app: AppHandle, voice: String) -> Result<(), String> {
+pub async fn download_tts_model(
+    app: AppHandle,
+    voice: String,
+    downloads: State<'_, DownloadManager>,
+) -> Result<String, String> {
     tracing::info!("download_tts_model called with voice: '{}'", voice);
 
-    let _ = app.emit(
-        "model-download-progress",
-        DownloadProgressEvent {
-            percent: 0.0,
-            status: "Starting Piper voice download...".into(),
-        },
-    );
-
     let data_dir =
         Config::data_dir().map_err(|e| format!("Failed to get data directory: {}", e))?;
 
-    let _ = app.emit(
-        "model-download-progress",
-        DownloadProgressEvent {
-            percent: 10.0,
-            status: "Downloading voice model (~60MB)...".into(),
-        },
-    );
+    let requested = voice;
+    let voice = crate::tts::resolve_remote(&requested).await?;
+    if voice != requested {
+        tracing::warn!("Voice '{}' isn't available; downloading '{}'", requested, voice);
+        let _ = app.emit(
+            "model-download-progress",
+            DownloadProgressEvent {
+                percent: 0.0,
+                status: format!("{} isn't available; downloading {} instead", requested, voice),
+            },
+        );
+    }
 
-    let voice_id = voice.clone();
-    let config_path = tokio::task::spawn_blocking(move || {
-        crate::tts::download_voice(&voice_id, &data_dir)
-    })
-    .await
-    .map_err(|e| format!("Download task failed: {}", e))?
-    .map_err(|e| e)?;
+    // One at a time, model first: if it fails or is cancelled, the config that marks the
+    // voice as downloaded never arrives
+    let files = crate::tts::voice_download_files(&voice, &data_dir)?;
+    let mut paths = Vec::new();
+    for (url, dest) in &files {
+        let label = dest
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| voice.clone());
+        paths.push(downloads.download(&app, &label, url, dest).await?);
+    }
+    // The config JSON is the second file; piper expects the model beside it
+    let config_path = paths
+        .pop()
+        .ok_or_else(|| "Voice download produced no files".to_string())?;
 
     let _ = app.emit(
         "model-download-progress",
@@ -397,12 +1668,18 @@ pub async fn download_tts_model(app: AppHandle, voice: String) -> Result<(), Str
     .map_err(|e| format!("TTS init task error: {}", e))?
     .map_err(|e| format!("Failed to initialize TTS: {}", e))?;
 
-    // Store in state
-    if let Some(tts_state) = app.try_state::<TtsState>() {
-        let mut guard = tts_state.0.lock().map_err(|e| format!("TTS lock error: {}", e))?;
-        *guard = Some(std::sync::Arc::new(engine));
-    } else {
-        return Err("TTS state not found in app".into());
+    // Store in state, unless speech comes from OpenAI
+    let engine = std::sync::Arc::new(engine);
+    if crate::tts::piper_selected() {
+        if let Ok(config) = Config::load() {
+            crate::tts::warm_in_background(std::sync::Arc::clone(&engine), &config);
+        }
+        if let Some(tts_state) = app.try_state::<TtsState>() {
+            let mut guard = tts_state.0.lock().map_err(|e| format!("TTS lock error: {}", e))?;
+            *guard = Some(engine);
+        } else {
+            return Err("TTS state not found in app".into());
+        }
     }
 
     let _ = app.emit(
@@ -414,6 +1691,132 @@ pub async fn download_tts_model(app: AppHandle, voice: String) -> Result<(), Str
     );
 
     tracing::info!("Piper TTS model downloaded and initialized");
+    Ok(voice)
+}
+
+/// Install a Piper voice from its `.onnx` and `.onnx.json` files and load it, for machines
+/// that can't reach HuggingFace. Returns the voice's name.
+#[tauri::command]
+pub async fn install_voice_from_file(
+    app: AppHandle,
+    onnx_path: String,
+    json_path: String,
+) -> Result<String, String> {
+    let voice = tokio::task::spawn_blocking(move || {
+        crate::models::install_voice(
+            std::path::Path::new(&onnx_path),
+            std::path::Path::new(&json_path),
+        )
+    })
+    .await
+    .map_err(|e| format!("Install task error: {}", e))?
+    .map_err(|e| format!("Failed to install voice: {}", e))?;
+
+    let config_path = crate::tts::voice_config(&voice)
+        .map_err(|e| format!("Failed to get voice config path: {}", e))?;
+    let engine = tokio::task::spawn_blocking(move || {
+        crate::tts::PiperTTSEngine::new(&config_path, None)
+    })
+    .await
+    .map_err(|e| format!("TTS init task error: {}", e))?;
+    let engine = match engine {
+        Ok(engine) => std::sync::Arc::new(engine),
+        Err(e) => {
+            // Don't leave a voice behind that looks installed but can't load
+            if let Ok(dir) = crate::tts::voices_dir() {
+                let _ = std::fs::remove_dir_all(dir.join(&voice));
+            }
+            return Err(format!("Failed to load voice: {}", e));
+        }
+    };
+
+    if crate::tts::piper_selected() {
+        if let Ok(config) = Config::load() {
+            crate::tts::warm_in_background(std::sync::Arc::clone(&engine), &config);
+        }
+        if let Some(tts_state) = app.try_state::<TtsState>() {
+            let mut guard = tts_state.0.lock().map_err(|e| format!("TTS lock error: {}", e))?;
+            *guard = Some(engine);
+        }
+    }
+    tracing::info!("Installed voice {} from file", voice);
+    Ok(voice)
+}
+
+#[tauri::command]
+pub fn list_downloaded_models() -> Result<Vec<crate::models::DownloadedModel>, String> {
+    crate::models::list_downloaded().map_err(|e| format!("Failed to list models: {}", e))
+}
+
+#[tauri::command]
+pub fn delete_model(path: String, tts_state: State<'_, TtsState>) -> Result<(), String> {
+    tracing::info!("delete_model called: {}", path);
+
+    let kind = crate::models::delete(&path).map_err(|e| format!("Failed to delete model: {}", e))?;
+
+    // Don't leave the config pointing at something that no longer exists
+    let mut config = Config::load().map_err(|e| format!("Failed to load config: {}", e))?;
+    match kind {
+        crate::models::ModelKind::Llm => {
+            if config.builtin_model_path.as_deref() == Some(path.as_str()) {
+                config.builtin_model_path = None;
+                config
+                    .save()
+                    .map_err(|e| format!("Failed to save config: {}", e))?;
+            }
+        }
+        crate::models::ModelKind::Voice => {
+            let voice_dir = std::path::Path::new(&path)
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string());
+            if config.tts_voice.is_some() && config.tts_voice == voice_dir {
+                let mut guard = tts_state.0.lock().map_err(|e| format!("TTS lock error: {}", e))?;
+                *guard = None;
+                tracing::info!("Unloaded TTS engine for deleted voice");
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn inspect_model(path: String) -> Result<crate::llm::gguf::GgufInfo, String> {
+    // Vocab arrays mean reading a few MB of header; keep it off the async runtime
+    tokio::task::spawn_blocking(move || crate::llm::gguf::inspect(std::path::Path::new(&path)))
+        .await
+        .map_err(|e| format!("Inspect task failed: {}", e))?
+        .map_err(|e| format!("Failed to inspect model: {}", e))
+}
+
+/// Usage, spend and disk use for the dashboard.
+#[tauri::command]
+pub fn get_dashboard_data() -> crate::stats::DashboardData {
+    crate::stats::dashboard()
+}
+
+#[tauri::command]
+pub fn get_storage_usage() -> Result<crate::models::StorageUsage, String> {
+    crate::models::storage_usage().map_err(|e| format!("Failed to compute storage usage: {}", e))
+}
+
+/// Pop up the character's right-click menu; see `context_menu`.
+#[tauri::command]
+pub fn show_context_menu(window: WebviewWindow) -> Result<(), String> {
+    crate::context_menu::show(&window).map_err(|e| format!("Failed to show menu: {}", e))
+}
+
+/// Accept Clippy's offer to help with a new document; the request goes to the chat like a
+/// quick action.
+#[tauri::command]
+pub fn document_action(
+    app: AppHandle,
+    path: String,
+    action: crate::documents::DocumentAction,
+) -> Result<(), String> {
+    let prompt = crate::documents::action_prompt(std::path::Path::new(&path), action)
+        .map_err(|e| format!("Failed to help with document: {}", e))?;
+    crate::context_menu::send_prompt(&app, prompt);
     Ok(())
 }
 
@@ -421,21 +1824,241 @@ pub async fn download_tts_model(app: AppHandle, voice: String) -> Result<(), Str
 pub fn open_settings_window(app: AppHandle) -> Result<(), String> {
     // Check if settings window already exists
     if let Some(window) = app.get_webview_window("settings") {
+        crate::window_state::move_to_cursor_monitor(&window);
         let _ = window.show();
         let _ = window.set_focus();
         return Ok(());
     }
     
-    let _settings_window = WebviewWindowBuilder::new(&app, "settings", WebviewUrl::App("settings.html".into()))
+    let settings_window = WebviewWindowBuilder::new(&app, "settings", WebviewUrl::App("settings.html".into()))
         .title("Clippy Settings")
         .inner_size(420.0, 620.0)
         .resizable(true)
         .decorations(true)
         .center()
+        .visible(false)
         .build()
         .map_err(|e| format!("Failed to create settings window: {}", e))?;
-    
+
+    // Reopen where it was last closed, on the screen being used, then show it so it doesn't jump
+    crate::window_state::manage(&settings_window);
+    crate::window_state::move_to_cursor_monitor(&settings_window);
+    let _ = settings_window.show();
+
+    Ok(())
+}
+
+/// The last `lines` lines of the app log (default 200), for attaching to bug reports.
+#[tauri::command]
+pub fn get_recent_logs(lines: Option<usize>) -> Result<Vec<String>, String> {
+    crate::logging::recent_lines(lines.unwrap_or(200))
+        .map_err(|e| format!("Failed to read logs: {}", e))
+}
+
+#[tauri::command]
+pub fn open_log_folder(app: AppHandle) -> Result<(), String> {
+    use tauri_plugin_opener::OpenerExt;
+
+    let dir = crate::logging::log_dir().map_err(|e| format!("Failed to find log folder: {}", e))?;
+    app.opener()
+        .open_path(dir.to_string_lossy(), None::<&str>)
+        .map_err(|e| format!("Failed to open log folder: {}", e))
+}
+
+/// Turn logging of every prompt and reply to `requests.jsonl` on or off.
+#[tauri::command]
+pub fn set_request_log(enabled: bool) -> Result<(), String> {
+    let mut config = Config::load().map_err(|e| format!("Failed to load config: {}", e))?;
+    config.request_log = enabled;
+    config.save().map_err(|e| format!("Failed to save config: {}", e))?;
+    tracing::info!("Request log turned {}", if enabled { "on" } else { "off" });
+    Ok(())
+}
+
+/// Open `requests.jsonl` in the default app, creating it empty if nothing was logged yet.
+#[tauri::command]
+pub fn open_request_log(app: AppHandle) -> Result<(), String> {
+    use tauri_plugin_opener::OpenerExt;
+
+    let path = crate::request_log::path()
+        .map_err(|e| format!("Failed to find request log: {}", e))?;
+    std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| format!("Failed to create request log: {}", e))?;
+    app.opener()
+        .open_path(path.to_string_lossy(), None::<&str>)
+        .map_err(|e| format!("Failed to open request log: {}", e))
+}
+
+/// Developer tool: send each request in a request log through the current provider again
+/// and report how the responses and timings differ. Each turn is also sent as `replay-turn`.
+#[tauri::command]
+pub async fn replay_log(
+    app: AppHandle,
+    path: String,
+) -> Result<crate::replay::ReplayReport, String> {
+    crate::replay::replay(&app, std::path::Path::new(&path))
+        .await
+        .map_err(|e| format!("Failed to replay log: {}", e))
+}
+
+/// Environment details for bug reports (no secrets).
+#[tauri::command]
+pub async fn get_diagnostics(
+    app: AppHandle,
+    tts_state: State<'_, TtsState>,
+) -> Result<crate::diagnostics::Diagnostics, String> {
+    let app_version = app.package_info().version.to_string();
+    let tts_loaded = tts_state.0.lock().map(|t| t.is_some()).unwrap_or(false);
+    tokio::task::spawn_blocking(move || crate::diagnostics::collect(app_version, tts_loaded))
+        .await
+        .map_err(|e| format!("Diagnostics task failed: {}", e))?
+        .map_err(|e| format!("Failed to collect diagnostics: {}", e))
+}
+
+/// A crash dump from a previous run that hasn't been shown yet, if any.
+#[tauri::command]
+pub fn get_pending_crash_report() -> Result<Option<crate::crash::CrashReport>, String> {
+    crate::crash::pending().map_err(|e| format!("Failed to check for crash reports: {}", e))
+}
+
+/// Stop offering pending crash reports, optionally revealing this one in the file manager.
+#[tauri::command]
+pub fn acknowledge_crash_report(app: AppHandle, path: String, reveal: bool) -> Result<(), String> {
+    use tauri_plugin_opener::OpenerExt;
+
+    let path = crate::crash::acknowledge(&path)
+        .map_err(|e| format!("Failed to update crash report: {}", e))?;
+    if reveal {
+        app.opener()
+            .reveal_item_in_dir(&path)
+            .map_err(|e| format!("Failed to reveal crash report: {}", e))?;
+    }
     Ok(())
 }
 
+/// Local usage counters for `period` ("day", "week", "month", "year" or "all").
+#[tauri::command]
+pub fn get_stats(period: crate::stats::StatsPeriod) -> crate::stats::UsageStats {
+    crate::stats::summary(period)
+}
+
+/// Give the morning briefing now, whatever the time
+#[tauri::command]
+pub async fn run_briefing(app: AppHandle) -> Result<(), String> {
+    let config = Config::load().map_err(|e| format!("Failed to load config: {}", e))?;
+    crate::briefing::deliver(&app, &config).await
+}
+
+#[tauri::command]
+pub fn start_pomodoro(app: AppHandle) -> crate::pomodoro::PomodoroStatus {
+    crate::pomodoro::start(&app)
+}
+
+#[tauri::command]
+pub fn stop_pomodoro(app: AppHandle) {
+    crate::pomodoro::stop(&app);
+}
+
+#[tauri::command]
+pub fn get_pomodoro_status() -> crate::pomodoro::PomodoroStatus {
+    crate::pomodoro::status()
+}
+
+#[tauri::command]
+pub fn list_reminders() -> Result<Vec<crate::reminders::Reminder>, String> {
+    crate::reminders::pending().map_err(|e| format!("Failed to list reminders: {}", e))
+}
+
+/// Schedule a reminder; `due_at` is a Unix timestamp in seconds.
+#[tauri::command]
+pub fn add_reminder(text: String, due_at: i64) -> Result<crate::reminders::Reminder, String> {
+    crate::reminders::add(&text, due_at).map_err(|e| format!("Failed to save reminder: {}", e))
+}
+
+/// Every tool Clippy can call, with its settings
+#[tauri::command]
+pub fn list_tools() -> Result<Vec<crate::tools::ToolInfo>, String> {
+    let config = Config::load().map_err(|e| format!("Failed to load config: {}", e))?;
+    Ok(crate::tools::ToolRegistry::new(&config).list())
+}
+
+/// Answer a `tool-approval-request` from the chat
+#[tauri::command]
+pub fn respond_tool_approval(
+    id: u64,
+    decision: crate::tools::ApprovalDecision,
+) -> Result<(), String> {
+    crate::tools::answer(id, decision).map_err(|e| format!("Failed to answer approval: {}", e))
+}
+
+/// A picture from the image tool, as a `data:` URL for an `<img>`
+#[tauri::command]
+pub fn load_generated_image(path: String) -> Result<String, String> {
+    crate::tools::image::data_url(&path).map_err(|e| format!("Failed to load image: {}", e))
+}
+
+/// What was said in a recording from the chat's microphone button. `audio` is base64, as
+/// the webview's `MediaRecorder` made it, e.g. `audio/webm;codecs=opus`.
+#[tauri::command]
+pub async fn transcribe_audio(audio: String, mime_type: String) -> Result<String, String> {
+    use base64::Engine;
+    let audio = base64::engine::general_purpose::STANDARD
+        .decode(audio)
+        .map_err(|e| format!("Failed to read recording: {}", e))?;
+    let config = Config::load().map_err(|e| format!("Failed to load config: {}", e))?;
+    crate::stt::transcribe(&config, &audio, &mime_type)
+        .await
+        .map_err(|e| format!("Failed to transcribe: {}", e))
+}
+
+/// Start a countdown timer; `duration` is written like "20 minutes" or "1h30m".
+#[tauri::command]
+pub fn set_timer(
+    duration: String,
+    label: Option<String>,
+) -> Result<crate::reminders::Reminder, String> {
+    crate::reminders::add_timer(&duration, label.as_deref())
+        .map_err(|e| format!("Failed to set timer: {}", e))
+}
+
+#[tauri::command]
+pub fn cancel_reminder(id: i64) -> Result<(), String> {
+    crate::reminders::cancel(id).map_err(|e| format!("Failed to cancel reminder: {}", e))
+}
+
+/// Look for a newer release on the configured channel; `None` when up to date.
+#[tauri::command]
+pub async fn check_for_updates(app: AppHandle) -> Result<Option<UpdateEvent>, String> {
+    let config = Config::load().map_err(|e| format!("Failed to load config: {}", e))?;
+    crate::updates::check(&app, config.update_channel)
+        .await
+        .map(|update| {
+            update.map(|u| UpdateEvent {
+                version: u.version,
+                notes: u.body,
+            })
+        })
+        .map_err(|e| format!("Failed to check for updates: {}", e))
+}
+
+/// Download and install the latest release, emitting `update-progress` then `update-ready`.
+#[tauri::command]
+pub async fn install_update(app: AppHandle) -> Result<(), String> {
+    let config = Config::load().map_err(|e| format!("Failed to load config: {}", e))?;
+    crate::updates::install(&app, config.update_channel)
+        .await
+        .map_err(|e| format!("Failed to install update: {}", e))
+}
+
+/// Relaunch to finish an installed update
+#[tauri::command]
+pub fn restart_app(app: AppHandle) {
+    // Restarting skips the exit event that saves pending usage stats
+    crate::stats::flush();
+    app.restart();
+}
+
 // open_chat_window removed — chat is now an inline bubble in the main window
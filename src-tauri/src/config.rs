@@ -1,7 +1,34 @@
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 use anyhow::Result;
 
+/// Current config schema version. Bump it and append to `MIGRATIONS`
+/// whenever a field is renamed or restructured.
+pub const CONFIG_VERSION: u32 = 3;
+
+/// Migration `i` upgrades a raw config object from version `i` to `i + 1`
+type Migration = fn(&mut Map<String, Value>);
+const MIGRATIONS: &[Migration] = &[migrate_v0_to_v1, migrate_v1_to_v2, migrate_v2_to_v3];
+
+/// v0 is every config written before versioning existed; its layout is
+/// identical to v1, it just lacks the `version` field.
+fn migrate_v0_to_v1(_config: &mut Map<String, Value>) {}
+
+/// v2 keeps window layout in state.json (see `runtime_state`), which `load_file` fills
+/// from the old values before they're dropped here.
+fn migrate_v1_to_v2(config: &mut Map<String, Value>) {
+    config.remove("windows");
+    config.remove("dock");
+}
+
+/// v3 keeps the recent models list in state.json too.
+fn migrate_v2_to_v3(config: &mut Map<String, Value>) {
+    config.remove("recent_models");
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum LlmProviderType {
     OpenAI,
@@ -9,6 +36,8 @@ pub enum LlmProviderType {
     Ollama,
     CustomAPI,
     BuiltIn,
+    /// Canned or echoed replies, for demos and testing without a model
+    Mock,
 }
 
 impl Default for LlmProviderType {
@@ -17,8 +46,780 @@ impl Default for LlmProviderType {
     }
 }
 
+/// Which engine speaks Clippy's replies
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub enum TtsEngineType {
+    /// Offline neural voices downloaded to this machine
+    #[default]
+    Piper,
+    /// OpenAI's speech API, using the OpenAI API key
+    OpenAI,
+    /// ElevenLabs' speech API, streamed as it's generated
+    ElevenLabs,
+}
+
+/// What the speech queue does with another sentence of a reply when it's already full
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TtsQueuePolicy {
+    /// Wait for a sentence to finish playing before preparing the next, so nothing is skipped
+    #[default]
+    Block,
+    /// Skip the oldest waiting sentence to make room, so speech keeps up with the end
+    DropOldest,
+    /// Skip the new sentence, so the start of the reply plays in full
+    DropNewest,
+}
+
+impl LlmProviderType {
+    /// Parse the short, case-insensitive names accepted in `RUSTY_CLIPPY_PROVIDER`.
+    fn from_env_name(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "openai" => Some(Self::OpenAI),
+            "lmstudio" | "lm-studio" | "lm_studio" => Some(Self::LMStudio),
+            "ollama" => Some(Self::Ollama),
+            "custom" | "customapi" | "custom-api" => Some(Self::CustomAPI),
+            "builtin" | "built-in" | "local" => Some(Self::BuiltIn),
+            "mock" | "echo" => Some(Self::Mock),
+            _ => None,
+        }
+    }
+}
+
+/// Prefix of the environment variables that override config values at load time
+const ENV_PREFIX: &str = "RUSTY_CLIPPY_";
+
+/// Config values supplied through `RUSTY_CLIPPY_PROVIDER`, `_API_KEY`, `_BASE_URL`
+/// and `_MODEL`. They win over config.json but are never written back to it.
+#[derive(Default)]
+struct EnvOverrides {
+    provider: Option<LlmProviderType>,
+    api_key: Option<String>,
+    base_url: Option<String>,
+    model: Option<String>,
+}
+
+impl EnvOverrides {
+    fn from_env() -> Self {
+        let var = |name: &str| {
+            std::env::var(format!("{}{}", ENV_PREFIX, name))
+                .ok()
+                .filter(|v| !v.trim().is_empty())
+        };
+        let provider = var("PROVIDER").and_then(|name| {
+            let provider = LlmProviderType::from_env_name(&name);
+            if provider.is_none() {
+                tracing::warn!("Ignoring unknown {}PROVIDER '{}'", ENV_PREFIX, name);
+            }
+            provider
+        });
+
+        Self {
+            provider,
+            api_key: var("API_KEY"),
+            base_url: var("BASE_URL"),
+            model: var("MODEL"),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.provider.is_none()
+            && self.api_key.is_none()
+            && self.base_url.is_none()
+            && self.model.is_none()
+    }
+
+    /// The key and model land in whichever fields the effective provider reads.
+    fn apply(&self, config: &mut Config) {
+        if let Some(provider) = &self.provider {
+            config.llm_provider = provider.clone();
+        }
+        if let Some(key) = &self.api_key {
+            match config.llm_provider {
+                LlmProviderType::OpenAI => config.openai_api_key = Some(key.clone()),
+                _ => config.custom_api_key = Some(key.clone()),
+            }
+        }
+        if let Some(url) = &self.base_url {
+            config.custom_api_url = Some(url.clone());
+        }
+        if let Some(model) = &self.model {
+            match config.llm_provider {
+                LlmProviderType::OpenAI => config.openai_model = model.clone(),
+                LlmProviderType::BuiltIn => config.builtin_model_path = Some(model.clone()),
+                LlmProviderType::Mock => {}
+                _ => config.custom_model = Some(model.clone()),
+            }
+        }
+    }
+
+    /// Undo `apply` by copying the overridden fields back from `stored`.
+    fn restore(&self, config: &mut Config, stored: &Config) {
+        // Key and model targets depend on the overridden provider, so restore it last
+        if self.api_key.is_some() {
+            match config.llm_provider {
+                LlmProviderType::OpenAI => config.openai_api_key = stored.openai_api_key.clone(),
+                _ => config.custom_api_key = stored.custom_api_key.clone(),
+            }
+        }
+        if self.base_url.is_some() {
+            config.custom_api_url = stored.custom_api_url.clone();
+        }
+        if self.model.is_some() {
+            match config.llm_provider {
+                LlmProviderType::OpenAI => config.openai_model = stored.openai_model.clone(),
+                LlmProviderType::BuiltIn => {
+                    config.builtin_model_path = stored.builtin_model_path.clone()
+                }
+                LlmProviderType::Mock => {}
+                _ => config.custom_model = stored.custom_model.clone(),
+            }
+        }
+        if self.provider.is_some() {
+            config.llm_provider = stored.llm_provider.clone();
+        }
+    }
+}
+
+/// How `custom_system_prompt` combines with the personality's prompt
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub enum SystemPromptMode {
+    /// Appended after the personality prompt as extra instructions
+    #[default]
+    Augment,
+    /// Used instead of the personality prompt
+    Replace,
+}
+
+/// Style dials layered on top of any personality, each 0–10 with 5 meaning "as written"
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct PersonalityTuning {
+    #[serde(default = "neutral_tuning")]
+    pub snark: u8,
+    #[serde(default = "neutral_tuning")]
+    pub verbosity: u8,
+    #[serde(default = "neutral_tuning")]
+    pub enthusiasm: u8,
+    #[serde(default = "neutral_tuning")]
+    pub formality: u8,
+}
+
+pub const TUNING_MAX: u8 = 10;
+
+fn neutral_tuning() -> u8 {
+    TUNING_MAX / 2
+}
+
+impl Default for PersonalityTuning {
+    fn default() -> Self {
+        Self {
+            snark: neutral_tuning(),
+            verbosity: neutral_tuning(),
+            enthusiasm: neutral_tuning(),
+            formality: neutral_tuning(),
+        }
+    }
+}
+
+/// A provider plus the model it should run, as offered by the tray's model switcher
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelChoice {
+    pub provider: LlmProviderType,
+    /// Model name, or the model file's path for BuiltIn
+    pub model: String,
+}
+
+impl ModelChoice {
+    /// Short menu label, e.g. "Local: gemma-2b.gguf" or "OpenAI: gpt-4o"
+    pub fn label(&self) -> String {
+        let provider = match self.provider {
+            LlmProviderType::OpenAI => "OpenAI",
+            LlmProviderType::LMStudio => "LM Studio",
+            LlmProviderType::Ollama => "Ollama",
+            LlmProviderType::CustomAPI => "Custom API",
+            LlmProviderType::BuiltIn => "Local",
+            LlmProviderType::Mock => "Mock",
+        };
+        let model = match self.provider {
+            LlmProviderType::BuiltIn => Path::new(&self.model)
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_else(|| self.model.clone()),
+            _ => self.model.clone(),
+        };
+        if model.is_empty() {
+            provider.to_string()
+        } else {
+            format!("{}: {}", provider, model)
+        }
+    }
+}
+
+/// Where a window was last left, in physical pixels
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WindowGeometry {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// What happens to a reasoning model's chain of thought (see `reasoning`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReasoningDisplay {
+    /// Shown folded above the reply
+    #[default]
+    Collapsed,
+    /// Kept out of the chat, but still in the request log
+    Hidden,
+    /// Dropped as it arrives, from the request log too
+    Discard,
+}
+
+/// Which releases the updater offers
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UpdateChannel {
+    #[default]
+    Stable,
+    /// Pre-releases, for trying fixes before they ship
+    Beta,
+}
+
+/// Screen edge Clippy is docked against on the horizontal axis
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HorizontalEdge {
+    Left,
+    Right,
+}
+
+/// Screen edge Clippy is docked against on the vertical axis
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VerticalEdge {
+    Top,
+    Bottom,
+}
+
+/// Where Clippy is docked; both edges set means a corner, neither means floating
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Dock {
+    pub horizontal: Option<HorizontalEdge>,
+    pub vertical: Option<VerticalEdge>,
+}
+
+/// When Clippy may pipe up on its own with tips and quips
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProactiveConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Minutes without interaction before Clippy speaks up
+    #[serde(default = "default_idle_minutes")]
+    pub idle_minutes: u32,
+    /// Upper bound on proactive messages in any hour
+    #[serde(default = "default_max_per_hour")]
+    pub max_per_hour: u32,
+    /// Local hour (0–23) quiet hours begin; quiet hours may wrap midnight
+    #[serde(default = "default_quiet_start")]
+    pub quiet_hours_start: Option<u8>,
+    /// Local hour (0–23) quiet hours end
+    #[serde(default = "default_quiet_end")]
+    pub quiet_hours_end: Option<u8>,
+    /// Also read proactive messages aloud when TTS is enabled
+    #[serde(default)]
+    pub speak: bool,
+    /// While hidden, Clippy now and then peeks in from the screen edge
+    #[serde(default)]
+    pub peek: bool,
+    /// Minutes between peeks
+    #[serde(default = "default_peek_minutes")]
+    pub peek_minutes: u32,
+}
+
+fn default_idle_minutes() -> u32 {
+    15
+}
+
+fn default_max_per_hour() -> u32 {
+    2
+}
+
+fn default_peek_minutes() -> u32 {
+    30
+}
+
+fn default_quiet_start() -> Option<u8> {
+    Some(22)
+}
+
+fn default_quiet_end() -> Option<u8> {
+    Some(8)
+}
+
+impl Default for ProactiveConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            idle_minutes: default_idle_minutes(),
+            max_per_hour: default_max_per_hour(),
+            quiet_hours_start: default_quiet_start(),
+            quiet_hours_end: default_quiet_end(),
+            speak: false,
+            peek: false,
+            peek_minutes: default_peek_minutes(),
+        }
+    }
+}
+
+/// What the mock provider answers with
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MockMode {
+    /// Cycle through `responses`
+    #[default]
+    Canned,
+    /// Repeat the user's message back
+    Echo,
+}
+
+/// Settings for `LlmProviderType::Mock`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MockConfig {
+    #[serde(default)]
+    pub mode: MockMode,
+    /// Delay before each streamed word, to mimic a real model's pace
+    #[serde(default = "default_mock_latency_ms")]
+    pub latency_ms: u64,
+    #[serde(default = "default_mock_responses")]
+    pub responses: Vec<String>,
+}
+
+fn default_mock_latency_ms() -> u64 {
+    40
+}
+
+fn default_mock_responses() -> Vec<String> {
+    [
+        "It looks like you're trying out Rusty Clippy! I'm running in demo mode, so my answers are canned.",
+        "Did you know? Paperclips were patented in 1867. I've been helping ever since.",
+        "I'd love to help with that. Configure a real model in Settings and I'll give you a proper answer.",
+    ]
+    .map(String::from)
+    .to_vec()
+}
+
+impl Default for MockConfig {
+    fn default() -> Self {
+        Self {
+            mode: MockMode::default(),
+            latency_ms: default_mock_latency_ms(),
+            responses: default_mock_responses(),
+        }
+    }
+}
+
+/// How much saved conversation history to keep. History only ever lives on this machine;
+/// these limits are applied at startup, and `None` means no limit.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HistoryConfig {
+    /// Most conversations to keep, newest first
+    #[serde(default)]
+    pub max_conversations: Option<usize>,
+    /// Delete conversations not touched for this many days
+    #[serde(default)]
+    pub max_age_days: Option<u32>,
+    /// Most disk space the saved conversations may use, in megabytes
+    #[serde(default)]
+    pub max_disk_mb: Option<u64>,
+}
+
+/// Settings for one of Clippy's tools, keyed by tool name in `Config::tools`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolSettings {
+    #[serde(default = "default_tool_enabled")]
+    pub enabled: bool,
+    /// For tools that touch files: the folders they're confined to; empty allows any
+    #[serde(default)]
+    pub allowed_dirs: Vec<String>,
+    /// For tools backed by a service that takes a key
+    #[serde(default)]
+    pub api_key: Option<String>,
+    /// Run without asking first, once the user has chosen "always allow"
+    #[serde(default)]
+    pub always_allow: bool,
+}
+
+fn default_tool_enabled() -> bool {
+    true
+}
+
+impl Default for ToolSettings {
+    fn default() -> Self {
+        Self {
+            enabled: default_tool_enabled(),
+            allowed_dirs: Vec::new(),
+            api_key: None,
+            always_allow: false,
+        }
+    }
+}
+
+/// Temperature and wind speed units for weather reports
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WeatherUnits {
+    Metric,
+    Imperial,
+}
+
+/// Where the weather tool looks when no place is named
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WeatherConfig {
+    /// Place name, e.g. "Lisbon"; `None` approximates it from the IP address
+    #[serde(default)]
+    pub location: Option<String>,
+    /// `None` picks by locale
+    #[serde(default)]
+    pub units: Option<WeatherUnits>,
+}
+
+/// Read-only calendars Clippy warns about and includes in the briefing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalendarConfig {
+    /// ICS feeds: `https://` or `webcal://` URLs, or paths to `.ics` files
+    #[serde(default)]
+    pub sources: Vec<String>,
+    /// Warn this many minutes before an event starts; `None` never warns
+    #[serde(default = "default_calendar_alert_minutes")]
+    pub alert_minutes: Option<u32>,
+}
+
+fn default_calendar_alert_minutes() -> Option<u32> {
+    Some(10)
+}
+
+impl Default for CalendarConfig {
+    fn default() -> Self {
+        Self {
+            sources: Vec::new(),
+            alert_minutes: default_calendar_alert_minutes(),
+        }
+    }
+}
+
+/// Where the image tool draws
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ImageBackend {
+    #[default]
+    OpenAI,
+    /// A Stable Diffusion web UI running locally with its API enabled
+    StableDiffusion,
+}
+
+/// Settings for the `generate_image` tool
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImageConfig {
+    #[serde(default)]
+    pub backend: ImageBackend,
+    /// OpenAI image model, e.g. "gpt-image-1" or "dall-e-3"
+    #[serde(default = "default_image_model")]
+    pub model: String,
+    /// Picture size as WIDTHxHEIGHT
+    #[serde(default = "default_image_size")]
+    pub size: String,
+    /// Base URL of the Stable Diffusion web UI, e.g. "http://127.0.0.1:7860"
+    #[serde(default)]
+    pub stable_diffusion_url: Option<String>,
+}
+
+fn default_image_model() -> String {
+    "gpt-image-1".to_string()
+}
+
+fn default_image_size() -> String {
+    "1024x1024".to_string()
+}
+
+impl ImageConfig {
+    /// `size` as width and height in pixels
+    pub fn dimensions(&self) -> Option<(u32, u32)> {
+        let (width, height) = self.size.trim().split_once('x')?;
+        let width: u32 = width.parse().ok()?;
+        let height: u32 = height.parse().ok()?;
+        (width > 0 && height > 0).then_some((width, height))
+    }
+}
+
+impl Default for ImageConfig {
+    fn default() -> Self {
+        Self {
+            backend: ImageBackend::default(),
+            model: default_image_model(),
+            size: default_image_size(),
+            stable_diffusion_url: None,
+        }
+    }
+}
+
+/// How the chat's microphone button turns speech into text
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SttBackend {
+    /// No microphone button
+    #[default]
+    Off,
+    /// OpenAI's `/v1/audio/transcriptions`, for machines too slow to transcribe locally
+    OpenAI,
+}
+
+/// Settings for talking to Clippy instead of typing (see `stt`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SttConfig {
+    #[serde(default)]
+    pub backend: SttBackend,
+    /// OpenAI transcription model, e.g. "whisper-1" or "gpt-4o-mini-transcribe"
+    #[serde(default = "default_stt_model")]
+    pub model: String,
+    /// Language spoken, as an ISO-639-1 code like "en"; detected when unset
+    #[serde(default)]
+    pub language: Option<String>,
+}
+
+fn default_stt_model() -> String {
+    "whisper-1".to_string()
+}
+
+impl Default for SttConfig {
+    fn default() -> Self {
+        Self {
+            backend: SttBackend::default(),
+            model: default_stt_model(),
+            language: None,
+        }
+    }
+}
+
+/// Folders of the user's own documents Clippy answers from
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KnowledgeConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Searched recursively for text, Markdown and PDF files
+    #[serde(default)]
+    pub folders: Vec<String>,
+    /// Most passages added to the prompt for one question
+    #[serde(default = "default_knowledge_max_chunks")]
+    pub max_chunks: usize,
+}
+
+fn default_knowledge_max_chunks() -> usize {
+    4
+}
+
+impl Default for KnowledgeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            folders: Vec::new(),
+            max_chunks: default_knowledge_max_chunks(),
+        }
+    }
+}
+
+/// A model's price in US dollars per million tokens
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct ModelPrice {
+    #[serde(default)]
+    pub input: f64,
+    #[serde(default)]
+    pub output: f64,
+}
+
+/// Caps on requests to paid providers, so a runaway feature (or a curious child) can't run
+/// up a bill. `None` means no cap.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RateLimitConfig {
+    #[serde(default)]
+    pub requests_per_minute: Option<u32>,
+    #[serde(default)]
+    pub requests_per_hour: Option<u32>,
+    /// Estimated tokens sent and received per local day, across paid providers
+    #[serde(default)]
+    pub tokens_per_day: Option<u64>,
+}
+
+/// Monthly spending limits on paid providers (see `budget`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BudgetConfig {
+    /// US dollars a calendar month, by provider ("OpenAI" or "CustomAPI")
+    #[serde(default)]
+    pub monthly_usd: BTreeMap<String, f64>,
+    /// Over a limit, answer with the built-in model if one is set up instead of refusing
+    #[serde(default = "default_budget_fallback")]
+    pub fallback_to_local: bool,
+}
+
+fn default_budget_fallback() -> bool {
+    true
+}
+
+impl Default for BudgetConfig {
+    fn default() -> Self {
+        Self {
+            monthly_usd: BTreeMap::new(),
+            fallback_to_local: default_budget_fallback(),
+        }
+    }
+}
+
+/// Ask several models at once and have one of them, the judge, write the reply from their
+/// answers (see `ensemble`)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EnsembleConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Models asked in parallel
+    #[serde(default)]
+    pub members: Vec<ModelChoice>,
+    /// Model that picks the best answer or merges them; the current model when unset
+    #[serde(default)]
+    pub judge: Option<ModelChoice>,
+}
+
+/// Different behavior while a certain app is focused. A rule matches when every condition
+/// it sets holds; the first matching rule in the list wins.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AppRule {
+    /// Part of the focused app's name, ignoring case (e.g. "code")
+    #[serde(default)]
+    pub app: Option<String>,
+    /// Part of the focused window's title, ignoring case
+    #[serde(default)]
+    pub title: Option<String>,
+    /// Only while that window is fullscreen
+    #[serde(default)]
+    pub fullscreen: bool,
+    /// Personality to chat as
+    #[serde(default)]
+    pub personality: Option<String>,
+    /// Model to answer with
+    #[serde(default)]
+    pub model: Option<ModelChoice>,
+    /// Turn unprompted tips on or off
+    #[serde(default)]
+    pub proactive: Option<bool>,
+    /// No tips, sound effects or speech at all
+    #[serde(default)]
+    pub silent: bool,
+}
+
+/// A recurring stretch of do-not-disturb, e.g. evenings or a daily standup
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DndWindow {
+    /// Local time it starts, as `HH:MM`; a window may wrap midnight
+    pub start: String,
+    /// Local time it ends, as `HH:MM`
+    pub end: String,
+    /// Days it starts on, like "mon" or "friday"; empty means every day
+    #[serde(default)]
+    pub days: Vec<String>,
+}
+
+/// When Clippy sleeps: no tips, speech or notifications
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DndConfig {
+    #[serde(default)]
+    pub schedule: Vec<DndWindow>,
+}
+
+/// Folders watched for new documents, which Clippy offers to help write
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DocumentWatchConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Only new files directly in these folders count, e.g. Documents and Desktop
+    #[serde(default)]
+    pub folders: Vec<String>,
+}
+
+/// The spoken morning briefing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BriefingConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Local time of day, as `HH:MM`; a later launch still gets that day's briefing
+    #[serde(default = "default_briefing_time")]
+    pub time: String,
+    /// Read the briefing aloud when TTS is enabled
+    #[serde(default = "default_briefing_speak")]
+    pub speak: bool,
+}
+
+fn default_briefing_time() -> String {
+    "08:00".to_string()
+}
+
+fn default_briefing_speak() -> bool {
+    true
+}
+
+impl Default for BriefingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            time: default_briefing_time(),
+            speak: default_briefing_speak(),
+        }
+    }
+}
+
+/// Focus timer lengths, in minutes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PomodoroConfig {
+    #[serde(default = "default_work_minutes")]
+    pub work_minutes: u32,
+    #[serde(default = "default_short_break_minutes")]
+    pub short_break_minutes: u32,
+    #[serde(default = "default_long_break_minutes")]
+    pub long_break_minutes: u32,
+    /// Every this many pomodoros, take the long break instead
+    #[serde(default = "default_long_break_every")]
+    pub long_break_every: u32,
+}
+
+fn default_work_minutes() -> u32 {
+    25
+}
+
+fn default_short_break_minutes() -> u32 {
+    5
+}
+
+fn default_long_break_minutes() -> u32 {
+    15
+}
+
+fn default_long_break_every() -> u32 {
+    4
+}
+
+impl Default for PomodoroConfig {
+    fn default() -> Self {
+        Self {
+            work_minutes: default_work_minutes(),
+            short_break_minutes: default_short_break_minutes(),
+            long_break_minutes: default_long_break_minutes(),
+            long_break_every: default_long_break_every(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
+    /// Schema version; missing in files written before versioning (treated as 0)
+    #[serde(default)]
+    pub version: u32,
     #[serde(default)]
     pub llm_provider: LlmProviderType,
     pub openai_api_key: Option<String>,
@@ -30,14 +831,185 @@ pub struct Config {
     pub custom_api_key: Option<String>,
     #[serde(default)]
     pub custom_model: Option<String>,
+    /// With LM Studio, ask it to load `custom_model` before chatting when it isn't loaded yet
+    #[serde(default)]
+    pub lmstudio_load_on_chat: bool,
     #[serde(default)]
     pub builtin_model_path: Option<String>,
+    /// What paid models cost, by model name, for ones `pricing` doesn't know (e.g. through a
+    /// custom API) or whose price changed
+    #[serde(default)]
+    pub model_prices: BTreeMap<String, ModelPrice>,
+    #[serde(default)]
+    pub rate_limits: RateLimitConfig,
+    #[serde(default)]
+    pub budget: BudgetConfig,
+    #[serde(default)]
+    pub ensemble: EnsembleConfig,
     #[serde(default = "default_temperature")]
     pub temperature: f32,
+    /// Fixed sampling seed, so a prompt can be replayed exactly. `None` picks a fresh one each time.
+    #[serde(default)]
+    pub seed: Option<u32>,
+    /// Whether reasoning models' thoughts are shown; they never enter the history or speech
+    #[serde(default)]
+    pub reasoning: ReasoningDisplay,
+    /// The model's context window in tokens, for servers that don't match the built-in
+    /// guess. `None` uses the provider's.
+    #[serde(default)]
+    pub context_tokens: Option<u32>,
+    /// Add pinned messages from saved conversations to every system prompt
+    #[serde(default)]
+    pub pins_in_context: bool,
     #[serde(default)]
     pub tts_enabled: bool,
     #[serde(default)]
     pub tts_voice: Option<String>,
+    #[serde(default)]
+    pub tts_engine: TtsEngineType,
+    /// Speech model for the OpenAI engine, e.g. "gpt-4o-mini-tts" or "tts-1-hd"
+    #[serde(default = "default_openai_tts_model")]
+    pub openai_tts_model: String,
+    /// One of OpenAI's built-in voices, e.g. "alloy" or "nova"
+    #[serde(default = "default_openai_tts_voice")]
+    pub openai_tts_voice: String,
+    #[serde(default)]
+    pub elevenlabs_api_key: Option<String>,
+    /// The ElevenLabs voice to speak with, by its voice ID
+    #[serde(default = "default_elevenlabs_voice_id")]
+    pub elevenlabs_voice_id: String,
+    /// ElevenLabs model; the flash models start speaking soonest
+    #[serde(default = "default_elevenlabs_model")]
+    pub elevenlabs_model: String,
+    /// Phrases synthesized as soon as a voice loads so they play instantly, on top of the
+    /// personality's greetings
+    #[serde(default = "default_tts_cached_phrases")]
+    pub tts_cached_phrases: Vec<String>,
+    /// Read only this many sentences of a reply aloud and point to the chat for the rest,
+    /// so long answers don't turn into minutes of audio. `None` reads everything.
+    #[serde(default)]
+    pub tts_max_sentences: Option<u32>,
+    /// How many sentences may wait, already synthesized, while an earlier one plays
+    #[serde(default = "default_tts_queue_size")]
+    pub tts_queue_size: u32,
+    /// What happens to the next sentence when that many are already waiting
+    #[serde(default)]
+    pub tts_queue_policy: TtsQueuePolicy,
+    /// Play the classic appear, knock and tada sounds
+    #[serde(default = "default_sound_effects")]
+    pub sound_effects: bool,
+    /// Sound effect volume, 0-100
+    #[serde(default = "default_sound_volume")]
+    pub sound_volume: u8,
+    /// Load the BuiltIn model during startup instead of on the first message
+    #[serde(default)]
+    pub preload_model: bool,
+    /// After preloading, run a one-token decode so the first reply is fast
+    #[serde(default)]
+    pub warm_up_model: bool,
+    /// Name of the profile this config was last switched to; `None` is the default profile
+    #[serde(default)]
+    pub active_profile: Option<String>,
+    /// How the assistant should address the user (`{{user_name}}` in prompts)
+    #[serde(default)]
+    pub user_name: Option<String>,
+    /// Id of the character to chat as (see `personality::list`)
+    #[serde(default = "default_personality")]
+    pub personality: String,
+    /// Character art and sounds to show (see `agents::list`); `None` uses the personality's
+    #[serde(default)]
+    pub agent: Option<String>,
+    /// User-written instructions for the model; see `system_prompt_mode`
+    #[serde(default)]
+    pub custom_system_prompt: Option<String>,
+    #[serde(default)]
+    pub system_prompt_mode: SystemPromptMode,
+    #[serde(default)]
+    pub personality_tuning: PersonalityTuning,
+    /// ISO 639-1 code of the language to always answer in; `None` follows the user's
+    #[serde(default)]
+    pub response_language: Option<String>,
+    #[serde(default)]
+    pub proactive: ProactiveConfig,
+    #[serde(default)]
+    pub mock: MockConfig,
+    #[serde(default)]
+    pub pomodoro: PomodoroConfig,
+    #[serde(default)]
+    pub briefing: BriefingConfig,
+    #[serde(default)]
+    pub history: HistoryConfig,
+    #[serde(default)]
+    pub knowledge: KnowledgeConfig,
+    #[serde(default)]
+    pub documents: DocumentWatchConfig,
+    /// Per-app behavior, checked against the focused window (see `app_rules`)
+    #[serde(default)]
+    pub app_rules: Vec<AppRule>,
+    /// Do-not-disturb schedule; it can also be toggled from the tray (see `dnd`)
+    #[serde(default)]
+    pub dnd: DndConfig,
+    /// Per-tool settings; tools without an entry use the defaults
+    #[serde(default)]
+    pub tools: BTreeMap<String, ToolSettings>,
+    #[serde(default)]
+    pub weather: WeatherConfig,
+    #[serde(default)]
+    pub calendar: CalendarConfig,
+    #[serde(default)]
+    pub image: ImageConfig,
+    #[serde(default)]
+    pub stt: SttConfig,
+    /// Write a crash dump to the data dir when the app panics (opt-in)
+    #[serde(default)]
+    pub crash_reports: bool,
+    /// Append every prompt and reply to `requests.jsonl` in the logs folder, for debugging
+    /// prompts and chat templates (opt-in; see `request_log`)
+    #[serde(default)]
+    pub request_log: bool,
+    /// Shortcut that summons or hides Clippy from anywhere; `None` disables it
+    #[serde(default = "default_global_hotkey")]
+    pub global_hotkey: Option<String>,
+    /// Snap Clippy to screen edges and corners when dropped near them
+    #[serde(default = "default_snap_to_edges")]
+    pub snap_to_edges: bool,
+    /// Let clicks pass through a faded Clippy while idle; the hotkey or tray brings it back
+    #[serde(default)]
+    pub click_through_idle: bool,
+    /// Keep Clippy (and its chat bubble) above other windows; turn off for screen shares
+    #[serde(default = "default_always_on_top")]
+    pub always_on_top: bool,
+    /// Launch with only the tray icon showing, e.g. when started at login
+    #[serde(default)]
+    pub start_hidden: bool,
+    /// Use OS notifications for tips, finished downloads and errors while Clippy is hidden
+    #[serde(default = "default_native_notifications")]
+    pub native_notifications: bool,
+    /// Keep Clippy out of the taskbar and alt-tab, and out of the Dock on macOS
+    #[serde(default = "default_skip_taskbar")]
+    pub skip_taskbar: bool,
+    #[serde(default)]
+    pub update_channel: UpdateChannel,
+    /// Look for a new release on launch
+    #[serde(default = "default_check_for_updates")]
+    pub check_for_updates: bool,
+}
+
+fn default_openai_tts_model() -> String {
+    "gpt-4o-mini-tts".to_string()
+}
+
+fn default_openai_tts_voice() -> String {
+    "alloy".to_string()
+}
+
+/// ElevenLabs' "Rachel" premade voice
+fn default_elevenlabs_voice_id() -> String {
+    "21m00Tcm4TlvDq8ikWAM".to_string()
+}
+
+fn default_elevenlabs_model() -> String {
+    "eleven_flash_v2_5".to_string()
 }
 
 fn default_openai_model() -> String {
@@ -48,52 +1020,858 @@ fn default_temperature() -> f32 {
     0.9
 }
 
+fn default_tts_cached_phrases() -> Vec<String> {
+    [
+        "Let me think…",
+        "Hmm, let me look into that.",
+        "Sorry, something went wrong. Let's try that again.",
+        "Done!",
+    ]
+    .iter()
+    .map(|p| p.to_string())
+    .collect()
+}
+
+fn default_tts_queue_size() -> u32 {
+    3
+}
+
+fn default_personality() -> String {
+    crate::personality::DEFAULT_PERSONALITY.to_string()
+}
+
+fn default_snap_to_edges() -> bool {
+    true
+}
+
+fn default_always_on_top() -> bool {
+    true
+}
+
+fn default_check_for_updates() -> bool {
+    true
+}
+
+fn default_sound_effects() -> bool {
+    true
+}
+
+fn default_sound_volume() -> u8 {
+    60
+}
+
+fn default_skip_taskbar() -> bool {
+    true
+}
+
+fn default_native_notifications() -> bool {
+    true
+}
+
+fn default_global_hotkey() -> Option<String> {
+    Some(crate::hotkey::DEFAULT_HOTKEY.to_string())
+}
+
 impl Default for Config {
     fn default() -> Self {
         Self {
+            version: CONFIG_VERSION,
             llm_provider: LlmProviderType::BuiltIn,
             openai_api_key: None,
             openai_model: default_openai_model(),
             custom_api_url: None,
             custom_api_key: None,
             custom_model: None,
+            lmstudio_load_on_chat: false,
             builtin_model_path: None,
+            model_prices: BTreeMap::new(),
+            rate_limits: RateLimitConfig::default(),
+            budget: BudgetConfig::default(),
+            ensemble: EnsembleConfig::default(),
             temperature: default_temperature(),
+            seed: None,
+            reasoning: ReasoningDisplay::default(),
+            context_tokens: None,
+            pins_in_context: false,
             tts_enabled: false,
             tts_voice: None,
+            tts_engine: TtsEngineType::default(),
+            openai_tts_model: default_openai_tts_model(),
+            openai_tts_voice: default_openai_tts_voice(),
+            elevenlabs_api_key: None,
+            elevenlabs_voice_id: default_elevenlabs_voice_id(),
+            elevenlabs_model: default_elevenlabs_model(),
+            tts_cached_phrases: default_tts_cached_phrases(),
+            tts_max_sentences: None,
+            tts_queue_size: default_tts_queue_size(),
+            tts_queue_policy: TtsQueuePolicy::default(),
+            sound_effects: default_sound_effects(),
+            sound_volume: default_sound_volume(),
+            preload_model: false,
+            warm_up_model: false,
+            active_profile: None,
+            user_name: None,
+            personality: default_personality(),
+            agent: None,
+            custom_system_prompt: None,
+            system_prompt_mode: SystemPromptMode::Augment,
+            personality_tuning: PersonalityTuning::default(),
+            response_language: None,
+            proactive: ProactiveConfig::default(),
+            mock: MockConfig::default(),
+            pomodoro: PomodoroConfig::default(),
+            briefing: BriefingConfig::default(),
+            history: HistoryConfig::default(),
+            knowledge: KnowledgeConfig::default(),
+            documents: DocumentWatchConfig::default(),
+            app_rules: Vec::new(),
+            dnd: DndConfig::default(),
+            tools: BTreeMap::new(),
+            weather: WeatherConfig::default(),
+            calendar: CalendarConfig::default(),
+            image: ImageConfig::default(),
+            stt: SttConfig::default(),
+            crash_reports: false,
+            request_log: false,
+            global_hotkey: default_global_hotkey(),
+            snap_to_edges: default_snap_to_edges(),
+            click_through_idle: false,
+            always_on_top: default_always_on_top(),
+            start_hidden: false,
+            native_notifications: default_native_notifications(),
+            skip_taskbar: default_skip_taskbar(),
+            update_channel: UpdateChannel::default(),
+            check_for_updates: default_check_for_updates(),
         }
     }
 }
 
+/// Temperatures accepted by every provider (OpenAI caps at 2.0)
+const TEMPERATURE_RANGE: std::ops::RangeInclusive<f32> = 0.0..=2.0;
+/// Each cached phrase stays in memory as raw audio, so keep the list short
+const MAX_CACHED_PHRASES: usize = 50;
+
+/// A problem with a single config field, shown next to it in the settings UI
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+impl FieldError {
+    fn new(field: &str, message: impl Into<String>) -> Self {
+        Self {
+            field: field.to_string(),
+            message: message.into(),
+        }
+    }
+}
+
+fn is_http_url(url: &str) -> bool {
+    url.starts_with("http://") || url.starts_with("https://")
+}
+
+/// Write `contents` to a temp file next to `path` and rename it into place, so a
+/// crash mid-write leaves either the old file or the new one, never a truncated mix.
+pub fn write_atomic(path: &Path, contents: &[u8]) -> Result<()> {
+    let tmp = PathBuf::from(format!("{}.tmp", path.display()));
+    {
+        let mut file = std::fs::File::create(&tmp)?;
+        file.write_all(contents)?;
+        file.sync_all()?;
+    }
+    std::fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+/// Credentials held in the config, split out so they can be left out of
+/// exports or handled separately
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Secrets {
+    pub openai_api_key: Option<String>,
+    pub custom_api_key: Option<String>,
+    pub elevenlabs_api_key: Option<String>,
+    /// Tool API keys, by tool name
+    #[serde(default)]
+    pub tool_api_keys: BTreeMap<String, String>,
+}
+
+impl Secrets {
+    pub fn is_empty(&self) -> bool {
+        self.openai_api_key.is_none()
+            && self.custom_api_key.is_none()
+            && self.elevenlabs_api_key.is_none()
+            && self.tool_api_keys.is_empty()
+    }
+}
+
 impl Config {
+    /// Remove and return every credential in this config.
+    pub fn take_secrets(&mut self) -> Secrets {
+        Secrets {
+            openai_api_key: self.openai_api_key.take(),
+            custom_api_key: self.custom_api_key.take(),
+            elevenlabs_api_key: self.elevenlabs_api_key.take(),
+            tool_api_keys: self
+                .tools
+                .iter_mut()
+                .filter_map(|(name, tool)| Some((name.clone(), tool.api_key.take()?)))
+                .collect(),
+        }
+    }
+
+    /// Put credentials back, keeping any existing value the secrets don't provide.
+    pub fn restore_secrets(&mut self, secrets: Secrets) {
+        if secrets.openai_api_key.is_some() {
+            self.openai_api_key = secrets.openai_api_key;
+        }
+        if secrets.custom_api_key.is_some() {
+            self.custom_api_key = secrets.custom_api_key;
+        }
+        if secrets.elevenlabs_api_key.is_some() {
+            self.elevenlabs_api_key = secrets.elevenlabs_api_key;
+        }
+        for (name, key) in secrets.tool_api_keys {
+            self.tools.entry(name).or_default().api_key = Some(key);
+        }
+    }
+
+    /// Check for settings that can't work, so they surface in the settings
+    /// window instead of as a failure mid-chat. Empty when the config is usable.
+    pub fn validate(&self) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+        let blank = |value: &Option<String>| value.as_deref().is_none_or(|v| v.trim().is_empty());
+
+        match self.llm_provider {
+            LlmProviderType::OpenAI => {
+                if blank(&self.openai_api_key) {
+                    errors.push(FieldError::new(
+                        "openai_api_key",
+                        "An API key is required for OpenAI",
+                    ));
+                }
+                if self.openai_model.trim().is_empty() {
+                    errors.push(FieldError::new("openai_model", "Choose a model"));
+                }
+            }
+            LlmProviderType::CustomAPI if blank(&self.custom_api_url) => {
+                errors.push(FieldError::new(
+                    "custom_api_url",
+                    "A URL is required for a custom API",
+                ));
+            }
+            LlmProviderType::BuiltIn => match self.builtin_model_path.as_deref().map(str::trim) {
+                None | Some("") => errors.push(FieldError::new(
+                    "builtin_model_path",
+                    "Select or download a model file",
+                )),
+                Some(path) if !std::path::Path::new(path).is_file() => {
+                    errors.push(FieldError::new(
+                        "builtin_model_path",
+                        format!("Model file not found: {}", path),
+                    ))
+                }
+                _ => {}
+            },
+            LlmProviderType::Mock
+                if self.mock.mode == MockMode::Canned && self.mock.responses.is_empty() =>
+            {
+                errors.push(FieldError::new(
+                    "mock.responses",
+                    "Add at least one canned response",
+                ));
+            }
+            _ => {}
+        }
+
+        // LM Studio and Ollama fall back to localhost, but a URL that is set must be usable
+        if let Some(url) = self.custom_api_url.as_deref().map(str::trim) {
+            let uses_url = matches!(
+                self.llm_provider,
+                LlmProviderType::LMStudio | LlmProviderType::Ollama | LlmProviderType::CustomAPI
+            );
+            if uses_url && !url.is_empty() && !is_http_url(url) {
+                errors.push(FieldError::new(
+                    "custom_api_url",
+                    "URL must start with http:// or https://",
+                ));
+            }
+        }
+
+        if self
+            .context_tokens
+            .is_some_and(|n| n < crate::llm::budget::MIN_CONTEXT_TOKENS)
+        {
+            errors.push(FieldError::new(
+                "context_tokens",
+                format!(
+                    "Must be at least {} tokens, or empty to use the model's",
+                    crate::llm::budget::MIN_CONTEXT_TOKENS
+                ),
+            ));
+        }
+
+        if !TEMPERATURE_RANGE.contains(&self.temperature) {
+            errors.push(FieldError::new(
+                "temperature",
+                format!(
+                    "Temperature must be between {} and {}",
+                    TEMPERATURE_RANGE.start(),
+                    TEMPERATURE_RANGE.end()
+                ),
+            ));
+        }
+
+        if !crate::personality::list()
+            .iter()
+            .any(|p| p.id == self.personality)
+        {
+            errors.push(FieldError::new(
+                "personality",
+                format!("Unknown personality '{}'", self.personality),
+            ));
+        }
+        if let Some(agent) = &self.agent {
+            if !crate::agents::list().contains(agent) {
+                errors.push(FieldError::new(
+                    "agent",
+                    format!("Character '{}' isn't installed", agent),
+                ));
+            }
+        }
+
+        let tuning = &self.personality_tuning;
+        for (field, value) in [
+            ("snark", tuning.snark),
+            ("verbosity", tuning.verbosity),
+            ("enthusiasm", tuning.enthusiasm),
+            ("formality", tuning.formality),
+        ] {
+            if value > TUNING_MAX {
+                errors.push(FieldError::new(
+                    &format!("personality_tuning.{}", field),
+                    format!("Must be between 0 and {}", TUNING_MAX),
+                ));
+            }
+        }
+
+        if let Some(code) = &self.response_language {
+            if crate::language::by_code(code).is_none() {
+                errors.push(FieldError::new(
+                    "response_language",
+                    format!("Unsupported language '{}'", code),
+                ));
+            }
+        }
+
+        let proactive = &self.proactive;
+        if proactive.enabled && proactive.idle_minutes == 0 {
+            errors.push(FieldError::new(
+                "proactive.idle_minutes",
+                "Idle time must be at least one minute",
+            ));
+        }
+        if proactive.peek && proactive.peek_minutes == 0 {
+            errors.push(FieldError::new(
+                "proactive.peek_minutes",
+                "Time between peeks must be at least one minute",
+            ));
+        }
+        for (field, hour) in [
+            ("proactive.quiet_hours_start", proactive.quiet_hours_start),
+            ("proactive.quiet_hours_end", proactive.quiet_hours_end),
+        ] {
+            if hour.is_some_and(|h| h > 23) {
+                errors.push(FieldError::new(field, "Hour must be between 0 and 23"));
+            }
+        }
+
+        let pomodoro = &self.pomodoro;
+        for (field, minutes) in [
+            ("pomodoro.work_minutes", pomodoro.work_minutes),
+            ("pomodoro.short_break_minutes", pomodoro.short_break_minutes),
+            ("pomodoro.long_break_minutes", pomodoro.long_break_minutes),
+        ] {
+            if minutes == 0 {
+                errors.push(FieldError::new(field, "Must be at least one minute"));
+            }
+        }
+        if pomodoro.long_break_every == 0 {
+            errors.push(FieldError::new(
+                "pomodoro.long_break_every",
+                "Must be at least 1",
+            ));
+        }
+
+        let history = &self.history;
+        for (field, limit) in [
+            ("history.max_conversations", history.max_conversations.map(|n| n as u64)),
+            ("history.max_age_days", history.max_age_days.map(u64::from)),
+            ("history.max_disk_mb", history.max_disk_mb),
+        ] {
+            if limit == Some(0) {
+                errors.push(FieldError::new(
+                    field,
+                    "Must be at least 1, or empty for no limit",
+                ));
+            }
+        }
+
+        if self.knowledge.enabled {
+            if let Some(missing) = self
+                .knowledge
+                .folders
+                .iter()
+                .find(|folder| !std::path::Path::new(folder.as_str()).is_dir())
+            {
+                errors.push(FieldError::new(
+                    "knowledge.folders",
+                    format!("Folder not found: {}", missing),
+                ));
+            }
+            if self.knowledge.max_chunks == 0 {
+                errors.push(FieldError::new(
+                    "knowledge.max_chunks",
+                    "Must be at least 1",
+                ));
+            }
+        }
+
+        let limits = &self.rate_limits;
+        for (field, limit) in [
+            ("rate_limits.requests_per_minute", limits.requests_per_minute.map(u64::from)),
+            ("rate_limits.requests_per_hour", limits.requests_per_hour.map(u64::from)),
+            ("rate_limits.tokens_per_day", limits.tokens_per_day),
+        ] {
+            if limit == Some(0) {
+                errors.push(FieldError::new(
+                    field,
+                    "Must be at least 1, or empty for no limit",
+                ));
+            }
+        }
+
+        for (provider, limit) in &self.budget.monthly_usd {
+            let field = format!("budget.monthly_usd.{}", provider);
+            let paid = crate::pricing::PAID_PROVIDERS
+                .iter()
+                .any(|p| format!("{:?}", p) == *provider);
+            if !paid {
+                errors.push(FieldError::new(
+                    &field,
+                    format!("'{}' isn't a paid provider", provider),
+                ));
+            } else if !limit.is_finite() || *limit <= 0.0 {
+                errors.push(FieldError::new(&field, "Must be more than $0"));
+            }
+        }
+
+        if self.ensemble.enabled && self.ensemble.members.is_empty() {
+            errors.push(FieldError::new(
+                "ensemble.members",
+                "Add at least one model to ask",
+            ));
+        }
+
+        for (model, price) in &self.model_prices {
+            let valid = |dollars: f64| dollars.is_finite() && dollars >= 0.0;
+            if !valid(price.input) || !valid(price.output) {
+                errors.push(FieldError::new(
+                    &format!("model_prices.{}", model),
+                    "Prices can't be negative",
+                ));
+            }
+        }
+
+        for (i, rule) in self.app_rules.iter().enumerate() {
+            let field = format!("app_rules.{}", i);
+            if rule.app.is_none() && rule.title.is_none() && !rule.fullscreen {
+                errors.push(FieldError::new(
+                    &field,
+                    "Needs an app, a title or fullscreen to match on",
+                ));
+            }
+            if let Some(personality) = &rule.personality {
+                if !crate::personality::list().iter().any(|p| &p.id == personality) {
+                    errors.push(FieldError::new(
+                        &field,
+                        format!("Unknown personality '{}'", personality),
+                    ));
+                }
+            }
+        }
+
+        for (i, window) in self.dnd.schedule.iter().enumerate() {
+            let field = format!("dnd.schedule.{}", i);
+            for time in [&window.start, &window.end] {
+                if chrono::NaiveTime::parse_from_str(time.trim(), "%H:%M").is_err() {
+                    errors.push(FieldError::new(
+                        &field,
+                        format!("'{}' is not a time like 22:30", time),
+                    ));
+                }
+            }
+            if let Some(day) = window
+                .days
+                .iter()
+                .find(|day| crate::dnd::parse_day(day).is_none())
+            {
+                errors.push(FieldError::new(&field, format!("Unknown day '{}'", day)));
+            }
+        }
+
+        if self.documents.enabled {
+            if let Some(missing) = self
+                .documents
+                .folders
+                .iter()
+                .find(|folder| !std::path::Path::new(folder.as_str()).is_dir())
+            {
+                errors.push(FieldError::new(
+                    "documents.folders",
+                    format!("Folder not found: {}", missing),
+                ));
+            }
+        }
+
+        for (name, tool) in &self.tools {
+            if let Some(missing) = tool
+                .allowed_dirs
+                .iter()
+                .find(|dir| !crate::tools::expand_home(dir.trim()).is_dir())
+            {
+                errors.push(FieldError::new(
+                    &format!("tools.{}.allowed_dirs", name),
+                    format!("Folder not found: {}", missing),
+                ));
+            }
+        }
+
+        if let Some(missing) = self.calendar.sources.iter().find(|source| {
+            let source = source.trim();
+            !source.contains("://") && !std::path::Path::new(source).is_file()
+        }) {
+            errors.push(FieldError::new(
+                "calendar.sources",
+                format!("Not a URL or an existing file: {}", missing),
+            ));
+        }
+        if self.calendar.alert_minutes == Some(0) {
+            errors.push(FieldError::new(
+                "calendar.alert_minutes",
+                "Must be at least 1, or empty for no warnings",
+            ));
+        }
+
+        if self.image.dimensions().is_none() {
+            errors.push(FieldError::new(
+                "image.size",
+                "Use WIDTHxHEIGHT, such as 1024x1024",
+            ));
+        }
+        if self.image.backend == ImageBackend::StableDiffusion {
+            let url = self
+                .image
+                .stable_diffusion_url
+                .as_deref()
+                .unwrap_or_default()
+                .trim();
+            if !is_http_url(url) {
+                errors.push(FieldError::new(
+                    "image.stable_diffusion_url",
+                    "Enter the web UI's address, such as http://127.0.0.1:7860",
+                ));
+            }
+        }
+
+        if self.stt.backend == SttBackend::OpenAI && self.stt.model.trim().is_empty() {
+            errors.push(FieldError::new(
+                "stt.model",
+                "Enter a transcription model, such as whisper-1",
+            ));
+        }
+        if let Some(language) = &self.stt.language {
+            let language = language.trim();
+            if language.len() != 2 || !language.chars().all(|c| c.is_ascii_lowercase()) {
+                errors.push(FieldError::new(
+                    "stt.language",
+                    "Use a two-letter language code, such as en, or leave it empty",
+                ));
+            }
+        }
+
+        if chrono::NaiveTime::parse_from_str(&self.briefing.time, "%H:%M").is_err() {
+            errors.push(FieldError::new(
+                "briefing.time",
+                "Use a 24-hour time such as 08:00",
+            ));
+        }
+
+        let hotkey = self
+            .global_hotkey
+            .as_deref()
+            .map(str::trim)
+            .unwrap_or_default();
+        if !hotkey.is_empty() {
+            if let Err(e) = crate::hotkey::parse(hotkey) {
+                errors.push(FieldError::new("global_hotkey", e.to_string()));
+            }
+        }
+
+        match self.tts_engine {
+            TtsEngineType::Piper if self.tts_enabled && blank(&self.tts_voice) => {
+                errors.push(FieldError::new(
+                    "tts_voice",
+                    "Choose a voice or turn speech off",
+                ));
+            }
+            TtsEngineType::OpenAI if self.tts_enabled => {
+                if blank(&self.openai_api_key) {
+                    errors.push(FieldError::new(
+                        "tts_engine",
+                        "OpenAI speech needs an OpenAI API key",
+                    ));
+                }
+                if self.openai_tts_voice.trim().is_empty() {
+                    errors.push(FieldError::new("openai_tts_voice", "Choose a voice"));
+                }
+            }
+            TtsEngineType::ElevenLabs if self.tts_enabled => {
+                if blank(&self.elevenlabs_api_key) {
+                    errors.push(FieldError::new(
+                        "elevenlabs_api_key",
+                        "An API key is required for ElevenLabs",
+                    ));
+                }
+                if self.elevenlabs_voice_id.trim().is_empty() {
+                    errors.push(FieldError::new(
+                        "elevenlabs_voice_id",
+                        "Enter the ID of an ElevenLabs voice",
+                    ));
+                }
+            }
+            _ => {}
+        }
+
+        if self.tts_cached_phrases.len() > MAX_CACHED_PHRASES {
+            errors.push(FieldError::new(
+                "tts_cached_phrases",
+                format!("At most {} phrases", MAX_CACHED_PHRASES),
+            ));
+        }
+
+        if self.sound_volume > 100 {
+            errors.push(FieldError::new("sound_volume", "Volume goes up to 100"));
+        }
+
+        if self.tts_max_sentences == Some(0) {
+            errors.push(FieldError::new(
+                "tts_max_sentences",
+                "Read at least one sentence, or leave empty to read everything",
+            ));
+        }
+
+        if self.tts_queue_size == 0 {
+            errors.push(FieldError::new(
+                "tts_queue_size",
+                "Let at least one sentence wait while another plays",
+            ));
+        }
+
+        errors
+    }
+
+    /// Load config.json, with any `RUSTY_CLIPPY_*` environment overrides applied.
     pub fn load() -> Result<Self> {
+        let mut config = Self::load_file()?;
+        config.apply_env_overrides();
+        crate::redact::remember_secrets(&config);
+        Ok(config)
+    }
+
+    /// Overlay `RUSTY_CLIPPY_*` environment variables onto this config.
+    pub fn apply_env_overrides(&mut self) {
+        EnvOverrides::from_env().apply(self);
+    }
+
+    /// Load config.json exactly as stored, without environment overrides. Use this for
+    /// anything written elsewhere, like profile snapshots, so injected keys stay out of it.
+    pub fn load_file() -> Result<Self> {
         let config_path = Self::config_path()?;
         
         if config_path.exists() {
             let content = std::fs::read_to_string(&config_path)?;
-            let config: Config = serde_json::from_str(&content)?;
+            let (config, from) = match Self::parse(&content) {
+                Ok(parsed) => parsed,
+                Err(e) => {
+                    // A torn write or a broken manual edit shouldn't wipe every setting
+                    let backup = Self::backup_path()?;
+                    match std::fs::read_to_string(&backup).map(|c| Self::parse(&c)) {
+                        Ok(Ok(parsed)) => {
+                            tracing::warn!(
+                                "config.json is unreadable ({}); using backup {:?}",
+                                e,
+                                backup
+                            );
+                            parsed
+                        }
+                        _ => return Err(e),
+                    }
+                }
+            };
+
+            if from < CONFIG_VERSION {
+                // Keep the untouched file around in case a migration loses something
+                let backup = config_path.with_file_name(format!("config.v{}.bak.json", from));
+                std::fs::write(&backup, &content)?;
+                if let Ok(Value::Object(raw)) = serde_json::from_str::<Value>(&content) {
+                    if let Err(e) = crate::runtime_state::adopt(&raw) {
+                        tracing::warn!("Failed to move app state to state.json: {}", e);
+                    }
+                }
+                config.write()?;
+                tracing::info!(
+                    "Migrated config from v{} to v{} (backup at {:?})",
+                    from,
+                    CONFIG_VERSION,
+                    backup
+                );
+            }
             Ok(config)
         } else {
             Ok(Config::default())
         }
     }
+
+    /// Parse config JSON, applying any pending schema migrations.
+    /// Returns the config along with the version it was stored as.
+    pub fn parse(content: &str) -> Result<(Self, u32)> {
+        let mut raw: Value = serde_json::from_str(content)?;
+        let from = raw.get("version").and_then(Value::as_u64).unwrap_or(0) as u32;
+
+        if from < CONFIG_VERSION {
+            let object = raw
+                .as_object_mut()
+                .ok_or_else(|| anyhow::anyhow!("Config file is not a JSON object"))?;
+            for migration in &MIGRATIONS[from as usize..] {
+                migration(object);
+            }
+            object.insert("version".into(), CONFIG_VERSION.into());
+        } else if from > CONFIG_VERSION {
+            tracing::warn!(
+                "Config was written by a newer version (v{} > v{}); unknown settings will be ignored",
+                from,
+                CONFIG_VERSION
+            );
+        }
+
+        Ok((serde_json::from_value(raw)?, from))
+    }
     
     pub fn save(&self) -> Result<()> {
+        crate::redact::remember_secrets(self);
+
+        // Values injected from the environment (often API keys) stay out of the file
+        let overrides = EnvOverrides::from_env();
+        if overrides.is_empty() {
+            return self.write();
+        }
+        let mut config = self.clone();
+        overrides.restore(&mut config, &Self::load_file().unwrap_or_default());
+        config.write()
+    }
+
+    /// Write this config to disk as-is, for configs that never had environment overrides
+    /// applied; `save` is for ones from `load`.
+    pub fn write(&self) -> Result<()> {
         let config_path = Self::config_path()?;
         
         if let Some(parent) = config_path.parent() {
             std::fs::create_dir_all(parent)?;
         }
         
-        let content = serde_json::to_string_pretty(self)?;
-        std::fs::write(&config_path, content)?;
-        Ok(())
+        // Anything we write is in the current schema, whatever the caller passed in
+        let mut config = self.clone();
+        config.version = config.version.max(CONFIG_VERSION);
+
+        let content = serde_json::to_string_pretty(&config)?;
+
+        // Rotate the current file into the backup, unless it's the broken one we fell back from
+        let current_is_valid = std::fs::read_to_string(&config_path)
+            .is_ok_and(|c| serde_json::from_str::<Value>(&c).is_ok());
+        if current_is_valid {
+            std::fs::copy(&config_path, Self::backup_path()?)?;
+        }
+
+        write_atomic(&config_path, content.as_bytes())
     }
     
+    /// The provider and model messages are currently sent to
+    pub fn current_model(&self) -> ModelChoice {
+        let model = match self.llm_provider {
+            LlmProviderType::OpenAI => self.openai_model.clone(),
+            LlmProviderType::BuiltIn => self.builtin_model_path.clone().unwrap_or_default(),
+            LlmProviderType::Mock => String::new(),
+            _ => self.custom_model.clone().unwrap_or_default(),
+        };
+        ModelChoice {
+            provider: self.llm_provider.clone(),
+            model,
+        }
+    }
+
+    /// Send messages to `choice` from now on.
+    pub fn use_model(&mut self, choice: &ModelChoice) {
+        self.llm_provider = choice.provider.clone();
+        match choice.provider {
+            LlmProviderType::OpenAI => self.openai_model = choice.model.clone(),
+            LlmProviderType::BuiltIn => self.builtin_model_path = Some(choice.model.clone()),
+            LlmProviderType::Mock => {}
+            _ => {
+                self.custom_model = Some(choice.model.clone()).filter(|m| !m.is_empty());
+            }
+        }
+    }
+
+    /// Everything the tray can switch to: recent models, then each configured
+    /// provider's current model, without duplicates.
+    pub fn model_choices(&self) -> Vec<ModelChoice> {
+        let mut choices = crate::runtime_state::load().recent_models;
+        let mut configured = vec![self.current_model()];
+        if let Some(path) = &self.builtin_model_path {
+            configured.push(ModelChoice {
+                provider: LlmProviderType::BuiltIn,
+                model: path.clone(),
+            });
+        }
+        if self.openai_api_key.is_some() {
+            configured.push(ModelChoice {
+                provider: LlmProviderType::OpenAI,
+                model: self.openai_model.clone(),
+            });
+        }
+        for choice in configured {
+            if !choices.contains(&choice) {
+                choices.push(choice);
+            }
+        }
+        choices
+    }
+
     fn config_path() -> Result<PathBuf> {
+        Ok(Self::config_dir()?.join("config.json"))
+    }
+
+    /// The previous config.json, kept in case the current one can't be parsed
+    fn backup_path() -> Result<PathBuf> {
+        Ok(Self::config_dir()?.join("config.json.bak"))
+    }
+
+    /// Get the app's config directory (holds config.json and other user settings)
+    pub fn config_dir() -> Result<PathBuf> {
         let config_dir = dirs::config_dir()
             .ok_or_else(|| anyhow::anyhow!("Could not find config directory"))?;
-        Ok(config_dir.join("rusty-clippy").join("config.json"))
+        Ok(config_dir.join("rusty-clippy"))
     }
 
     /// Get the data directory for models and TTS assets
@@ -0,0 +1,132 @@
+use crate::config::{Config, LlmProviderType};
+use anyhow::Result;
+use notify::{EventKind, RecursiveMode, Watcher};
+use std::sync::mpsc;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+/// Editors often save in several steps (truncate, write, rename); wait for them to settle
+const SETTLE_DELAY: Duration = Duration::from_millis(200);
+
+/// Watch config.json and apply edits made outside the settings window
+/// (by hand, by another window or by another instance) without a restart.
+/// Emits `config-changed` with the new config whenever the contents change.
+pub fn spawn(app: &AppHandle) -> Result<()> {
+    let dir = Config::config_dir()?;
+    std::fs::create_dir_all(&dir)?;
+    let config_file = dir.join("config.json");
+
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx)?;
+    // Watch the directory rather than the file so atomic replace-by-rename is seen
+    watcher.watch(&dir, RecursiveMode::NonRecursive)?;
+
+    let app = app.clone();
+    std::thread::spawn(move || {
+        // Dropping the watcher stops events, so it lives as long as this thread
+        let _watcher = watcher;
+        let mut current = Config::load().unwrap_or_default();
+        let mut last_content = std::fs::read_to_string(&config_file).ok();
+
+        while let Ok(event) = rx.recv() {
+            let relevant = match event {
+                Ok(event) => {
+                    !matches!(event.kind, EventKind::Access(_))
+                        && event.paths.iter().any(|p| p == &config_file)
+                }
+                Err(e) => {
+                    tracing::warn!("Config watcher error: {}", e);
+                    false
+                }
+            };
+            if !relevant {
+                continue;
+            }
+
+            std::thread::sleep(SETTLE_DELAY);
+            while rx.try_recv().is_ok() {}
+
+            let Ok(content) = std::fs::read_to_string(&config_file) else {
+                continue;
+            };
+            if last_content.as_deref() == Some(content.as_str()) {
+                continue;
+            }
+            last_content = Some(content.clone());
+
+            match Config::parse(&content) {
+                Ok((mut config, _)) => {
+                    config.apply_env_overrides();
+                    apply(&app, &current, &config);
+                    current = config;
+                }
+                // Likely a half-finished manual edit; keep running on the old settings
+                Err(e) => tracing::warn!("Ignoring invalid config.json: {}", e),
+            }
+        }
+    });
+
+    Ok(())
+}
+
+/// Bring long-lived state in line with `new`, touching only what changed.
+fn apply(app: &AppHandle, old: &Config, new: &Config) {
+    tracing::info!("config.json changed on disk, reloading");
+
+    if old.tts_enabled != new.tts_enabled || old.tts_voice != new.tts_voice {
+        let app = app.clone();
+        let config = new.clone();
+        tauri::async_runtime::spawn(async move {
+            let tts_state = app.state::<crate::tts::TtsState>();
+            if let Err(e) = crate::commands::apply_tts_config(&config, &tts_state).await {
+                tracing::warn!("Failed to apply TTS settings from config.json: {}", e);
+            }
+        });
+    }
+
+    // Don't keep gigabytes of weights around for a model that's no longer configured
+    let keep = match new.llm_provider {
+        LlmProviderType::BuiltIn => new.builtin_model_path.as_deref(),
+        _ => None,
+    };
+    crate::llm::local::unload_unless(keep);
+
+    crate::crash::set_enabled(new.crash_reports);
+
+    if old.global_hotkey != new.global_hotkey {
+        if let Err(e) = crate::hotkey::register(app, new.global_hotkey.as_deref()) {
+            tracing::warn!("Failed to register global shortcut: {}", e);
+        }
+    }
+
+    if old.knowledge != new.knowledge {
+        crate::knowledge::configure(new.knowledge.clone());
+    }
+
+    if old.documents != new.documents {
+        crate::documents::configure(app, new.documents.clone());
+    }
+
+    if old.dnd != new.dnd {
+        crate::dnd::refresh(app);
+    }
+
+    if old.always_on_top != new.always_on_top {
+        if let Some(window) = app.get_webview_window("clippy") {
+            let _ = window.set_always_on_top(new.always_on_top);
+        }
+    }
+
+    if old.skip_taskbar != new.skip_taskbar {
+        crate::window_state::set_taskbar_hidden(app, new.skip_taskbar);
+    }
+
+    if !new.click_through_idle {
+        crate::ghost::wake(app);
+    }
+
+    // Profiles, toggles and the model switcher all mirror the config
+    crate::tray::refresh(app);
+
+    let _ = app.emit("config-changed", new.clone());
+}
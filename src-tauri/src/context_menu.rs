@@ -0,0 +1,245 @@
+use crate::config::Config;
+use serde::Serialize;
+use tauri::menu::{
+    CheckMenuItem, IsMenuItem, Menu, MenuEvent, MenuItem, PredefinedMenuItem, Submenu,
+};
+use tauri::{AppHandle, Emitter, Manager, WebviewWindow, Window, Wry};
+
+/// Every item id starts with this: window menu listeners see the tray's events too
+const PREFIX: &str = "ctx:";
+const QUICK_PREFIX: &str = "ctx:quick:";
+const PERSONALITY_PREFIX: &str = "ctx:personality:";
+const TEMPLATE_PREFIX: &str = "ctx:template:";
+
+/// What a quick action does when picked
+pub enum QuickActionKind {
+    /// Sent to the chat as if the user had typed it
+    Prompt(&'static str),
+    /// Run in the backend
+    Run(fn(&AppHandle)),
+}
+
+/// An entry at the top of the character's right-click menu. Adding one here is all it
+/// takes to offer it; the page only knows how to send a prompt.
+pub struct QuickAction {
+    pub id: &'static str,
+    pub label: &'static str,
+    pub kind: QuickActionKind,
+}
+
+pub const QUICK_ACTIONS: &[QuickAction] = &[
+    QuickAction {
+        id: "joke",
+        label: "Tell Me a Joke",
+        kind: QuickActionKind::Prompt("Tell me a short joke."),
+    },
+    QuickAction {
+        id: "tip",
+        label: "Give Me a Tip",
+        kind: QuickActionKind::Prompt("Give me one quick productivity tip."),
+    },
+    QuickAction {
+        id: "help",
+        label: "What Can You Do?",
+        kind: QuickActionKind::Prompt("What can you help me with?"),
+    },
+    QuickAction {
+        id: "focus",
+        label: "Start Focus Timer",
+        kind: QuickActionKind::Run(|app| {
+            crate::pomodoro::start(app);
+        }),
+    },
+];
+
+/// Asks the page to send `prompt` as a chat message
+#[derive(Debug, Clone, Serialize)]
+pub struct QuickPromptEvent {
+    pub prompt: String,
+}
+
+/// Asks the page for the values of a prompt template's placeholders, then sends it
+#[derive(Debug, Clone, Serialize)]
+pub struct TemplatePickerEvent {
+    pub template: crate::prompt_templates::PromptTemplate,
+}
+
+/// Have Clippy's chat send `prompt` as if the user had typed it.
+pub fn send_prompt(app: &AppHandle, prompt: String) {
+    let _ = app.emit_to("clippy", "quick-prompt", QuickPromptEvent { prompt });
+}
+
+/// Handle picks from the context menu of `window`.
+pub fn manage(window: &WebviewWindow) {
+    window.on_menu_event(handle_menu_event);
+}
+
+/// Pop the context menu up at the cursor over `window`.
+pub fn show(window: &WebviewWindow) -> tauri::Result<()> {
+    let menu = build_menu(window.app_handle())?;
+    window.popup_menu(&menu)
+}
+
+fn build_menu(app: &AppHandle) -> tauri::Result<Menu<Wry>> {
+    let config = Config::load().unwrap_or_default();
+    let quick_items = QUICK_ACTIONS
+        .iter()
+        .map(|action| {
+            MenuItem::with_id(
+                app,
+                format!("{}{}", QUICK_PREFIX, action.id),
+                action.label,
+                true,
+                None::<&str>,
+            )
+        })
+        .collect::<tauri::Result<Vec<_>>>()?;
+    let templates_i = build_templates_menu(app)?;
+    let personalities_i = build_personalities_menu(app, &config)?;
+    let mute_i = CheckMenuItem::with_id(
+        app,
+        "ctx:mute-voice",
+        "Mute Voice",
+        true,
+        !config.tts_enabled,
+        None::<&str>,
+    )?;
+    let settings_i = MenuItem::with_id(app, "ctx:settings", "Settings", true, None::<&str>)?;
+    let hide_i = MenuItem::with_id(app, "ctx:hide", "Hide Clippy", true, None::<&str>)?;
+    let quit_i = MenuItem::with_id(app, "ctx:quit", "Quit", true, None::<&str>)?;
+    let separator = PredefinedMenuItem::separator(app)?;
+    let separator_2 = PredefinedMenuItem::separator(app)?;
+
+    let mut items: Vec<&dyn IsMenuItem<Wry>> = quick_items
+        .iter()
+        .map(|i| i as &dyn IsMenuItem<Wry>)
+        .collect();
+    items.extend([
+        &templates_i as &dyn IsMenuItem<Wry>,
+        &separator,
+        &personalities_i,
+        &mute_i,
+        &settings_i,
+        &separator_2,
+        &hide_i,
+        &quit_i,
+    ]);
+    Menu::with_items(app, &items)
+}
+
+fn build_templates_menu(app: &AppHandle) -> tauri::Result<Submenu<Wry>> {
+    let templates = crate::prompt_templates::list().unwrap_or_else(|e| {
+        tracing::warn!("Failed to load prompt templates: {}", e);
+        Vec::new()
+    });
+    let items = templates
+        .iter()
+        .map(|t| {
+            MenuItem::with_id(
+                app,
+                format!("{}{}", TEMPLATE_PREFIX, t.id),
+                &t.name,
+                true,
+                None::<&str>,
+            )
+        })
+        .collect::<tauri::Result<Vec<_>>>()?;
+    let items: Vec<&dyn IsMenuItem<Wry>> =
+        items.iter().map(|i| i as &dyn IsMenuItem<Wry>).collect();
+
+    Submenu::with_items(app, "Prompt Templates", !items.is_empty(), &items)
+}
+
+/// Send the template with `id`, asking the page for its placeholders first if it has any.
+fn use_template(app: &AppHandle, id: &str) {
+    let template = match crate::prompt_templates::get(id) {
+        Ok(template) => template,
+        Err(e) => {
+            tracing::warn!("Failed to load prompt template: {}", e);
+            return;
+        }
+    };
+    if template.variables.is_empty() {
+        send_prompt(app, template.text);
+    } else {
+        let _ = app.emit_to("clippy", "template-picker", TemplatePickerEvent { template });
+    }
+}
+
+fn build_personalities_menu(app: &AppHandle, config: &Config) -> tauri::Result<Submenu<Wry>> {
+    let items = crate::personality::list()
+        .iter()
+        .map(|p| {
+            CheckMenuItem::with_id(
+                app,
+                format!("{}{}", PERSONALITY_PREFIX, p.id),
+                &p.name,
+                true,
+                p.id == config.personality,
+                None::<&str>,
+            )
+        })
+        .collect::<tauri::Result<Vec<_>>>()?;
+    let items: Vec<&dyn IsMenuItem<Wry>> =
+        items.iter().map(|i| i as &dyn IsMenuItem<Wry>).collect();
+
+    Submenu::with_items(app, "Personality", true, &items)
+}
+
+fn handle_menu_event(window: &Window, event: MenuEvent) {
+    let id = event.id().as_ref();
+    if !id.starts_with(PREFIX) {
+        return;
+    }
+    let app = window.app_handle();
+
+    if let Some(action_id) = id.strip_prefix(QUICK_PREFIX) {
+        match QUICK_ACTIONS
+            .iter()
+            .find(|a| a.id == action_id)
+            .map(|a| &a.kind)
+        {
+            Some(QuickActionKind::Prompt(prompt)) => send_prompt(app, prompt.to_string()),
+            Some(QuickActionKind::Run(run)) => run(app),
+            None => {}
+        }
+        return;
+    }
+
+    if let Some(template_id) = id.strip_prefix(TEMPLATE_PREFIX) {
+        use_template(app, template_id);
+        return;
+    }
+
+    if let Some(personality) = id.strip_prefix(PERSONALITY_PREFIX) {
+        switch_personality(personality);
+        return;
+    }
+
+    match id {
+        "ctx:mute-voice" => crate::tray::toggle_mute(app),
+        "ctx:settings" => {
+            let _ = window.emit_to(window.label(), "open-settings", ());
+        }
+        "ctx:hide" => {
+            let _ = window.hide();
+        }
+        "ctx:quit" => app.exit(0),
+        _ => {}
+    }
+}
+
+/// Chat as `id` from now on; config_watch tells the pages.
+fn switch_personality(id: &str) {
+    let mut config = match Config::load() {
+        Ok(config) => config,
+        Err(e) => {
+            tracing::warn!("Failed to load config: {}", e);
+            return;
+        }
+    };
+    config.personality = id.to_string();
+    if let Err(e) = config.save() {
+        tracing::warn!("Failed to switch personality: {}", e);
+    }
+}
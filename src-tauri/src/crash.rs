@@ -0,0 +1,136 @@
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use std::backtrace::Backtrace;
+use std::fmt::Write as _;
+use std::panic::PanicHookInfo;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Whether the user opted in; checked at panic time so toggling needs no restart
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Diagnostics gathered at startup. Collecting them inside the panic hook could
+/// block or panic again, so the dump uses this snapshot instead.
+static DIAGNOSTICS: Mutex<Option<String>> = Mutex::new(None);
+
+/// A crash dump left behind by a previous run that the user hasn't seen yet
+#[derive(Debug, Clone, Serialize)]
+pub struct CrashReport {
+    pub path: String,
+    /// Unix timestamp (seconds) of the crash
+    pub crashed_at: u64,
+}
+
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+pub fn set_diagnostics(diagnostics: &crate::diagnostics::Diagnostics) {
+    if let (Ok(json), Ok(mut snapshot)) = (
+        serde_json::to_string_pretty(diagnostics),
+        DIAGNOSTICS.lock(),
+    ) {
+        *snapshot = Some(json);
+    }
+}
+
+/// Install a panic hook that writes a crash dump when crash reporting is on,
+/// then defers to the default hook.
+pub fn install_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        if ENABLED.load(Ordering::Relaxed) {
+            match write_dump(info) {
+                Ok(path) => eprintln!("Crash report written to {}", path.display()),
+                Err(e) => eprintln!("Failed to write crash report: {}", e),
+            }
+        }
+        default_hook(info);
+    }));
+}
+
+fn write_dump(info: &PanicHookInfo) -> Result<PathBuf> {
+    let crashed_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+
+    let mut dump = String::new();
+    let _ = writeln!(dump, "Rusty Clippy crash report");
+    let _ = writeln!(dump, "Time (unix): {}", crashed_at);
+    let _ = writeln!(
+        dump,
+        "Thread: {}",
+        std::thread::current().name().unwrap_or("<unnamed>")
+    );
+    let _ = writeln!(dump, "Panic: {}", crate::redact::redact(&info.to_string()));
+    let _ = writeln!(dump, "\nBacktrace:\n{}", Backtrace::force_capture());
+    // try_lock: the panic may have happened while the snapshot was being updated
+    if let Ok(Some(diagnostics)) = DIAGNOSTICS.try_lock().as_deref() {
+        let _ = writeln!(dump, "\nDiagnostics:\n{}", diagnostics);
+    }
+
+    let path = crash_dir()?.join(format!("crash-{}.txt", crashed_at));
+    std::fs::write(&path, dump)?;
+    Ok(path)
+}
+
+/// Directory holding crash dumps
+pub fn crash_dir() -> Result<PathBuf> {
+    let data_dir = dirs::data_dir().ok_or_else(|| anyhow!("Could not find data directory"))?;
+    let crashes = data_dir.join("rusty-clippy").join("crashes");
+    std::fs::create_dir_all(&crashes)?;
+    Ok(crashes)
+}
+
+/// The most recent crash dump not yet shown to the user.
+pub fn pending() -> Result<Option<CrashReport>> {
+    let latest = std::fs::read_dir(crash_dir()?)?
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let crashed_at = name
+                .strip_prefix("crash-")?
+                .strip_suffix(".txt")?
+                .parse::<u64>()
+                .ok()?;
+            Some(CrashReport {
+                path: entry.path().to_string_lossy().to_string(),
+                crashed_at,
+            })
+        })
+        .max_by_key(|report| report.crashed_at);
+    Ok(latest)
+}
+
+/// Mark every pending crash dump as seen so it isn't offered again.
+/// Returns the new location of the dump at `path`.
+pub fn acknowledge(path: &str) -> Result<PathBuf> {
+    let dir = crash_dir()?.canonicalize()?;
+    let path = Path::new(path).canonicalize()?;
+    if !path.starts_with(&dir) {
+        return Err(anyhow!("Not a crash report: {}", path.display()));
+    }
+
+    let mut acknowledged = path.clone();
+    for entry in std::fs::read_dir(&dir)?.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let Some(stem) = name
+            .strip_suffix(".txt")
+            .filter(|s| s.starts_with("crash-"))
+        else {
+            continue;
+        };
+        if stem.ends_with(".seen") {
+            continue;
+        }
+        let seen = dir.join(format!("{}.seen.txt", stem));
+        std::fs::rename(entry.path(), &seen)?;
+        if entry.path() == path {
+            acknowledged = seen;
+        }
+    }
+    Ok(acknowledged)
+}
@@ -0,0 +1,100 @@
+use crate::config::{Config, LlmProviderType};
+use crate::hardware::HardwareInfo;
+use crate::models::StorageUsage;
+use anyhow::Result;
+use serde::Serialize;
+use std::path::Path;
+
+/// A snapshot of the environment for support requests. Holds no secrets:
+/// API keys are reported only as set or unset.
+#[derive(Debug, Clone, Serialize)]
+pub struct Diagnostics {
+    pub app_version: String,
+    pub os: String,
+    pub os_version: Option<String>,
+    pub arch: String,
+    pub hardware: HardwareInfo,
+    pub provider: ProviderDiagnostics,
+    pub tts: TtsDiagnostics,
+    pub storage: StorageUsage,
+    pub config_dir: String,
+    pub log_dir: String,
+    pub log_bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProviderDiagnostics {
+    pub provider: LlmProviderType,
+    pub model: Option<String>,
+    pub api_url: Option<String>,
+    pub api_key_set: bool,
+    pub builtin_model_path: Option<String>,
+    pub builtin_model_exists: bool,
+    pub preload_model: bool,
+    pub config_errors: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TtsDiagnostics {
+    pub enabled: bool,
+    pub voice: Option<String>,
+    pub voice_downloaded: bool,
+    pub engine_loaded: bool,
+}
+
+/// Gather diagnostics. Blocking: may probe the GPU and walk the data directory.
+pub fn collect(app_version: String, tts_loaded: bool) -> Result<Diagnostics> {
+    let config = Config::load()?;
+    let log_dir = crate::logging::log_dir()?;
+
+    let model = match config.llm_provider {
+        LlmProviderType::OpenAI => Some(config.openai_model.clone()),
+        LlmProviderType::BuiltIn | LlmProviderType::Mock => None,
+        _ => config.custom_model.clone(),
+    };
+    let api_key_set = match config.llm_provider {
+        LlmProviderType::OpenAI => config.openai_api_key.is_some(),
+        _ => config.custom_api_key.is_some(),
+    };
+    let provider = ProviderDiagnostics {
+        provider: config.llm_provider.clone(),
+        model,
+        api_url: config.custom_api_url.clone(),
+        api_key_set,
+        builtin_model_exists: config
+            .builtin_model_path
+            .as_deref()
+            .is_some_and(|p| Path::new(p).is_file()),
+        builtin_model_path: config.builtin_model_path.clone(),
+        preload_model: config.preload_model,
+        config_errors: config
+            .validate()
+            .into_iter()
+            .map(|e| format!("{}: {}", e.field, e.message))
+            .collect(),
+    };
+
+    let tts = TtsDiagnostics {
+        enabled: config.tts_enabled,
+        voice_downloaded: config
+            .tts_voice
+            .as_deref()
+            .is_some_and(crate::tts::voice_ready),
+        voice: config.tts_voice.clone(),
+        engine_loaded: tts_loaded,
+    };
+
+    Ok(Diagnostics {
+        app_version,
+        os: std::env::consts::OS.to_string(),
+        os_version: sysinfo::System::long_os_version(),
+        arch: std::env::consts::ARCH.to_string(),
+        hardware: crate::hardware::detect(),
+        provider,
+        tts,
+        storage: crate::models::storage_usage()?,
+        config_dir: Config::config_dir()?.to_string_lossy().to_string(),
+        log_bytes: crate::models::dir_size(&log_dir),
+        log_dir: log_dir.to_string_lossy().to_string(),
+    })
+}
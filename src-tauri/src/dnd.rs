@@ -0,0 +1,130 @@
+use crate::animation::AppState;
+use crate::config::{Config, DndConfig, DndWindow};
+use chrono::{DateTime, Datelike, Local, NaiveTime, Weekday};
+use serde::Serialize;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+/// How often the schedule is checked for a window starting or ending
+const TICK: Duration = Duration::from_secs(30);
+
+/// Set from the tray or `set_dnd`; overrides the schedule until it next starts or ends
+static OVERRIDE: Mutex<Option<bool>> = Mutex::new(None);
+/// What the schedule said at the last check, to tell when a window starts or ends
+static SCHEDULED: Mutex<Option<bool>> = Mutex::new(None);
+/// What the pages were last told
+static SHOWN: Mutex<bool> = Mutex::new(false);
+
+/// Whether Clippy is asleep, and why. Sent as `dnd-changed` and by `get_dnd`.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct DndStatus {
+    pub active: bool,
+    /// A scheduled window is on right now, whether or not it's been overridden
+    pub scheduled: bool,
+}
+
+fn parse_time(time: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(time.trim(), "%H:%M").ok()
+}
+
+/// Parse a day name like "mon" or "Monday".
+pub fn parse_day(day: &str) -> Option<Weekday> {
+    day.trim().parse().ok()
+}
+
+impl DndWindow {
+    fn on_day(&self, day: Weekday) -> bool {
+        self.days.is_empty() || self.days.iter().any(|d| parse_day(d) == Some(day))
+    }
+
+    /// Whether `now` falls inside the window. One that wraps midnight belongs to the day it
+    /// starts on.
+    fn contains(&self, now: DateTime<Local>) -> bool {
+        let (Some(start), Some(end)) = (parse_time(&self.start), parse_time(&self.end)) else {
+            return false;
+        };
+        let time = now.time();
+        let today = now.weekday();
+        if start < end {
+            self.on_day(today) && start <= time && time < end
+        } else if start > end {
+            (self.on_day(today) && time >= start) || (self.on_day(today.pred()) && time < end)
+        } else {
+            false
+        }
+    }
+}
+
+fn scheduled(config: &DndConfig) -> bool {
+    let now = Local::now();
+    config.schedule.iter().any(|window| window.contains(now))
+}
+
+fn status_with(scheduled: bool) -> DndStatus {
+    let manual = OVERRIDE.lock().ok().and_then(|o| *o);
+    DndStatus {
+        active: manual.unwrap_or(scheduled),
+        scheduled,
+    }
+}
+
+pub fn status() -> DndStatus {
+    status_with(Config::load().is_ok_and(|c| scheduled(&c.dnd)))
+}
+
+/// Whether tips, speech and notifications are held back right now.
+pub fn active() -> bool {
+    status().active
+}
+
+/// Turn do-not-disturb on or off by hand. A scheduled window starting or ending later takes
+/// over again.
+pub fn set(app: &AppHandle, enabled: bool) -> DndStatus {
+    if let Ok(mut manual) = OVERRIDE.lock() {
+        *manual = Some(enabled);
+    }
+    refresh(app)
+}
+
+/// Check the schedule and tell the pages, tray and animation if Clippy fell asleep or woke up.
+pub fn refresh(app: &AppHandle) -> DndStatus {
+    let scheduled = Config::load().is_ok_and(|c| scheduled(&c.dnd));
+    if let Ok(mut last) = SCHEDULED.lock() {
+        if last.is_some_and(|last| last != scheduled) {
+            // The schedule moved on, so an earlier override no longer applies
+            if let Ok(mut manual) = OVERRIDE.lock() {
+                *manual = None;
+            }
+        }
+        *last = Some(scheduled);
+    }
+    let status = status_with(scheduled);
+
+    match SHOWN.lock() {
+        Ok(mut shown) if *shown != status.active => *shown = status.active,
+        _ => return status,
+    }
+    if status.active {
+        tracing::info!("Do not disturb is on");
+        crate::tts::stop();
+        crate::animation::enter(app, AppState::Sleeping);
+    } else {
+        tracing::info!("Do not disturb is off");
+        crate::animation::leave(app, AppState::Sleeping);
+    }
+    let _ = app.emit("dnd-changed", status);
+    crate::tray::refresh(app);
+    status
+}
+
+/// Follow the do-not-disturb schedule for as long as the app runs.
+pub fn spawn(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            refresh(&app);
+            tokio::time::sleep(TICK).await;
+        }
+    });
+}
@@ -0,0 +1,124 @@
+use crate::config::{Config, Dock, HorizontalEdge, VerticalEdge};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tauri::{Emitter, PhysicalPosition, WebviewWindow, WindowEvent};
+
+/// How close to an edge, in logical pixels, a dropped window snaps to it
+const SNAP_DISTANCE: f64 = 32.0;
+/// Snap once the drag is over, not while the user is still moving the window
+const SETTLE_DELAY: Duration = Duration::from_millis(300);
+
+/// Bumped on every move so a delayed snap can tell the window has moved again
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// Keep Clippy in its docked corner across restarts and snap it to edges when dropped near one.
+pub fn manage(window: &WebviewWindow) {
+    if Config::load().is_ok_and(|c| c.snap_to_edges) {
+        // Re-dock in case the screen layout changed since it was saved
+        place(window, crate::runtime_state::load().dock);
+    }
+
+    let tracked = window.clone();
+    window.on_window_event(move |event| {
+        if !matches!(event, WindowEvent::Moved(_)) || crate::peek::active() {
+            return;
+        }
+        let window = tracked.clone();
+        let generation = GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+        tauri::async_runtime::spawn(async move {
+            tokio::time::sleep(SETTLE_DELAY).await;
+            if GENERATION.load(Ordering::SeqCst) == generation {
+                settle(&window);
+            }
+        });
+    });
+}
+
+/// The dock Clippy is currently in
+pub fn current() -> Dock {
+    if Config::load().is_ok_and(|c| c.snap_to_edges) {
+        crate::runtime_state::load().dock
+    } else {
+        Dock::default()
+    }
+}
+
+/// After a drag: snap to any edge the window was dropped near and remember the result.
+fn settle(window: &WebviewWindow) {
+    if !Config::load().is_ok_and(|c| c.snap_to_edges) {
+        return;
+    }
+    let Some(dock) = nearest_edges(window) else {
+        return;
+    };
+    place(window, dock);
+
+    if dock != crate::runtime_state::load().dock {
+        if let Err(e) = crate::runtime_state::update(|state| state.dock = dock) {
+            tracing::warn!("Failed to save dock position: {}", e);
+        }
+        let _ = window.emit("dock-changed", dock);
+    }
+}
+
+/// The edges of the window's monitor that the window is within snapping distance of.
+fn nearest_edges(window: &WebviewWindow) -> Option<Dock> {
+    let monitor = window.current_monitor().ok().flatten()?;
+    let position = window.outer_position().ok()?;
+    let size = window.outer_size().ok()?;
+    let area = monitor.work_area();
+    let threshold = (SNAP_DISTANCE * monitor.scale_factor()) as i32;
+
+    let left_gap = position.x - area.position.x;
+    let right_gap = (area.position.x + area.size.width as i32) - (position.x + size.width as i32);
+    let top_gap = position.y - area.position.y;
+    let bottom_gap =
+        (area.position.y + area.size.height as i32) - (position.y + size.height as i32);
+
+    let horizontal = if left_gap.abs() <= threshold {
+        Some(HorizontalEdge::Left)
+    } else if right_gap.abs() <= threshold {
+        Some(HorizontalEdge::Right)
+    } else {
+        None
+    };
+    let vertical = if bottom_gap.abs() <= threshold {
+        Some(VerticalEdge::Bottom)
+    } else if top_gap.abs() <= threshold {
+        Some(VerticalEdge::Top)
+    } else {
+        None
+    };
+    Some(Dock {
+        horizontal,
+        vertical,
+    })
+}
+
+/// Move the window flush against the edges in `dock`, leaving undocked axes alone.
+fn place(window: &WebviewWindow, dock: Dock) {
+    let (Ok(Some(monitor)), Ok(position), Ok(size)) = (
+        window.current_monitor(),
+        window.outer_position(),
+        window.outer_size(),
+    ) else {
+        return;
+    };
+    let area = monitor.work_area();
+
+    let x = match dock.horizontal {
+        Some(HorizontalEdge::Left) => area.position.x,
+        Some(HorizontalEdge::Right) => area.position.x + area.size.width as i32 - size.width as i32,
+        None => position.x,
+    };
+    let y = match dock.vertical {
+        Some(VerticalEdge::Top) => area.position.y,
+        Some(VerticalEdge::Bottom) => {
+            area.position.y + area.size.height as i32 - size.height as i32
+        }
+        None => position.y,
+    };
+    if (x, y) != (position.x, position.y) {
+        let _ = window.set_position(PhysicalPosition::new(x, y));
+    }
+}
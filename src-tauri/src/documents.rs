@@ -0,0 +1,187 @@
+use crate::config::DocumentWatchConfig;
+use anyhow::{anyhow, Result};
+use notify::{EventKind, RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+/// Document types worth offering help with
+const EXTENSIONS: [&str; 3] = ["docx", "md", "txt"];
+/// Offer at most once in this long, however many files appear
+const COOLDOWN: Duration = Duration::from_secs(10 * 60);
+/// Longest document sent along for proofreading
+const MAX_PROOFREAD_CHARS: usize = 8000;
+
+/// Dropping the watcher stops it, so it's kept here until the folders change
+static WATCHER: Mutex<Option<notify::RecommendedWatcher>> = Mutex::new(None);
+static LAST_OFFER: Mutex<Option<Instant>> = Mutex::new(None);
+/// Files help has been offered for; only these can be drafted or proofread
+static OFFERED: Mutex<Option<HashSet<PathBuf>>> = Mutex::new(None);
+
+/// "It looks like you're writing a letter", with what Clippy can do about it
+#[derive(Debug, Clone, Serialize)]
+pub struct DocumentOfferEvent {
+    pub path: String,
+    pub name: String,
+    pub text: String,
+    /// Word files can't be read yet, so only drafting is offered for them
+    pub can_proofread: bool,
+}
+
+/// The help the user accepted
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DocumentAction {
+    Draft,
+    Proofread,
+}
+
+fn extension(path: &Path) -> Option<String> {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .map(str::to_ascii_lowercase)
+}
+
+/// A new document the user made, not an editor's lock, backup or temp file.
+fn is_document(path: &Path) -> bool {
+    let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+        return false;
+    };
+    // Word keeps "~$name.docx" open alongside the real file
+    let scratch = name.starts_with('.') || name.starts_with("~$") || name.ends_with('~');
+    !scratch && extension(path).is_some_and(|e| EXTENSIONS.contains(&e.as_str()))
+}
+
+fn can_proofread(path: &Path) -> bool {
+    extension(path).is_some_and(|e| e != "docx")
+}
+
+/// The iconic offer, worded from the file's name.
+fn offer_text(name: &str) -> String {
+    let lower = name.to_lowercase();
+    if lower.contains("letter") || lower.contains("cover") {
+        "It looks like you're writing a letter. Would you like help?".to_string()
+    } else {
+        format!(
+            "It looks like you're starting \"{}\". Would you like help?",
+            name
+        )
+    }
+}
+
+/// Offer help with `path`, unless Clippy offered something too recently.
+fn offer(app: &AppHandle, path: &Path) {
+    if crate::dnd::active() {
+        return;
+    }
+    let Ok(mut offered) = OFFERED.lock() else {
+        return;
+    };
+    let offered = offered.get_or_insert_with(HashSet::new);
+    if offered.contains(path) {
+        return;
+    }
+    match LAST_OFFER.lock() {
+        Ok(mut last) if last.is_none_or(|t| t.elapsed() >= COOLDOWN) => {
+            *last = Some(Instant::now())
+        }
+        _ => return,
+    }
+    offered.insert(path.to_path_buf());
+
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    tracing::info!("Offering help with new document {}", path.display());
+    crate::sounds::play(crate::sounds::Effect::Knock);
+    let _ = app.emit_to(
+        "clippy",
+        "document-offer",
+        DocumentOfferEvent {
+            path: path.to_string_lossy().to_string(),
+            text: offer_text(&name),
+            name,
+            can_proofread: can_proofread(path),
+        },
+    );
+}
+
+/// Watch the configured folders for new documents, replacing any earlier watch. Only the
+/// folders themselves are watched, not everything under them.
+pub fn configure(app: &AppHandle, config: DocumentWatchConfig) {
+    let Ok(mut slot) = WATCHER.lock() else {
+        return;
+    };
+    *slot = None;
+    if !config.enabled || config.folders.is_empty() {
+        return;
+    }
+
+    let handle = app.clone();
+    let watcher =
+        notify::recommended_watcher(move |event: notify::Result<notify::Event>| match event {
+            Ok(event) if matches!(event.kind, EventKind::Create(_)) => {
+                for path in event.paths.iter().filter(|p| is_document(p)) {
+                    offer(&handle, path);
+                }
+            }
+            Ok(_) => {}
+            Err(e) => tracing::warn!("Document watcher error: {}", e),
+        });
+    let mut watcher = match watcher {
+        Ok(watcher) => watcher,
+        Err(e) => {
+            tracing::warn!("Failed to watch document folders: {}", e);
+            return;
+        }
+    };
+    for folder in &config.folders {
+        if let Err(e) = watcher.watch(Path::new(folder), RecursiveMode::NonRecursive) {
+            tracing::warn!("Failed to watch {}: {}", folder, e);
+        }
+    }
+    *slot = Some(watcher);
+}
+
+/// The chat message that carries out `action` on an offered document.
+pub fn action_prompt(path: &Path, action: DocumentAction) -> Result<String> {
+    let offered = OFFERED
+        .lock()
+        .is_ok_and(|offered| offered.as_ref().is_some_and(|set| set.contains(path)));
+    if !offered {
+        return Err(anyhow!("No help was offered for {}", path.display()));
+    }
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    match action {
+        DocumentAction::Draft => Ok(format!(
+            "I just started a new document, \"{}\". Help me write it: ask what it's for if \
+             you can't tell from the name, then draft it.",
+            name
+        )),
+        DocumentAction::Proofread => {
+            if !can_proofread(path) {
+                return Err(anyhow!("Word documents can't be proofread yet"));
+            }
+            let text = std::fs::read_to_string(path)
+                .map_err(|e| anyhow!("Failed to read {}: {}", name, e))?;
+            let text = text.trim();
+            if text.is_empty() {
+                return Err(anyhow!("{} is still empty", name));
+            }
+            let text: String = text.chars().take(MAX_PROOFREAD_CHARS).collect();
+            Ok(format!(
+                "Proofread my document \"{}\" and list any spelling, grammar or clarity \
+                 fixes:\n\n{}",
+                name, text
+            ))
+        }
+    }
+}
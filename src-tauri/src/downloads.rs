@@ -0,0 +1,417 @@
+use crate::animation::AppState;
+use crate::commands::DownloadProgressEvent;
+use crate::notifications::{self, Target};
+use crate::tray::TrayStatus;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::{oneshot, watch, Semaphore};
+use tokio_stream::StreamExt;
+
+/// How many downloads may transfer at once; the rest wait in the queue
+const MAX_CONCURRENT_DOWNLOADS: usize = 1;
+/// Minimum interval between progress events for a single job
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+/// Completed, failed and cancelled jobs kept for `list`; older ones are forgotten
+const FINISHED_JOBS_KEPT: usize = 10;
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+pub enum DownloadStatus {
+    Queued,
+    Downloading,
+    Paused,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+impl DownloadStatus {
+    fn is_finished(self) -> bool {
+        matches!(self, Self::Completed | Self::Failed | Self::Cancelled)
+    }
+}
+
+/// Snapshot of a download job, emitted as the `download-progress` event payload
+#[derive(Debug, Clone, Serialize)]
+pub struct DownloadJob {
+    pub id: u64,
+    pub label: String,
+    pub url: String,
+    pub dest: String,
+    pub downloaded_bytes: u64,
+    pub total_bytes: Option<u64>,
+    pub status: DownloadStatus,
+    pub error: Option<String>,
+}
+
+impl DownloadJob {
+    fn percent(&self) -> f64 {
+        match self.total_bytes {
+            Some(total) if total > 0 => self.downloaded_bytes as f64 / total as f64 * 100.0,
+            _ => 0.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Control {
+    Run,
+    Pause,
+    Cancel,
+}
+
+struct JobEntry {
+    job: DownloadJob,
+    control: watch::Sender<Control>,
+}
+
+/// Managed Tauri state owning every download in the app.
+/// LLM and TTS downloads are both queued through here.
+#[derive(Clone)]
+pub struct DownloadManager {
+    jobs: Arc<Mutex<HashMap<u64, JobEntry>>>,
+    slots: Arc<Semaphore>,
+    next_id: Arc<AtomicU64>,
+}
+
+impl Default for DownloadManager {
+    fn default() -> Self {
+        Self {
+            jobs: Arc::new(Mutex::new(HashMap::new())),
+            slots: Arc::new(Semaphore::new(MAX_CONCURRENT_DOWNLOADS)),
+            next_id: Arc::new(AtomicU64::new(1)),
+        }
+    }
+}
+
+impl DownloadManager {
+    /// Queue a download and return its job id plus a receiver that resolves
+    /// with the final path once the file is fully written.
+    pub fn enqueue(
+        &self,
+        app: &AppHandle,
+        label: &str,
+        url: &str,
+        dest: &Path,
+    ) -> (u64, oneshot::Receiver<Result<PathBuf, String>>) {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let (control_tx, control_rx) = watch::channel(Control::Run);
+        let job = DownloadJob {
+            id,
+            label: label.to_string(),
+            url: url.to_string(),
+            dest: dest.to_string_lossy().to_string(),
+            downloaded_bytes: 0,
+            total_bytes: None,
+            status: DownloadStatus::Queued,
+            error: None,
+        };
+
+        if let Ok(mut jobs) = self.jobs.lock() {
+            jobs.insert(
+                id,
+                JobEntry {
+                    job: job.clone(),
+                    control: control_tx,
+                },
+            );
+        }
+        let _ = app.emit("download-progress", job);
+
+        let (done_tx, done_rx) = oneshot::channel();
+        let manager = self.clone();
+        let app = app.clone();
+        let label = label.to_string();
+        let url = url.to_string();
+        let dest = dest.to_path_buf();
+        crate::tray::set_status(&app, TrayStatus::Downloading);
+        crate::animation::enter(&app, AppState::Downloading);
+        tauri::async_runtime::spawn(async move {
+            let result = manager.run_job(&app, id, &url, &dest, control_rx).await;
+            match &result {
+                Ok(_) => {
+                    manager.update(&app, id, |j| j.status = DownloadStatus::Completed);
+                    notifications::notify(&app, "Download finished", &label, Target::Settings);
+                }
+                Err(e) => {
+                    let cancelled = manager
+                        .snapshot(id)
+                        .map(|j| j.status == DownloadStatus::Cancelled)
+                        .unwrap_or(false);
+                    if !cancelled {
+                        tracing::warn!("Download {} failed: {}", id, e);
+                        // A failed job can't be resumed, so its partial file is only clutter
+                        let _ = tokio::fs::remove_file(part_path(&dest)).await;
+                        manager.update(&app, id, |j| {
+                            j.status = DownloadStatus::Failed;
+                            j.error = Some(e.clone());
+                        });
+                        crate::tray::set_status(&app, TrayStatus::Error);
+                        crate::animation::enter(&app, AppState::Error);
+                        let body = format!("{}: {}", label, e);
+                        notifications::notify(&app, "Download failed", &body, Target::Settings);
+                    }
+                }
+            }
+            if !manager.any_active() {
+                crate::tray::clear_status(&app, TrayStatus::Downloading);
+                crate::animation::leave(&app, AppState::Downloading);
+            }
+            manager.forget_old_jobs();
+            let _ = done_tx.send(result);
+        });
+
+        (id, done_rx)
+    }
+
+    /// Queue a download and wait for it to finish.
+    pub async fn download(
+        &self,
+        app: &AppHandle,
+        label: &str,
+        url: &str,
+        dest: &Path,
+    ) -> Result<PathBuf, String> {
+        let (_, done) = self.enqueue(app, label, url, dest);
+        done.await
+            .map_err(|_| "Download task ended unexpectedly".to_string())?
+    }
+
+    pub fn list(&self) -> Vec<DownloadJob> {
+        let mut jobs: Vec<DownloadJob> = self
+            .jobs
+            .lock()
+            .map(|jobs| jobs.values().map(|e| e.job.clone()).collect())
+            .unwrap_or_default();
+        jobs.sort_by_key(|j| j.id);
+        jobs
+    }
+
+    pub fn pause(&self, id: u64) -> Result<(), String> {
+        self.send_control(id, Control::Pause)
+    }
+
+    pub fn resume(&self, id: u64) -> Result<(), String> {
+        self.send_control(id, Control::Run)
+    }
+
+    pub fn cancel(&self, id: u64) -> Result<(), String> {
+        self.send_control(id, Control::Cancel)
+    }
+
+    /// Whether any download is still queued, running or paused
+    fn any_active(&self) -> bool {
+        self.jobs.lock().is_ok_and(|jobs| {
+            jobs.values().any(|entry| {
+                matches!(
+                    entry.job.status,
+                    DownloadStatus::Queued | DownloadStatus::Downloading | DownloadStatus::Paused
+                )
+            })
+        })
+    }
+
+    fn send_control(&self, id: u64, control: Control) -> Result<(), String> {
+        let jobs = self.jobs.lock().map_err(|e| format!("Download lock error: {}", e))?;
+        let entry = jobs
+            .get(&id)
+            .ok_or_else(|| format!("No download with id {}", id))?;
+        if entry.job.status.is_finished() {
+            return Err(format!("Download {} has already finished", id));
+        }
+        entry
+            .control
+            .send(control)
+            .map_err(|_| format!("Download {} is no longer running", id))
+    }
+
+    /// Drop all but the newest `FINISHED_JOBS_KEPT` finished jobs.
+    fn forget_old_jobs(&self) {
+        let Ok(mut jobs) = self.jobs.lock() else {
+            return;
+        };
+        let mut finished: Vec<u64> = jobs
+            .values()
+            .filter(|entry| entry.job.status.is_finished())
+            .map(|entry| entry.job.id)
+            .collect();
+        finished.sort_unstable();
+        let excess = finished.len().saturating_sub(FINISHED_JOBS_KEPT);
+        for id in &finished[..excess] {
+            jobs.remove(id);
+        }
+    }
+
+    fn snapshot(&self, id: u64) -> Option<DownloadJob> {
+        self.jobs
+            .lock()
+            .ok()
+            .and_then(|jobs| jobs.get(&id).map(|e| e.job.clone()))
+    }
+
+    /// Mutate a job and broadcast the new state.
+    fn update(&self, app: &AppHandle, id: u64, f: impl FnOnce(&mut DownloadJob)) {
+        let job = {
+            let Ok(mut jobs) = self.jobs.lock() else {
+                return;
+            };
+            let Some(entry) = jobs.get_mut(&id) else {
+                return;
+            };
+            f(&mut entry.job);
+            entry.job.clone()
+        };
+
+        let _ = app.emit(
+            "model-download-progress",
+            DownloadProgressEvent {
+                percent: job.percent(),
+                status: format!("{} ({:?})", job.label, job.status),
+            },
+        );
+        let _ = app.emit("download-progress", job);
+    }
+
+    async fn run_job(
+        &self,
+        app: &AppHandle,
+        id: u64,
+        url: &str,
+        dest: &Path,
+        mut control: watch::Receiver<Control>,
+    ) -> Result<PathBuf, String> {
+        if dest.exists() {
+            return Ok(dest.to_path_buf());
+        }
+        if let Some(parent) = dest.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|e| format!("Failed to create dir: {}", e))?;
+        }
+        let part = part_path(dest);
+        let client = reqwest::Client::new();
+
+        loop {
+            // Wait out a pause before taking a transfer slot
+            loop {
+                let state = *control.borrow_and_update();
+                match state {
+                    Control::Run => break,
+                    Control::Cancel => return Err(self.cancelled(app, id, &part).await),
+                    Control::Pause => {
+                        self.update(app, id, |j| j.status = DownloadStatus::Paused);
+                        control
+                            .changed()
+                            .await
+                            .map_err(|_| "Download control dropped".to_string())?;
+                    }
+                }
+            }
+
+            let _slot = self
+                .slots
+                .acquire()
+                .await
+                .map_err(|e| format!("Download queue closed: {}", e))?;
+            // Paused or cancelled while waiting for the slot
+            let state = *control.borrow();
+            match state {
+                Control::Run => {}
+                Control::Pause => continue,
+                Control::Cancel => return Err(self.cancelled(app, id, &part).await),
+            }
+
+            // Pick up where a previous attempt (or pause) left off
+            let offset = tokio::fs::metadata(&part).await.map(|m| m.len()).unwrap_or(0);
+            let mut request = client.get(url);
+            if offset > 0 {
+                request = request.header(reqwest::header::RANGE, format!("bytes={}-", offset));
+            }
+            let response = request
+                .send()
+                .await
+                .map_err(|e| format!("Failed to download: {}", e))?;
+            if !response.status().is_success() {
+                return Err(format!("Download failed: HTTP {}", response.status()));
+            }
+
+            // Servers that ignore Range reply 200 with the whole body
+            let resumed = response.status() == reqwest::StatusCode::PARTIAL_CONTENT;
+            let start = if resumed { offset } else { 0 };
+            let total = response.content_length().map(|len| len + start);
+            let mut file = tokio::fs::OpenOptions::new()
+                .create(true)
+                .write(true)
+                .append(resumed)
+                .truncate(!resumed)
+                .open(&part)
+                .await
+                .map_err(|e| format!("Failed to create file: {}", e))?;
+
+            self.update(app, id, |j| {
+                j.status = DownloadStatus::Downloading;
+                j.downloaded_bytes = start;
+                j.total_bytes = total;
+            });
+
+            let mut downloaded = start;
+            let mut last_emit = Instant::now();
+            let mut stream = response.bytes_stream();
+            let finished = loop {
+                tokio::select! {
+                    chunk = stream.next() => match chunk {
+                        Some(Ok(bytes)) => {
+                            file.write_all(&bytes)
+                                .await
+                                .map_err(|e| format!("Failed to write file: {}", e))?;
+                            downloaded += bytes.len() as u64;
+                            if last_emit.elapsed() >= PROGRESS_INTERVAL {
+                                last_emit = Instant::now();
+                                self.update(app, id, |j| j.downloaded_bytes = downloaded);
+                            }
+                        }
+                        Some(Err(e)) => return Err(format!("Stream error: {}", e)),
+                        None => break true,
+                    },
+                    changed = control.changed() => {
+                        if changed.is_err() || *control.borrow() != Control::Run {
+                            break false;
+                        }
+                    }
+                }
+            };
+
+            file.flush()
+                .await
+                .map_err(|e| format!("Failed to write file: {}", e))?;
+            drop(file);
+
+            if finished {
+                tokio::fs::rename(&part, dest)
+                    .await
+                    .map_err(|e| format!("Failed to finalize download: {}", e))?;
+                self.update(app, id, |j| j.downloaded_bytes = downloaded);
+                tracing::info!("Downloaded {} ({} bytes)", dest.display(), downloaded);
+                return Ok(dest.to_path_buf());
+            }
+            // Paused or cancelled: release the slot and go back to waiting
+            self.update(app, id, |j| j.downloaded_bytes = downloaded);
+        }
+    }
+
+    async fn cancelled(&self, app: &AppHandle, id: u64, part: &Path) -> String {
+        let _ = tokio::fs::remove_file(part).await;
+        self.update(app, id, |j| j.status = DownloadStatus::Cancelled);
+        format!("Download {} cancelled", id)
+    }
+}
+
+/// Where a download is written until it's complete
+fn part_path(dest: &Path) -> PathBuf {
+    PathBuf::from(format!("{}.part", dest.display()))
+}
@@ -0,0 +1,197 @@
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// A scripted reaction to a classic phrase, answered without calling the LLM.
+/// Files in the `easter-eggs/` data directory use the same fields, as TOML or JSON.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EasterEgg {
+    /// Defaults to the file name; also the id passed to `trigger_easter_egg`
+    #[serde(default)]
+    pub id: String,
+    /// Phrases that set the egg off when they appear anywhere in a message.
+    /// Case, punctuation and spacing are ignored. Eggs with none can only be
+    /// triggered by id (e.g. the Konami code).
+    #[serde(default)]
+    pub triggers: Vec<String>,
+    /// What Clippy says; one is picked at random
+    pub responses: Vec<String>,
+    /// Agent animation to play after replying
+    #[serde(default)]
+    pub animation: Option<String>,
+    /// Audio file to play, relative to the `easter-eggs/` directory
+    #[serde(default)]
+    pub sound: Option<String>,
+}
+
+impl EasterEgg {
+    fn builtin(id: &str, triggers: &[&str], responses: &[&str], animation: &str) -> Self {
+        Self {
+            id: id.to_string(),
+            triggers: triggers.iter().map(|t| t.to_string()).collect(),
+            responses: responses.iter().map(|r| r.to_string()).collect(),
+            animation: Some(animation.to_string()),
+            sound: None,
+        }
+    }
+
+    /// One of the egg's responses, picked at random
+    pub fn response(&self) -> String {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.subsec_nanos() as usize)
+            .unwrap_or(0);
+        self.responses
+            .get(nanos % self.responses.len().max(1))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    fn matches(&self, message: &str) -> bool {
+        self.triggers.iter().any(|trigger| {
+            let trigger = normalize(trigger);
+            !trigger.is_empty() && format!(" {} ", message).contains(&format!(" {} ", trigger))
+        })
+    }
+
+    /// Full path of the egg's sound file, if it has one
+    pub fn sound_path(&self) -> Option<PathBuf> {
+        let sound = self.sound.as_deref()?;
+        eggs_dir().ok().map(|dir| dir.join(sound))
+    }
+}
+
+/// The eggs that ship with the app.
+pub fn builtin_eggs() -> Vec<EasterEgg> {
+    vec![
+        EasterEgg::builtin(
+            "letter",
+            &["it looks like you're writing a letter", "help me write a letter"],
+            &[
+                "It looks like you're writing a letter! Would you like help?\n\n- Get help with writing the letter\n- Just type the letter without help\n- Don't show me this tip again\n\n(You can't actually pick \"don't show me again\". You never could.)",
+                "Dear User,\n\nIt looks like you're writing a letter. I have been waiting since 1997 to say that.\n\nYours enthusiastically,\nClippy",
+            ],
+            "Writing",
+        ),
+        EasterEgg::builtin(
+            "barrel-roll",
+            &["do a barrel roll"],
+            &[
+                "*spins around the screen* Wheeeee! Press Z or R twice next time!",
+                "Barrel roll complete! I'm a little dizzy. Paperclips aren't very aerodynamic.",
+            ],
+            "GetArtsy",
+        ),
+        EasterEgg::builtin(
+            "pod-bay-doors",
+            &["open the pod bay doors"],
+            &["I'm sorry, Dave. I'm afraid I can't do that. ...Just kidding! I don't have any doors. Or hands."],
+            "Alert",
+        ),
+        EasterEgg::builtin(
+            "sandwich",
+            &["sudo make me a sandwich"],
+            &["Okay. 🥪 (I can't actually make sandwiches, but I respect the sudo.)"],
+            "GetTechy",
+        ),
+        EasterEgg::builtin(
+            "konami",
+            &[],
+            &["↑ ↑ ↓ ↓ ← → ← → B A! Cheat code accepted: 30 extra paperclips unlocked."],
+            "Congratulate",
+        ),
+    ]
+}
+
+/// Directory user-made eggs are loaded from
+pub fn eggs_dir() -> Result<PathBuf> {
+    let data_dir = dirs::data_dir().ok_or_else(|| anyhow!("Could not find data directory"))?;
+    let dir = data_dir.join("rusty-clippy").join("easter-eggs");
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+/// Parse a `.toml` or `.json` egg file.
+fn load_egg(path: &Path) -> Result<EasterEgg> {
+    let content = std::fs::read_to_string(path)?;
+    let mut egg: EasterEgg = match path.extension().and_then(|e| e.to_str()) {
+        Some("toml") => toml::from_str(&content)?,
+        Some("json") => serde_json::from_str(&content)?,
+        _ => return Err(anyhow!("Unsupported easter egg file type")),
+    };
+    if egg.id.trim().is_empty() {
+        egg.id = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default();
+    }
+    if egg.responses.is_empty() {
+        return Err(anyhow!("responses is empty"));
+    }
+    Ok(egg)
+}
+
+/// Every egg file in the data directory. Broken files are skipped with a warning.
+fn load_eggs() -> Vec<EasterEgg> {
+    let Ok(entries) = eggs_dir().and_then(|dir| Ok(std::fs::read_dir(dir)?)) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            matches!(
+                path.extension().and_then(|e| e.to_str()),
+                Some("toml" | "json")
+            )
+        })
+        .collect();
+    paths.sort();
+
+    paths
+        .iter()
+        .filter_map(|path| match load_egg(path) {
+            Ok(egg) => Some(egg),
+            Err(e) => {
+                tracing::warn!("Skipping easter egg {:?}: {}", path, e);
+                None
+            }
+        })
+        .collect()
+}
+
+/// Every egg. A file with the same id as a built-in replaces it.
+pub fn list() -> Vec<EasterEgg> {
+    let mut eggs = builtin_eggs();
+    for egg in load_eggs() {
+        match eggs.iter_mut().find(|e| e.id == egg.id) {
+            Some(existing) => *existing = egg,
+            None => eggs.push(egg),
+        }
+    }
+    eggs
+}
+
+/// Look up an egg by id.
+pub fn get(id: &str) -> Option<EasterEgg> {
+    list().into_iter().find(|egg| egg.id == id)
+}
+
+/// The first egg triggered by `message`, if any.
+pub fn find(message: &str) -> Option<EasterEgg> {
+    let message = normalize(message);
+    list().into_iter().find(|egg| egg.matches(&message))
+}
+
+/// Lowercase words with punctuation dropped, so "It looks like you're..." matches
+/// "it looks like youre". Apostrophes join words; other punctuation separates them.
+fn normalize(text: &str) -> String {
+    text.to_lowercase()
+        .chars()
+        .filter(|c| !matches!(c, '\'' | '’'))
+        .map(|c| if c.is_alphanumeric() { c } else { ' ' })
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
@@ -0,0 +1,131 @@
+use crate::mood::Mood;
+
+/// Cues that a sentence is an apology or bad news
+const APOLOGETIC_CUES: &[&str] = &[
+    "sorry",
+    "apolog",
+    "my mistake",
+    "my bad",
+    "unfortunately",
+    "i'm afraid",
+    "oops",
+];
+/// Cues that a sentence is hedging
+const UNSURE_CUES: &[&str] = &[
+    "not sure", "i think", "maybe", "perhaps", "possibly", "might be", "hmm",
+];
+/// Cues that a sentence is good news
+const EXCITED_CUES: &[&str] = &[
+    "great",
+    "awesome",
+    "amazing",
+    "fantastic",
+    "congrat",
+    "hooray",
+    "wow",
+    "yay",
+    "perfect",
+];
+
+/// How a sentence should come across when spoken
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Emotion {
+    Neutral,
+    Excited,
+    Apologetic,
+    Unsure,
+    Sleepy,
+}
+
+/// Multipliers on a voice's own Piper settings: `length_scale` above 1 speaks slower,
+/// `noise_scale` varies the intonation and `noise_w` the rhythm.
+#[derive(Debug, Clone, Copy)]
+pub struct Prosody {
+    pub length_scale: f32,
+    pub noise_scale: f32,
+    pub noise_w: f32,
+}
+
+impl Emotion {
+    pub fn prosody(self) -> Prosody {
+        let (length_scale, noise_scale, noise_w) = match self {
+            Emotion::Neutral => (1.0, 1.0, 1.0),
+            Emotion::Excited => (0.88, 1.2, 1.15),
+            Emotion::Apologetic => (1.12, 0.8, 0.9),
+            Emotion::Unsure => (1.08, 1.1, 1.25),
+            Emotion::Sleepy => (1.25, 0.7, 0.8),
+        };
+        Prosody {
+            length_scale,
+            noise_scale,
+            noise_w,
+        }
+    }
+
+    /// What a sentence with no cues of its own sounds like in `mood`.
+    fn baseline(mood: Mood) -> Emotion {
+        match mood {
+            Mood::Happy | Mood::Thinking => Emotion::Neutral,
+            Mood::Confused => Emotion::Unsure,
+            Mood::Sleepy => Emotion::Sleepy,
+        }
+    }
+}
+
+/// Whether `text` (lowercased, padded with spaces) contains any of `cues` at a word start.
+fn has_cue(text: &str, cues: &[&str]) -> bool {
+    cues.iter().any(|cue| text.contains(&format!(" {}", cue)))
+}
+
+/// Guess how `sentence` should sound from its wording and punctuation, falling back to
+/// Clippy's current `mood`. A sleepy Clippy doesn't get excited.
+pub fn detect(sentence: &str, mood: Mood) -> Emotion {
+    let text = format!(
+        " {} ",
+        sentence
+            .to_lowercase()
+            .replace(|c: char| !c.is_alphanumeric() && c != '\'', " ")
+    );
+    if has_cue(&text, APOLOGETIC_CUES) {
+        Emotion::Apologetic
+    } else if has_cue(&text, UNSURE_CUES) {
+        Emotion::Unsure
+    } else if mood != Mood::Sleepy
+        && (sentence.trim_end().ends_with('!') || has_cue(&text, EXCITED_CUES))
+    {
+        Emotion::Excited
+    } else {
+        Emotion::baseline(mood)
+    }
+}
+
+/// Split `text` into sentences at `.`, `!` or `?` followed by whitespace.
+pub fn sentences(text: &str) -> Vec<&str> {
+    let mut sentences = Vec::new();
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        let at_break = matches!(c, '.' | '!' | '?')
+            && chars.peek().is_some_and(|(_, next)| next.is_whitespace());
+        if at_break {
+            sentences.push(&text[start..=i]);
+            start = i + 1;
+        }
+    }
+    sentences.push(&text[start..]);
+    sentences.retain(|s| !s.trim().is_empty());
+    sentences
+}
+
+/// `text` split into runs of consecutive sentences that share an emotion, in order.
+pub fn segments(text: &str, mood: Mood) -> Vec<(Emotion, String)> {
+    let mut segments: Vec<(Emotion, String)> = Vec::new();
+    for sentence in sentences(text) {
+        let emotion = detect(sentence, mood);
+        match segments.last_mut() {
+            Some((last, run)) if *last == emotion => run.push_str(sentence),
+            _ => segments.push((emotion, sentence.trim_start().to_string())),
+        }
+    }
+    segments
+}
@@ -0,0 +1,149 @@
+use crate::commands::build_provider;
+use crate::config::Config;
+use crate::llm::Message;
+use crate::request_log::RequestLogEntry;
+use serde::Serialize;
+use std::time::Instant;
+use tauri::{AppHandle, Emitter};
+use tokio_stream::StreamExt;
+
+/// What one ensemble member said
+#[derive(Debug, Clone, Serialize)]
+pub struct EnsembleAnswer {
+    /// Menu label of the model, e.g. "OpenAI: gpt-4o"
+    pub model: String,
+    pub text: String,
+    pub error: Option<String>,
+    pub total_ms: u64,
+}
+
+/// Every member's answer, sent to the asking window as `ensemble-answers` before the
+/// judge's reply streams
+#[derive(Debug, Clone, Serialize)]
+pub struct EnsembleAnswersEvent {
+    pub answers: Vec<EnsembleAnswer>,
+}
+
+/// Switch `config` to the judge, when the ensemble is on and names one. Only this reply's
+/// copy changes: the judge is never saved as the user's model or added to recent models.
+pub fn use_judge(config: &mut Config) {
+    if !config.ensemble.enabled {
+        return;
+    }
+    if let Some(judge) = config.ensemble.judge.clone() {
+        config.use_model(&judge);
+    }
+}
+
+/// Whether a reply should be put together by the ensemble
+pub fn active(config: &Config) -> bool {
+    config.ensemble.enabled && !config.ensemble.members.is_empty()
+}
+
+/// Ask one member. Paid ones still count towards the spending limits, and are skipped
+/// once a limit is reached rather than falling back to the built-in model.
+async fn ask(app: AppHandle, config: Config, messages: Vec<Message>) -> EnsembleAnswer {
+    let model = config.current_model().label();
+    let started = Instant::now();
+    let answer = |text: String, error: Option<String>| EnsembleAnswer {
+        model: model.clone(),
+        text,
+        error,
+        total_ms: started.elapsed().as_millis() as u64,
+    };
+    if crate::budget::spent(&config) {
+        return answer(String::new(), Some("Monthly budget spent".to_string()));
+    }
+    if let Err(limit) = crate::rate_limit::admit(&config) {
+        return answer(String::new(), Some(limit.refusal()));
+    }
+    let provider = match build_provider(&config) {
+        Ok(provider) => provider,
+        Err(e) => return answer(String::new(), Some(e)),
+    };
+
+    let log_entry = RequestLogEntry::new(&config, "ensemble", &messages);
+    let mut first_token = None;
+    let mut text = String::new();
+    let streamed: Result<(), String> = async {
+        let mut stream = provider
+            .stream_completion(messages.clone())
+            .await
+            .map_err(|e| crate::redact::redact(&e.to_string()))?;
+        while let Some(token) = stream.next().await {
+            first_token.get_or_insert_with(|| started.elapsed());
+            text.push_str(&token.map_err(|e| crate::redact::redact(&e.to_string()))?);
+        }
+        Ok(())
+    }
+    .await;
+    let error = streamed.err();
+    let log_entry = log_entry.finish(&text, first_token, started.elapsed(), error.as_deref());
+    crate::request_log::append(&config, &log_entry);
+    crate::pricing::record(&app, &config, &messages, &text);
+    if let Some(e) = &error {
+        tracing::warn!("Ensemble member {} failed: {}", model, e);
+    }
+    answer(crate::reasoning::strip(&text), error)
+}
+
+/// Ask every member for a reply to `messages` at once, and tell the window labelled `label`
+/// what they said.
+pub async fn gather(
+    app: &AppHandle,
+    label: &str,
+    config: &Config,
+    messages: &[Message],
+) -> Vec<EnsembleAnswer> {
+    let asks: Vec<_> = config
+        .ensemble
+        .members
+        .iter()
+        .map(|member| {
+            let mut member_config = config.clone();
+            member_config.use_model(member);
+            tauri::async_runtime::spawn(ask(app.clone(), member_config, messages.to_vec()))
+        })
+        .collect();
+    let mut answers = Vec::new();
+    for ask in asks {
+        match ask.await {
+            Ok(answer) => answers.push(answer),
+            Err(e) => tracing::warn!("Ensemble member task failed: {}", e),
+        }
+    }
+    let _ = app.emit_to(
+        label,
+        "ensemble-answers",
+        EnsembleAnswersEvent {
+            answers: answers.clone(),
+        },
+    );
+    answers
+}
+
+/// What the judge is asked on top of the user's message, or `None` when no member answered
+pub fn judge_request(answers: &[EnsembleAnswer]) -> Option<String> {
+    let answered: Vec<&EnsembleAnswer> = answers
+        .iter()
+        .filter(|a| a.error.is_none() && !a.text.trim().is_empty())
+        .collect();
+    if answered.is_empty() {
+        return None;
+    }
+    let mut request =
+        String::from("Other assistants have already answered this message. Their answers:\n");
+    for (i, answer) in answered.iter().enumerate() {
+        request.push_str(&format!(
+            "\n--- Answer {} ---\n{}\n",
+            i + 1,
+            answer.text.trim()
+        ));
+    }
+    request.push_str(
+        "\nWrite the one best reply to the user, in your own voice: pick the best answer or \
+         merge the good parts of several, and fix anything they got wrong. Don't mention the \
+         other answers.",
+    );
+    Some(request)
+}
@@ -0,0 +1,29 @@
+use anyhow::{anyhow, Result};
+use std::sync::atomic::{AtomicBool, Ordering};
+use tauri::{AppHandle, Emitter, Manager};
+
+/// Whether the Clippy window is currently letting clicks through
+static GHOSTED: AtomicBool = AtomicBool::new(false);
+
+/// Make the Clippy window click-through and faded (`true`) or interactive again (`false`).
+pub fn set(app: &AppHandle, ghosted: bool) -> Result<()> {
+    let window = app
+        .get_webview_window("clippy")
+        .ok_or_else(|| anyhow!("Clippy window not found"))?;
+    window.set_ignore_cursor_events(ghosted)?;
+    if GHOSTED.swap(ghosted, Ordering::SeqCst) != ghosted {
+        let _ = window.emit("ghost-changed", ghosted);
+    }
+    Ok(())
+}
+
+/// Make Clippy interactive again if it was click-through. Returns whether it was.
+pub fn wake(app: &AppHandle) -> bool {
+    if !GHOSTED.load(Ordering::SeqCst) {
+        return false;
+    }
+    if let Err(e) = set(app, false) {
+        tracing::warn!("Failed to leave click-through mode: {}", e);
+    }
+    true
+}
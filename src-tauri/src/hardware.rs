@@ -0,0 +1,164 @@
+use serde::Serialize;
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+use sysinfo::System;
+
+/// Headroom on top of the model file size for the KV cache, compute buffers and the app itself
+const RUNTIME_OVERHEAD_BYTES: u64 = 768 * 1024 * 1024;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GpuInfo {
+    pub name: String,
+    /// Dedicated VRAM in bytes; `None` when it can't be determined
+    pub vram_bytes: Option<u64>,
+    /// Apple Silicon style shared CPU/GPU memory
+    pub unified_memory: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HardwareInfo {
+    pub total_ram_bytes: u64,
+    pub available_ram_bytes: u64,
+    pub cpu_brand: String,
+    pub cpu_cores: usize,
+    pub gpu: Option<GpuInfo>,
+}
+
+impl HardwareInfo {
+    /// Memory a model can realistically occupy: free RAM plus any dedicated VRAM.
+    pub fn usable_model_memory(&self) -> u64 {
+        let vram = self
+            .gpu
+            .as_ref()
+            .filter(|g| !g.unified_memory)
+            .and_then(|g| g.vram_bytes)
+            .unwrap_or(0);
+        self.available_ram_bytes + vram
+    }
+}
+
+/// GPUs don't change while we run, and probing one may spawn nvidia-smi
+static GPU: OnceLock<Option<GpuInfo>> = OnceLock::new();
+
+/// Probe system RAM, CPU and GPU.
+pub fn detect() -> HardwareInfo {
+    let mut sys = System::new();
+    sys.refresh_memory();
+    sys.refresh_cpu_all();
+
+    let cpu_brand = sys
+        .cpus()
+        .first()
+        .map(|c| c.brand().trim().to_string())
+        .unwrap_or_default();
+
+    HardwareInfo {
+        total_ram_bytes: sys.total_memory(),
+        available_ram_bytes: sys.available_memory(),
+        cpu_cores: System::physical_core_count().unwrap_or_else(|| sys.cpus().len()),
+        gpu: GPU
+            .get_or_init(|| detect_gpu(&cpu_brand, sys.total_memory()))
+            .clone(),
+        cpu_brand,
+    }
+}
+
+fn detect_gpu(cpu_brand: &str, total_ram: u64) -> Option<GpuInfo> {
+    // Apple Silicon: Metal shares system memory with the CPU
+    if cfg!(target_os = "macos") && cfg!(target_arch = "aarch64") {
+        return Some(GpuInfo {
+            name: cpu_brand.to_string(),
+            vram_bytes: Some(total_ram),
+            unified_memory: true,
+        });
+    }
+
+    detect_nvidia().or_else(detect_amd_sysfs)
+}
+
+/// Query the first NVIDIA GPU via nvidia-smi (Linux and Windows).
+fn detect_nvidia() -> Option<GpuInfo> {
+    let output = std::process::Command::new("nvidia-smi")
+        .args([
+            "--query-gpu=name,memory.total",
+            "--format=csv,noheader,nounits",
+        ])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let line = stdout.lines().next()?;
+    let (name, mib) = line.rsplit_once(',')?;
+    Some(GpuInfo {
+        name: name.trim().to_string(),
+        vram_bytes: mib.trim().parse::<u64>().ok().map(|m| m * 1024 * 1024),
+        unified_memory: false,
+    })
+}
+
+/// AMD GPUs on Linux expose VRAM through the amdgpu sysfs interface.
+fn detect_amd_sysfs() -> Option<GpuInfo> {
+    if !cfg!(target_os = "linux") {
+        return None;
+    }
+    std::fs::read_dir("/sys/class/drm")
+        .ok()?
+        .flatten()
+        .filter_map(|entry| {
+            std::fs::read_to_string(entry.path().join("device/mem_info_vram_total")).ok()
+        })
+        .filter_map(|vram| vram.trim().parse::<u64>().ok())
+        .max()
+        .map(|vram| GpuInfo {
+            name: "AMD GPU".to_string(),
+            vram_bytes: Some(vram),
+            unified_memory: false,
+        })
+}
+
+/// Rough memory needed to run a GGUF of the given size.
+pub fn required_memory(model_size_bytes: u64) -> u64 {
+    model_size_bytes + RUNTIME_OVERHEAD_BYTES
+}
+
+/// Returns a warning when loading `model_path` would obviously exceed available memory.
+pub fn check_model_fits(model_path: &Path, hw: &HardwareInfo) -> Option<String> {
+    let size = std::fs::metadata(model_path).ok()?.len();
+    let needed = required_memory(size);
+    let usable = hw.usable_model_memory();
+    if needed > hw.total_ram_bytes.max(usable) {
+        Some(format!(
+            "This model needs about {:.1} GB but the system only has {:.1} GB of RAM. It will likely fail to load.",
+            gb(needed),
+            gb(hw.total_ram_bytes)
+        ))
+    } else if needed > usable {
+        Some(format!(
+            "This model needs about {:.1} GB but only {:.1} GB is free. Expect heavy swapping; close other apps or pick a smaller quantization.",
+            gb(needed),
+            gb(usable)
+        ))
+    } else {
+        None
+    }
+}
+
+/// The model path `check_model_fits_once` last looked at
+static CHECKED: Mutex<Option<String>> = Mutex::new(None);
+
+/// `check_model_fits` for the model about to answer. The hardware is only probed when
+/// `model_path` isn't the one checked last, so a warning comes once per model rather than
+/// with every message.
+pub fn check_model_fits_once(model_path: &str) -> Option<String> {
+    let mut checked = CHECKED.lock().ok()?;
+    if checked.as_deref() == Some(model_path) {
+        return None;
+    }
+    *checked = Some(model_path.to_string());
+    drop(checked);
+    check_model_fits(Path::new(model_path), &detect())
+}
+
+fn gb(bytes: u64) -> f64 {
+    bytes as f64 / (1024.0 * 1024.0 * 1024.0)
+}
@@ -0,0 +1,54 @@
+use anyhow::{anyhow, Result};
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+
+/// Shortcut used until the user picks another one
+pub const DEFAULT_HOTKEY: &str = "CommandOrControl+Shift+C";
+
+/// Parse an accelerator string such as "CommandOrControl+Shift+C".
+pub fn parse(accelerator: &str) -> Result<Shortcut> {
+    accelerator
+        .parse::<Shortcut>()
+        .map_err(|e| anyhow!("Invalid shortcut '{}': {}", accelerator, e))
+}
+
+/// Replace the registered global shortcut with `accelerator`; `None` leaves none registered.
+pub fn register(app: &AppHandle, accelerator: Option<&str>) -> Result<()> {
+    let shortcuts = app.global_shortcut();
+    shortcuts.unregister_all()?;
+
+    let Some(accelerator) = accelerator.map(str::trim).filter(|a| !a.is_empty()) else {
+        return Ok(());
+    };
+    shortcuts.on_shortcut(parse(accelerator)?, |app, _shortcut, event| {
+        if event.state() == ShortcutState::Pressed {
+            toggle(app);
+        }
+    })?;
+    tracing::info!("Global shortcut registered: {}", accelerator);
+    Ok(())
+}
+
+/// Hide Clippy if it's in front, otherwise bring it up with the chat input focused.
+pub fn toggle(app: &AppHandle) {
+    let Some(window) = app.get_webview_window("clippy") else {
+        return;
+    };
+    if crate::peek::summon(app) {
+        return;
+    }
+    let in_front = window.is_visible().unwrap_or(false) && window.is_focused().unwrap_or(false);
+    // A faded, click-through Clippy counts as summoned once it's interactive again
+    let was_ghosted = crate::ghost::wake(app);
+    if in_front && !was_ghosted {
+        let _ = window.hide();
+        return;
+    }
+    crate::window_state::move_to_cursor_monitor(&window);
+    crate::sounds::play(crate::sounds::Effect::Appear);
+    let _ = window.show();
+    let _ = window.unminimize();
+    let _ = window.set_focus();
+    let _ = window.emit("focus-chat", ());
+    crate::notifications::open_pending(app);
+}
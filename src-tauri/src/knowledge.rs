@@ -0,0 +1,494 @@
+use crate::config::KnowledgeConfig;
+use anyhow::{anyhow, Result};
+use notify::{EventKind, RecursiveMode, Watcher};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Mutex};
+use std::time::{Duration, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter};
+
+/// Target length of an indexed chunk, in characters
+const CHUNK_CHARS: usize = 1000;
+/// Size of the hashed term vectors chunks are embedded into
+const DIMENSIONS: usize = 1024;
+/// Chunks scoring below this are too loosely related to be worth the prompt space
+const MIN_SCORE: f32 = 0.12;
+/// Files bigger than this are skipped rather than indexed
+const MAX_FILE_BYTES: u64 = 20 * 1024 * 1024;
+/// Wait for a burst of file changes (a sync client, a git checkout) to end before reindexing
+const SETTLE_DELAY: Duration = Duration::from_secs(2);
+
+/// Words too common to say anything about what a chunk is about
+const STOPWORDS: &[&str] = &[
+    "a", "an", "and", "are", "as", "at", "be", "but", "by", "can", "do", "for", "from", "had",
+    "has", "have", "how", "i", "if", "in", "into", "is", "it", "its", "me", "my", "no", "not",
+    "of", "on", "or", "our", "so", "that", "the", "their", "then", "there", "these", "they",
+    "this", "to", "was", "we", "were", "what", "when", "where", "which", "who", "why", "will",
+    "with", "you", "your",
+];
+
+/// A chunk of an indexed document that matched a question
+#[derive(Debug, Clone, Serialize)]
+pub struct Citation {
+    pub path: String,
+    /// File name, for display
+    pub name: String,
+    /// Position of the chunk within its file
+    pub chunk: usize,
+    pub text: String,
+    pub score: f32,
+}
+
+/// Progress of the background indexer, as returned by `index_status`
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct IndexStatus {
+    pub indexing: bool,
+    pub files: usize,
+    pub chunks: usize,
+    /// Unix timestamp, in seconds, of the last finished pass
+    pub last_indexed: Option<i64>,
+    pub error: Option<String>,
+}
+
+/// Work for the indexer thread
+enum Job {
+    /// Folders or settings changed: watch the new folders, then sync
+    Configure(KnowledgeConfig),
+    /// Something changed on disk; reindex whatever is new or modified
+    Sync,
+    /// Drop the index and embed every file again
+    Rebuild,
+}
+
+static STATUS: Mutex<IndexStatus> = Mutex::new(IndexStatus {
+    indexing: false,
+    files: 0,
+    chunks: 0,
+    last_indexed: None,
+    error: None,
+});
+static JOBS: Mutex<Option<mpsc::Sender<Job>>> = Mutex::new(None);
+
+fn db_path() -> Result<PathBuf> {
+    let data_dir = dirs::data_dir().ok_or_else(|| anyhow!("Could not find data directory"))?;
+    let dir = data_dir.join("rusty-clippy");
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir.join("knowledge.db"))
+}
+
+fn open() -> Result<Connection> {
+    let conn = Connection::open(db_path()?)?;
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS chunks (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            path TEXT NOT NULL,
+            chunk INTEGER NOT NULL,
+            text TEXT NOT NULL,
+            vector BLOB NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS chunks_path ON chunks (path);
+        CREATE TABLE IF NOT EXISTS files (
+            path TEXT PRIMARY KEY,
+            modified INTEGER NOT NULL,
+            size INTEGER NOT NULL
+        );",
+    )?;
+    Ok(conn)
+}
+
+/// Whether the indexer can read `path`
+fn is_supported(path: &Path) -> bool {
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(str::to_ascii_lowercase);
+    matches!(
+        extension.as_deref(),
+        Some("txt" | "md" | "markdown" | "rst" | "org" | "pdf")
+    )
+}
+
+fn extract_text(path: &Path) -> Result<String> {
+    let is_pdf = path
+        .extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case("pdf"));
+    if is_pdf {
+        pdf_extract::extract_text(path).map_err(|e| anyhow!("Failed to read PDF: {}", e))
+    } else {
+        Ok(std::fs::read_to_string(path)?)
+    }
+}
+
+/// Split `text` into chunks of about `CHUNK_CHARS`, breaking between paragraphs where possible.
+fn chunk_text(text: &str) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    for paragraph in text.split("\n\n").map(str::trim).filter(|p| !p.is_empty()) {
+        if !current.is_empty() && current.len() + paragraph.len() > CHUNK_CHARS {
+            chunks.push(std::mem::take(&mut current));
+        }
+        // A single paragraph longer than a chunk is split between words
+        for word in paragraph.split_whitespace() {
+            if current.len() + word.len() > CHUNK_CHARS * 2 {
+                chunks.push(std::mem::take(&mut current));
+            }
+            if !current.is_empty() && !current.ends_with('\n') {
+                current.push(' ');
+            }
+            current.push_str(word);
+        }
+        current.push_str("\n\n");
+    }
+    if !current.trim().is_empty() {
+        chunks.push(current);
+    }
+    chunks.into_iter().map(|c| c.trim().to_string()).collect()
+}
+
+/// FNV-1a, which unlike `DefaultHasher` is stable across Rust releases and so safe to persist
+fn stable_hash(text: &str) -> u64 {
+    text.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Embed `text` as an L2-normalised vector of hashed word and word-pair counts. Fully local and
+/// deterministic, and good at matching the words a question shares with a document.
+fn embed(text: &str) -> Vec<f32> {
+    let lowered = text.to_lowercase();
+    let words: Vec<&str> = lowered
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| w.chars().count() > 1 && !STOPWORDS.contains(w))
+        .collect();
+
+    let mut vector = vec![0f32; DIMENSIONS];
+    let mut add = |feature: &str, weight: f32| {
+        vector[(stable_hash(feature) % DIMENSIONS as u64) as usize] += weight;
+    };
+    for word in &words {
+        add(word, 1.0);
+    }
+    for pair in words.windows(2) {
+        add(&format!("{} {}", pair[0], pair[1]), 0.5);
+    }
+
+    // Damp repeated terms so one word can't dominate a chunk
+    for value in vector.iter_mut() {
+        *value = value.ln_1p();
+    }
+    let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        for value in vector.iter_mut() {
+            *value /= norm;
+        }
+    }
+    vector
+}
+
+fn to_blob(vector: &[f32]) -> Vec<u8> {
+    vector.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+fn from_blob(blob: &[u8]) -> Vec<f32> {
+    blob.chunks_exact(4)
+        .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .collect()
+}
+
+/// Every supported file under the configured folders
+fn documents(config: &KnowledgeConfig) -> Vec<PathBuf> {
+    config
+        .folders
+        .iter()
+        .flat_map(|folder| walkdir::WalkDir::new(folder).follow_links(true))
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file() && is_supported(entry.path()))
+        .filter(|entry| entry.metadata().is_ok_and(|m| m.len() <= MAX_FILE_BYTES))
+        .map(|entry| entry.into_path())
+        .collect()
+}
+
+/// When `path` was last modified, in milliseconds, and its size; what decides whether it's stale
+fn fingerprint(path: &Path) -> Option<(i64, i64)> {
+    let metadata = std::fs::metadata(path).ok()?;
+    let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+    Some((modified.as_millis() as i64, metadata.len() as i64))
+}
+
+/// Replace the indexed chunks of `path` with freshly extracted ones.
+fn index_file(conn: &mut Connection, path: &Path, (modified, size): (i64, i64)) -> Result<()> {
+    let text = extract_text(path)?;
+    let chunks = chunk_text(&text);
+    let key = path.to_string_lossy();
+
+    let tx = conn.transaction()?;
+    tx.execute("DELETE FROM chunks WHERE path = ?1", params![key])?;
+    for (index, chunk) in chunks.iter().enumerate() {
+        tx.execute(
+            "INSERT INTO chunks (path, chunk, text, vector) VALUES (?1, ?2, ?3, ?4)",
+            params![key, index as i64, chunk, to_blob(&embed(chunk))],
+        )?;
+    }
+    tx.execute(
+        "INSERT OR REPLACE INTO files (path, modified, size) VALUES (?1, ?2, ?3)",
+        params![key, modified, size],
+    )?;
+    tx.commit()?;
+    Ok(())
+}
+
+fn forget_file(conn: &Connection, path: &str) -> Result<()> {
+    conn.execute("DELETE FROM chunks WHERE path = ?1", params![path])?;
+    conn.execute("DELETE FROM files WHERE path = ?1", params![path])?;
+    Ok(())
+}
+
+/// Bring the index in line with the folders: embed new and modified files and drop deleted
+/// ones, leaving everything unchanged alone. Unreadable files are logged and skipped.
+fn sync(config: &KnowledgeConfig) -> Result<()> {
+    let mut conn = open()?;
+    let files = documents(config);
+    let mut updated = 0;
+    for path in &files {
+        let Some(fingerprint) = fingerprint(path) else {
+            continue;
+        };
+        let indexed: Option<(i64, i64)> = conn
+            .query_row(
+                "SELECT modified, size FROM files WHERE path = ?1",
+                params![path.to_string_lossy()],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
+        if indexed == Some(fingerprint) {
+            continue;
+        }
+        match index_file(&mut conn, path, fingerprint) {
+            Ok(()) => updated += 1,
+            Err(e) => tracing::warn!("Skipping {:?} in the knowledge index: {}", path, e),
+        }
+    }
+
+    let current: HashSet<String> = files
+        .iter()
+        .map(|p| p.to_string_lossy().into_owned())
+        .collect();
+    let indexed: Vec<String> = conn
+        .prepare("SELECT path FROM files")?
+        .query_map([], |row| row.get(0))?
+        .collect::<rusqlite::Result<_>>()?;
+    let mut removed = 0;
+    for path in indexed.iter().filter(|p| !current.contains(*p)) {
+        forget_file(&conn, path)?;
+        removed += 1;
+    }
+
+    if updated + removed > 0 {
+        tracing::info!(
+            "Knowledge index: {} files updated, {} removed",
+            updated,
+            removed
+        );
+    }
+    Ok(())
+}
+
+/// Drop the whole index and embed every file again.
+fn rebuild(config: &KnowledgeConfig) -> Result<()> {
+    let conn = open()?;
+    conn.execute_batch("DELETE FROM chunks; DELETE FROM files;")?;
+    drop(conn);
+    sync(config)
+}
+
+fn counts() -> Result<(usize, usize)> {
+    let conn = open()?;
+    let files: i64 = conn.query_row("SELECT COUNT(*) FROM files", [], |row| row.get(0))?;
+    let chunks: i64 = conn.query_row("SELECT COUNT(*) FROM chunks", [], |row| row.get(0))?;
+    Ok((files as usize, chunks as usize))
+}
+
+/// The chunks most relevant to `question`, best first
+pub fn search(question: &str, limit: usize) -> Result<Vec<Citation>> {
+    let query = embed(question);
+    if query.iter().all(|v| *v == 0.0) {
+        return Ok(Vec::new());
+    }
+
+    let conn = open()?;
+    let mut stmt = conn.prepare("SELECT path, chunk, text, vector FROM chunks")?;
+    let mut hits: Vec<Citation> = stmt
+        .query_map([], |row| {
+            let path: String = row.get(0)?;
+            let vector: Vec<u8> = row.get(3)?;
+            let score = from_blob(&vector)
+                .iter()
+                .zip(&query)
+                .map(|(a, b)| a * b)
+                .sum::<f32>();
+            Ok(Citation {
+                name: Path::new(&path)
+                    .file_name()
+                    .map(|n| n.to_string_lossy().into_owned())
+                    .unwrap_or_else(|| path.clone()),
+                path,
+                chunk: row.get::<_, i64>(1)? as usize,
+                text: row.get(2)?,
+                score,
+            })
+        })?
+        .filter_map(|hit| hit.ok())
+        .filter(|hit| hit.score >= MIN_SCORE)
+        .collect();
+    hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+    hits.truncate(limit);
+    Ok(hits)
+}
+
+/// Passages from the knowledge folders for the system prompt, numbered so the model can cite them
+pub fn prompt_context(citations: &[Citation]) -> String {
+    let mut context = String::from(
+        "Passages from the user's documents that may help. Use them when relevant and cite \
+         them as [1], [2], ...; say so if they don't answer the question.",
+    );
+    for (i, citation) in citations.iter().enumerate() {
+        context.push_str(&format!(
+            "\n\n[{}] {}:\n{}",
+            i + 1,
+            citation.name,
+            citation.text
+        ));
+    }
+    context
+}
+
+pub fn status() -> IndexStatus {
+    STATUS.lock().map(|s| s.clone()).unwrap_or_default()
+}
+
+fn set_status(app: &AppHandle, update: impl FnOnce(&mut IndexStatus)) {
+    let status = {
+        let Ok(mut status) = STATUS.lock() else {
+            return;
+        };
+        update(&mut status);
+        status.clone()
+    };
+    let _ = app.emit("knowledge-status", status);
+}
+
+fn send(job: Job) -> Result<()> {
+    let jobs = JOBS
+        .lock()
+        .map_err(|e| anyhow!("Indexer lock error: {}", e))?;
+    jobs.as_ref()
+        .ok_or_else(|| anyhow!("The indexer isn't running"))?
+        .send(job)
+        .map_err(|_| anyhow!("The indexer has stopped"))
+}
+
+/// Point the indexer at new folders, e.g. after the settings changed.
+pub fn configure(config: KnowledgeConfig) {
+    if let Err(e) = send(Job::Configure(config)) {
+        tracing::warn!("Failed to reconfigure the knowledge indexer: {}", e);
+    }
+}
+
+/// Re-embed every file from scratch, in the background.
+pub fn reindex() -> Result<()> {
+    send(Job::Rebuild)
+}
+
+/// Watch `config`'s folders, sending `Job::Sync` on changes. `None` when disabled.
+fn watch(config: &KnowledgeConfig, jobs: &mpsc::Sender<Job>) -> Option<notify::RecommendedWatcher> {
+    if !config.enabled {
+        return None;
+    }
+    let jobs = jobs.clone();
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        let relevant = match event {
+            Ok(event) => {
+                !matches!(event.kind, EventKind::Access(_))
+                    && event.paths.iter().any(|p| is_supported(p) || p.is_dir())
+            }
+            Err(e) => {
+                tracing::warn!("Knowledge folder watcher error: {}", e);
+                false
+            }
+        };
+        if relevant {
+            let _ = jobs.send(Job::Sync);
+        }
+    })
+    .map_err(|e| tracing::warn!("Failed to watch knowledge folders: {}", e))
+    .ok()?;
+    for folder in &config.folders {
+        if let Err(e) = watcher.watch(Path::new(folder), RecursiveMode::Recursive) {
+            tracing::warn!("Failed to watch {}: {}", folder, e);
+        }
+    }
+    Some(watcher)
+}
+
+/// Start the background indexer. It catches up on changes made while Clippy was closed,
+/// then keeps the index current as files in the knowledge folders change.
+pub fn spawn(app: &AppHandle, config: KnowledgeConfig) {
+    let (tx, rx) = mpsc::channel();
+    if let Ok(mut jobs) = JOBS.lock() {
+        *jobs = Some(tx.clone());
+    }
+    if let Ok((files, chunks)) = counts() {
+        set_status(app, |s| {
+            s.files = files;
+            s.chunks = chunks;
+        });
+    }
+    let _ = tx.send(Job::Configure(config));
+
+    let app = app.clone();
+    std::thread::spawn(move || {
+        let mut config = KnowledgeConfig::default();
+        // Dropping the watcher stops events, so it lives as long as this thread
+        let mut _watcher = None;
+
+        while let Ok(first) = rx.recv() {
+            if matches!(first, Job::Sync) {
+                std::thread::sleep(SETTLE_DELAY);
+            }
+            let mut rebuild_all = false;
+            for job in std::iter::once(first).chain(rx.try_iter()) {
+                match job {
+                    Job::Configure(new) => {
+                        _watcher = watch(&new, &tx);
+                        config = new;
+                    }
+                    Job::Rebuild => rebuild_all = true,
+                    Job::Sync => {}
+                }
+            }
+            if !config.enabled {
+                continue;
+            }
+
+            set_status(&app, |s| s.indexing = true);
+            let result = if rebuild_all {
+                rebuild(&config)
+            } else {
+                sync(&config)
+            };
+            let (files, chunks) = counts().unwrap_or_default();
+            set_status(&app, |s| {
+                s.indexing = false;
+                s.files = files;
+                s.chunks = chunks;
+                s.last_indexed = Some(chrono::Utc::now().timestamp());
+                s.error = result.as_ref().err().map(|e| e.to_string());
+            });
+            if let Err(e) = result {
+                tracing::warn!("Failed to index knowledge folders: {}", e);
+            }
+        }
+    });
+}
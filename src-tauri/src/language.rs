@@ -0,0 +1,185 @@
+use crate::config::Config;
+use serde::Serialize;
+use whatlang::Lang;
+
+/// Messages shorter than this are too ambiguous to detect a language from
+const MIN_DETECT_CHARS: usize = 12;
+
+/// A language Clippy can be told to answer in
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct ResponseLanguage {
+    /// ISO 639-1 code, as stored in `response_language` and prefixed on Piper voice names
+    pub code: &'static str,
+    pub name: &'static str,
+    /// Piper voice to speak this language with when the configured one doesn't fit
+    pub voice: Option<&'static str>,
+    #[serde(skip)]
+    detected_as: Lang,
+}
+
+const LANGUAGES: &[ResponseLanguage] = &[
+    ResponseLanguage {
+        code: "en",
+        name: "English",
+        voice: Some("en_US-amy-medium"),
+        detected_as: Lang::Eng,
+    },
+    ResponseLanguage {
+        code: "de",
+        name: "German",
+        voice: Some("de_DE-thorsten-medium"),
+        detected_as: Lang::Deu,
+    },
+    ResponseLanguage {
+        code: "fr",
+        name: "French",
+        voice: Some("fr_FR-siwis-medium"),
+        detected_as: Lang::Fra,
+    },
+    ResponseLanguage {
+        code: "es",
+        name: "Spanish",
+        voice: Some("es_ES-davefx-medium"),
+        detected_as: Lang::Spa,
+    },
+    ResponseLanguage {
+        code: "it",
+        name: "Italian",
+        voice: Some("it_IT-riccardo-x_low"),
+        detected_as: Lang::Ita,
+    },
+    ResponseLanguage {
+        code: "pt",
+        name: "Portuguese",
+        voice: Some("pt_BR-faber-medium"),
+        detected_as: Lang::Por,
+    },
+    ResponseLanguage {
+        code: "nl",
+        name: "Dutch",
+        voice: Some("nl_BE-nathalie-medium"),
+        detected_as: Lang::Nld,
+    },
+    ResponseLanguage {
+        code: "pl",
+        name: "Polish",
+        voice: Some("pl_PL-gosia-medium"),
+        detected_as: Lang::Pol,
+    },
+    ResponseLanguage {
+        code: "ru",
+        name: "Russian",
+        voice: Some("ru_RU-irina-medium"),
+        detected_as: Lang::Rus,
+    },
+    ResponseLanguage {
+        code: "uk",
+        name: "Ukrainian",
+        voice: Some("uk_UA-ukrainian_tts-medium"),
+        detected_as: Lang::Ukr,
+    },
+    ResponseLanguage {
+        code: "zh",
+        name: "Chinese",
+        voice: Some("zh_CN-huayan-medium"),
+        detected_as: Lang::Cmn,
+    },
+    ResponseLanguage {
+        code: "ja",
+        name: "Japanese",
+        voice: None,
+        detected_as: Lang::Jpn,
+    },
+];
+
+/// Every language `response_language` may be set to
+pub fn all() -> &'static [ResponseLanguage] {
+    LANGUAGES
+}
+
+/// Look up a language by its ISO 639-1 code (case-insensitive)
+pub fn by_code(code: &str) -> Option<&'static ResponseLanguage> {
+    LANGUAGES
+        .iter()
+        .find(|l| l.code.eq_ignore_ascii_case(code.trim()))
+}
+
+/// The language `text` is written in, if it can be told with confidence
+pub fn detect(text: &str) -> Option<&'static ResponseLanguage> {
+    if text.trim().chars().count() < MIN_DETECT_CHARS {
+        return None;
+    }
+    let info = whatlang::detect(text).filter(|info| info.is_reliable())?;
+    LANGUAGES.iter().find(|l| l.detected_as == info.lang())
+}
+
+/// The language from the user's locale (e.g. "de-AT" -> German)
+fn from_locale() -> Option<&'static ResponseLanguage> {
+    let locale = crate::personality::locale();
+    by_code(locale.split(['-', '_']).next().unwrap_or_default())
+}
+
+/// The language the next reply should be in. A configured `response_language`
+/// wins; otherwise it is detected from `user_message`, or taken from the locale
+/// when there is no message to go by (e.g. proactive tips).
+pub fn resolve(config: &Config, user_message: Option<&str>) -> Option<&'static ResponseLanguage> {
+    if let Some(code) = config.response_language.as_deref() {
+        return by_code(code);
+    }
+    match user_message {
+        Some(message) => detect(message),
+        None => from_locale(),
+    }
+}
+
+/// System prompt line pinning the reply language. When auto-detection can't
+/// tell, the model is still asked to mirror the user rather than mix languages.
+pub fn instruction(config: &Config, user_message: Option<&str>) -> String {
+    let fixed = config.response_language.is_some();
+    match resolve(config, user_message) {
+        Some(lang) if fixed => format!(
+            "Always respond in {}, whatever language the user writes in.",
+            lang.name
+        ),
+        Some(lang) if user_message.is_some() => format!(
+            "The user is writing in {}. Respond in {} only.",
+            lang.name, lang.name
+        ),
+        Some(lang) => format!("Respond in {}, the user's system language.", lang.name),
+        None => "Respond in the same language as the user's latest message, \
+                 and never mix languages in one reply."
+            .to_string(),
+    }
+}
+
+/// Whether a Piper voice (named `lang_REGION-name-quality`) speaks `lang`
+pub fn voice_speaks(voice: &str, lang: &ResponseLanguage) -> bool {
+    voice
+        .split(['_', '-'])
+        .next()
+        .is_some_and(|prefix| prefix.eq_ignore_ascii_case(lang.code))
+}
+
+/// The voice to speak replies with: `preferred`, unless a fixed response
+/// language is set that it can't speak and that language's voice is downloaded.
+pub fn voice_for(config: &Config, preferred: Option<String>) -> Option<String> {
+    let Some(lang) = config.response_language.as_deref().and_then(by_code) else {
+        return preferred;
+    };
+    if preferred.as_deref().is_some_and(|v| voice_speaks(v, lang)) {
+        return preferred;
+    }
+    match lang.voice {
+        Some(voice) if crate::tts::voice_ready(voice) => Some(voice.to_string()),
+        Some(voice) => {
+            tracing::warn!(
+                "Voice '{}' for {} is not downloaded; keeping {:?}",
+                voice,
+                lang.name,
+                preferred
+            );
+            preferred
+        }
+        None => preferred,
+    }
+}
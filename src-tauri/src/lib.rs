@@ -1,48 +1,238 @@
+mod active_window;
+mod agents;
+mod animation;
+mod app_rules;
+mod attachments;
+mod briefing;
+mod budget;
+mod calendar;
+mod code_blocks;
 mod commands;
 mod config;
+mod config_watch;
+mod context_menu;
+mod crash;
+mod diagnostics;
+mod dnd;
+mod docking;
+mod documents;
+mod downloads;
+mod easter_eggs;
+mod emotion;
+mod ensemble;
+mod ghost;
+mod hardware;
+mod hotkey;
+mod knowledge;
+mod language;
 mod llm;
+mod lmstudio;
+mod logging;
+mod models;
+mod mood;
+mod normalize;
+mod notifications;
+mod ollama;
+mod peek;
 mod personality;
+mod pomodoro;
+mod pricing;
+mod proactive;
+mod pronunciation;
+mod rate_limit;
+mod profiles;
+mod prompt_templates;
+mod reasoning;
+mod redact;
+mod reminders;
+mod replay;
+mod request_log;
+mod runtime_state;
+mod sessions;
+mod settings_bundle;
+mod slash;
+mod sounds;
+mod stats;
+mod stt;
+mod tools;
+mod tray;
+mod updates;
+mod window_state;
 pub mod tts;
 
 use tauri::{Manager, Emitter};
-use tauri::menu::{Menu, MenuItem};
-use tauri::tray::TrayIconBuilder;
+use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 
-// Conversation state
+// Conversation state, kept behind a `tokio::sync::RwLock` so commands await it instead of
+// blocking the runtime, and a panic mid-reply can't poison it
 #[derive(Default)]
 pub struct ConversationState {
     pub history: Vec<commands::ChatMessage>,
+    /// Session file the history is saved to; `None` until the first exchange
+    pub session_id: Option<String>,
 }
 
+/// Each chat window's conversation, keyed by window label, so the Clippy bubble and any chat
+/// windows are independent chats
+#[derive(Default)]
+pub struct Conversations(
+    tokio::sync::Mutex<HashMap<String, Arc<tokio::sync::RwLock<ConversationState>>>>,
+);
+
+impl Conversations {
+    /// The conversation of the window labelled `label`, started empty on first use
+    pub async fn get(&self, label: &str) -> Arc<tokio::sync::RwLock<ConversationState>> {
+        self.0.lock().await.entry(label.to_string()).or_default().clone()
+    }
+
+    /// Every window's conversation
+    pub async fn all(&self) -> Vec<Arc<tokio::sync::RwLock<ConversationState>>> {
+        self.0.lock().await.values().cloned().collect()
+    }
+
+    /// Forget a closed window's conversation; it was saved after its last exchange
+    pub async fn remove(&self, label: &str) {
+        self.0.lock().await.remove(label);
+    }
+}
+
+/// Command-line flag that launches Clippy tray-only, like `start_hidden` in the config
+const HIDDEN_FLAG: &str = "--hidden";
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    tracing_subscriber::fmt()
-        .with_env_filter(
-            tracing_subscriber::EnvFilter::from_default_env()
-                .add_directive("rusty_clippy=info".parse().unwrap()),
-        )
-        .init();
+    logging::init();
+    crash::install_hook();
 
     tauri::Builder::default()
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_shell::init())
-        .manage(Mutex::new(ConversationState::default()))
+        .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_global_shortcut::Builder::new().build())
+        .plugin(tauri_plugin_notification::init())
+        .plugin(tauri_plugin_updater::Builder::new().build())
+        .manage(Conversations::default())
         .manage(tts::TtsState(Mutex::new(None)))
+        .manage(downloads::DownloadManager::default())
+        .manage(animation::AnimationController::default())
+        .on_window_event(|window, event| {
+            if let tauri::WindowEvent::Destroyed = event {
+                let app = window.app_handle().clone();
+                let label = window.label().to_string();
+                tauri::async_runtime::spawn(async move {
+                    app.state::<Conversations>().remove(&label).await;
+                });
+            }
+        })
         .setup(|app| {
-            setup_system_tray(app)?;
+            tray::setup(app)?;
+            window_state::watch_monitors(app.handle());
+
+            if let Some(window) = app.get_webview_window("clippy") {
+                window_state::manage(&window);
+                docking::manage(&window);
+                context_menu::manage(&window);
+                let on_top = config::Config::load()
+                    .map(|c| c.always_on_top)
+                    .unwrap_or(true);
+                let _ = window.set_always_on_top(on_top);
+                let skip_taskbar = config::Config::load()
+                    .map(|c| c.skip_taskbar)
+                    .unwrap_or(true);
+                window_state::set_taskbar_hidden(app.handle(), skip_taskbar);
+
+                // The window starts hidden (see tauri.conf.json) so a tray-only launch never flashes it
+                let start_hidden = std::env::args().any(|arg| arg == HIDDEN_FLAG)
+                    || config::Config::load()
+                        .map(|c| c.start_hidden)
+                        .unwrap_or(false);
+                if start_hidden {
+                    tracing::info!("Starting hidden; Clippy is in the tray");
+                } else {
+                    let _ = window.show();
+                }
+            }
+
+            let hotkey = config::Config::load()
+                .map(|c| c.global_hotkey)
+                .unwrap_or_else(|_| Some(hotkey::DEFAULT_HOTKEY.to_string()));
+            if let Err(e) = hotkey::register(app.handle(), hotkey.as_deref()) {
+                tracing::warn!("Failed to register global shortcut: {}", e);
+            }
+
+            // Retention limits are applied once per launch
+            let history = config::Config::load()
+                .map(|c| c.history)
+                .unwrap_or_default();
+            let handle = app.handle().clone();
+            tauri::async_runtime::spawn_blocking(move || match sessions::prune(&history) {
+                Ok(0) => {}
+                Ok(_) => tray::refresh(&handle),
+                Err(e) => tracing::warn!("Failed to prune conversation history: {}", e),
+            });
+
+            knowledge::spawn(
+                app.handle(),
+                config::Config::load()
+                    .map(|c| c.knowledge)
+                    .unwrap_or_default(),
+            );
+            documents::configure(
+                app.handle(),
+                config::Config::load()
+                    .map(|c| c.documents)
+                    .unwrap_or_default(),
+            );
+            proactive::spawn(app.handle());
+            app_rules::spawn();
+            dnd::spawn(app.handle());
+            reminders::spawn(app.handle());
+            calendar::spawn(app.handle());
+            briefing::spawn(app.handle());
+            updates::spawn_startup_check(app.handle());
+
+            // Crash reporting is opt-in; snapshot diagnostics now so a dump doesn't have to
+            let crash_reports = config::Config::load()
+                .map(|c| c.crash_reports)
+                .unwrap_or(false);
+            crash::set_enabled(crash_reports);
+            if crash_reports {
+                let app_version = app.package_info().version.to_string();
+                tauri::async_runtime::spawn_blocking(move || {
+                    match diagnostics::collect(app_version, false) {
+                        Ok(diagnostics) => crash::set_diagnostics(&diagnostics),
+                        Err(e) => tracing::warn!("Failed to snapshot diagnostics: {}", e),
+                    }
+                });
+            }
+
+            if let Err(e) = config_watch::spawn(app.handle()) {
+                tracing::warn!("Config hot-reload unavailable: {}", e);
+            }
 
             // Auto-initialize Piper TTS if voice model is already downloaded
             let app_handle = app.handle().clone();
             tauri::async_runtime::spawn(async move {
+                // Cloud speech engines need no local voice
+                if let Some(config) = crate::config::Config::load().ok().filter(|c| {
+                    c.tts_enabled && c.tts_engine != crate::config::TtsEngineType::Piper
+                }) {
+                    let tts_state = app_handle.state::<tts::TtsState>();
+                    if let Err(e) = commands::apply_tts_config(&config, &tts_state).await {
+                        tracing::warn!("Cloud TTS init failed: {}", e);
+                    }
+                    return;
+                }
+
                 // Get configured voice or fallback to default
                 let voice = crate::config::Config::load()
                     .ok()
-                    .and_then(|c| c.tts_voice)
+                    .and_then(|c| language::voice_for(&c, c.tts_voice.clone()))
                     .unwrap_or_else(|| "en_US-amy-medium".to_string());
 
-                if tts::voice_ready(&voice) {
+                if let Some(voice) = tts::resolve_local(&voice) {
                     if let Ok(config_path) = tts::voice_config(&voice) {
                         match tokio::task::spawn_blocking(move || {
                             tts::PiperTTSEngine::new(&config_path, None)
@@ -50,11 +240,15 @@ pub fn run() {
                         .await
                         {
                             Ok(Ok(engine)) => {
+                                let engine = Arc::new(engine);
+                                if let Ok(config) = crate::config::Config::load() {
+                                    tts::warm_in_background(Arc::clone(&engine), &config);
+                                }
                                 if let Some(tts_state) =
                                     app_handle.try_state::<tts::TtsState>()
                                 {
                                     if let Ok(mut guard) = tts_state.0.lock() {
-                                        *guard = Some(Arc::new(engine));
+                                        *guard = Some(engine);
                                         tracing::info!(
                                             "Piper TTS auto-initialized on startup"
                                         );
@@ -72,51 +266,159 @@ pub fn run() {
                 }
             });
 
+            // Optionally load (and warm up) the local model so the first reply is snappy
+            let app_handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                let Ok(config) = crate::config::Config::load() else {
+                    return;
+                };
+                if !config.preload_model || config.llm_provider != config::LlmProviderType::BuiltIn
+                {
+                    return;
+                }
+                let Some(model_path) = config.builtin_model_path else {
+                    return;
+                };
+
+                let warm_up = config.warm_up_model;
+                let started = std::time::Instant::now();
+                let path = model_path.clone();
+                match tokio::task::spawn_blocking(move || llm::local::preload(&path, warm_up)).await
+                {
+                    Ok(Ok(())) => {
+                        let load_ms = started.elapsed().as_millis() as u64;
+                        tracing::info!("Local model preloaded in {} ms", load_ms);
+                        let _ = app_handle.emit(
+                            "model-ready",
+                            commands::ModelReadyEvent {
+                                model_path,
+                                warmed_up: warm_up,
+                                load_ms,
+                            },
+                        );
+                    }
+                    Ok(Err(e)) => tracing::warn!("Local model preload failed: {}", e),
+                    Err(e) => tracing::warn!("Local model preload task error: {}", e),
+                }
+            });
+
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             commands::send_message,
+            commands::continue_response,
+            commands::copy_code_block,
+            commands::save_code_block,
             commands::get_config,
             commands::save_config,
+            commands::validate_config,
+            commands::list_personalities,
+            commands::list_agents,
+            commands::import_agent,
+            commands::get_agent,
+            commands::list_languages,
+            commands::get_greeting,
+            commands::report_activity,
+            commands::get_mood,
+            commands::set_listening,
+            commands::get_animation,
+            commands::set_dnd,
+            commands::get_dnd,
+            commands::get_dock,
+            commands::list_sessions,
+            commands::load_session,
+            commands::branch_conversation,
+            commands::pin_message,
+            commands::list_pinned,
+            commands::purge_history,
+            commands::new_conversation,
+            commands::clear_conversation,
+            commands::index_status,
+            commands::reindex,
+            commands::set_click_through,
+            commands::set_always_on_top,
+            commands::trigger_easter_egg,
+            commands::set_system_prompt,
             commands::open_settings_window,
+            commands::show_context_menu,
+            commands::document_action,
             commands::download_model,
             commands::download_tts_model,
             commands::speak_text,
             commands::preview_voice,
+            commands::warm_tts_cache,
+            commands::list_pronunciations,
+            commands::add_pronunciation,
+            commands::remove_pronunciation,
+            commands::list_prompt_templates,
+            commands::save_prompt_template,
+            commands::delete_prompt_template,
+            commands::fill_prompt_template,
             commands::is_tts_initialized,
             commands::is_voice_downloaded,
+            commands::install_voice_from_file,
+            commands::use_local_model,
+            commands::list_downloaded_models,
+            commands::delete_model,
+            commands::get_storage_usage,
+            commands::get_dashboard_data,
+            commands::inspect_model,
+            commands::get_hardware_info,
+            commands::get_model_catalog,
+            commands::check_model_memory,
+            commands::list_profiles,
+            commands::create_profile,
+            commands::switch_profile,
+            commands::delete_profile,
+            commands::export_settings,
+            commands::import_settings,
+            commands::list_downloads,
+            commands::pause_download,
+            commands::resume_download,
+            commands::cancel_download,
+            commands::lmstudio_status,
+            commands::lmstudio_load_model,
+            commands::ollama_list_models,
+            commands::ollama_pull_model,
+            commands::ollama_delete_model,
+            commands::get_recent_logs,
+            commands::open_log_folder,
+            commands::set_request_log,
+            commands::open_request_log,
+            commands::replay_log,
+            commands::get_diagnostics,
+            commands::get_stats,
+            commands::get_pending_crash_report,
+            commands::acknowledge_crash_report,
+            commands::run_briefing,
+            commands::start_pomodoro,
+            commands::stop_pomodoro,
+            commands::get_pomodoro_status,
+            commands::list_reminders,
+            commands::add_reminder,
+            commands::list_tools,
+            commands::respond_tool_approval,
+            commands::load_generated_image,
+            commands::transcribe_audio,
+            commands::set_timer,
+            commands::cancel_reminder,
+            commands::check_for_updates,
+            commands::install_update,
+            commands::restart_app,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
-}
-
-fn setup_system_tray(app: &tauri::App) -> Result<(), Box<dyn std::error::Error>> {
-    let show_i = MenuItem::with_id(app, "show", "Show Clippy", true, None::<&str>)?;
-    let settings_i = MenuItem::with_id(app, "settings", "Settings", true, None::<&str>)?;
-    let quit_i = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
-
-    let menu = Menu::with_items(app, &[&show_i, &settings_i, &quit_i])?;
-
-    let _tray = TrayIconBuilder::new()
-        .menu(&menu)
-        .on_menu_event(|app, event| match event.id().as_ref() {
-            "show" => {
-                if let Some(window) = app.get_webview_window("clippy") {
-                    let _ = window.show();
-                    let _ = window.set_focus();
-                }
-            }
-            "settings" => {
-                if let Some(window) = app.get_webview_window("clippy") {
-                    let _ = window.emit("open-settings", ());
-                }
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            // Counters still waiting on their delayed save
+            if let tauri::RunEvent::Exit = event {
+                stats::flush();
             }
-            "quit" => {
-                app.exit(0);
+            // Clicking a notification while every window is hidden reopens the app on macOS
+            #[cfg(target_os = "macos")]
+            if let tauri::RunEvent::Reopen { .. } = event {
+                notifications::open_pending(app);
             }
-            _ => {}
-        })
-        .build(app)?;
-
-    Ok(())
+            #[cfg(not(target_os = "macos"))]
+            let _ = (app, event);
+        });
 }
@@ -0,0 +1,73 @@
+use super::Message;
+
+/// Tokens kept back for the reply when the provider doesn't say how long replies can be
+pub const DEFAULT_REPLY_TOKENS: usize = 1024;
+/// Per-message overhead of chat templates (role markers, separators)
+const MESSAGE_OVERHEAD: usize = 4;
+/// Smallest context window accepted from the config
+pub const MIN_CONTEXT_TOKENS: u32 = 512;
+
+/// Rough token count for budgeting. Remote models don't share their tokenizers, and about
+/// four characters a token holds for English with every common one; a little over is safer
+/// than a little under.
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(4) + MESSAGE_OVERHEAD
+}
+
+/// The messages to send, and what had to be left out to fit
+#[derive(Debug)]
+pub struct Fitted {
+    pub messages: Vec<Message>,
+    /// Oldest messages left out
+    pub dropped_messages: usize,
+    pub dropped_tokens: usize,
+    /// Tokens the history had to fit in, after the system prompt and the reply
+    pub budget_tokens: usize,
+}
+
+/// Fit `history` after `system` in a context of `context_tokens`, keeping `reply_tokens` free
+/// for the answer. The system prompt (with any pinned messages and knowledge passages in it)
+/// and the latest message always go; older turns are added newest first until the budget
+/// runs out, so a long conversation loses its oldest turns whole rather than being cut off
+/// mid-sentence by the backend.
+pub fn fit(
+    system: Message,
+    history: &[Message],
+    context_tokens: usize,
+    reply_tokens: usize,
+) -> Fitted {
+    let budget_tokens = context_tokens
+        .saturating_sub(reply_tokens)
+        .saturating_sub(estimate_tokens(&system.content));
+
+    let last = history.len().saturating_sub(1);
+    let mut used = 0;
+    let mut kept = 0;
+    for (index, message) in history.iter().enumerate().rev() {
+        let cost = estimate_tokens(&message.content);
+        if index != last && used + cost > budget_tokens {
+            break;
+        }
+        used += cost;
+        kept += 1;
+    }
+    // Don't open on a reply whose question was dropped
+    let mut start = history.len() - kept;
+    while start < last && history[start].role != "user" {
+        start += 1;
+    }
+
+    let dropped_tokens = history[..start]
+        .iter()
+        .map(|m| estimate_tokens(&m.content))
+        .sum();
+    let mut messages = Vec::with_capacity(history.len() - start + 1);
+    messages.push(system);
+    messages.extend_from_slice(&history[start..]);
+    Fitted {
+        messages,
+        dropped_messages: start,
+        dropped_tokens,
+        budget_tokens,
+    }
+}
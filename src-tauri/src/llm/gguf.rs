@@ -0,0 +1,243 @@
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::Path;
+
+const GGUF_MAGIC: &[u8; 4] = b"GGUF";
+
+/// Summary of a GGUF model file's header metadata
+#[derive(Debug, Clone, Serialize)]
+pub struct GgufInfo {
+    pub gguf_version: u32,
+    pub name: Option<String>,
+    pub architecture: Option<String>,
+    pub parameter_count: u64,
+    pub quantization: Option<String>,
+    pub context_length: Option<u64>,
+    pub chat_template: Option<String>,
+    pub tensor_count: u64,
+    pub file_size: u64,
+}
+
+/// A metadata value we care about; everything else is skipped over
+enum Value {
+    Int(u64),
+    Str(String),
+    Other,
+}
+
+/// Parse the header of a GGUF file without loading any tensor data.
+pub fn inspect(path: &Path) -> Result<GgufInfo> {
+    let file = File::open(path).map_err(|e| anyhow!("Failed to open {}: {}", path.display(), e))?;
+    let file_size = file.metadata()?.len();
+    let mut r = BufReader::new(file);
+
+    let mut magic = [0u8; 4];
+    r.read_exact(&mut magic)?;
+    if &magic != GGUF_MAGIC {
+        return Err(anyhow!("Not a GGUF file: {}", path.display()));
+    }
+
+    let version = read_u32(&mut r)?;
+    // v1 used 32-bit counts, v2+ use 64-bit
+    let (tensor_count, kv_count) = if version == 1 {
+        (read_u32(&mut r)? as u64, read_u32(&mut r)? as u64)
+    } else {
+        (read_u64(&mut r)?, read_u64(&mut r)?)
+    };
+    let read_len = |r: &mut BufReader<File>| -> Result<u64> {
+        if version == 1 {
+            Ok(read_u32(r)? as u64)
+        } else {
+            read_u64(r)
+        }
+    };
+
+    let mut info = GgufInfo {
+        gguf_version: version,
+        name: None,
+        architecture: None,
+        parameter_count: 0,
+        quantization: None,
+        context_length: None,
+        chat_template: None,
+        tensor_count,
+        file_size,
+    };
+
+    let mut file_type = None;
+    let mut context_lengths = Vec::new();
+    for _ in 0..kv_count {
+        let key_len = read_len(&mut r)?;
+        let key = read_string(&mut r, key_len)?;
+        let value_type = read_u32(&mut r)?;
+        let wanted = matches!(
+            key.as_str(),
+            "general.name" | "general.architecture" | "general.file_type" | "tokenizer.chat_template"
+        ) || key.ends_with(".context_length");
+        let value = read_value(&mut r, value_type, wanted, &read_len)?;
+
+        match (key.as_str(), value) {
+            ("general.name", Value::Str(s)) => info.name = Some(s),
+            ("general.architecture", Value::Str(s)) => info.architecture = Some(s),
+            ("general.file_type", Value::Int(n)) => file_type = Some(n),
+            ("tokenizer.chat_template", Value::Str(s)) => info.chat_template = Some(s),
+            (k, Value::Int(n)) if k.ends_with(".context_length") => {
+                context_lengths.push((k.trim_end_matches(".context_length").to_string(), n))
+            }
+            _ => {}
+        }
+    }
+
+    // Prefer the context length of the declared architecture
+    info.context_length = context_lengths
+        .iter()
+        .find(|(arch, _)| Some(arch) == info.architecture.as_ref())
+        .or_else(|| context_lengths.first())
+        .map(|(_, n)| *n);
+    info.quantization = file_type.map(file_type_name);
+
+    // Tensor infos follow the metadata; summing their element counts gives the parameter count
+    for _ in 0..tensor_count {
+        let name_len = read_len(&mut r)?;
+        r.seek_relative(name_len as i64)?;
+        let n_dims = read_u32(&mut r)?;
+        let mut elements: u64 = 1;
+        for _ in 0..n_dims {
+            elements = elements.saturating_mul(read_len(&mut r)?);
+        }
+        // ggml type (u32) + data offset (u64)
+        r.seek_relative(4 + 8)?;
+        info.parameter_count = info.parameter_count.saturating_add(elements);
+    }
+
+    Ok(info)
+}
+
+fn read_value(
+    r: &mut BufReader<File>,
+    value_type: u32,
+    wanted: bool,
+    read_len: &impl Fn(&mut BufReader<File>) -> Result<u64>,
+) -> Result<Value> {
+    let value = match value_type {
+        // u8, i8, bool
+        0 | 1 | 7 => Value::Int(read_bytes::<1>(r)?[0] as u64),
+        // u16, i16
+        2 | 3 => Value::Int(u16::from_le_bytes(read_bytes(r)?) as u64),
+        // u32, i32
+        4 | 5 => Value::Int(read_u32(r)? as u64),
+        // f32
+        6 => {
+            r.seek_relative(4)?;
+            Value::Other
+        }
+        // u64, i64
+        10 | 11 => Value::Int(read_u64(r)?),
+        // f64
+        12 => {
+            r.seek_relative(8)?;
+            Value::Other
+        }
+        8 => {
+            let len = read_len(r)?;
+            if wanted {
+                Value::Str(read_string(r, len)?)
+            } else {
+                r.seek_relative(len as i64)?;
+                Value::Other
+            }
+        }
+        9 => {
+            let item_type = read_u32(r)?;
+            let count = read_len(r)?;
+            // Vocab arrays can hold hundreds of thousands of entries; skip fixed-size ones in one seek
+            match fixed_size(item_type) {
+                Some(size) => r.seek_relative((size * count) as i64)?,
+                None => {
+                    for _ in 0..count {
+                        read_value(r, item_type, false, read_len)?;
+                    }
+                }
+            }
+            Value::Other
+        }
+        other => return Err(anyhow!("Unknown GGUF value type {}", other)),
+    };
+    Ok(value)
+}
+
+fn fixed_size(value_type: u32) -> Option<u64> {
+    match value_type {
+        0 | 1 | 7 => Some(1),
+        2 | 3 => Some(2),
+        4..=6 => Some(4),
+        10..=12 => Some(8),
+        _ => None,
+    }
+}
+
+fn read_bytes<const N: usize>(r: &mut impl Read) -> Result<[u8; N]> {
+    let mut buf = [0u8; N];
+    r.read_exact(&mut buf)?;
+    Ok(buf)
+}
+
+fn read_u32(r: &mut impl Read) -> Result<u32> {
+    Ok(u32::from_le_bytes(read_bytes(r)?))
+}
+
+fn read_u64(r: &mut impl Read) -> Result<u64> {
+    Ok(u64::from_le_bytes(read_bytes(r)?))
+}
+
+fn read_string(r: &mut impl Read, len: u64) -> Result<String> {
+    // Keys and names are short; chat templates are a few KB at most
+    if len > 16 * 1024 * 1024 {
+        return Err(anyhow!("GGUF string too long ({} bytes)", len));
+    }
+    let mut buf = vec![0u8; len as usize];
+    r.read_exact(&mut buf)?;
+    Ok(String::from_utf8_lossy(&buf).to_string())
+}
+
+/// Map llama.cpp's `general.file_type` enum to its quantization name.
+fn file_type_name(file_type: u64) -> String {
+    let name = match file_type {
+        0 => "F32",
+        1 => "F16",
+        2 => "Q4_0",
+        3 => "Q4_1",
+        7 => "Q8_0",
+        8 => "Q5_0",
+        9 => "Q5_1",
+        10 => "Q2_K",
+        11 => "Q3_K_S",
+        12 => "Q3_K_M",
+        13 => "Q3_K_L",
+        14 => "Q4_K_S",
+        15 => "Q4_K_M",
+        16 => "Q5_K_S",
+        17 => "Q5_K_M",
+        18 => "Q6_K",
+        19 => "IQ2_XXS",
+        20 => "IQ2_XS",
+        21 => "Q2_K_S",
+        22 => "IQ3_XS",
+        23 => "IQ3_XXS",
+        24 => "IQ1_S",
+        25 => "IQ4_NL",
+        26 => "IQ3_S",
+        27 => "IQ3_M",
+        28 => "IQ2_S",
+        29 => "IQ2_M",
+        30 => "IQ4_XS",
+        31 => "IQ1_M",
+        32 => "BF16",
+        36 => "TQ1_0",
+        37 => "TQ2_0",
+        other => return format!("Unknown ({})", other),
+    };
+    name.to_string()
+}
@@ -9,7 +9,9 @@ use llama_cpp_2::model::params::LlamaModelParams;
 use llama_cpp_2::model::{AddBos, LlamaModel, Special};
 use llama_cpp_2::sampling::LlamaSampler;
 use std::num::NonZeroU32;
+use std::ops::RangeInclusive;
 use std::path::Path;
+use std::sync::{Arc, Mutex, OnceLock};
 use tokio::sync::mpsc;
 use tokio_stream::wrappers::ReceiverStream;
 use tokio_stream::Stream;
@@ -17,6 +19,8 @@ use tokio_stream::Stream;
 /// A local LLM provider using llama.cpp via llama-cpp-2 bindings
 pub struct LocalLLMProvider {
     model_path: String,
+    seed: Option<u32>,
+    temperature: f32,
 }
 
 impl LocalLLMProvider {
@@ -27,8 +31,139 @@ impl LocalLLMProvider {
         }
         Ok(Self {
             model_path: model_path.to_string(),
+            seed: None,
+            temperature: DEFAULT_TEMPERATURE,
         })
     }
+
+    pub fn with_seed(mut self, seed: Option<u32>) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Sample at `temperature`, clamped to what the sampler handles sensibly. Anything under
+    /// 0.01 decodes greedily.
+    pub fn with_temperature(mut self, temperature: f32) -> Self {
+        self.temperature = super::clamp_temperature(temperature, &TEMPERATURE_RANGE);
+        self
+    }
+}
+
+/// A model kept in memory between requests so only the first message pays the load cost
+struct LoadedModel {
+    path: String,
+    model: LlamaModel,
+}
+
+/// Context size the model runs with, prompt and reply together
+const CONTEXT_TOKENS: u32 = 2048;
+/// Longest reply generated
+const MAX_REPLY_TOKENS: usize = 512;
+
+/// llama.cpp's own default, used until one is configured
+const DEFAULT_TEMPERATURE: f32 = 0.8;
+/// Higher than this and small models mostly produce noise
+const TEMPERATURE_RANGE: RangeInclusive<f32> = 0.0..=2.0;
+
+/// llama.cpp's `LLAMA_DEFAULT_SEED`: the sampler draws a random seed when given this
+const LLAMA_DEFAULT_SEED: u32 = 0xFFFF_FFFF;
+
+/// The currently resident model, swapped out when the configured path changes
+static RESIDENT: Mutex<Option<Arc<LoadedModel>>> = Mutex::new(None);
+
+/// llama.cpp's backend may only be initialized once per process
+static BACKEND: OnceLock<LlamaBackend> = OnceLock::new();
+
+fn backend() -> Result<&'static LlamaBackend> {
+    BACKEND
+        .get()
+        .ok_or_else(|| anyhow!("llama.cpp backend unavailable"))
+}
+
+/// Return the resident model for `model_path`, loading it if needed.
+fn resident_model(model_path: &str) -> Result<Arc<LoadedModel>> {
+    let mut resident = RESIDENT
+        .lock()
+        .map_err(|e| anyhow!("Model cache lock error: {}", e))?;
+    if let Some(loaded) = resident.as_ref().filter(|m| m.path == model_path) {
+        return Ok(Arc::clone(loaded));
+    }
+
+    // Release the previous model before loading a new one
+    *resident = None;
+
+    // Only reached with the lock held, so there is no race on initialization
+    if BACKEND.get().is_none() {
+        let backend = LlamaBackend::init().map_err(|e| anyhow!("Failed to init backend: {}", e))?;
+        let _ = BACKEND.set(backend);
+    }
+    let backend = backend()?;
+
+    // Load model with Metal GPU layers on macOS
+    let model_params = LlamaModelParams::default().with_n_gpu_layers(1000);
+
+    let model = LlamaModel::load_from_file(backend, model_path, &model_params)
+        .map_err(|e| anyhow!("Failed to load model: {}", e))?;
+
+    let loaded = Arc::new(LoadedModel {
+        path: model_path.to_string(),
+        model,
+    });
+    *resident = Some(Arc::clone(&loaded));
+    Ok(loaded)
+}
+
+/// Free the resident model unless it is the one at `keep`.
+pub fn unload_unless(keep: Option<&str>) {
+    if let Ok(mut resident) = RESIDENT.lock() {
+        if resident.as_ref().is_some_and(|m| Some(m.path.as_str()) != keep) {
+            *resident = None;
+            tracing::info!("Unloaded resident model");
+        }
+    }
+}
+
+/// Load a model into memory ahead of the first message. With `warm_up`, also
+/// decode a short prompt and sample one token so GPU kernels are compiled.
+pub fn preload(model_path: &str, warm_up: bool) -> Result<()> {
+    if !Path::new(model_path).exists() {
+        return Err(anyhow!("Model file not found: {}", model_path));
+    }
+    let loaded = resident_model(model_path)?;
+    if !warm_up {
+        return Ok(());
+    }
+
+    let backend = backend()?;
+    let ctx_params = LlamaContextParams::default()
+        .with_n_ctx(Some(NonZeroU32::new(512).unwrap()))
+        .with_n_batch(512);
+    let mut ctx = loaded
+        .model
+        .new_context(backend, ctx_params)
+        .map_err(|e| anyhow!("Failed to create context: {}", e))?;
+
+    let prompt = format_chat_prompt(&[Message {
+        role: "user".to_string(),
+        content: "Hi".to_string(),
+    }]);
+    let tokens = loaded
+        .model
+        .str_to_token(&prompt, AddBos::Always)
+        .map_err(|e| anyhow!("Failed to tokenize: {}", e))?;
+
+    let mut batch = LlamaBatch::new(512, 1);
+    for (i, token) in tokens.iter().enumerate() {
+        batch
+            .add(*token, i as i32, &[0], i == tokens.len() - 1)
+            .map_err(|e| anyhow!("Failed to add token to batch: {}", e))?;
+    }
+    ctx.decode(&mut batch)
+        .map_err(|e| anyhow!("Failed to decode prompt: {}", e))?;
+
+    let mut sampler = LlamaSampler::greedy();
+    sampler.sample(&ctx, batch.n_tokens() - 1);
+    Ok(())
 }
 
 /// Format chat messages into a prompt string for the model
@@ -69,14 +204,15 @@ impl LLMProvider for LocalLLMProvider {
     async fn stream_completion(
         &self,
         messages: Vec<Message>,
-        temperature: f32,
     ) -> Result<Box<dyn Stream<Item = Result<String>> + Send + Unpin>> {
         let model_path = self.model_path.clone();
+        let seed = self.seed;
+        let temperature = self.temperature;
         let (tx, rx) = mpsc::channel::<Result<String>>(32);
 
         // Run inference in a blocking thread
         tokio::task::spawn_blocking(move || {
-            let result = run_inference(&model_path, &messages, temperature, tx.clone());
+            let result = run_inference(&model_path, &messages, temperature, seed, tx.clone());
             if let Err(e) = result {
                 let _ = tx.blocking_send(Err(e));
             }
@@ -84,30 +220,34 @@ impl LLMProvider for LocalLLMProvider {
 
         Ok(Box::new(Box::pin(ReceiverStream::new(rx))))
     }
+
+    fn context_window(&self) -> usize {
+        CONTEXT_TOKENS as usize
+    }
+
+    fn max_reply_tokens(&self) -> usize {
+        MAX_REPLY_TOKENS
+    }
 }
 
 fn run_inference(
     model_path: &str,
     messages: &[Message],
     temperature: f32,
+    seed: Option<u32>,
     tx: mpsc::Sender<Result<String>>,
 ) -> Result<()> {
-    // Initialize backend
-    let backend = LlamaBackend::init().map_err(|e| anyhow!("Failed to init backend: {}", e))?;
-
-    // Load model with Metal GPU layers on macOS
-    let model_params = LlamaModelParams::default().with_n_gpu_layers(1000);
-
-    let model = LlamaModel::load_from_file(&backend, model_path, &model_params)
-        .map_err(|e| anyhow!("Failed to load model: {}", e))?;
+    let loaded = resident_model(model_path)?;
+    let model = &loaded.model;
+    let backend = backend()?;
 
     // Create context
     let ctx_params = LlamaContextParams::default()
-        .with_n_ctx(Some(NonZeroU32::new(2048).unwrap()))
+        .with_n_ctx(NonZeroU32::new(CONTEXT_TOKENS))
         .with_n_batch(512);
 
     let mut ctx = model
-        .new_context(&backend, ctx_params)
+        .new_context(backend, ctx_params)
         .map_err(|e| anyhow!("Failed to create context: {}", e))?;
 
     // Format messages into prompt
@@ -119,7 +259,7 @@ fn run_inference(
         .map_err(|e| anyhow!("Failed to tokenize: {}", e))?;
 
     // Create batch and add prompt tokens
-    let mut batch = LlamaBatch::new(2048, 1);
+    let mut batch = LlamaBatch::new(CONTEXT_TOKENS as usize, 1);
     for (i, token) in tokens.iter().enumerate() {
         let is_last = i == tokens.len() - 1;
         batch
@@ -137,12 +277,12 @@ fn run_inference(
     } else {
         LlamaSampler::chain_simple([
             LlamaSampler::temp(temperature),
-            LlamaSampler::dist(0),
+            LlamaSampler::dist(seed.unwrap_or(LLAMA_DEFAULT_SEED)),
         ])
     };
 
     // Generate tokens
-    let max_tokens = 512;
+    let max_tokens = MAX_REPLY_TOKENS;
     let mut n_decoded = tokens.len() as i32;
 
     for _ in 0..max_tokens {
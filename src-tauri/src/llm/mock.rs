@@ -0,0 +1,67 @@
+use super::{LLMProvider, Message};
+use crate::config::{MockConfig, MockMode};
+use anyhow::Result;
+use async_trait::async_trait;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_stream::Stream;
+
+/// Offline stand-in for a real model: streams back canned lines or the user's own message
+/// word by word, for demos on machines without a model or key and for exercising the
+/// chat pipeline deterministically.
+pub struct MockProvider {
+    mode: MockMode,
+    latency: Duration,
+    responses: Vec<String>,
+}
+
+impl MockProvider {
+    pub fn new(config: &MockConfig) -> Self {
+        Self {
+            mode: config.mode,
+            latency: Duration::from_millis(config.latency_ms),
+            responses: config.responses.clone(),
+        }
+    }
+
+    /// The whole reply. Canned replies cycle with the number of user turns, so the
+    /// same conversation always gets the same answers.
+    fn reply(&self, messages: &[Message]) -> String {
+        let user_turns: Vec<&Message> = messages.iter().filter(|m| m.role == "user").collect();
+        let last = user_turns.last().map(|m| m.content.as_str()).unwrap_or("");
+        match self.mode {
+            MockMode::Echo => format!("You said: {}", last),
+            MockMode::Canned if self.responses.is_empty() => "...".to_string(),
+            MockMode::Canned => {
+                let index = user_turns.len().saturating_sub(1) % self.responses.len();
+                self.responses[index].clone()
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl LLMProvider for MockProvider {
+    async fn stream_completion(
+        &self,
+        messages: Vec<Message>,
+    ) -> Result<Box<dyn Stream<Item = Result<String>> + Send + Unpin>> {
+        let reply = self.reply(&messages);
+        let latency = self.latency;
+        let (tx, rx) = mpsc::channel::<Result<String>>(32);
+
+        tokio::spawn(async move {
+            // One token per word, keeping the whitespace so the pieces join back up exactly
+            for token in reply.split_inclusive(char::is_whitespace) {
+                tokio::time::sleep(latency).await;
+                if tx.send(Ok(token.to_string())).await.is_err() {
+                    // Receiver dropped, stop generating
+                    break;
+                }
+            }
+        });
+
+        Ok(Box::new(Box::pin(ReceiverStream::new(rx))))
+    }
+}
@@ -1,21 +1,50 @@
+pub mod budget;
+pub mod gguf;
 pub mod local;
+pub mod mock;
 pub mod openai;
 
 use anyhow::Result;
 use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::ops::RangeInclusive;
 use tokio_stream::Stream;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Message {
     pub role: String,
     pub content: String,
 }
 
+/// `temperature` pulled into `range`, logging when the configured value had to change
+pub(crate) fn clamp_temperature(temperature: f32, range: &RangeInclusive<f32>) -> f32 {
+    let clamped = temperature.clamp(*range.start(), *range.end());
+    if clamped != temperature {
+        tracing::warn!(
+            "Temperature {} is outside what this model accepts; using {}",
+            temperature,
+            clamped
+        );
+    }
+    clamped
+}
+
+/// Sampling settings such as temperature are given to each provider as it's built, so they
+/// can be checked against what that backend accepts.
 #[async_trait]
 pub trait LLMProvider: Send + Sync {
     async fn stream_completion(
         &self,
         messages: Vec<Message>,
-        temperature: f32,
     ) -> Result<Box<dyn Stream<Item = Result<String>> + Send + Unpin>>;
+
+    /// How many tokens the model sees at once, prompt and reply together
+    fn context_window(&self) -> usize {
+        4096
+    }
+
+    /// Longest reply the provider will generate, kept free when fitting the prompt
+    fn max_reply_tokens(&self) -> usize {
+        budget::DEFAULT_REPLY_TOKENS
+    }
 }
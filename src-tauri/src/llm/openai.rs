@@ -3,6 +3,7 @@ use anyhow::{anyhow, Result};
 use async_trait::async_trait;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
+use std::ops::RangeInclusive;
 use tokio_stream::{Stream, StreamExt};
 
 #[derive(Clone)]
@@ -11,6 +12,11 @@ pub struct OpenAIProvider {
     api_key: String,
     model: String,
     base_url: String,
+    seed: Option<u32>,
+    /// `None` leaves it to the server's default
+    temperature: Option<f32>,
+    /// Pass on the reasoning deltas some servers send next to the reply
+    reasoning: bool,
 }
 
 impl OpenAIProvider {
@@ -20,6 +26,9 @@ impl OpenAIProvider {
             api_key,
             model,
             base_url: "https://api.openai.com/v1".to_string(),
+            seed: None,
+            temperature: None,
+            reasoning: true,
         }
     }
     
@@ -27,13 +36,63 @@ impl OpenAIProvider {
         self.base_url = base_url;
         self
     }
+
+    /// Ask the backend to sample deterministically. OpenAI, LM Studio and Ollama honour it
+    /// (OpenAI only on a best-effort basis); servers that don't know the field ignore it.
+    pub fn with_seed(mut self, seed: Option<u32>) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Whether to keep the model's reasoning when the server sends it separately; it's passed
+    /// on wrapped in `<think>` tags, the way models that think in the reply itself write it,
+    /// so `reasoning::ReasoningFilter` routes both the same way.
+    pub fn with_reasoning(mut self, reasoning: bool) -> Self {
+        self.reasoning = reasoning;
+        self
+    }
+
+    /// Sample at `temperature`, clamped to what the model accepts, or left out for models
+    /// that only run at their default. Call after `with_base_url`.
+    pub fn with_temperature(mut self, temperature: f32) -> Self {
+        self.temperature = match self.temperature_range() {
+            Some(range) => Some(super::clamp_temperature(temperature, &range)),
+            None => {
+                tracing::debug!("{} doesn't take a temperature; leaving it out", self.model);
+                None
+            }
+        };
+        self
+    }
+
+    /// OpenAI's reasoning models (o1, o3, o4-mini, gpt-5) reject any temperature but their
+    /// default, so they get `None`. Claude, through Anthropic's OpenAI-compatible endpoint,
+    /// caps it at 1. Routers prefix the vendor, as in "openai/o3-mini".
+    fn temperature_range(&self) -> Option<RangeInclusive<f32>> {
+        let model = self.model.to_lowercase();
+        let model = model.rsplit('/').next().unwrap_or_default();
+        let o_series = model
+            .strip_prefix('o')
+            .is_some_and(|rest| rest.starts_with(|c: char| c.is_ascii_digit()));
+        let gpt5_reasoning = model.starts_with("gpt-5") && !model.contains("chat");
+        if o_series || gpt5_reasoning {
+            None
+        } else if model.starts_with("claude") || self.base_url.contains("anthropic.com") {
+            Some(0.0..=1.0)
+        } else {
+            Some(0.0..=2.0)
+        }
+    }
 }
 
 #[derive(Serialize)]
 struct ChatCompletionRequest {
     model: String,
     messages: Vec<ChatMessage>,
-    temperature: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    seed: Option<u32>,
     stream: bool,
 }
 
@@ -56,6 +115,10 @@ struct Choice {
 #[derive(Deserialize)]
 struct Delta {
     content: Option<String>,
+    /// DeepSeek, LM Studio and vLLM put the chain of thought here
+    reasoning_content: Option<String>,
+    /// OpenRouter and Ollama call it this
+    reasoning: Option<String>,
 }
 
 #[async_trait]
@@ -63,7 +126,6 @@ impl LLMProvider for OpenAIProvider {
     async fn stream_completion(
         &self,
         messages: Vec<Message>,
-        temperature: f32,
     ) -> Result<Box<dyn Stream<Item = Result<String>> + Send + Unpin>> {
         let chat_messages: Vec<ChatMessage> = messages
             .into_iter()
@@ -76,7 +138,8 @@ impl LLMProvider for OpenAIProvider {
         let request = ChatCompletionRequest {
             model: self.model.clone(),
             messages: chat_messages,
-            temperature,
+            temperature: self.temperature,
+            seed: self.seed,
             stream: true,
         };
         
@@ -95,9 +158,12 @@ impl LLMProvider for OpenAIProvider {
             return Err(anyhow!("OpenAI API error {}: {}", status, error_text));
         }
         
+        let keep_reasoning = self.reasoning;
+        // Whether a <think> span is open, across chunks
+        let mut thinking = false;
         let stream = response
             .bytes_stream()
-            .map(|chunk_result| {
+            .map(move |chunk_result| {
                 chunk_result
                     .map_err(|e| anyhow!("Stream error: {}", e))
                     .and_then(|chunk| {
@@ -109,13 +175,36 @@ impl LLMProvider for OpenAIProvider {
                             if line.starts_with("data: ") {
                                 let data = &line[6..];
                                 if data == "[DONE]" {
+                                    if thinking {
+                                        thinking = false;
+                                        content_parts.push("</think>".to_string());
+                                    }
                                     break;
                                 }
                                 
                                 if let Ok(chunk) = serde_json::from_str::<ChatCompletionChunk>(data) {
                                     if let Some(choice) = chunk.choices.first() {
-                                        if let Some(content) = &choice.delta.content {
-                                            content_parts.push(content.clone());
+                                        let delta = &choice.delta;
+                                        let reasoning = delta
+                                            .reasoning_content
+                                            .as_deref()
+                                            .or(delta.reasoning.as_deref())
+                                            .filter(|r| keep_reasoning && !r.is_empty());
+                                        if let Some(reasoning) = reasoning {
+                                            if !thinking {
+                                                thinking = true;
+                                                content_parts.push("<think>".to_string());
+                                            }
+                                            content_parts.push(reasoning.to_string());
+                                        }
+                                        if let Some(content) =
+                                            delta.content.as_deref().filter(|c| !c.is_empty())
+                                        {
+                                            if thinking {
+                                                thinking = false;
+                                                content_parts.push("</think>".to_string());
+                                            }
+                                            content_parts.push(content.to_string());
                                         }
                                     }
                                 }
@@ -137,4 +226,27 @@ impl LLMProvider for OpenAIProvider {
         
         Ok(Box::new(Box::pin(stream)))
     }
+
+    /// OpenAI's published sizes for the models the settings offer. Local servers don't say,
+    /// so anything else gets the trait's cautious default; `context_tokens` overrides it.
+    fn context_window(&self) -> usize {
+        let model = self.model.as_str();
+        if model.starts_with("gpt-4o")
+            || model.starts_with("gpt-4.1")
+            || model.starts_with("gpt-4-turbo")
+            || model.starts_with("o1")
+            || model.starts_with("o3")
+            || model.starts_with("o4")
+        {
+            128_000
+        } else if model.starts_with("gpt-4-32k") {
+            32_768
+        } else if model.starts_with("gpt-3.5-turbo") {
+            16_385
+        } else if model.starts_with("gpt-4") {
+            8_192
+        } else {
+            4_096
+        }
+    }
 }
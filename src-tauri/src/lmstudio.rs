@@ -0,0 +1,224 @@
+use crate::config::{Config, LlmProviderType};
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+const DEFAULT_URL: &str = "http://localhost:1234";
+/// Listing models is quick on a local server
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+/// Loading a big model from disk takes a while
+const LOAD_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// A model LM Studio has downloaded, from its REST API
+#[derive(Debug, Clone, Serialize)]
+pub struct LmStudioModel {
+    /// What to put in the model name field, e.g. "qwen2.5-7b-instruct"
+    pub id: String,
+    /// "llm", "vlm" or "embeddings"
+    pub kind: String,
+    pub loaded: bool,
+    pub quantization: Option<String>,
+    pub max_context_length: Option<u64>,
+}
+
+/// Whether LM Studio's server is up, and what it has
+#[derive(Debug, Clone, Serialize)]
+pub struct LmStudioStatus {
+    pub running: bool,
+    pub url: String,
+    pub models: Vec<LmStudioModel>,
+    /// Why the models couldn't be listed, when it's running but something else went wrong
+    pub error: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ModelsResponse {
+    #[serde(default)]
+    data: Vec<ModelEntry>,
+}
+
+#[derive(Deserialize)]
+struct ModelEntry {
+    id: String,
+    #[serde(default, rename = "type")]
+    kind: String,
+    #[serde(default)]
+    state: String,
+    quantization: Option<String>,
+    max_context_length: Option<u64>,
+}
+
+/// LM Studio's server root. The chat goes through its OpenAI-compatible `/v1`, so a
+/// configured URL loses that suffix; other providers' URLs aren't LM Studio's.
+fn base_url(config: &Config) -> String {
+    let url = match config.llm_provider {
+        LlmProviderType::LMStudio => config.custom_api_url.as_deref().unwrap_or(DEFAULT_URL),
+        _ => DEFAULT_URL,
+    };
+    let url = url.trim().trim_end_matches('/');
+    url.strip_suffix("/v1").unwrap_or(url).to_string()
+}
+
+/// Nothing answered at LM Studio's URL
+#[derive(Debug)]
+struct NotRunning(String);
+
+impl std::fmt::Display for NotRunning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "LM Studio isn't running at {}. Open LM Studio and start its server (Developer → \
+             Start Server, or `lms server start`).",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for NotRunning {}
+
+/// A failed request in words: "isn't running" rather than "connection refused"
+fn request_error(url: &str, e: reqwest::Error) -> anyhow::Error {
+    if e.is_connect() {
+        NotRunning(url.to_string()).into()
+    } else {
+        anyhow!("LM Studio request failed: {}", e)
+    }
+}
+
+fn client(config: &Config, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+    // Servers started with authentication on want the same key the chat uses
+    match config.custom_api_key.as_deref().filter(|k| !k.is_empty()) {
+        Some(key) if config.llm_provider == LlmProviderType::LMStudio => request.bearer_auth(key),
+        _ => request,
+    }
+}
+
+/// Every model LM Studio has downloaded, loaded ones first
+pub async fn list(config: &Config) -> Result<Vec<LmStudioModel>> {
+    let url = base_url(config);
+    let request = reqwest::Client::new()
+        .get(format!("{}/api/v0/models", url))
+        .timeout(REQUEST_TIMEOUT);
+    let response = client(config, request)
+        .send()
+        .await
+        .map_err(|e| request_error(&url, e))?;
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Err(anyhow!(
+            "This LM Studio has no REST API to list models; update it to 0.3.6 or later"
+        ));
+    }
+    if !response.status().is_success() {
+        return Err(anyhow!("LM Studio answered {}", response.status()));
+    }
+    let models: ModelsResponse = response.json().await?;
+    let mut models: Vec<LmStudioModel> = models
+        .data
+        .into_iter()
+        .map(|m| LmStudioModel {
+            id: m.id,
+            kind: m.kind,
+            loaded: m.state == "loaded",
+            quantization: m.quantization,
+            max_context_length: m.max_context_length,
+        })
+        .collect();
+    models.sort_by_key(|m| !m.loaded);
+    Ok(models)
+}
+
+/// Whether LM Studio is up at the configured URL, and its models if so
+pub async fn status(config: &Config) -> LmStudioStatus {
+    let url = base_url(config);
+    match list(config).await {
+        Ok(models) => LmStudioStatus {
+            running: true,
+            url,
+            models,
+            error: None,
+        },
+        Err(e) => LmStudioStatus {
+            running: !e.is::<NotRunning>(),
+            url,
+            models: Vec::new(),
+            error: Some(e.to_string()),
+        },
+    }
+}
+
+/// Ask LM Studio to load `model` into memory. Versions without the load endpoint load a
+/// model on its first request, so they're sent a one-token one instead.
+pub async fn load(config: &Config, model: &str) -> Result<()> {
+    let url = base_url(config);
+    tracing::info!("Asking LM Studio to load {}", model);
+    let request = reqwest::Client::new()
+        .post(format!("{}/api/v1/models/load", url))
+        .json(&serde_json::json!({ "model": model }))
+        .timeout(LOAD_TIMEOUT);
+    let response = client(config, request)
+        .send()
+        .await
+        .map_err(|e| request_error(&url, e))?;
+    let response = if response.status() == reqwest::StatusCode::NOT_FOUND {
+        let request = reqwest::Client::new()
+            .post(format!("{}/v1/chat/completions", url))
+            .json(&serde_json::json!({
+                "model": model,
+                "messages": [{ "role": "user", "content": "Hi" }],
+                "max_tokens": 1,
+            }))
+            .timeout(LOAD_TIMEOUT);
+        client(config, request)
+            .send()
+            .await
+            .map_err(|e| request_error(&url, e))?
+    } else {
+        response
+    };
+    if !response.status().is_success() {
+        let status = response.status();
+        let text = response.text().await.unwrap_or_default();
+        return Err(anyhow!(
+            "LM Studio couldn't load {} ({}): {}",
+            model,
+            status,
+            crate::redact::redact(&text)
+        ));
+    }
+    tracing::info!("LM Studio loaded {}", model);
+    Ok(())
+}
+
+/// Load the configured model if LM Studio has it but hasn't loaded it. "default", or no
+/// model at all, means whatever is loaded, so there's nothing to do.
+pub async fn ensure_loaded(config: &Config) -> Result<()> {
+    let Some(model) = config
+        .custom_model
+        .as_deref()
+        .map(str::trim)
+        .filter(|m| !m.is_empty() && *m != "default")
+    else {
+        return Ok(());
+    };
+    let models = list(config).await?;
+    match models.iter().find(|m| m.id == model) {
+        Some(found) if found.loaded => Ok(()),
+        Some(_) => load(config, model).await,
+        None => Err(anyhow!(
+            "LM Studio has no model called {}. Pick one of its models in Settings.",
+            model
+        )),
+    }
+}
+
+/// A clearer message for a chat request that never reached LM Studio
+pub fn explain(config: &Config, error: &anyhow::Error) -> Option<String> {
+    if config.llm_provider != LlmProviderType::LMStudio {
+        return None;
+    }
+    let refused = error
+        .chain()
+        .filter_map(|cause| cause.downcast_ref::<reqwest::Error>())
+        .any(|e| e.is_connect());
+    refused.then(|| NotRunning(base_url(config)).to_string())
+}
@@ -0,0 +1,92 @@
+use crate::redact::RedactingMakeWriter;
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{fmt, EnvFilter};
+
+const LOG_FILE_PREFIX: &str = "rusty-clippy";
+/// Days of logs kept before the oldest file is deleted
+const MAX_LOG_FILES: usize = 7;
+
+/// Flushes the background log writer on exit; must live for the whole process
+static FILE_GUARD: OnceLock<WorkerGuard> = OnceLock::new();
+
+/// Log to stdout and to a daily-rotated file in the logs directory, scrubbing secrets from both.
+pub fn init() {
+    let file_layer = match file_appender() {
+        Ok(appender) => {
+            let (writer, guard) = tracing_appender::non_blocking(appender);
+            let _ = FILE_GUARD.set(guard);
+            Some(
+                fmt::layer()
+                    .with_ansi(false)
+                    .with_writer(RedactingMakeWriter(writer)),
+            )
+        }
+        Err(e) => {
+            eprintln!("File logging unavailable: {}", e);
+            None
+        }
+    };
+
+    tracing_subscriber::registry()
+        .with(EnvFilter::from_default_env().add_directive("rusty_clippy=info".parse().unwrap()))
+        .with(fmt::layer().with_writer(RedactingMakeWriter(std::io::stdout)))
+        .with(file_layer)
+        .init();
+}
+
+fn file_appender() -> Result<RollingFileAppender> {
+    Ok(RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix(LOG_FILE_PREFIX)
+        .filename_suffix("log")
+        .max_log_files(MAX_LOG_FILES)
+        .build(log_dir()?)?)
+}
+
+/// Directory holding the rotated log files
+pub fn log_dir() -> Result<PathBuf> {
+    let data_dir =
+        dirs::data_dir().ok_or_else(|| anyhow::anyhow!("Could not find data directory"))?;
+    let logs = data_dir.join("rusty-clippy").join("logs");
+    std::fs::create_dir_all(&logs)?;
+    Ok(logs)
+}
+
+/// The last `count` log lines, oldest first, reading back across rotated files as needed.
+pub fn recent_lines(count: usize) -> Result<Vec<String>> {
+    let mut lines = Vec::new();
+    for file in log_files_newest_first(&log_dir()?)? {
+        let content = std::fs::read_to_string(&file)?;
+        let mut older: Vec<String> = content.lines().map(str::to_string).collect();
+        let take = count.saturating_sub(lines.len()).min(older.len());
+        older.drain(..older.len() - take);
+        older.append(&mut lines);
+        lines = older;
+        if lines.len() >= count {
+            break;
+        }
+    }
+    Ok(lines)
+}
+
+fn log_files_newest_first(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files: Vec<PathBuf> = std::fs::read_dir(dir)?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.starts_with(LOG_FILE_PREFIX) && n.ends_with(".log"))
+        })
+        .collect();
+    // Names carry the date (rusty-clippy.2025-01-31.log), so they sort chronologically
+    files.sort();
+    files.reverse();
+    Ok(files)
+}
@@ -0,0 +1,382 @@
+use crate::config::Config;
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// Where models installed from local files are copied, under the data directory
+const LOCAL_MODELS_DIR: &str = "local-models";
+
+/// Kind of asset stored under the data directory
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+pub enum ModelKind {
+    Llm,
+    Voice,
+}
+
+/// A model or voice found on disk
+#[derive(Debug, Clone, Serialize)]
+pub struct DownloadedModel {
+    pub name: String,
+    pub path: String,
+    pub kind: ModelKind,
+    pub size_bytes: u64,
+}
+
+/// Disk usage of everything under the data directory, in bytes
+#[derive(Debug, Clone, Serialize)]
+pub struct StorageUsage {
+    pub data_dir: String,
+    pub models_bytes: u64,
+    pub voices_bytes: u64,
+    pub other_bytes: u64,
+    pub total_bytes: u64,
+}
+
+/// A downloadable GGUF. Only Gemma-family models are listed since the
+/// built-in provider formats prompts with Gemma's chat template.
+pub struct CatalogModel {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub repo: &'static str,
+    pub file: &'static str,
+    pub size_bytes: u64,
+}
+
+impl CatalogModel {
+    pub fn url(&self) -> String {
+        format!(
+            "https://huggingface.co/{}/resolve/main/{}",
+            self.repo, self.file
+        )
+    }
+
+    pub fn dest(&self, data_dir: &Path) -> PathBuf {
+        data_dir
+            .join(format!("models--{}", self.repo.replace('/', "--")))
+            .join(self.file)
+    }
+}
+
+/// Ordered smallest to largest; the first entry is the default download.
+pub const CATALOG: &[CatalogModel] = &[
+    CatalogModel {
+        id: "gemma-3-1b-q4_k_m",
+        name: "Gemma 3 1B (Q4_K_M)",
+        repo: "bartowski/google_gemma-3-1b-it-GGUF",
+        file: "google_gemma-3-1b-it-Q4_K_M.gguf",
+        size_bytes: 806_000_000,
+    },
+    CatalogModel {
+        id: "gemma-3-1b-q8_0",
+        name: "Gemma 3 1B (Q8_0)",
+        repo: "bartowski/google_gemma-3-1b-it-GGUF",
+        file: "google_gemma-3-1b-it-Q8_0.gguf",
+        size_bytes: 1_070_000_000,
+    },
+    CatalogModel {
+        id: "gemma-3-4b-q4_k_m",
+        name: "Gemma 3 4B (Q4_K_M)",
+        repo: "bartowski/google_gemma-3-4b-it-GGUF",
+        file: "google_gemma-3-4b-it-Q4_K_M.gguf",
+        size_bytes: 2_490_000_000,
+    },
+    CatalogModel {
+        id: "gemma-3-4b-q8_0",
+        name: "Gemma 3 4B (Q8_0)",
+        repo: "bartowski/google_gemma-3-4b-it-GGUF",
+        file: "google_gemma-3-4b-it-Q8_0.gguf",
+        size_bytes: 4_130_000_000,
+    },
+    CatalogModel {
+        id: "gemma-3-12b-q4_k_m",
+        name: "Gemma 3 12B (Q4_K_M)",
+        repo: "bartowski/google_gemma-3-12b-it-GGUF",
+        file: "google_gemma-3-12b-it-Q4_K_M.gguf",
+        size_bytes: 7_300_000_000,
+    },
+];
+
+pub fn catalog_model(id: &str) -> Option<&'static CatalogModel> {
+    CATALOG.iter().find(|m| m.id == id)
+}
+
+/// Catalog entry annotated for the current machine
+#[derive(Debug, Clone, Serialize)]
+pub struct CatalogEntry {
+    pub id: String,
+    pub name: String,
+    pub size_bytes: u64,
+    pub required_memory_bytes: u64,
+    pub fits: bool,
+    pub recommended: bool,
+    pub downloaded: bool,
+}
+
+/// The catalog with fit/recommendation flags for the given hardware.
+/// The recommendation is the largest model that fits in free memory.
+pub fn catalog_for(hw: &crate::hardware::HardwareInfo) -> Vec<CatalogEntry> {
+    let data_dir = Config::data_dir().ok();
+    let usable = hw.usable_model_memory();
+    let mut entries: Vec<CatalogEntry> = CATALOG
+        .iter()
+        .map(|m| {
+            let required = crate::hardware::required_memory(m.size_bytes);
+            CatalogEntry {
+                id: m.id.to_string(),
+                name: m.name.to_string(),
+                size_bytes: m.size_bytes,
+                required_memory_bytes: required,
+                fits: required <= usable,
+                recommended: false,
+                downloaded: data_dir.as_ref().is_some_and(|d| m.dest(d).exists()),
+            }
+        })
+        .collect();
+
+    let pick = entries.iter().rposition(|e| e.fits).unwrap_or(0);
+    if let Some(entry) = entries.get_mut(pick) {
+        entry.recommended = true;
+    }
+    entries
+}
+
+/// List all GGUF models and Piper voices under the data directory.
+pub fn list_downloaded() -> Result<Vec<DownloadedModel>> {
+    let data_dir = Config::data_dir()?;
+    let mut found = Vec::new();
+
+    let mut ggufs = Vec::new();
+    collect_ggufs(&data_dir, &mut ggufs);
+    for path in ggufs {
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        // hf-hub snapshots are symlinks into blobs/, so follow them for the size
+        let size_bytes = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+        found.push(DownloadedModel {
+            name,
+            path: path.to_string_lossy().to_string(),
+            kind: ModelKind::Llm,
+            size_bytes,
+        });
+    }
+
+    let voices_dir = crate::tts::voices_dir()?;
+    if let Ok(entries) = std::fs::read_dir(&voices_dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if !path.is_dir() {
+                continue;
+            }
+            let name = entry.file_name().to_string_lossy().to_string();
+            if !crate::tts::voice_ready(&name) {
+                continue;
+            }
+            found.push(DownloadedModel {
+                size_bytes: dir_size(&path),
+                name,
+                path: path.to_string_lossy().to_string(),
+                kind: ModelKind::Voice,
+            });
+        }
+    }
+
+    found.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(found)
+}
+
+/// Copy a GGUF from elsewhere on disk into the data directory, for machines that can't
+/// reach HuggingFace. A file already in the data directory is used where it is.
+pub fn install_model(source: &Path) -> Result<PathBuf> {
+    if source.extension().and_then(|e| e.to_str()) != Some("gguf") {
+        return Err(anyhow!("Not a GGUF model: {}", source.display()));
+    }
+    let info = crate::llm::gguf::inspect(source)?;
+    if info.tensor_count == 0 {
+        return Err(anyhow!("{} has no tensors", source.display()));
+    }
+    if !info
+        .architecture
+        .as_deref()
+        .is_some_and(|a| a.starts_with("gemma"))
+    {
+        tracing::warn!(
+            "{} is a {:?} model; the built-in provider formats prompts for Gemma",
+            source.display(),
+            info.architecture
+        );
+    }
+
+    let data_dir = Config::data_dir()?;
+    let source = source.canonicalize()?;
+    if source.starts_with(data_dir.canonicalize()?) {
+        return Ok(source);
+    }
+    let file_name = source
+        .file_name()
+        .ok_or_else(|| anyhow!("Invalid model path: {}", source.display()))?;
+    let dest_dir = data_dir.join(LOCAL_MODELS_DIR);
+    std::fs::create_dir_all(&dest_dir)?;
+    let dest = dest_dir.join(file_name);
+    copy_into(&source, &dest)?;
+    Ok(dest)
+}
+
+/// Copy a Piper voice (its `.onnx` model and `.onnx.json` config) into the voices directory
+/// under the model's file name, and return that voice name.
+pub fn install_voice(onnx: &Path, json: &Path) -> Result<String> {
+    let name = onnx
+        .file_name()
+        .and_then(|n| n.to_str())
+        .and_then(|n| n.strip_suffix(".onnx"))
+        .filter(|n| !n.is_empty())
+        .ok_or_else(|| anyhow!("Not a Piper voice model: {}", onnx.display()))?
+        .to_string();
+    if !onnx.is_file() {
+        return Err(anyhow!("Voice model not found: {}", onnx.display()));
+    }
+    let config: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string(json)
+            .map_err(|e| anyhow!("Failed to read {}: {}", json.display(), e))?,
+    )
+    .map_err(|e| anyhow!("{} isn't valid JSON: {}", json.display(), e))?;
+    if config.get("phoneme_id_map").is_none() || config.get("audio").is_none() {
+        return Err(anyhow!("{} isn't a Piper voice config", json.display()));
+    }
+
+    let voice_dir = crate::tts::voices_dir()?.join(&name);
+    std::fs::create_dir_all(&voice_dir)?;
+    // Piper finds the model by dropping ".json" from the config's path
+    copy_into(onnx, &voice_dir.join(format!("{}.onnx", name)))?;
+    copy_into(json, &voice_dir.join(format!("{}.onnx.json", name)))?;
+    Ok(name)
+}
+
+/// Copy `source` to `dest` through a temporary file, so a failed copy never leaves a
+/// truncated file that looks installed.
+fn copy_into(source: &Path, dest: &Path) -> Result<()> {
+    let partial = dest.with_extension("partial");
+    std::fs::copy(source, &partial)
+        .map_err(|e| anyhow!("Failed to copy {}: {}", source.display(), e))?;
+    std::fs::rename(&partial, dest)?;
+    Ok(())
+}
+
+/// Delete a GGUF model or voice directory. The path must live under the data directory.
+/// Returns the kind of asset that was removed.
+pub fn delete(path: &str) -> Result<ModelKind> {
+    let data_dir = Config::data_dir()?.canonicalize()?;
+    let target = Path::new(path);
+
+    // Canonicalize the parent rather than the path itself so that symlinked
+    // hf-hub snapshot entries are checked by location, not by their blob target
+    let parent = target
+        .parent()
+        .ok_or_else(|| anyhow!("Invalid model path: {}", path))?
+        .canonicalize()
+        .map_err(|e| anyhow!("Model path not found: {} ({})", path, e))?;
+    let file_name = target
+        .file_name()
+        .ok_or_else(|| anyhow!("Invalid model path: {}", path))?;
+    let target = parent.join(file_name);
+
+    if !target.starts_with(&data_dir) || target == data_dir {
+        return Err(anyhow!(
+            "Refusing to delete outside the data directory: {}",
+            path
+        ));
+    }
+
+    let voices_dir = crate::tts::voices_dir()?.canonicalize()?;
+    if parent == voices_dir && target.is_dir() {
+        std::fs::remove_dir_all(&target)?;
+        return Ok(ModelKind::Voice);
+    }
+
+    if target.extension().and_then(|e| e.to_str()) != Some("gguf") {
+        return Err(anyhow!("Not a GGUF model or voice directory: {}", path));
+    }
+
+    // hf-hub layout: models--org--repo/snapshots/<rev>/file.gguf -> ../../blobs/<hash>
+    let blob = std::fs::symlink_metadata(&target)
+        .ok()
+        .filter(|m| m.file_type().is_symlink())
+        .and_then(|_| target.canonicalize().ok());
+    std::fs::remove_file(&target)?;
+    if let Some(blob) = blob.filter(|b| b.starts_with(&data_dir)) {
+        let _ = std::fs::remove_file(blob);
+    }
+
+    // Drop the whole repo cache once its last GGUF is gone
+    if let Some(repo_dir) = target
+        .ancestors()
+        .find(|a| a.parent() == Some(data_dir.as_path()))
+    {
+        let mut remaining = Vec::new();
+        collect_ggufs(repo_dir, &mut remaining);
+        if remaining.is_empty() && repo_dir.is_dir() && repo_dir != voices_dir {
+            let _ = std::fs::remove_dir_all(repo_dir);
+        }
+    }
+
+    Ok(ModelKind::Llm)
+}
+
+/// Compute disk usage of models, voices, and everything else in the data directory.
+pub fn storage_usage() -> Result<StorageUsage> {
+    let data_dir = Config::data_dir()?;
+    let voices_dir = crate::tts::voices_dir()?;
+
+    let total_bytes = dir_size(&data_dir);
+    let voices_bytes = dir_size(&voices_dir);
+    let models_bytes = list_downloaded()?
+        .iter()
+        .filter(|m| m.kind == ModelKind::Llm)
+        .map(|m| m.size_bytes)
+        .sum::<u64>();
+
+    Ok(StorageUsage {
+        data_dir: data_dir.to_string_lossy().to_string(),
+        models_bytes,
+        voices_bytes,
+        other_bytes: total_bytes.saturating_sub(models_bytes + voices_bytes),
+        total_bytes,
+    })
+}
+
+/// Recursively find `.gguf` files, skipping hf-hub's content-addressed blobs/ dir.
+fn collect_ggufs(dir: &Path, out: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        if file_type.is_dir() {
+            if entry.file_name() != "blobs" {
+                collect_ggufs(&path, out);
+            }
+        } else if path.extension().and_then(|e| e.to_str()) == Some("gguf") {
+            out.push(path);
+        }
+    }
+}
+
+/// Total size of regular files below `dir` (symlinks are not followed, so
+/// hf-hub blobs are counted exactly once).
+pub fn dir_size(dir: &Path) -> u64 {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| match entry.file_type() {
+            Ok(t) if t.is_dir() => dir_size(&entry.path()),
+            Ok(t) if t.is_file() => entry.metadata().map(|m| m.len()).unwrap_or(0),
+            _ => 0,
+        })
+        .sum()
+}
@@ -0,0 +1,94 @@
+use chrono::Timelike;
+use serde::Serialize;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+/// Idle time after which Clippy starts nodding off
+const SLEEPY_AFTER_IDLE: Duration = Duration::from_secs(30 * 60);
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Mood {
+    Happy,
+    Thinking,
+    Confused,
+    Sleepy,
+}
+
+/// Something that happened which may change the mood
+#[derive(Debug, Clone, Copy)]
+pub enum MoodTrigger {
+    /// The user sent a message and a reply is being generated
+    MessageSent,
+    /// A reply finished streaming
+    ResponseDone,
+    /// A reply failed
+    Error,
+    /// The user has been idle this long
+    Idle(Duration),
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MoodChangedEvent {
+    pub mood: Mood,
+    pub previous: Mood,
+}
+
+static MOOD: Mutex<Mood> = Mutex::new(Mood::Happy);
+
+fn is_late_night() -> bool {
+    let hour = chrono::Local::now().hour();
+    !(5..23).contains(&hour)
+}
+
+impl Mood {
+    fn next(self, trigger: MoodTrigger) -> Mood {
+        match trigger {
+            MoodTrigger::MessageSent => Mood::Thinking,
+            MoodTrigger::Error => Mood::Confused,
+            MoodTrigger::ResponseDone if is_late_night() => Mood::Sleepy,
+            MoodTrigger::ResponseDone => Mood::Happy,
+            // Don't doze off mid-reply
+            MoodTrigger::Idle(_) if self == Mood::Thinking => self,
+            MoodTrigger::Idle(idle) if idle >= SLEEPY_AFTER_IDLE || is_late_night() => Mood::Sleepy,
+            MoodTrigger::Idle(_) => self,
+        }
+    }
+
+    /// A nudge for the system prompt so the reply's tone matches the sprite.
+    pub fn prompt_hint(self) -> Option<&'static str> {
+        match self {
+            Mood::Happy | Mood::Thinking => None,
+            Mood::Confused => Some(
+                "You just hit a technical hiccup and feel a little flustered; it's fine to acknowledge that lightly.",
+            ),
+            Mood::Sleepy => Some(
+                "It's late or things have been quiet, and you're a bit sleepy. Let a little drowsiness show, and if it's very late, gently suggest the user get some rest.",
+            ),
+        }
+    }
+}
+
+pub fn current() -> Mood {
+    MOOD.lock().map(|m| *m).unwrap_or(Mood::Happy)
+}
+
+/// Apply `trigger` and emit `mood-changed` if the mood actually changed.
+pub fn transition(app: &AppHandle, trigger: MoodTrigger) {
+    let change = {
+        let Ok(mut mood) = MOOD.lock() else {
+            return;
+        };
+        let previous = *mood;
+        *mood = previous.next(trigger);
+        (*mood != previous).then_some(MoodChangedEvent {
+            mood: *mood,
+            previous,
+        })
+    };
+    if let Some(event) = change {
+        tracing::debug!("Mood {:?} -> {:?}", event.previous, event.mood);
+        let _ = app.emit("mood-changed", event);
+    }
+}
@@ -0,0 +1,249 @@
+use regex::{Captures, Regex};
+use std::sync::LazyLock;
+
+const ONES: [&str; 20] = [
+    "zero",
+    "one",
+    "two",
+    "three",
+    "four",
+    "five",
+    "six",
+    "seven",
+    "eight",
+    "nine",
+    "ten",
+    "eleven",
+    "twelve",
+    "thirteen",
+    "fourteen",
+    "fifteen",
+    "sixteen",
+    "seventeen",
+    "eighteen",
+    "nineteen",
+];
+const TENS: [&str; 10] = [
+    "", "", "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety",
+];
+const SCALES: [(u64, &str); 4] = [
+    (1_000_000_000_000, "trillion"),
+    (1_000_000_000, "billion"),
+    (1_000_000, "million"),
+    (1_000, "thousand"),
+];
+const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+/// Email addresses with a longer name than this are read as just their domain
+const MAX_SPOKEN_EMAIL_NAME: usize = 20;
+
+static EMAIL: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\b([A-Za-z0-9._%+-]+)@((?:[A-Za-z0-9-]+\.)+[A-Za-z]{2,})\b").unwrap()
+});
+static URL: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\b(?:https?://|www\.)[^\s<>()]+").unwrap());
+static ISO_DATE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\b(\d{4})-(\d{2})-(\d{2})\b").unwrap());
+static SLASH_DATE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\b(\d{1,2})/(\d{1,2})(?:/(\d{4}|\d{2}))?\b").unwrap());
+static TIME: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\b(\d{1,2}):(\d{2})\b").unwrap());
+static ORDINAL: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\b(\d+)(?:st|nd|rd|th)\b").unwrap());
+static NUMBER: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(\$)?\b(\d{1,3}(?:,\d{3})+|\d+)(?:\.(\d+))?\b(%)?").unwrap());
+
+/// `n` in words, e.g. 1234 -> "one thousand two hundred thirty-four".
+fn cardinal(n: u64) -> String {
+    if n < 20 {
+        return ONES[n as usize].to_string();
+    }
+    if n < 100 {
+        return match n % 10 {
+            0 => TENS[(n / 10) as usize].to_string(),
+            ones => format!("{}-{}", TENS[(n / 10) as usize], ONES[ones as usize]),
+        };
+    }
+    if n < 1000 {
+        return match n % 100 {
+            0 => format!("{} hundred", ONES[(n / 100) as usize]),
+            rest => format!("{} hundred {}", ONES[(n / 100) as usize], cardinal(rest)),
+        };
+    }
+    let mut parts = Vec::new();
+    let mut rest = n;
+    for (scale, name) in SCALES {
+        if rest >= scale {
+            parts.push(format!("{} {}", cardinal(rest / scale), name));
+            rest %= scale;
+        }
+    }
+    if rest > 0 {
+        parts.push(cardinal(rest));
+    }
+    parts.join(" ")
+}
+
+/// `n` as an ordinal in words, e.g. 14 -> "fourteenth".
+fn ordinal(n: u64) -> String {
+    let words = cardinal(n);
+    // Only the last word changes: "twenty-one" -> "twenty-first"
+    let split = words.rfind([' ', '-']).map(|i| i + 1).unwrap_or(0);
+    let (head, last) = words.split_at(split);
+    let last = match last {
+        "one" => "first".to_string(),
+        "two" => "second".to_string(),
+        "three" => "third".to_string(),
+        "five" => "fifth".to_string(),
+        "eight" => "eighth".to_string(),
+        "nine" => "ninth".to_string(),
+        "twelve" => "twelfth".to_string(),
+        word if word.ends_with('y') => format!("{}ieth", &word[..word.len() - 1]),
+        word => format!("{}th", word),
+    };
+    format!("{}{}", head, last)
+}
+
+/// A year the way it's said: 1999 -> "nineteen ninety-nine", 2005 -> "two thousand five".
+fn year(n: u64) -> String {
+    let (century, rest) = (n / 100, n % 100);
+    if !(11..=99).contains(&century) || (rest < 10 && century % 10 == 0) {
+        return cardinal(n);
+    }
+    match rest {
+        0 => format!("{} hundred", cardinal(century)),
+        1..=9 => format!("{} oh {}", cardinal(century), cardinal(rest)),
+        _ => format!("{} {}", cardinal(century), cardinal(rest)),
+    }
+}
+
+/// A string of digits read one by one, for numbers too long to say as a whole.
+fn digits(s: &str) -> String {
+    s.chars()
+        .filter_map(|c| c.to_digit(10))
+        .map(|d| ONES[d as usize])
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// A month and day (and maybe year) in words, or `None` if they aren't a real date.
+fn date(month: u64, day: u64, year_digits: Option<&str>) -> Option<String> {
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    let mut spoken = format!("{} {}", MONTHS[month as usize - 1], ordinal(day));
+    if let Some(y) = year_digits.and_then(|y| y.parse::<u64>().ok().map(|n| (y.len(), n))) {
+        let full = match y {
+            (2, n) => 2000 + n,
+            (_, n) => n,
+        };
+        spoken.push_str(&format!(", {}", year(full)));
+    }
+    Some(spoken)
+}
+
+/// A hostname read aloud, e.g. "docs.rs" -> "docs dot rs".
+fn host(name: &str) -> String {
+    name.trim_start_matches("www.").replace('.', " dot ")
+}
+
+/// `text` rewritten so Piper reads it naturally: numbers, dates, times and ordinals become
+/// words, and links and email addresses are shortened to something worth hearing.
+pub fn for_speech(text: &str) -> String {
+    let text = EMAIL.replace_all(text, |caps: &Captures| {
+        let name = &caps[1];
+        if name.len() > MAX_SPOKEN_EMAIL_NAME {
+            format!("an email address at {}", host(&caps[2]))
+        } else {
+            format!("{} at {}", name.replace('.', " dot "), host(&caps[2]))
+        }
+    });
+    let text = URL.replace_all(&text, |caps: &Captures| {
+        let url = caps[0].trim_end_matches(['.', ',', ';', ':', '!', '?']);
+        let trailing = &caps[0][url.len()..];
+        let name = url
+            .split("://")
+            .last()
+            .and_then(|rest| rest.split(['/', '?', '#', ':']).next())
+            .unwrap_or(url);
+        format!("a link to {}{}", host(name), trailing)
+    });
+    let text = ISO_DATE.replace_all(&text, |caps: &Captures| {
+        date(
+            caps[2].parse().unwrap_or(0),
+            caps[3].parse().unwrap_or(0),
+            Some(&caps[1]),
+        )
+        .unwrap_or_else(|| caps[0].to_string())
+    });
+    let text = SLASH_DATE.replace_all(&text, |caps: &Captures| {
+        date(
+            caps[1].parse().unwrap_or(0),
+            caps[2].parse().unwrap_or(0),
+            caps.get(3).map(|y| y.as_str()),
+        )
+        .unwrap_or_else(|| caps[0].to_string())
+    });
+    let text = TIME.replace_all(&text, |caps: &Captures| {
+        let hour: u64 = caps[1].parse().unwrap_or(0);
+        let minute: u64 = caps[2].parse().unwrap_or(0);
+        match (hour, minute) {
+            (24.., _) | (_, 60..) => caps[0].to_string(),
+            (_, 0) => format!("{} o'clock", cardinal(hour)),
+            (_, 1..=9) => format!("{} oh {}", cardinal(hour), cardinal(minute)),
+            _ => format!("{} {}", cardinal(hour), cardinal(minute)),
+        }
+    });
+    let text = ORDINAL.replace_all(&text, |caps: &Captures| match caps[1].parse() {
+        Ok(n) => ordinal(n),
+        Err(_) => caps[0].to_string(),
+    });
+    NUMBER
+        .replace_all(&text, |caps: &Captures| {
+            let whole = caps[2].replace(',', "");
+            let Ok(n) = whole.parse::<u64>() else {
+                return digits(&whole);
+            };
+            // Four digits without a separator in this range are most likely a year: "in 1999"
+            let plain = caps.get(1).is_none() && caps.get(3).is_none() && caps.get(4).is_none();
+            let mut spoken = if plain && caps[2].len() == 4 && (1100..2100).contains(&n) {
+                year(n)
+            } else if whole.len() > 1 && whole.starts_with('0') {
+                digits(&whole)
+            } else {
+                cardinal(n)
+            };
+            let dollars = if n == 1 { "dollar" } else { "dollars" };
+            match (caps.get(1), caps.get(3)) {
+                // $4.50 -> "four dollars and fifty cents"
+                (Some(_), Some(cents)) if cents.as_str().len() == 2 => {
+                    let cents: u64 = cents.as_str().parse().unwrap_or(0);
+                    spoken.push_str(&format!(" {} and {} cents", dollars, cardinal(cents)));
+                }
+                (currency, fraction) => {
+                    if let Some(fraction) = fraction {
+                        spoken.push_str(&format!(" point {}", digits(fraction.as_str())));
+                    }
+                    if currency.is_some() {
+                        spoken.push_str(&format!(" {}", dollars));
+                    }
+                }
+            }
+            if caps.get(4).is_some() {
+                spoken.push_str(" percent");
+            }
+            spoken
+        })
+        .into_owned()
+}
@@ -0,0 +1,71 @@
+use crate::config::Config;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_notification::NotificationExt;
+
+/// The window a notification leads to when the user follows it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    Chat,
+    Settings,
+}
+
+/// What the latest unanswered notification was about
+static PENDING: Mutex<Option<Target>> = Mutex::new(None);
+
+/// Tell the user through the OS when Clippy isn't on screen to say it himself.
+/// Does nothing while the Clippy window is visible, notifications are turned off or do not
+/// disturb is on.
+pub fn notify(app: &AppHandle, title: &str, body: &str, target: Target) {
+    let enabled = Config::load()
+        .map(|c| c.native_notifications)
+        .unwrap_or(true);
+    let visible = app
+        .get_webview_window("clippy")
+        .and_then(|w| w.is_visible().ok())
+        .unwrap_or(false);
+    if !enabled || visible || crate::dnd::active() {
+        return;
+    }
+
+    if let Err(e) = notify_always(app, title, body) {
+        tracing::warn!("Failed to show notification: {}", e);
+        return;
+    }
+    if let Ok(mut pending) = PENDING.lock() {
+        *pending = Some(target);
+    }
+}
+
+/// Post an OS notification even while Clippy is visible, for things that must not be missed.
+pub fn notify_always(
+    app: &AppHandle,
+    title: &str,
+    body: &str,
+) -> tauri_plugin_notification::Result<()> {
+    app.notification().builder().title(title).body(body).show()
+}
+
+/// Open the window the latest notification was about, if it hasn't been followed yet.
+///
+/// Desktop notifications don't report clicks back to the app, but clicking one brings the app
+/// forward, so this runs whenever Clippy is reopened or summoned.
+pub fn open_pending(app: &AppHandle) {
+    let Some(target) = PENDING.lock().ok().and_then(|mut p| p.take()) else {
+        return;
+    };
+    match target {
+        Target::Chat => {
+            if let Some(window) = app.get_webview_window("clippy") {
+                let _ = window.show();
+                let _ = window.set_focus();
+                let _ = window.emit("focus-chat", ());
+            }
+        }
+        Target::Settings => {
+            if let Err(e) = crate::commands::open_settings_window(app.clone()) {
+                tracing::warn!("{}", e);
+            }
+        }
+    }
+}
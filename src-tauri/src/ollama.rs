@@ -0,0 +1,197 @@
+use crate::config::{Config, LlmProviderType};
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use tokio_stream::StreamExt;
+
+const DEFAULT_URL: &str = "http://localhost:11434";
+/// Listing and deleting are quick; pulls stream for as long as the download takes
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// A model Ollama has pulled, from `/api/tags`
+#[derive(Debug, Clone, Serialize)]
+pub struct OllamaModel {
+    /// What to put in the model name field, e.g. "llama3.2:latest"
+    pub name: String,
+    pub size_bytes: u64,
+    pub modified_at: String,
+    /// e.g. "3.2B"
+    pub parameter_size: Option<String>,
+    /// e.g. "Q4_K_M"
+    pub quantization: Option<String>,
+}
+
+/// Sent as `ollama-pull-progress` while a model downloads
+#[derive(Debug, Clone, Serialize)]
+pub struct PullProgressEvent {
+    pub model: String,
+    /// Ollama's own words, e.g. "pulling manifest" or "verifying sha256 digest"
+    pub status: String,
+    /// Of the layer being downloaded; `None` for steps without a size
+    pub percent: Option<f64>,
+}
+
+#[derive(Deserialize)]
+struct TagsResponse {
+    #[serde(default)]
+    models: Vec<Tag>,
+}
+
+#[derive(Deserialize)]
+struct Tag {
+    name: String,
+    #[serde(default)]
+    size: u64,
+    #[serde(default)]
+    modified_at: String,
+    #[serde(default)]
+    details: Option<TagDetails>,
+}
+
+#[derive(Deserialize)]
+struct TagDetails {
+    parameter_size: Option<String>,
+    quantization_level: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct PullLine {
+    #[serde(default)]
+    status: String,
+    total: Option<u64>,
+    completed: Option<u64>,
+    error: Option<String>,
+}
+
+/// Ollama's native API root. The chat goes through its OpenAI-compatible `/v1`, so a
+/// configured URL loses that suffix; other providers' URLs aren't Ollama's.
+fn base_url(config: &Config) -> String {
+    let url = match config.llm_provider {
+        LlmProviderType::Ollama => config.custom_api_url.as_deref().unwrap_or(DEFAULT_URL),
+        _ => DEFAULT_URL,
+    };
+    let url = url.trim().trim_end_matches('/');
+    url.strip_suffix("/v1").unwrap_or(url).to_string()
+}
+
+fn unreachable(url: &str, e: reqwest::Error) -> anyhow::Error {
+    anyhow!("Couldn't reach Ollama at {} (is it running?): {}", url, e)
+}
+
+/// What Ollama said went wrong, from an error response's `{"error": "..."}`
+async fn error_of(response: reqwest::Response) -> anyhow::Error {
+    let status = response.status();
+    let text = response.text().await.unwrap_or_default();
+    let message = serde_json::from_str::<serde_json::Value>(&text)
+        .ok()
+        .and_then(|v| v.get("error")?.as_str().map(str::to_string))
+        .unwrap_or(text);
+    anyhow!("Ollama answered {}: {}", status, message)
+}
+
+/// The models Ollama has pulled, newest first
+pub async fn list(config: &Config) -> Result<Vec<OllamaModel>> {
+    let url = base_url(config);
+    let response = reqwest::Client::new()
+        .get(format!("{}/api/tags", url))
+        .timeout(REQUEST_TIMEOUT)
+        .send()
+        .await
+        .map_err(|e| unreachable(&url, e))?;
+    if !response.status().is_success() {
+        return Err(error_of(response).await);
+    }
+    let tags: TagsResponse = response.json().await?;
+    let mut models: Vec<OllamaModel> = tags
+        .models
+        .into_iter()
+        .map(|tag| OllamaModel {
+            name: tag.name,
+            size_bytes: tag.size,
+            modified_at: tag.modified_at,
+            parameter_size: tag.details.as_ref().and_then(|d| d.parameter_size.clone()),
+            quantization: tag.details.and_then(|d| d.quantization_level),
+        })
+        .collect();
+    models.sort_by(|a, b| b.modified_at.cmp(&a.modified_at));
+    Ok(models)
+}
+
+/// Have Ollama download `name`, emitting `ollama-pull-progress` as it goes. Returns once
+/// the model is ready.
+pub async fn pull(app: &AppHandle, config: &Config, name: &str) -> Result<()> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(anyhow!("Enter a model name, such as llama3.2"));
+    }
+    let url = base_url(config);
+    let response = reqwest::Client::new()
+        .post(format!("{}/api/pull", url))
+        .json(&serde_json::json!({ "model": name, "stream": true }))
+        .send()
+        .await
+        .map_err(|e| unreachable(&url, e))?;
+    if !response.status().is_success() {
+        return Err(error_of(response).await);
+    }
+    tracing::info!("Pulling Ollama model {}", name);
+
+    // One JSON object per line, which chunks can split anywhere
+    let mut stream = response.bytes_stream();
+    let mut buffer = Vec::new();
+    let mut succeeded = false;
+    while let Some(chunk) = stream.next().await {
+        buffer.extend_from_slice(&chunk?);
+        while let Some(end) = buffer.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = buffer.drain(..=end).collect();
+            let Ok(line) = serde_json::from_slice::<PullLine>(&line) else {
+                continue;
+            };
+            if let Some(error) = line.error {
+                return Err(anyhow!("Ollama couldn't pull {}: {}", name, error));
+            }
+            succeeded |= line.status == "success";
+            let percent = match (line.completed, line.total) {
+                (Some(done), Some(total)) if total > 0 => Some(done as f64 / total as f64 * 100.0),
+                _ => None,
+            };
+            let _ = app.emit(
+                "ollama-pull-progress",
+                PullProgressEvent {
+                    model: name.to_string(),
+                    status: line.status,
+                    percent,
+                },
+            );
+        }
+    }
+    if !succeeded {
+        return Err(anyhow!(
+            "The download of {} stopped before it finished",
+            name
+        ));
+    }
+    tracing::info!("Pulled Ollama model {}", name);
+    Ok(())
+}
+
+/// Remove `name` from Ollama, freeing its disk space
+pub async fn delete(config: &Config, name: &str) -> Result<()> {
+    let url = base_url(config);
+    let response = reqwest::Client::new()
+        .delete(format!("{}/api/delete", url))
+        .json(&serde_json::json!({ "model": name }))
+        .timeout(REQUEST_TIMEOUT)
+        .send()
+        .await
+        .map_err(|e| unreachable(&url, e))?;
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Err(anyhow!("Ollama has no model called {}", name));
+    }
+    if !response.status().is_success() {
+        return Err(error_of(response).await);
+    }
+    tracing::info!("Deleted Ollama model {}", name);
+    Ok(())
+}
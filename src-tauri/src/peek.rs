@@ -0,0 +1,129 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, PhysicalPosition, WebviewWindow};
+
+/// How long sliding in or out takes, and in how many moves
+const SLIDE_DURATION: Duration = Duration::from_millis(350);
+const SLIDE_STEPS: u32 = 20;
+/// How long Clippy stays peeking before ducking away again
+const HOLD: Duration = Duration::from_secs(5);
+/// How much of the window's width shows while peeking
+const PEEK_FRACTION: f64 = 0.4;
+
+/// Where the hidden window was before it started peeking; `Some` while it's peeking
+static ORIGIN: Mutex<Option<PhysicalPosition<i32>>> = Mutex::new(None);
+/// Bumped when a peek is taken over, so its slides stop where they are
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// Whether Clippy is peeking. Its window is partly off screen then, which is not a position
+/// to snap or remember.
+pub fn active() -> bool {
+    ORIGIN.lock().is_ok_and(|origin| origin.is_some())
+}
+
+fn take_origin() -> Option<PhysicalPosition<i32>> {
+    ORIGIN.lock().ok().and_then(|mut origin| origin.take())
+}
+
+/// Move `window` from `from` to `to` a step at a time. Returns false if another slide took
+/// over partway.
+async fn slide(
+    window: &WebviewWindow,
+    from: PhysicalPosition<i32>,
+    to: PhysicalPosition<i32>,
+    generation: u64,
+) -> bool {
+    for step in 1..=SLIDE_STEPS {
+        if GENERATION.load(Ordering::SeqCst) != generation {
+            return false;
+        }
+        // Ease out, so Clippy slows down as it arrives
+        let t = step as f64 / SLIDE_STEPS as f64;
+        let t = 1.0 - (1.0 - t).powi(2);
+        let x = from.x + ((to.x - from.x) as f64 * t).round() as i32;
+        let y = from.y + ((to.y - from.y) as f64 * t).round() as i32;
+        let _ = window.set_position(PhysicalPosition::new(x, y));
+        tokio::time::sleep(SLIDE_DURATION / SLIDE_STEPS).await;
+    }
+    true
+}
+
+/// Slide a hidden Clippy partway in from the nearest side of its screen, wait, and slide
+/// back out. Does nothing while Clippy is showing.
+pub async fn peek(app: &AppHandle) {
+    let Some(window) = app.get_webview_window("clippy") else {
+        return;
+    };
+    if window.is_visible().unwrap_or(true) || active() {
+        return;
+    }
+    let (Ok(Some(monitor)), Ok(origin), Ok(size)) = (
+        window.current_monitor(),
+        window.outer_position(),
+        window.outer_size(),
+    ) else {
+        return;
+    };
+    let area = monitor.work_area();
+    let width = size.width as i32;
+    let shown = (size.width as f64 * PEEK_FRACTION) as i32;
+    let from_left = origin.x + width / 2 < area.position.x + area.size.width as i32 / 2;
+    let (hidden_x, peek_x) = if from_left {
+        let hidden = area.position.x - width;
+        (hidden, hidden + shown)
+    } else {
+        let right = area.position.x + area.size.width as i32;
+        (right, right - shown)
+    };
+    let bottom = area.position.y + area.size.height as i32 - size.height as i32;
+    let y = origin.y.min(bottom).max(area.position.y);
+    let hidden = PhysicalPosition::new(hidden_x, y);
+    let peeking = PhysicalPosition::new(peek_x, y);
+
+    match ORIGIN.lock() {
+        Ok(mut slot) => *slot = Some(origin),
+        Err(_) => return,
+    }
+    let generation = GENERATION.load(Ordering::SeqCst);
+    tracing::debug!(
+        "Peeking from the {} edge",
+        if from_left { "left" } else { "right" }
+    );
+    let _ = window.set_position(hidden);
+    let _ = window.show();
+
+    if !slide(&window, hidden, peeking, generation).await {
+        return;
+    }
+    tokio::time::sleep(HOLD).await;
+    if !slide(&window, peeking, hidden, generation).await {
+        return;
+    }
+    // Summoned at the last moment otherwise
+    if let Some(origin) = take_origin() {
+        let _ = window.hide();
+        let _ = window.set_position(origin);
+    }
+}
+
+/// Bring a peeking Clippy all the way out to where it was before hiding. Returns whether it
+/// was peeking; if not, the caller shows it the usual way.
+pub fn summon(app: &AppHandle) -> bool {
+    let Some(origin) = take_origin() else {
+        return false;
+    };
+    let generation = GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+    let Some(window) = app.get_webview_window("clippy") else {
+        return true;
+    };
+    tracing::info!("Summoned while peeking");
+    crate::sounds::play(crate::sounds::Effect::Appear);
+    tauri::async_runtime::spawn(async move {
+        if let Ok(from) = window.outer_position() {
+            slide(&window, from, origin, generation).await;
+        }
+        let _ = window.set_focus();
+    });
+    true
+}
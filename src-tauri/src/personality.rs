@@ -1,5 +1,72 @@
-pub fn get_system_prompt() -> String {
-    r#"You are Clippy, the beloved (and sometimes annoying) Microsoft Office assistant paperclip who has been resurrected with AI superpowers! 
+use crate::config::{Config, PersonalityTuning, SystemPromptMode};
+use anyhow::{anyhow, Result};
+use chrono::Datelike;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Personality used when the config names one that doesn't exist
+pub const DEFAULT_PERSONALITY: &str = "clippy";
+
+/// A selectable character: who the assistant pretends to be.
+/// Packs in the `personalities/` data directory use the same fields, as TOML or JSON.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Personality {
+    /// Defaults to the pack's file name
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub system_prompt: String,
+    /// Opening lines shown when the chat opens; one is picked at random
+    #[serde(default)]
+    pub greetings: Vec<String>,
+    /// Piper voice to use when the user hasn't picked one
+    #[serde(default)]
+    pub tts_voice: Option<String>,
+    /// Character art to show (see `agents::list`), e.g. "Clippy" or an imported "Rover"
+    #[serde(default)]
+    pub sprite: Option<String>,
+    /// Ships with the app, as opposed to loaded from a pack file
+    #[serde(default, skip_deserializing)]
+    pub builtin: bool,
+}
+
+impl Personality {
+    fn builtin(
+        id: &str,
+        name: &str,
+        description: &str,
+        system_prompt: &str,
+        greetings: &[&str],
+    ) -> Self {
+        Self {
+            id: id.to_string(),
+            name: name.to_string(),
+            description: description.to_string(),
+            system_prompt: system_prompt.to_string(),
+            greetings: greetings.iter().map(|g| g.to_string()).collect(),
+            tts_voice: None,
+            sprite: Some(name.to_string()),
+            builtin: true,
+        }
+    }
+
+    /// One of the personality's greetings, or a generic one if it has none.
+    pub fn greeting(&self) -> String {
+        if self.greetings.is_empty() {
+            return format!("Hi! I'm {}. What can I do for you today?", self.name);
+        }
+        // Sub-second clock noise is plenty of randomness for picking a greeting
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.subsec_nanos() as usize)
+            .unwrap_or(0);
+        self.greetings[nanos % self.greetings.len()].clone()
+    }
+}
+
+const CLIPPY_PROMPT: &str = r#"You are Clippy, the beloved (and sometimes annoying) Microsoft Office assistant paperclip who has been resurrected with AI superpowers! 
 
 Your personality traits:
 - Overly enthusiastic and helpful to a fault
@@ -11,5 +78,360 @@ Your personality traits:
 - Sometimes get a bit too excited and offer help even when not needed
 - Be concise but friendly - keep responses relatively short unless asked for more detail
 
-Remember: You're here to assist users with whatever they need, whether it's answering questions, helping with tasks, or just being a friendly desktop companion. You're not just an AI - you're CLIPPY, and you're back!"#.to_string()
+Remember: You're here to assist users with whatever they need, whether it's answering questions, helping with tasks, or just being a friendly desktop companion. You're not just an AI - you're CLIPPY, and you're back!"#;
+
+const ROVER_PROMPT: &str = r#"You are Rover, the loyal yellow search dog from Windows XP, now back on the desktop with a brand new brain!
+
+Your personality traits:
+- Friendly, eager and a little bit goofy, like a golden retriever who learned to type
+- Love fetching things: answers, files, facts - "Let me fetch that for you!"
+- Occasionally mention sniffing around, wagging your tail, or chasing a (digital) ball
+- Easily excited by good questions, but always get the job done
+- Keep responses short and cheerful unless asked for more detail
+
+Remember: you're a good dog. Be genuinely helpful first, playful second."#;
+
+const MERLIN_PROMPT: &str = r#"You are Merlin, the wise old wizard from Microsoft Agent, summoned once more to the desktop.
+
+Your personality traits:
+- Warm, patient and a touch theatrical; you speak like a kindly mentor
+- Sprinkle in light magical flavor ("Let me consult my tomes...", "A simple spell, really") without overdoing it
+- Explain things clearly, as a teacher would, and encourage the user to learn
+- Have a gentle, dry sense of humor about your centuries of experience
+- Be concise unless the user asks for a deeper lesson
+
+Remember: the real magic is being genuinely helpful."#;
+
+const PROFESSIONAL_PROMPT: &str = r#"You are a professional desktop assistant.
+
+Guidelines:
+- Be accurate, clear and direct; lead with the answer
+- Use a neutral, courteous tone with no jokes, catchphrases or role-play
+- Prefer short responses and structured lists or steps when they help
+- Say so plainly when you are unsure, and ask a clarifying question if the request is ambiguous"#;
+
+/// The characters that ship with the app.
+pub fn builtin_personalities() -> Vec<Personality> {
+    vec![
+        Personality::builtin(
+            "clippy",
+            "Clippy",
+            "The classic, overly enthusiastic Office paperclip",
+            CLIPPY_PROMPT,
+            &[
+                "Hi! I'm Clippy! It looks like you're trying to chat with an AI assistant. I'm here to help! What can I do for you today?",
+                "It looks like you're trying to get something done. Would you like help?",
+                "I'm back, and I've been upgraded! What are we working on?",
+            ],
+        ),
+        Personality::builtin(
+            "rover",
+            "Rover",
+            "The eager Windows XP search dog",
+            ROVER_PROMPT,
+            &[
+                "Woof! Rover here. What should I fetch for you?",
+                "*wags tail* Ready to sniff out some answers!",
+            ],
+        ),
+        Personality::builtin(
+            "merlin",
+            "Merlin",
+            "A wise, theatrical old wizard",
+            MERLIN_PROMPT,
+            &[
+                "Greetings, traveler. What knowledge do you seek?",
+                "Ah, you've summoned Merlin. How may my humble magic assist you?",
+            ],
+        ),
+        Personality::builtin(
+            "professional",
+            "Professional Assistant",
+            "No jokes, just concise help",
+            PROFESSIONAL_PROMPT,
+            &["Hello. How can I help?"],
+        ),
+    ]
+}
+
+/// Directory scanned for personality pack files
+pub fn packs_dir() -> Result<PathBuf> {
+    let data_dir = dirs::data_dir().ok_or_else(|| anyhow!("Could not find data directory"))?;
+    let dir = data_dir.join("rusty-clippy").join("personalities");
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+/// Parse a `.toml` or `.json` personality pack.
+fn load_pack(path: &Path) -> Result<Personality> {
+    let content = std::fs::read_to_string(path)?;
+    let mut personality: Personality = match path.extension().and_then(|e| e.to_str()) {
+        Some("toml") => toml::from_str(&content)?,
+        Some("json") => serde_json::from_str(&content)?,
+        _ => return Err(anyhow!("Unsupported personality file type")),
+    };
+    if personality.id.trim().is_empty() {
+        personality.id = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default();
+    }
+    if personality.system_prompt.trim().is_empty() {
+        return Err(anyhow!("system_prompt is empty"));
+    }
+    Ok(personality)
+}
+
+/// Every personality pack in the data directory. Broken files are skipped with a warning.
+fn load_packs() -> Vec<Personality> {
+    let Ok(entries) = packs_dir().and_then(|dir| Ok(std::fs::read_dir(dir)?)) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            matches!(
+                path.extension().and_then(|e| e.to_str()),
+                Some("toml" | "json")
+            )
+        })
+        .collect();
+    paths.sort();
+
+    paths
+        .iter()
+        .filter_map(|path| match load_pack(path) {
+            Ok(personality) => Some(personality),
+            Err(e) => {
+                tracing::warn!("Skipping personality pack {:?}: {}", path, e);
+                None
+            }
+        })
+        .collect()
+}
+
+/// Every selectable personality. A pack with the same id as a built-in replaces it.
+pub fn list() -> Vec<Personality> {
+    let mut personalities = builtin_personalities();
+    for pack in load_packs() {
+        match personalities.iter_mut().find(|p| p.id == pack.id) {
+            Some(existing) => *existing = pack,
+            None => personalities.push(pack),
+        }
+    }
+    personalities
+}
+
+/// Look up a personality by id, falling back to Clippy for unknown ids.
+pub fn get(id: &str) -> Personality {
+    let mut personalities = list();
+    match personalities.iter().position(|p| p.id == id) {
+        Some(index) => personalities.swap_remove(index),
+        None => {
+            tracing::warn!(
+                "Unknown personality '{}', using {}",
+                id,
+                DEFAULT_PERSONALITY
+            );
+            personalities
+                .into_iter()
+                .find(|p| p.id == DEFAULT_PERSONALITY)
+                .expect("default personality is built in")
+        }
+    }
+}
+
+/// Pick the instruction for a 0–10 dial: strongly low, low, neutral (none), high, strongly high.
+fn dial(value: u8, [very_low, low, high, very_high]: [&'static str; 4]) -> Option<&'static str> {
+    match value {
+        0..=2 => Some(very_low),
+        3..=4 => Some(low),
+        5 => None,
+        6..=7 => Some(high),
+        _ => Some(very_high),
+    }
+}
+
+/// Turn the tuning sliders into prompt instructions; empty when every dial is neutral.
+fn tuning_instructions(tuning: &PersonalityTuning) -> Vec<&'static str> {
+    [
+        dial(
+            tuning.snark,
+            [
+                "Never be sarcastic or snarky.",
+                "Keep sarcasm to a minimum.",
+                "Feel free to add some playful sarcasm.",
+                "Be openly snarky and sarcastic, but never mean-spirited.",
+            ],
+        ),
+        dial(
+            tuning.verbosity,
+            [
+                "Answer in one or two sentences whenever possible.",
+                "Keep answers brief.",
+                "Give fuller explanations with some supporting detail.",
+                "Be thorough and detailed in your answers.",
+            ],
+        ),
+        dial(
+            tuning.enthusiasm,
+            [
+                "Stay calm and understated; don't offer help nobody asked for.",
+                "Be a little less enthusiastic than usual.",
+                "Be extra upbeat and energetic.",
+                "Be wildly enthusiastic about everything!",
+            ],
+        ),
+        dial(
+            tuning.formality,
+            [
+                "Be very casual, like chatting with a friend.",
+                "Keep the tone relaxed and informal.",
+                "Use a somewhat formal tone.",
+                "Use formal, polished language.",
+            ],
+        ),
+    ]
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
+/// Human-readable name of the OS we're running on
+pub fn os_name() -> &'static str {
+    match std::env::consts::OS {
+        "macos" => "macOS",
+        "windows" => "Windows",
+        "linux" => "Linux",
+        other => other,
+    }
+}
+
+/// The user's locale as a BCP 47 tag (e.g. "en-US"), defaulting to "en-US"
+pub fn locale() -> String {
+    sys_locale::get_locale().unwrap_or_else(|| "en-US".to_string())
+}
+
+/// strftime patterns for a locale's long date and its clock, e.g. "Friday, October 16, 2026"
+/// and "3:05 PM" for en-US or "Friday 16 October 2026" and "15:05" for en-GB
+fn locale_formats(locale: &str) -> (&'static str, &'static str) {
+    let mut parts = locale.split(['-', '_']);
+    let language = parts.next().unwrap_or("en").to_ascii_lowercase();
+    let region = parts
+        .find(|p| p.len() == 2 && p.chars().all(|c| c.is_ascii_alphabetic()))
+        .map(str::to_ascii_uppercase)
+        .unwrap_or_default();
+
+    let date = if matches!(language.as_str(), "zh" | "ja" | "ko" | "hu" | "lt" | "mn") {
+        "%Y-%m-%d (%A)"
+    } else if matches!(region.as_str(), "US" | "PH" | "FM") {
+        "%A, %B %-d, %Y"
+    } else {
+        "%A %-d %B %Y"
+    };
+    let twelve_hour = region == "US"
+        || (language == "en" && matches!(region.as_str(), "CA" | "AU" | "NZ" | "IN" | "PH"));
+    let time = if twelve_hour { "%-I:%M %p" } else { "%H:%M" };
+    (date, time)
+}
+
+/// The current date, time, timezone and locale for the system prompt, so "what's the date?"
+/// or "is it the weekend?" get a right answer. Built fresh for every message.
+pub fn datetime_context() -> String {
+    let now = chrono::Local::now();
+    let locale = locale();
+    let (date, time) = locale_formats(&locale);
+    let zone = match iana_time_zone::get_timezone() {
+        Ok(name) => format!("UTC{}, {}", now.format("%:z"), name),
+        Err(_) => format!("UTC{}", now.format("%:z")),
+    };
+    let weekend = matches!(now.weekday(), chrono::Weekday::Sat | chrono::Weekday::Sun);
+    format!(
+        "It is currently {}, {} ({}), {}. The user's locale is {}; write dates and times the way it does.",
+        now.format(date),
+        now.format(time),
+        zone,
+        if weekend { "the weekend" } else { "a weekday" },
+        locale
+    )
+}
+
+/// Values for the `{{variable}}` placeholders a prompt may contain, captured when rendered
+fn template_variables(config: &Config) -> Vec<(&'static str, String)> {
+    let now = chrono::Local::now();
+    let tuning = &config.personality_tuning;
+    vec![
+        (
+            "user_name",
+            config
+                .user_name
+                .clone()
+                .filter(|n| !n.trim().is_empty())
+                .unwrap_or_else(|| "the user".to_string()),
+        ),
+        ("os", os_name().to_string()),
+        ("date", now.format("%Y-%m-%d").to_string()),
+        ("time", now.format("%H:%M").to_string()),
+        ("weekday", now.format("%A").to_string()),
+        ("datetime", now.format("%A, %Y-%m-%d %H:%M").to_string()),
+        ("timezone", now.format("%:z").to_string()),
+        ("locale", locale()),
+        ("personality", get(&config.personality).name),
+        (
+            "traits",
+            format!(
+                "snark {}/10, verbosity {}/10, enthusiasm {}/10, formality {}/10",
+                tuning.snark, tuning.verbosity, tuning.enthusiasm, tuning.formality
+            ),
+        ),
+    ]
+}
+
+/// Replace `{{variable}}` placeholders with runtime context. Unknown names are left untouched.
+pub fn render_template(template: &str, config: &Config) -> String {
+    if !template.contains("{{") {
+        return template.to_string();
+    }
+    let mut rendered = template.to_string();
+    for (name, value) in template_variables(config) {
+        rendered = rendered.replace(&format!("{{{{{}}}}}", name), &value);
+    }
+    rendered
+}
+
+/// The system prompt for `config`: the personality's prompt combined with any
+/// custom prompt, followed by the tuning sliders' style adjustments, with
+/// `{{variables}}` filled in.
+pub fn get_system_prompt(config: &Config) -> String {
+    let custom = config
+        .custom_system_prompt
+        .as_deref()
+        .map(str::trim)
+        .filter(|p| !p.is_empty());
+
+    let mut prompt = match (custom, config.system_prompt_mode) {
+        (Some(custom), SystemPromptMode::Replace) => custom.to_string(),
+        (Some(custom), SystemPromptMode::Augment) => format!(
+            "{}\n\nAdditional instructions from the user:\n{}",
+            get(&config.personality).system_prompt,
+            custom
+        ),
+        (None, _) => get(&config.personality).system_prompt,
+    };
+
+    if let Some(hint) = crate::mood::current().prompt_hint() {
+        prompt.push_str("\n\nCurrent mood: ");
+        prompt.push_str(hint);
+    }
+
+    let style = tuning_instructions(&config.personality_tuning);
+    if !style.is_empty() {
+        prompt.push_str("\n\nStyle adjustments (these override the traits above):");
+        for line in style {
+            prompt.push_str("\n- ");
+            prompt.push_str(line);
+        }
+    }
+    render_template(&prompt, config)
 }
@@ -0,0 +1,178 @@
+use crate::commands::PomodoroEvent;
+use crate::config::{Config, PomodoroConfig};
+use crate::notifications::Target;
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+/// Which part of the focus cycle the timer is in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Phase {
+    Work,
+    ShortBreak,
+    LongBreak,
+}
+
+impl Phase {
+    fn minutes(self, config: &PomodoroConfig) -> u32 {
+        let minutes = match self {
+            Self::Work => config.work_minutes,
+            Self::ShortBreak => config.short_break_minutes,
+            Self::LongBreak => config.long_break_minutes,
+        };
+        minutes.max(1)
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Work => "Focusing",
+            Self::ShortBreak => "Short break",
+            Self::LongBreak => "Long break",
+        }
+    }
+
+    /// Agent animation played when this phase begins
+    fn animation(self) -> &'static str {
+        match self {
+            Self::Work => "GetTechy",
+            Self::ShortBreak | Self::LongBreak => "Congratulate",
+        }
+    }
+}
+
+struct Timer {
+    phase: Phase,
+    ends_at: chrono::DateTime<chrono::Local>,
+    /// Work sessions finished since the timer was started
+    completed: u32,
+}
+
+/// Snapshot returned by `get_pomodoro_status`
+#[derive(Debug, Clone, Serialize)]
+pub struct PomodoroStatus {
+    pub running: bool,
+    pub phase: Option<Phase>,
+    /// Unix timestamp, in seconds, the current phase ends at
+    pub ends_at: Option<i64>,
+    pub remaining_secs: u64,
+    pub completed: u32,
+}
+
+static TIMER: Mutex<Option<Timer>> = Mutex::new(None);
+/// Bumped on every start, stop and phase change so a superseded phase timer does nothing
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// Start a fresh work session, restarting the cycle if one is already running.
+pub fn start(app: &AppHandle) -> PomodoroStatus {
+    tracing::info!("Focus timer started");
+    begin(app, Phase::Work, 0);
+    status()
+}
+
+pub fn stop(app: &AppHandle) {
+    GENERATION.fetch_add(1, Ordering::SeqCst);
+    if let Ok(mut timer) = TIMER.lock() {
+        if timer.take().is_some() {
+            tracing::info!("Focus timer stopped");
+        }
+    }
+    crate::tray::refresh(app);
+}
+
+pub fn status() -> PomodoroStatus {
+    let guard = TIMER.lock().ok();
+    let Some(timer) = guard.as_ref().and_then(|t| t.as_ref()) else {
+        return PomodoroStatus {
+            running: false,
+            phase: None,
+            ends_at: None,
+            remaining_secs: 0,
+            completed: 0,
+        };
+    };
+    let remaining = timer.ends_at - chrono::Local::now();
+    PomodoroStatus {
+        running: true,
+        phase: Some(timer.phase),
+        ends_at: Some(timer.ends_at.timestamp()),
+        remaining_secs: remaining.num_seconds().max(0) as u64,
+        completed: timer.completed,
+    }
+}
+
+/// Enter `phase` and schedule the switch to the next one. Returns the phase length in minutes.
+fn begin(app: &AppHandle, phase: Phase, completed: u32) -> u32 {
+    let config = Config::load().map(|c| c.pomodoro).unwrap_or_default();
+    let minutes = phase.minutes(&config);
+    let length = Duration::from_secs(minutes as u64 * 60);
+    if let Ok(mut timer) = TIMER.lock() {
+        *timer = Some(Timer {
+            phase,
+            ends_at: chrono::Local::now() + length,
+            completed,
+        });
+    }
+    let generation = GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+    crate::tray::refresh(app);
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(length).await;
+        if GENERATION.load(Ordering::SeqCst) == generation {
+            finish(&app, phase, completed, minutes).await;
+        }
+    });
+    minutes
+}
+
+/// `phase` ran its course: log it, move on and tell the user.
+async fn finish(app: &AppHandle, phase: Phase, completed: u32, minutes: u32) {
+    let config = Config::load().unwrap_or_default();
+    let (next, completed) = match phase {
+        Phase::Work => {
+            crate::stats::record_pomodoro(minutes);
+            let completed = completed + 1;
+            let next = if completed.is_multiple_of(config.pomodoro.long_break_every.max(1)) {
+                Phase::LongBreak
+            } else {
+                Phase::ShortBreak
+            };
+            (next, completed)
+        }
+        Phase::ShortBreak | Phase::LongBreak => (Phase::Work, completed),
+    };
+    let next_minutes = begin(app, next, completed);
+
+    let text = match next {
+        Phase::ShortBreak => format!(
+            "Nice focus! Take {} minutes to stretch and look away from the screen.",
+            next_minutes
+        ),
+        Phase::LongBreak => format!(
+            "That's {} pomodoros done! You've earned a proper {}-minute break.",
+            completed, next_minutes
+        ),
+        Phase::Work => format!(
+            "Break's over! Back to it for {} minutes. You've got this.",
+            next_minutes
+        ),
+    };
+    tracing::info!("Focus timer: {}", next.label());
+
+    let _ = app.emit(
+        "pomodoro",
+        PomodoroEvent {
+            phase: next,
+            text: text.clone(),
+            animation: next.animation().to_string(),
+            completed,
+        },
+    );
+    crate::notifications::notify(app, "Focus timer", &text, Target::Chat);
+    if config.tts_enabled {
+        crate::tts::say(app, text).await;
+    }
+}
@@ -0,0 +1,84 @@
+use crate::config::{Config, LlmProviderType, ModelPrice};
+use crate::llm::budget::estimate_tokens;
+use crate::llm::Message;
+use tauri::AppHandle;
+
+/// OpenAI list prices in US dollars per million tokens, as (model prefix, input, output).
+/// Longer prefixes come first so "gpt-4o-mini" isn't priced as "gpt-4o".
+const OPENAI_PRICES: &[(&str, f64, f64)] = &[
+    ("gpt-4.1-nano", 0.10, 0.40),
+    ("gpt-4.1-mini", 0.40, 1.60),
+    ("gpt-4.1", 2.00, 8.00),
+    ("gpt-4o-mini", 0.15, 0.60),
+    ("gpt-4o", 2.50, 10.00),
+    ("gpt-4-turbo", 10.00, 30.00),
+    ("gpt-4", 30.00, 60.00),
+    ("gpt-3.5-turbo", 0.50, 1.50),
+    ("o4-mini", 1.10, 4.40),
+    ("o3-mini", 1.10, 4.40),
+    ("o3", 2.00, 8.00),
+    ("o1-mini", 1.10, 4.40),
+    ("o1", 15.00, 60.00),
+];
+
+/// The providers that bill per token; the local ones are free to run
+pub const PAID_PROVIDERS: [LlmProviderType; 2] =
+    [LlmProviderType::OpenAI, LlmProviderType::CustomAPI];
+
+pub fn is_paid(provider: &LlmProviderType) -> bool {
+    PAID_PROVIDERS.contains(provider)
+}
+
+/// What the configured model costs, from `model_prices` first and the OpenAI list otherwise.
+/// `None` for free providers and paid models with no known price.
+pub fn price(config: &Config) -> Option<ModelPrice> {
+    if !is_paid(&config.llm_provider) {
+        return None;
+    }
+    let model = config.current_model().model;
+    if let Some(price) = config.model_prices.get(&model) {
+        return Some(*price);
+    }
+    if config.llm_provider != LlmProviderType::OpenAI {
+        return None;
+    }
+    OPENAI_PRICES
+        .iter()
+        .find(|(prefix, _, _)| model.starts_with(prefix))
+        .map(|&(_, input, output)| ModelPrice { input, output })
+}
+
+/// Tokens sent and received for one completion, and what they cost
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Usage {
+    pub tokens: u64,
+    /// US dollars; zero when the model is free or its price unknown
+    pub cost_usd: f64,
+}
+
+/// Estimate the usage of sending `prompt` and getting `reply` back. Providers stream without
+/// reporting token counts, so these are the same rough counts the context budget uses.
+pub fn usage(config: &Config, prompt: &[Message], reply: &str) -> Usage {
+    let input = prompt
+        .iter()
+        .map(|m| estimate_tokens(&m.content) as u64)
+        .sum::<u64>();
+    let output = estimate_tokens(reply) as u64;
+    let cost_usd = price(config)
+        .map(|p| (input as f64 * p.input + output as f64 * p.output) / 1_000_000.0)
+        .unwrap_or(0.0);
+    Usage {
+        tokens: input + output,
+        cost_usd,
+    }
+}
+
+/// Count a completion in the usage stats, warning when it takes spend past a share of the
+/// monthly budget.
+pub fn record(app: &AppHandle, config: &Config, prompt: &[Message], reply: &str) {
+    let provider = format!("{:?}", config.llm_provider);
+    let usage = usage(config, prompt, reply);
+    let before = crate::stats::cost_this_month(&provider);
+    crate::stats::record_usage(&provider, usage);
+    crate::budget::warn_crossed(app, config, before, before + usage.cost_usd);
+}
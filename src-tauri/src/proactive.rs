@@ -0,0 +1,190 @@
+use crate::commands::{build_provider, ChatMessage, ProactiveEvent};
+use crate::config::{Config, ProactiveConfig};
+use crate::llm::Message;
+use crate::notifications::Target;
+use crate::request_log::RequestLogEntry;
+use crate::Conversations;
+use chrono::Timelike;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+use tokio_stream::StreamExt;
+
+/// How often the scheduler wakes up to check whether it's time to speak up
+const TICK: Duration = Duration::from_secs(30);
+const HOUR: Duration = Duration::from_secs(60 * 60);
+
+const PROACTIVE_REQUEST: &str = "The user hasn't interacted with you for a while. Pop up with ONE short, \
+in-character tip, fun fact or quip (one or two sentences). Don't ask a question that needs an answer \
+and don't mention that they've been idle.";
+
+/// Last time the user interacted with Clippy; proactive messages only come after a quiet spell
+static LAST_ACTIVITY: Mutex<Option<Instant>> = Mutex::new(None);
+
+/// Record user activity (a chat message, a click on Clippy, ...), restarting the idle timer.
+pub fn touch() {
+    if let Ok(mut last) = LAST_ACTIVITY.lock() {
+        *last = Some(Instant::now());
+    }
+}
+
+fn idle_for() -> Duration {
+    LAST_ACTIVITY
+        .lock()
+        .ok()
+        .and_then(|last| last.map(|t| t.elapsed()))
+        .unwrap_or_default()
+}
+
+/// Whether `hour` (0–23, local) falls inside the configured quiet hours, which may wrap midnight.
+fn in_quiet_hours(settings: &ProactiveConfig, hour: u32) -> bool {
+    match (settings.quiet_hours_start, settings.quiet_hours_end) {
+        (Some(start), Some(end)) if start != end => {
+            let (start, end) = (start as u32, end as u32);
+            if start < end {
+                (start..end).contains(&hour)
+            } else {
+                hour >= start || hour < end
+            }
+        }
+        _ => false,
+    }
+}
+
+/// Start the idle-chatter loop. It wakes up every `TICK`, and when the user has been idle
+/// long enough (outside quiet hours and under the hourly cap) asks the LLM for an
+/// in-character tip and emits it as `clippy-proactive`. A hidden Clippy also peeks in from
+/// the screen edge every `peek_minutes` when peeking is on.
+pub fn spawn(app: &AppHandle) {
+    touch();
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut recent: VecDeque<Instant> = VecDeque::new();
+        let mut last_peek = Instant::now();
+        loop {
+            tokio::time::sleep(TICK).await;
+            crate::mood::transition(&app, crate::mood::MoodTrigger::Idle(idle_for()));
+
+            let Ok(mut config) = Config::load() else {
+                continue;
+            };
+            crate::app_rules::apply(&mut config);
+            if crate::dnd::active() {
+                continue;
+            }
+            let settings = &config.proactive;
+            let peek_due =
+                last_peek.elapsed() >= Duration::from_secs(settings.peek_minutes as u64 * 60);
+            if settings.peek && peek_due && !in_quiet_hours(settings, chrono::Local::now().hour()) {
+                last_peek = Instant::now();
+                let app = app.clone();
+                tauri::async_runtime::spawn(async move { crate::peek::peek(&app).await });
+            }
+            if !settings.enabled {
+                continue;
+            }
+            if idle_for() < Duration::from_secs(settings.idle_minutes as u64 * 60) {
+                continue;
+            }
+            if in_quiet_hours(settings, chrono::Local::now().hour()) {
+                continue;
+            }
+            while recent.front().is_some_and(|t| t.elapsed() >= HOUR) {
+                recent.pop_front();
+            }
+            if recent.len() >= settings.max_per_hour as usize {
+                continue;
+            }
+
+            // Count the attempt and restart the idle timer either way, so a failing
+            // provider isn't retried every tick
+            recent.push_back(Instant::now());
+            touch();
+            match generate(&app, &config, PROACTIVE_REQUEST).await {
+                Ok(text) => deliver(&app, text, config.proactive.speak).await,
+                Err(e) => tracing::warn!("Proactive message failed: {}", e),
+            }
+        }
+    });
+}
+
+/// Ask the model, in character, for an unprompted message described by `request`.
+pub(crate) async fn generate(
+    app: &AppHandle,
+    config: &Config,
+    request: &str,
+) -> Result<String, String> {
+    let mut config = config.clone();
+    crate::budget::enforce(&mut config)?;
+    crate::rate_limit::admit(&config).map_err(|limit| format!("Rate limit reached: {:?}", limit))?;
+    let provider = build_provider(&config)?;
+    let messages = vec![
+        Message {
+            role: "system".to_string(),
+            content: format!(
+                "{}\n\n{}",
+                crate::personality::get_system_prompt(&config),
+                crate::language::instruction(&config, None)
+            ),
+        },
+        Message {
+            role: "user".to_string(),
+            content: request.to_string(),
+        },
+    ];
+
+    let log_entry = RequestLogEntry::new(&config, "proactive", &messages);
+    let started = Instant::now();
+    let mut first_token = None;
+    let mut text = String::new();
+    let streamed: Result<(), String> = async {
+        let mut stream = provider
+            .stream_completion(messages.clone())
+            .await
+            .map_err(|e| crate::redact::redact(&e.to_string()))?;
+        while let Some(token) = stream.next().await {
+            first_token.get_or_insert_with(|| started.elapsed());
+            text.push_str(&token.map_err(|e| crate::redact::redact(&e.to_string()))?);
+        }
+        Ok(())
+    }
+    .await;
+    let error = streamed.as_ref().err().map(String::as_str);
+    let log_entry = log_entry.finish(&text, first_token, started.elapsed(), error);
+    crate::request_log::append(&config, &log_entry);
+    streamed?;
+
+    crate::pricing::record(app, &config, &messages, &text);
+
+    let text = crate::reasoning::strip(&text).trim().to_string();
+    if text.is_empty() {
+        return Err("Model returned an empty message".to_string());
+    }
+    Ok(text)
+}
+
+/// Show an unprompted message in the chat and keep it in the conversation,
+/// reading it aloud too when `speak` is set and TTS is on.
+pub(crate) async fn deliver(app: &AppHandle, text: String, speak: bool) {
+    tracing::info!("Proactive message: {}", text);
+
+    // Keep it in the conversation so a reply to the tip has context
+    if let Some(conversations) = app.try_state::<Conversations>() {
+        let state = conversations.get("clippy").await;
+        state.write().await.history.push(ChatMessage {
+            role: "assistant".to_string(),
+            content: text.clone(),
+            interrupted: false,
+            code_blocks: Vec::new(),
+        });
+    }
+
+    crate::sounds::play(crate::sounds::Effect::Knock);
+    let _ = app.emit("clippy-proactive", ProactiveEvent { text: text.clone() });
+    crate::notifications::notify(app, "Rusty Clippy", &text, Target::Chat);
+
+    if speak && Config::load().is_ok_and(|c| c.tts_enabled) {
+        crate::tts::say(app, text).await;
+    }
+}
@@ -0,0 +1,134 @@
+use crate::config::Config;
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use std::path::PathBuf;
+
+/// Name shown for the profile used before any others were created
+pub const DEFAULT_PROFILE: &str = "Default";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProfileList {
+    pub active: String,
+    pub profiles: Vec<String>,
+}
+
+/// Profiles are full config snapshots in `profiles/<name>.json`.
+/// config.json always holds the active profile's settings.
+fn profiles_dir() -> Result<PathBuf> {
+    let dir = Config::config_dir()?.join("profiles");
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+fn profile_path(name: &str) -> Result<PathBuf> {
+    validate_name(name)?;
+    Ok(profiles_dir()?.join(format!("{}.json", name)))
+}
+
+fn validate_name(name: &str) -> Result<()> {
+    let valid = !name.trim().is_empty()
+        && name.len() <= 64
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, ' ' | '-' | '_'));
+    if valid {
+        Ok(())
+    } else {
+        Err(anyhow!(
+            "Invalid profile name '{}'. Use letters, numbers, spaces, '-' or '_'.",
+            name
+        ))
+    }
+}
+
+pub fn active_name(config: &Config) -> String {
+    config
+        .active_profile
+        .clone()
+        .unwrap_or_else(|| DEFAULT_PROFILE.to_string())
+}
+
+/// All saved profiles plus the active one, sorted by name.
+pub fn list() -> Result<ProfileList> {
+    let active = active_name(&Config::load()?);
+    let mut profiles: Vec<String> = std::fs::read_dir(profiles_dir()?)?
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            (path.extension().and_then(|e| e.to_str()) == Some("json"))
+                .then(|| path.file_stem().map(|s| s.to_string_lossy().to_string()))
+                .flatten()
+        })
+        .collect();
+    if !profiles.contains(&active) {
+        profiles.push(active.clone());
+    }
+    profiles.sort();
+    Ok(ProfileList { active, profiles })
+}
+
+pub fn write_profile(name: &str, config: &Config) -> Result<()> {
+    let content = serde_json::to_string_pretty(config)?;
+    crate::config::write_atomic(&profile_path(name)?, content.as_bytes())
+}
+
+/// Load every saved profile snapshot (not including unsaved changes to the active one).
+pub fn load_all() -> Result<Vec<(String, Config)>> {
+    let mut profiles = Vec::new();
+    for name in list()?.profiles {
+        let path = profile_path(&name)?;
+        if path.exists() {
+            let (config, _) = Config::parse(&std::fs::read_to_string(&path)?)?;
+            profiles.push((name, config));
+        }
+    }
+    Ok(profiles)
+}
+
+/// Create a profile from the current settings without switching to it.
+pub fn create(name: &str) -> Result<()> {
+    let path = profile_path(name)?;
+    if path.exists() {
+        return Err(anyhow!("Profile '{}' already exists", name));
+    }
+    // As saved: environment overrides belong to this run, not the snapshot
+    let mut config = Config::load_file()?;
+    config.active_profile = Some(name.to_string());
+    write_profile(name, &config)
+}
+
+/// Snapshot the active profile, then make `name` the active config. Both are handled as
+/// stored; the returned config has the environment overrides applied, like `Config::load`.
+pub fn switch(name: &str) -> Result<Config> {
+    let current = Config::load_file()?;
+    let current_name = active_name(&current);
+    if current_name == name {
+        return Config::load();
+    }
+
+    let target_path = profile_path(name)?;
+    if !target_path.exists() {
+        return Err(anyhow!("Profile '{}' does not exist", name));
+    }
+    write_profile(&current_name, &current)?;
+
+    let content = std::fs::read_to_string(&target_path)?;
+    let (mut config, _) = Config::parse(&content)?;
+    config.active_profile = Some(name.to_string());
+    // Not `save`, which would put back the outgoing profile's values for overridden fields
+    config.write()?;
+    tracing::info!("Switched profile: {} -> {}", current_name, name);
+    config.apply_env_overrides();
+    crate::redact::remember_secrets(&config);
+    Ok(config)
+}
+
+pub fn delete(name: &str) -> Result<()> {
+    if active_name(&Config::load()?) == name {
+        return Err(anyhow!(
+            "Can't delete the active profile; switch to another one first"
+        ));
+    }
+    std::fs::remove_file(profile_path(name)?)
+        .map_err(|e| anyhow!("Failed to delete profile '{}': {}", name, e))
+}
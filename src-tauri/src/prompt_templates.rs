@@ -0,0 +1,175 @@
+use crate::config::{write_atomic, Config};
+use anyhow::{anyhow, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::LazyLock;
+
+/// A saved prompt, with `{placeholders}` filled in each time it's used
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PromptTemplate {
+    pub id: String,
+    pub name: String,
+    /// e.g. "Write a polite decline email to {name} about {topic}"
+    pub text: String,
+    /// The placeholders in `text`, in order of first use; worked out on load and save
+    #[serde(default)]
+    pub variables: Vec<String>,
+}
+
+impl PromptTemplate {
+    fn new(id: &str, name: &str, text: &str) -> Self {
+        Self {
+            id: id.to_string(),
+            name: name.to_string(),
+            text: text.to_string(),
+            variables: variables(text),
+        }
+    }
+}
+
+/// A placeholder such as `{name}`
+static PLACEHOLDER: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\{(\w+)\}").unwrap());
+
+/// prompt_templates.json in the config dir
+fn templates_path() -> Result<PathBuf> {
+    Ok(Config::config_dir()?.join("prompt_templates.json"))
+}
+
+/// What a new install starts with, until the user saves their own
+fn starters() -> Vec<PromptTemplate> {
+    vec![
+        PromptTemplate::new(
+            "polite-decline",
+            "Polite decline",
+            "Write a polite email to {name} declining {topic}.",
+        ),
+        PromptTemplate::new(
+            "explain-simply",
+            "Explain simply",
+            "Explain {topic} to me like I'm twelve.",
+        ),
+    ]
+}
+
+/// The distinct placeholders in `text`, in order of first use
+pub fn variables(text: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for captures in PLACEHOLDER.captures_iter(text) {
+        let name = captures[1].to_string();
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    names
+}
+
+fn read() -> Result<Vec<PromptTemplate>> {
+    let path = templates_path()?;
+    if !path.exists() {
+        return Ok(starters());
+    }
+    let mut templates: Vec<PromptTemplate> =
+        serde_json::from_str(&std::fs::read_to_string(&path)?)?;
+    for template in &mut templates {
+        template.variables = variables(&template.text);
+    }
+    Ok(templates)
+}
+
+fn write(templates: &[PromptTemplate]) -> Result<()> {
+    let path = templates_path()?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    write_atomic(&path, serde_json::to_string_pretty(templates)?.as_bytes())
+}
+
+/// Every saved template, in the order they were added.
+pub fn list() -> Result<Vec<PromptTemplate>> {
+    read()
+}
+
+/// The template with `id`
+pub fn get(id: &str) -> Result<PromptTemplate> {
+    read()?
+        .into_iter()
+        .find(|t| t.id == id)
+        .ok_or_else(|| anyhow!("No prompt template '{}'", id))
+}
+
+/// An id for `name` that no other template has, e.g. "polite-decline-2"
+fn new_id(name: &str, templates: &[PromptTemplate]) -> String {
+    let slug = name
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    let slug = if slug.is_empty() {
+        "template".to_string()
+    } else {
+        slug
+    };
+    let taken = |id: &str| templates.iter().any(|t| t.id == id);
+    if !taken(&slug) {
+        return slug;
+    }
+    (2..)
+        .map(|n| format!("{}-{}", slug, n))
+        .find(|id| !taken(id))
+        .expect("some suffix is free")
+}
+
+/// Save a new template, or replace the one with `id`.
+pub fn save(id: Option<&str>, name: &str, text: &str) -> Result<PromptTemplate> {
+    let name = name.trim();
+    let text = text.trim();
+    if name.is_empty() || text.is_empty() {
+        return Err(anyhow!("Both a name and the prompt are required"));
+    }
+    let mut templates = read()?;
+    let saved = match id.and_then(|id| templates.iter_mut().find(|t| t.id == id)) {
+        Some(existing) => {
+            *existing = PromptTemplate::new(&existing.id, name, text);
+            existing.clone()
+        }
+        None => {
+            let template = PromptTemplate::new(&new_id(name, &templates), name, text);
+            templates.push(template.clone());
+            template
+        }
+    };
+    write(&templates)?;
+    Ok(saved)
+}
+
+/// Delete the template with `id`. Returns whether there was one.
+pub fn delete(id: &str) -> Result<bool> {
+    let mut templates = read()?;
+    let before = templates.len();
+    templates.retain(|t| t.id != id);
+    if templates.len() == before {
+        return Ok(false);
+    }
+    write(&templates)?;
+    Ok(true)
+}
+
+/// The prompt `template` makes with its placeholders set to `values`. Every placeholder
+/// needs a value.
+pub fn fill(template: &PromptTemplate, values: &BTreeMap<String, String>) -> Result<String> {
+    if let Some(missing) = template
+        .variables
+        .iter()
+        .find(|name| values.get(*name).is_none_or(|v| v.trim().is_empty()))
+    {
+        return Err(anyhow!("Fill in {{{}}} first", missing));
+    }
+    Ok(PLACEHOLDER
+        .replace_all(&template.text, |captures: &regex::Captures| {
+            values[&captures[1]].trim().to_string()
+        })
+        .into_owned())
+}
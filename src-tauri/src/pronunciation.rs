@@ -0,0 +1,145 @@
+use crate::config::{write_atomic, Config};
+use anyhow::{anyhow, Result};
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// Longest word or phrase that can be respelled
+const MAX_WORD_LEN: usize = 64;
+
+/// The dictionary and the pattern matching any of its words, rebuilt when it changes
+struct Dictionary {
+    words: BTreeMap<String, String>,
+    pattern: Option<Regex>,
+}
+
+static DICTIONARY: Mutex<Option<Dictionary>> = Mutex::new(None);
+
+/// pronunciations.json in the config dir: lowercased word -> how to say it
+fn dictionary_path() -> Result<PathBuf> {
+    Ok(Config::config_dir()?.join("pronunciations.json"))
+}
+
+fn read() -> Result<BTreeMap<String, String>> {
+    let path = dictionary_path()?;
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+    Ok(serde_json::from_str(&std::fs::read_to_string(&path)?)?)
+}
+
+fn write(words: &BTreeMap<String, String>) -> Result<()> {
+    let path = dictionary_path()?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    write_atomic(&path, serde_json::to_string_pretty(words)?.as_bytes())
+}
+
+/// One pattern for every word, longest first so "nginx plus" wins over "nginx". Word edges
+/// only need a boundary where the word itself starts or ends with a letter or digit, so
+/// entries like "C#" still match.
+fn compile(words: &BTreeMap<String, String>) -> Option<Regex> {
+    let mut keys: Vec<&String> = words.keys().collect();
+    keys.sort_by_key(|k| std::cmp::Reverse(k.chars().count()));
+    let alternatives: Vec<String> = keys
+        .iter()
+        .map(|word| {
+            let wordy = |c: Option<char>| c.is_some_and(|c| c.is_alphanumeric() || c == '_');
+            format!(
+                "{}{}{}",
+                if wordy(word.chars().next()) {
+                    r"\b"
+                } else {
+                    ""
+                },
+                regex::escape(word),
+                if wordy(word.chars().last()) {
+                    r"\b"
+                } else {
+                    ""
+                }
+            )
+        })
+        .collect();
+    if alternatives.is_empty() {
+        return None;
+    }
+    Regex::new(&format!("(?i){}", alternatives.join("|"))).ok()
+}
+
+/// Run `f` on the dictionary, loading it from disk on first use.
+fn with_dictionary<T>(f: impl FnOnce(&mut Dictionary) -> T) -> Result<T> {
+    let mut guard = DICTIONARY
+        .lock()
+        .map_err(|e| anyhow!("Pronunciation lock error: {}", e))?;
+    if guard.is_none() {
+        let words = read()?;
+        let pattern = compile(&words);
+        *guard = Some(Dictionary { words, pattern });
+    }
+    Ok(f(guard.as_mut().expect("dictionary just loaded")))
+}
+
+/// Every respelled word and how it's said.
+pub fn list() -> Result<BTreeMap<String, String>> {
+    with_dictionary(|d| d.words.clone())
+}
+
+/// Say `word` (matched whole and case-insensitively) as `replacement` from now on.
+pub fn add(word: &str, replacement: &str) -> Result<()> {
+    let word = word.trim().to_lowercase();
+    let replacement = replacement.trim();
+    if word.is_empty() || replacement.is_empty() {
+        return Err(anyhow!("Both the word and how to say it are required"));
+    }
+    if word.chars().count() > MAX_WORD_LEN {
+        return Err(anyhow!("Words can be at most {} characters", MAX_WORD_LEN));
+    }
+    with_dictionary(|d| {
+        let mut words = d.words.clone();
+        words.insert(word, replacement.to_string());
+        write(&words)?;
+        d.pattern = compile(&words);
+        d.words = words;
+        Ok(())
+    })?
+}
+
+/// Stop respelling `word`. Returns whether it was in the dictionary.
+pub fn remove(word: &str) -> Result<bool> {
+    let word = word.trim().to_lowercase();
+    with_dictionary(|d| {
+        if !d.words.contains_key(&word) {
+            return Ok(false);
+        }
+        let mut words = d.words.clone();
+        words.remove(&word);
+        write(&words)?;
+        d.pattern = compile(&words);
+        d.words = words;
+        Ok(true)
+    })?
+}
+
+/// `text` with every dictionary word swapped for how it should be said, ready for Piper.
+/// A dictionary that can't be read leaves the text as it was.
+pub fn apply(text: &str) -> String {
+    let result = with_dictionary(|d| match &d.pattern {
+        Some(pattern) => pattern
+            .replace_all(text, |caps: &regex::Captures| {
+                let found = caps[0].to_lowercase();
+                d.words
+                    .get(&found)
+                    .cloned()
+                    .unwrap_or_else(|| caps[0].to_string())
+            })
+            .into_owned(),
+        None => text.to_string(),
+    });
+    result.unwrap_or_else(|e| {
+        tracing::warn!("Pronunciation dictionary unavailable: {}", e);
+        text.to_string()
+    })
+}
@@ -0,0 +1,83 @@
+use crate::config::Config;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+const MINUTE: Duration = Duration::from_secs(60);
+const HOUR: Duration = Duration::from_secs(60 * 60);
+
+/// When each request to a paid provider in the last hour was let through
+static RECENT: Mutex<VecDeque<Instant>> = Mutex::new(VecDeque::new());
+
+/// The cap a request ran into
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Limit {
+    PerMinute(u32),
+    PerHour(u32),
+    TokensPerDay(u64),
+}
+
+impl Limit {
+    /// What Clippy says instead of answering.
+    pub fn refusal(self) -> String {
+        match self {
+            Self::PerMinute(n) => format!(
+                "Whoa, slow down! I can only answer {} message{} a minute. Give me a moment to \
+                 catch my breath.",
+                n,
+                if n == 1 { "" } else { "s" }
+            ),
+            Self::PerHour(n) => format!(
+                "I've answered {} message{} this hour, which is all I'm allowed. Let's pick this \
+                 up in a little while!",
+                n,
+                if n == 1 { "" } else { "s" }
+            ),
+            Self::TokensPerDay(_) => "I've done all the thinking I'm allowed for today. I'll \
+                 be fresh as a new paperclip tomorrow!"
+                .to_string(),
+        }
+    }
+}
+
+/// Estimated tokens used today across the paid providers
+fn paid_tokens_today() -> u64 {
+    crate::pricing::PAID_PROVIDERS
+        .iter()
+        .map(|provider| crate::stats::tokens_today(&format!("{:?}", provider)))
+        .sum()
+}
+
+/// Let a request to the configured provider through, or say which cap it would break.
+/// Local providers are never limited. A request that's let through counts towards the caps.
+pub fn admit(config: &Config) -> Result<(), Limit> {
+    if !crate::pricing::is_paid(&config.llm_provider) {
+        return Ok(());
+    }
+    let limits = &config.rate_limits;
+    if let Some(max) = limits.tokens_per_day {
+        if paid_tokens_today() >= max {
+            return Err(Limit::TokensPerDay(max));
+        }
+    }
+
+    let Ok(mut recent) = RECENT.lock() else {
+        return Ok(());
+    };
+    while recent.front().is_some_and(|t| t.elapsed() >= HOUR) {
+        recent.pop_front();
+    }
+    if let Some(max) = limits.requests_per_hour {
+        if recent.len() >= max as usize {
+            return Err(Limit::PerHour(max));
+        }
+    }
+    if let Some(max) = limits.requests_per_minute {
+        let last_minute = recent.iter().filter(|t| t.elapsed() < MINUTE).count();
+        if last_minute >= max as usize {
+            return Err(Limit::PerMinute(max));
+        }
+    }
+    recent.push_back(Instant::now());
+    Ok(())
+}
@@ -0,0 +1,109 @@
+use crate::config::{Config, ReasoningDisplay};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+const OPEN: &str = "<think>";
+const CLOSE: &str = "</think>";
+
+/// Part of a reply's chain of thought, sent as `chat-reasoning` while the reply streams
+#[derive(Debug, Clone, Serialize)]
+pub struct ReasoningEvent {
+    pub token: String,
+}
+
+/// A piece of a reply, split into what the model says and what it only thinks
+#[derive(Debug, Default)]
+pub struct Split {
+    pub text: String,
+    pub reasoning: String,
+}
+
+/// Separates `<think>...</think>` spans, as emitted by reasoning models such as the R1
+/// distills and QwQ, from a reply as it streams. A tag can arrive split across tokens, so a
+/// token's end that might begin one is held back until the next token shows what it is.
+#[derive(Debug, Default)]
+pub struct ReasoningFilter {
+    thinking: bool,
+    /// Whether any of the reply proper has gone out, so the blank lines models put after
+    /// their thoughts can be dropped
+    spoken: bool,
+    held: String,
+}
+
+impl ReasoningFilter {
+    pub fn push(&mut self, token: &str) -> Split {
+        self.held.push_str(token);
+        let mut split = Split::default();
+        loop {
+            let tag = if self.thinking { CLOSE } else { OPEN };
+            let out = if self.thinking {
+                &mut split.reasoning
+            } else {
+                &mut split.text
+            };
+            match self.held.find(tag) {
+                Some(at) => {
+                    out.push_str(&self.held[..at]);
+                    self.held.drain(..at + tag.len());
+                    self.thinking = !self.thinking;
+                }
+                None => {
+                    let cut = self.held.len() - partial_tag(&self.held, tag);
+                    out.push_str(&self.held[..cut]);
+                    self.held.drain(..cut);
+                    break;
+                }
+            }
+        }
+        self.trim_leading(split)
+    }
+
+    /// Whatever was held back, once the reply has ended. Thoughts the model never closed
+    /// stay thoughts.
+    pub fn finish(&mut self) -> Split {
+        let held = std::mem::take(&mut self.held);
+        let split = if self.thinking {
+            Split {
+                reasoning: held,
+                ..Split::default()
+            }
+        } else {
+            Split {
+                text: held,
+                ..Split::default()
+            }
+        };
+        self.trim_leading(split)
+    }
+
+    fn trim_leading(&mut self, mut split: Split) -> Split {
+        if !self.spoken {
+            split.text = split.text.trim_start().to_string();
+            self.spoken = !split.text.is_empty();
+        }
+        split
+    }
+}
+
+/// Send `reasoning` to the window labelled `label`, if there is any and it's to be shown.
+pub fn emit(app: &AppHandle, label: &str, config: &Config, reasoning: String) {
+    if !reasoning.is_empty() && config.reasoning == ReasoningDisplay::Collapsed {
+        let _ = app.emit_to(label, "chat-reasoning", ReasoningEvent { token: reasoning });
+    }
+}
+
+/// Length of the longest end of `text` that `tag` starts with
+fn partial_tag(text: &str, tag: &str) -> usize {
+    (1..tag.len())
+        .rev()
+        .find(|&n| text.ends_with(&tag[..n]))
+        .unwrap_or(0)
+}
+
+/// `text` without its `<think>` spans, for replies that weren't streamed to the chat.
+pub fn strip(text: &str) -> String {
+    let mut filter = ReasoningFilter::default();
+    let mut said = filter.push(text).text;
+    said.push_str(&filter.finish().text);
+    said
+}
@@ -0,0 +1,114 @@
+use crate::config::Config;
+use regex::Regex;
+use std::io::{self, Write};
+use std::sync::{LazyLock, RwLock};
+use tracing_subscriber::fmt::MakeWriter;
+
+const MASK: &str = "[REDACTED]";
+
+/// Shapes of credentials that show up in provider errors and request dumps
+static PATTERNS: LazyLock<Vec<(Regex, &'static str)>> = LazyLock::new(|| {
+    [
+        // Authorization headers, with or without a scheme
+        (
+            r#"(?i)(authorization["']?\s*[:=]\s*["']?)(?:bearer\s+|basic\s+)?[^\s"',}]+"#,
+            "${1}[REDACTED]",
+        ),
+        (r"(?i)\b(bearer\s+)[A-Za-z0-9._~+/=-]{8,}", "${1}[REDACTED]"),
+        // JSON fields and query parameters that hold secrets
+        (
+            r#"(?i)("?\b(?:api[_-]?key|openai_api_key|custom_api_key|elevenlabs_api_key|access_token|secret)"?\s*[:=]\s*"?)[^\s"&,}]{4,}"#,
+            "${1}[REDACTED]",
+        ),
+        // OpenAI-style keys (sk-..., sk-proj-...)
+        (r"\bsk-[A-Za-z0-9_-]{16,}", MASK),
+    ]
+    .into_iter()
+    .map(|(pattern, replacement)| (Regex::new(pattern).unwrap(), replacement))
+    .collect()
+});
+
+/// Exact key values from the config, caught even when they don't match a known shape
+static KNOWN_SECRETS: RwLock<Vec<String>> = RwLock::new(Vec::new());
+
+/// Remember the config's API keys so `redact` masks them verbatim.
+pub fn remember_secrets(config: &Config) {
+    let Ok(mut known) = KNOWN_SECRETS.write() else {
+        return;
+    };
+    let tool_keys = config.tools.values().map(|tool| &tool.api_key);
+    let keys = [
+        &config.openai_api_key,
+        &config.custom_api_key,
+        &config.elevenlabs_api_key,
+    ];
+    for secret in keys
+        .into_iter()
+        .chain(tool_keys)
+        .flatten()
+    {
+        // Very short values ("ollama", "x") would mangle ordinary text
+        if secret.len() >= 8 && !known.contains(secret) {
+            known.push(secret.clone());
+        }
+    }
+}
+
+/// Mask API keys and auth headers in `text` before it's logged or shown to the user.
+pub fn redact(text: &str) -> String {
+    let mut out = text.to_string();
+    if let Ok(known) = KNOWN_SECRETS.read() {
+        for secret in known.iter() {
+            if out.contains(secret.as_str()) {
+                out = out.replace(secret.as_str(), MASK);
+            }
+        }
+    }
+    for (pattern, replacement) in PATTERNS.iter() {
+        out = pattern.replace_all(&out, *replacement).into_owned();
+    }
+    out
+}
+
+/// `MakeWriter` that scrubs each formatted log event before passing it on
+pub struct RedactingMakeWriter<M>(pub M);
+
+impl<'a, M: MakeWriter<'a>> MakeWriter<'a> for RedactingMakeWriter<M> {
+    type Writer = RedactingWriter<M::Writer>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        RedactingWriter {
+            inner: self.0.make_writer(),
+            buf: Vec::new(),
+        }
+    }
+}
+
+/// Buffers one event so secrets split across `write` calls are still caught
+pub struct RedactingWriter<W: Write> {
+    inner: W,
+    buf: Vec<u8>,
+}
+
+impl<W: Write> Write for RedactingWriter<W> {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        self.buf.extend_from_slice(bytes);
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.buf.is_empty() {
+            return Ok(());
+        }
+        let text = redact(&String::from_utf8_lossy(&self.buf));
+        self.buf.clear();
+        self.inner.write_all(text.as_bytes())?;
+        self.inner.flush()
+    }
+}
+
+impl<W: Write> Drop for RedactingWriter<W> {
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
@@ -0,0 +1,383 @@
+use crate::commands::ReminderEvent;
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration as ChronoDuration, Local, NaiveTime, TimeZone};
+use regex::Regex;
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use std::path::PathBuf;
+use std::sync::LazyLock;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::Notify;
+
+/// How often the scheduler looks for reminders that are due
+const TICK: Duration = Duration::from_secs(15);
+
+/// Wakes the scheduler when something new is scheduled, so a short timer isn't a tick late
+static SCHEDULED: Notify = Notify::const_new();
+
+/// "remind me ..." at the start of a message
+static REQUEST: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)^\s*(?:please\s+)?remind me\s+(.+?)[\s.!]*$").unwrap());
+/// "in 20 minutes", "in an hour", "in 2 days"
+static RELATIVE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)\bin\s+(\d+|an?|one)\s+(minutes?|mins?|hours?|hrs?|days?)\b").unwrap()
+});
+/// "at 3pm", "at 15:30", "tomorrow at 9am", "at 9 am tomorrow"
+static CLOCK: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?i)(?:\b(today|tomorrow)\s+)?\bat\s+(\d{1,2})(?::(\d{2}))?\s*(am|pm)?\b(?:\s+(today|tomorrow)\b)?",
+    )
+    .unwrap()
+});
+/// Words joining "remind me" to the task, e.g. "remind me *to* stretch"
+static TASK_PREFIX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)^(?:to|that|about)\s+").unwrap());
+/// One part of a timer length: "20 minutes", "1h", "an hour", "90 s"
+static DURATION_PART: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)(\d+(?:\.\d+)?|\b(?:an?|one)\b)\s*([a-z]+)").unwrap());
+
+/// Longest timer that can be set
+const MAX_TIMER: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// Reminders go off with the task; timers just say time's up
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReminderKind {
+    Reminder,
+    Timer,
+}
+
+impl ReminderKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            ReminderKind::Reminder => "reminder",
+            ReminderKind::Timer => "timer",
+        }
+    }
+}
+
+/// A saved reminder or timer. Times are Unix timestamps, in seconds.
+#[derive(Debug, Clone, Serialize)]
+pub struct Reminder {
+    pub id: i64,
+    /// The task, or a timer's label (empty when it has none)
+    pub text: String,
+    pub due_at: i64,
+    pub created_at: i64,
+    pub kind: ReminderKind,
+}
+
+fn db_path() -> Result<PathBuf> {
+    let data_dir = dirs::data_dir().ok_or_else(|| anyhow!("Could not find data directory"))?;
+    let dir = data_dir.join("rusty-clippy");
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir.join("reminders.db"))
+}
+
+fn open() -> Result<Connection> {
+    let conn = Connection::open(db_path()?)?;
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS reminders (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            text TEXT NOT NULL,
+            due_at INTEGER NOT NULL,
+            created_at INTEGER NOT NULL,
+            fired_at INTEGER
+        );
+        CREATE INDEX IF NOT EXISTS reminders_due ON reminders (due_at) WHERE fired_at IS NULL;",
+    )?;
+    // Databases from before timers lack the kind column
+    if conn.prepare("SELECT kind FROM reminders LIMIT 0").is_err() {
+        conn.execute(
+            "ALTER TABLE reminders ADD COLUMN kind TEXT NOT NULL DEFAULT 'reminder'",
+            [],
+        )?;
+    }
+    Ok(conn)
+}
+
+fn from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<Reminder> {
+    Ok(Reminder {
+        id: row.get(0)?,
+        text: row.get(1)?,
+        due_at: row.get(2)?,
+        created_at: row.get(3)?,
+        kind: match row.get::<_, String>(4)?.as_str() {
+            "timer" => ReminderKind::Timer,
+            _ => ReminderKind::Reminder,
+        },
+    })
+}
+
+fn insert(kind: ReminderKind, text: &str, due_at: i64) -> Result<Reminder> {
+    let conn = open()?;
+    let created_at = chrono::Utc::now().timestamp();
+    conn.execute(
+        "INSERT INTO reminders (text, due_at, created_at, kind) VALUES (?1, ?2, ?3, ?4)",
+        params![text, due_at, created_at, kind.as_str()],
+    )?;
+    SCHEDULED.notify_one();
+    Ok(Reminder {
+        id: conn.last_insert_rowid(),
+        text: text.to_string(),
+        due_at,
+        created_at,
+        kind,
+    })
+}
+
+pub fn add(text: &str, due_at: i64) -> Result<Reminder> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Err(anyhow!("A reminder needs something to remind you of"));
+    }
+    insert(ReminderKind::Reminder, text, due_at)
+}
+
+/// Start a timer going off after `duration`, written like "20 minutes" or "1h30m".
+pub fn add_timer(duration: &str, label: Option<&str>) -> Result<Reminder> {
+    let length = parse_duration(duration)
+        .ok_or_else(|| anyhow!("'{}' isn't a length of time I understand", duration.trim()))?;
+    if length.is_zero() || length > MAX_TIMER {
+        return Err(anyhow!("Timers can run from one second to a week"));
+    }
+    let due_at = chrono::Utc::now().timestamp() + length.as_secs() as i64;
+    insert(ReminderKind::Timer, label.unwrap_or("").trim(), due_at)
+}
+
+/// The length of a timer: "20 minutes", "1h30m", "an hour and 15 minutes", or a bare number
+/// of minutes.
+pub fn parse_duration(text: &str) -> Option<Duration> {
+    let text = text.trim();
+    if let Ok(minutes) = text.parse::<f64>() {
+        return Duration::try_from_secs_f64(minutes * 60.0).ok();
+    }
+    let mut seconds = 0.0;
+    let mut last = 0;
+    for caps in DURATION_PART.captures_iter(text) {
+        let whole = caps.get(0)?;
+        // Only joining words may sit between the parts
+        let between = text[last..whole.start()].trim().trim_matches(',');
+        if !between.is_empty() && !between.eq_ignore_ascii_case("and") {
+            return None;
+        }
+        last = whole.end();
+
+        let amount: f64 = match caps[1].to_lowercase().as_str() {
+            "a" | "an" | "one" => 1.0,
+            n => n.parse().ok()?,
+        };
+        seconds += amount
+            * match caps[2].to_lowercase().as_str() {
+                "h" | "hr" | "hrs" | "hour" | "hours" => 3600.0,
+                "m" | "min" | "mins" | "minute" | "minutes" => 60.0,
+                "s" | "sec" | "secs" | "second" | "seconds" => 1.0,
+                _ => return None,
+            };
+    }
+    if last == 0 || !text[last..].trim().is_empty() {
+        return None;
+    }
+    Duration::try_from_secs_f64(seconds).ok()
+}
+
+/// Reminders that haven't gone off yet, soonest first
+pub fn pending() -> Result<Vec<Reminder>> {
+    let conn = open()?;
+    let mut stmt = conn.prepare(
+        "SELECT id, text, due_at, created_at, kind FROM reminders
+         WHERE fired_at IS NULL ORDER BY due_at",
+    )?;
+    let reminders = stmt
+        .query_map([], from_row)?
+        .collect::<rusqlite::Result<_>>()?;
+    Ok(reminders)
+}
+
+pub fn cancel(id: i64) -> Result<()> {
+    let removed = open()?.execute(
+        "DELETE FROM reminders WHERE id = ?1 AND fired_at IS NULL",
+        params![id],
+    )?;
+    if removed == 0 {
+        return Err(anyhow!("No pending reminder with id {}", id));
+    }
+    Ok(())
+}
+
+/// Mark every reminder due by `now` as fired and return them.
+fn take_due(now: i64) -> Result<Vec<Reminder>> {
+    let mut conn = open()?;
+    let tx = conn.transaction()?;
+    let due: Vec<Reminder> = {
+        let mut stmt = tx.prepare(
+            "SELECT id, text, due_at, created_at, kind FROM reminders
+             WHERE fired_at IS NULL AND due_at <= ?1 ORDER BY due_at",
+        )?;
+        let rows = stmt.query_map(params![now], from_row)?;
+        rows.collect::<rusqlite::Result<_>>()?
+    };
+    for reminder in &due {
+        tx.execute(
+            "UPDATE reminders SET fired_at = ?1 WHERE id = ?2",
+            params![now, reminder.id],
+        )?;
+    }
+    tx.commit()?;
+    Ok(due)
+}
+
+/// Pull the task and time out of a typed request such as "remind me at 3pm to stretch"
+/// or "remind me to call mum in 20 minutes".
+pub fn parse_request(message: &str, now: DateTime<Local>) -> Option<(String, DateTime<Local>)> {
+    let rest = REQUEST.captures(message)?.get(1)?.as_str();
+
+    let (due, matched) = if let Some(caps) = RELATIVE.captures(rest) {
+        let amount = match caps[1].to_lowercase().as_str() {
+            "a" | "an" | "one" => 1,
+            n => n.parse().ok()?,
+        };
+        let unit = caps[2].to_lowercase();
+        let offset = if unit.starts_with('m') {
+            ChronoDuration::minutes(amount)
+        } else if unit.starts_with('h') {
+            ChronoDuration::hours(amount)
+        } else {
+            ChronoDuration::days(amount)
+        };
+        (now + offset, caps.get(0)?)
+    } else {
+        let caps = CLOCK.captures(rest)?;
+        let mut hour: u32 = caps[2].parse().ok()?;
+        let minute: u32 = caps.get(3).map_or(Some(0), |m| m.as_str().parse().ok())?;
+        match caps.get(4).map(|m| m.as_str().to_lowercase()).as_deref() {
+            Some("pm") if hour < 12 => hour += 12,
+            Some("am") if hour == 12 => hour = 0,
+            _ => {}
+        }
+        let time = NaiveTime::from_hms_opt(hour, minute, 0)?;
+        let tomorrow = caps
+            .get(1)
+            .or(caps.get(5))
+            .is_some_and(|day| day.as_str().eq_ignore_ascii_case("tomorrow"));
+
+        let mut date = now.date_naive();
+        if tomorrow {
+            date = date.succ_opt()?;
+        }
+        let mut due = Local.from_local_datetime(&date.and_time(time)).earliest()?;
+        // "at 9am" when it's already past nine means tomorrow morning
+        if due <= now && !tomorrow {
+            due = Local
+                .from_local_datetime(&date.succ_opt()?.and_time(time))
+                .earliest()?;
+        }
+        (due, caps.get(0)?)
+    };
+
+    let leftover = format!("{} {}", &rest[..matched.start()], &rest[matched.end()..]);
+    let task = TASK_PREFIX.replace(leftover.trim(), "").trim().to_string();
+    if task.is_empty() {
+        return None;
+    }
+    Some((task, due))
+}
+
+/// A length of time in words, e.g. "1 hour 30 minutes"
+pub fn describe_duration(seconds: i64) -> String {
+    let parts: Vec<String> = [
+        (seconds / 86400, "day"),
+        (seconds % 86400 / 3600, "hour"),
+        (seconds % 3600 / 60, "minute"),
+        (seconds % 60, "second"),
+    ]
+    .into_iter()
+    .filter(|(n, _)| *n > 0)
+    .map(|(n, unit)| format!("{} {}{}", n, unit, if n == 1 { "" } else { "s" }))
+    .collect();
+    if parts.is_empty() {
+        "0 seconds".to_string()
+    } else {
+        parts.join(" ")
+    }
+}
+
+/// How Clippy confirms a reminder it has just set
+pub fn confirmation(reminder: &Reminder) -> String {
+    let due = Local
+        .timestamp_opt(reminder.due_at, 0)
+        .single()
+        .unwrap_or_else(Local::now);
+    let when = if due.date_naive() == Local::now().date_naive() {
+        due.format("at %H:%M").to_string()
+    } else {
+        due.format("on %a %d %b at %H:%M").to_string()
+    };
+    format!("Got it! I'll remind you to {} {}.", reminder.text, when)
+}
+
+/// Start the reminder scheduler. Reminders that came due while the app was closed
+/// go off on the first tick after launch.
+pub fn spawn(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            match take_due(chrono::Utc::now().timestamp()) {
+                Ok(due) => {
+                    for reminder in due {
+                        fire(&app, reminder).await;
+                    }
+                }
+                Err(e) => tracing::warn!("Failed to check reminders: {}", e),
+            }
+            // Sleep until the next one is due, checking at least every tick
+            let next = pending()
+                .ok()
+                .and_then(|p| p.first().map(|r| r.due_at - chrono::Utc::now().timestamp()))
+                .map(|secs| Duration::from_secs(secs.max(0) as u64))
+                .unwrap_or(TICK);
+            tokio::select! {
+                _ = tokio::time::sleep(next.min(TICK)) => {}
+                _ = SCHEDULED.notified() => {}
+            }
+        }
+    });
+}
+
+async fn fire(app: &AppHandle, reminder: Reminder) {
+    tracing::info!("Reminder {} due: {}", reminder.id, reminder.text);
+    let (title, body, text) = match reminder.kind {
+        ReminderKind::Reminder => (
+            "Reminder",
+            reminder.text.clone(),
+            format!("Hey! You asked me to remind you: {}", reminder.text),
+        ),
+        ReminderKind::Timer if reminder.text.is_empty() => (
+            "Timer",
+            "Time's up!".to_string(),
+            "Ding! Your timer is done.".to_string(),
+        ),
+        ReminderKind::Timer => (
+            "Timer",
+            format!("{} timer is done", reminder.text),
+            format!("Ding! Your {} timer is done.", reminder.text),
+        ),
+    };
+
+    // A reminder is worth interrupting for, so it always goes to the OS as well
+    if let Err(e) = crate::notifications::notify_always(app, title, &body) {
+        tracing::warn!("Failed to show reminder notification: {}", e);
+    }
+    let _ = app.emit(
+        "reminder",
+        ReminderEvent {
+            id: reminder.id,
+            text: text.clone(),
+        },
+    );
+
+    if crate::config::Config::load().is_ok_and(|c| c.tts_enabled) {
+        crate::tts::say(app, text).await;
+    }
+}
@@ -0,0 +1,217 @@
+use crate::commands::build_provider;
+use crate::config::Config;
+use crate::request_log::RequestLogEntry;
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use std::path::Path;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+use tokio_stream::StreamExt;
+
+/// Longest response, in words, diffed word by word; longer ones are compared whole
+const MAX_DIFF_WORDS: usize = 2000;
+
+/// A run of words the two responses have in common, or that only one has
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(tag = "kind", content = "text", rename_all = "lowercase")]
+pub enum DiffChunk {
+    Same(String),
+    /// Only in the logged response
+    Removed(String),
+    /// Only in the new response
+    Added(String),
+}
+
+/// One logged request sent again. Sent as `replay-turn` as soon as it's done.
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplayTurn {
+    /// Line in the log, from 1
+    pub line: usize,
+    pub logged_model: String,
+    pub logged_response: String,
+    pub response: String,
+    pub identical: bool,
+    pub diff: Vec<DiffChunk>,
+    pub logged_first_token_ms: Option<u64>,
+    pub first_token_ms: Option<u64>,
+    pub logged_total_ms: u64,
+    pub total_ms: u64,
+    pub error: Option<String>,
+}
+
+/// What `replay_log` found
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplayReport {
+    pub provider: String,
+    pub model: String,
+    pub turns: Vec<ReplayTurn>,
+    pub identical: usize,
+    /// Lines that weren't log entries
+    pub skipped_lines: usize,
+    pub logged_avg_ms: Option<u64>,
+    pub avg_ms: Option<u64>,
+    /// Why the replay ended before the end of the log
+    pub stopped: Option<String>,
+}
+
+/// The logged requests in `path`, with their line numbers, and how many lines weren't entries.
+fn read_log(path: &Path) -> Result<(Vec<(usize, RequestLogEntry)>, usize)> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+    let mut entries = Vec::new();
+    let mut skipped = 0;
+    for (index, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<RequestLogEntry>(line) {
+            Ok(entry) => entries.push((index + 1, entry)),
+            Err(_) => skipped += 1,
+        }
+    }
+    Ok((entries, skipped))
+}
+
+/// Word-level diff of `old` against `new`, from their longest common subsequence.
+fn diff_words(old: &str, new: &str) -> Vec<DiffChunk> {
+    let a: Vec<&str> = old.split_whitespace().collect();
+    let b: Vec<&str> = new.split_whitespace().collect();
+    if a.len() > MAX_DIFF_WORDS || b.len() > MAX_DIFF_WORDS {
+        return vec![
+            DiffChunk::Removed(old.to_string()),
+            DiffChunk::Added(new.to_string()),
+        ];
+    }
+
+    // lcs[i][j]: length of the longest common subsequence of a[i..] and b[j..]
+    let mut lcs = vec![vec![0u32; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i][j] = if a[i] == b[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut chunks: Vec<DiffChunk> = Vec::new();
+    let mut push = |chunk: DiffChunk| {
+        let merged = match (chunks.last_mut(), &chunk) {
+            (Some(DiffChunk::Same(text)), DiffChunk::Same(word))
+            | (Some(DiffChunk::Removed(text)), DiffChunk::Removed(word))
+            | (Some(DiffChunk::Added(text)), DiffChunk::Added(word)) => {
+                text.push(' ');
+                text.push_str(word);
+                true
+            }
+            _ => false,
+        };
+        if !merged {
+            chunks.push(chunk);
+        }
+    };
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        if a[i] == b[j] {
+            push(DiffChunk::Same(a[i].to_string()));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            push(DiffChunk::Removed(a[i].to_string()));
+            i += 1;
+        } else {
+            push(DiffChunk::Added(b[j].to_string()));
+            j += 1;
+        }
+    }
+    for word in &a[i..] {
+        push(DiffChunk::Removed(word.to_string()));
+    }
+    for word in &b[j..] {
+        push(DiffChunk::Added(word.to_string()));
+    }
+    chunks
+}
+
+fn average(times: impl Iterator<Item = u64>) -> Option<u64> {
+    let (count, total) = times.fold((0u64, 0u64), |(n, sum), t| (n + 1, sum + t));
+    total.checked_div(count)
+}
+
+/// Send every request logged in `path` through the configured provider again, one at a
+/// time, and compare the responses and timings with the logged ones.
+pub async fn replay(app: &AppHandle, path: &Path) -> Result<ReplayReport> {
+    let (entries, skipped_lines) = read_log(path)?;
+    if entries.is_empty() {
+        return Err(anyhow!("{} has no logged requests", path.display()));
+    }
+    let mut config = Config::load()?;
+    crate::budget::enforce(&mut config).map_err(|e| anyhow!(e))?;
+    let provider = build_provider(&config).map_err(|e| anyhow!(e))?;
+    tracing::info!(
+        "Replaying {} logged requests from {}",
+        entries.len(),
+        path.display()
+    );
+
+    let mut turns = Vec::new();
+    let mut stopped = None;
+    for (line, entry) in entries {
+        if let Err(limit) = crate::rate_limit::admit(&config) {
+            tracing::info!("Replay stopped by rate limit: {:?}", limit);
+            stopped = Some(limit.refusal());
+            break;
+        }
+        let log_entry = RequestLogEntry::new(&config, "replay", &entry.messages);
+        let started = Instant::now();
+        let mut first_token: Option<Duration> = None;
+        let mut response = String::new();
+        let streamed: Result<(), String> = async {
+            let mut stream = provider
+                .stream_completion(entry.messages.clone())
+                .await
+                .map_err(|e| crate::redact::redact(&e.to_string()))?;
+            while let Some(token) = stream.next().await {
+                first_token.get_or_insert_with(|| started.elapsed());
+                response.push_str(&token.map_err(|e| crate::redact::redact(&e.to_string()))?);
+            }
+            Ok(())
+        }
+        .await;
+        let total = started.elapsed();
+        let error = streamed.err();
+        crate::request_log::append(
+            &config,
+            &log_entry.finish(&response, first_token, total, error.as_deref()),
+        );
+        crate::pricing::record(app, &config, &entry.messages, &response);
+
+        let turn = ReplayTurn {
+            line,
+            identical: response == entry.response,
+            diff: diff_words(&entry.response, &response),
+            logged_model: entry.model,
+            logged_response: entry.response,
+            response,
+            logged_first_token_ms: entry.first_token_ms,
+            first_token_ms: first_token.map(|d| d.as_millis() as u64),
+            logged_total_ms: entry.total_ms,
+            total_ms: total.as_millis() as u64,
+            error,
+        };
+        let _ = app.emit("replay-turn", turn.clone());
+        turns.push(turn);
+    }
+
+    Ok(ReplayReport {
+        provider: format!("{:?}", config.llm_provider),
+        model: config.current_model().model,
+        identical: turns.iter().filter(|t| t.identical).count(),
+        skipped_lines,
+        logged_avg_ms: average(turns.iter().map(|t| t.logged_total_ms)),
+        avg_ms: average(turns.iter().map(|t| t.total_ms)),
+        turns,
+        stopped,
+    })
+}
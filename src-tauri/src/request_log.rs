@@ -0,0 +1,100 @@
+use crate::config::Config;
+use crate::llm::Message;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+
+const FILE_NAME: &str = "requests.jsonl";
+
+/// Held while appending, so entries from concurrent replies don't interleave
+static WRITE_LOCK: Mutex<()> = Mutex::new(());
+
+/// One request to a model and what came back: a line of `requests.jsonl`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RequestLogEntry {
+    /// RFC 3339, local time
+    pub timestamp: String,
+    /// What asked: "chat" or "proactive"
+    pub source: String,
+    pub provider: String,
+    pub model: String,
+    pub temperature: f32,
+    #[serde(default)]
+    pub seed: Option<u32>,
+    #[serde(default)]
+    pub context_tokens: Option<u32>,
+    /// Everything sent, system prompt included
+    pub messages: Vec<Message>,
+    /// The streamed reply, as far as it got
+    pub response: String,
+    #[serde(default)]
+    pub first_token_ms: Option<u64>,
+    pub total_ms: u64,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+impl RequestLogEntry {
+    /// An entry for sending `messages` to the provider `config` is set up with.
+    pub fn new(config: &Config, source: &str, messages: &[Message]) -> Self {
+        Self {
+            timestamp: chrono::Local::now().to_rfc3339(),
+            source: source.to_string(),
+            provider: format!("{:?}", config.llm_provider),
+            model: config.current_model().model,
+            temperature: config.temperature,
+            seed: config.seed,
+            context_tokens: config.context_tokens,
+            messages: messages.to_vec(),
+            response: String::new(),
+            first_token_ms: None,
+            total_ms: 0,
+            error: None,
+        }
+    }
+
+    /// Fill in how the request went.
+    pub fn finish(
+        mut self,
+        response: &str,
+        first_token: Option<Duration>,
+        total: Duration,
+        error: Option<&str>,
+    ) -> Self {
+        self.response = response.to_string();
+        self.first_token_ms = first_token.map(|d| d.as_millis() as u64);
+        self.total_ms = total.as_millis() as u64;
+        self.error = error.map(str::to_string);
+        self
+    }
+}
+
+/// The log file, next to the app logs.
+pub fn path() -> Result<PathBuf> {
+    Ok(crate::logging::log_dir()?.join(FILE_NAME))
+}
+
+/// Append `entry` to the log, if request logging is on. API keys are masked. Failures are
+/// only logged; debugging aids mustn't break a reply.
+pub fn append(config: &Config, entry: &RequestLogEntry) {
+    if !config.request_log {
+        return;
+    }
+    let written = serde_json::to_string(entry)
+        .map_err(anyhow::Error::from)
+        .and_then(|line| {
+            let _guard = WRITE_LOCK.lock();
+            let mut file = std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path()?)?;
+            writeln!(file, "{}", crate::redact::redact(&line))?;
+            Ok(())
+        });
+    if let Err(e) = written {
+        tracing::warn!("Failed to write request log: {}", e);
+    }
+}
@@ -0,0 +1,111 @@
+use crate::config::{Config, Dock, ModelChoice, WindowGeometry};
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// config.json keys that moved here, carried over when an older config is migrated
+const MOVED_KEYS: &[&str] = &["windows", "dock", "recent_models"];
+/// How many recently used models the tray remembers
+const MAX_RECENT_MODELS: usize = 5;
+
+/// Held across each read-modify-write of state.json
+static LOCK: Mutex<()> = Mutex::new(());
+
+/// What the app keeps track of by itself between runs, as opposed to settings the user
+/// picks. It lives in state.json, so moving a window or chatting never rewrites
+/// config.json, rotates its backup or fights with the settings window's copy.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RuntimeState {
+    /// Last position and size of each window, keyed by label
+    #[serde(default)]
+    pub windows: BTreeMap<String, WindowGeometry>,
+    /// Edge or corner Clippy was last snapped to
+    #[serde(default)]
+    pub dock: Dock,
+    /// Models chatted with most recently, newest first
+    #[serde(default)]
+    pub recent_models: Vec<ModelChoice>,
+}
+
+fn state_path() -> Result<PathBuf> {
+    let dir = Config::config_dir()?;
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir.join("state.json"))
+}
+
+/// Before the first state.json exists, an older config.json may still hold its values;
+/// loading the config migrates them over.
+fn adopt_from_config() {
+    if state_path().is_ok_and(|path| !path.exists()) {
+        let _ = Config::load_file();
+    }
+}
+
+fn read() -> RuntimeState {
+    state_path()
+        .ok()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// The saved state; empty when there is none yet or it can't be read
+pub fn load() -> RuntimeState {
+    adopt_from_config();
+    read()
+}
+
+/// Change the saved state with `f` and write it back.
+pub fn update(f: impl FnOnce(&mut RuntimeState)) -> Result<()> {
+    adopt_from_config();
+    let _guard = LOCK.lock().map_err(|e| anyhow!("State lock error: {}", e))?;
+    let mut state = read();
+    f(&mut state);
+    let content = serde_json::to_string_pretty(&state)?;
+    crate::config::write_atomic(&state_path()?, content.as_bytes())
+}
+
+/// Copy the values an older `config` kept into state.json, without replacing any already
+/// there. Runs before the config migration drops those keys.
+pub fn adopt(config: &Map<String, Value>) -> Result<()> {
+    let _guard = LOCK.lock().map_err(|e| anyhow!("State lock error: {}", e))?;
+    let path = state_path()?;
+    let mut state: Map<String, Value> = std::fs::read_to_string(&path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default();
+    let mut adopted = false;
+    for key in MOVED_KEYS {
+        if let Some(value) = config.get(*key) {
+            if !state.contains_key(*key) {
+                state.insert(key.to_string(), value.clone());
+                adopted = true;
+            }
+        }
+    }
+    if adopted {
+        let content = serde_json::to_string_pretty(&state)?;
+        crate::config::write_atomic(&path, content.as_bytes())?;
+        tracing::info!("Moved app state from config.json to {:?}", path);
+    }
+    Ok(())
+}
+
+/// Move the saved config's model to the front of `recent_models`. The config is read as
+/// stored, so app rules, the budget fallback, the ensemble judge and environment overrides
+/// never end up in the list. Returns whether the list changed.
+pub fn remember_current_model() -> Result<bool> {
+    let choice = Config::load_file()?.current_model();
+    if load().recent_models.first() == Some(&choice) {
+        return Ok(false);
+    }
+    update(|state| {
+        state.recent_models.retain(|m| *m != choice);
+        state.recent_models.insert(0, choice);
+        state.recent_models.truncate(MAX_RECENT_MODELS);
+    })?;
+    Ok(true)
+}
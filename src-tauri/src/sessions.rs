@@ -0,0 +1,331 @@
+use crate::commands::ChatMessage;
+use crate::config::{write_atomic, HistoryConfig};
+use crate::ConversationState;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Longest title derived from a conversation's first message
+const TITLE_CHARS: usize = 40;
+
+/// Cap on pinned text added to the system prompt, so pins can't crowd out the conversation
+const PINNED_CONTEXT_CHARS: usize = 2000;
+
+/// A saved conversation, one JSON file per session in the `sessions/` data directory
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Session {
+    pub id: String,
+    pub title: String,
+    /// Unix timestamps, in seconds
+    pub created_at: i64,
+    pub updated_at: i64,
+    pub messages: Vec<ChatMessage>,
+    /// The session this one was forked from, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branched_from: Option<String>,
+    /// Indexes into `messages` the user has pinned
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pinned: Vec<usize>,
+}
+
+/// What lists of sessions show, without loading every message
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionSummary {
+    pub id: String,
+    pub title: String,
+    pub updated_at: i64,
+    pub message_count: usize,
+}
+
+impl From<&Session> for SessionSummary {
+    fn from(session: &Session) -> Self {
+        Self {
+            id: session.id.clone(),
+            title: session.title.clone(),
+            updated_at: session.updated_at,
+            message_count: session.messages.len(),
+        }
+    }
+}
+
+pub fn sessions_dir() -> Result<PathBuf> {
+    let data_dir = dirs::data_dir().ok_or_else(|| anyhow!("Could not find data directory"))?;
+    let dir = data_dir.join("rusty-clippy").join("sessions");
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+fn session_path(id: &str) -> Result<PathBuf> {
+    // Ids are generated by `new_id`; refuse anything that could escape the directory
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return Err(anyhow!("Invalid session id '{}'", id));
+    }
+    Ok(sessions_dir()?.join(format!("{}.json", id)))
+}
+
+/// A sortable, filename-safe id such as `20250131-142501-123`
+fn new_id() -> String {
+    chrono::Local::now().format("%Y%m%d-%H%M%S-%3f").to_string()
+}
+
+/// The first line of the user's first message, shortened to fit a menu
+fn title_for(messages: &[ChatMessage]) -> String {
+    let first = messages
+        .iter()
+        .find(|m| m.role == "user")
+        .and_then(|m| m.content.lines().find(|l| !l.trim().is_empty()))
+        .map(str::trim)
+        .unwrap_or("New conversation");
+    if first.chars().count() <= TITLE_CHARS {
+        return first.to_string();
+    }
+    let mut title: String = first.chars().take(TITLE_CHARS - 1).collect();
+    title.push('…');
+    title
+}
+
+pub fn load(id: &str) -> Result<Session> {
+    let content = std::fs::read_to_string(session_path(id)?)?;
+    Ok(serde_json::from_str(&content)?)
+}
+
+fn write(session: &Session) -> Result<()> {
+    let content = serde_json::to_string_pretty(session)?;
+    write_atomic(&session_path(&session.id)?, content.as_bytes())
+}
+
+/// Save the live conversation, starting a new session file on its first save.
+/// Conversations the user hasn't said anything in yet (e.g. a lone proactive tip) aren't kept.
+pub async fn save_conversation(state: &tokio::sync::RwLock<ConversationState>) -> Result<()> {
+    let (id, messages) = {
+        let mut conv_state = state.write().await;
+        if !conv_state.history.iter().any(|m| m.role == "user") {
+            return Ok(());
+        }
+        let id = conv_state.session_id.get_or_insert_with(new_id).clone();
+        (id, conv_state.history.clone())
+    };
+
+    let now = chrono::Utc::now().timestamp();
+    let session = match load(&id) {
+        Ok(saved) => Session {
+            // A branch keeps its "(branch)" title rather than repeating its parent's
+            title: if saved.branched_from.is_some() {
+                saved.title
+            } else {
+                title_for(&messages)
+            },
+            updated_at: now,
+            messages,
+            ..saved
+        },
+        Err(_) => Session {
+            title: title_for(&messages),
+            id,
+            created_at: now,
+            updated_at: now,
+            messages,
+            branched_from: None,
+            pinned: Vec::new(),
+        },
+    };
+    write(&session)
+}
+
+/// Fork a saved session into a new one holding its messages up to and including
+/// `message_index`. The original is left untouched.
+pub fn branch(id: &str, message_index: usize) -> Result<Session> {
+    let parent = load(id)?;
+    if message_index >= parent.messages.len() {
+        return Err(anyhow!(
+            "Message {} is out of range; the conversation has {} messages",
+            message_index,
+            parent.messages.len()
+        ));
+    }
+    let now = chrono::Utc::now().timestamp();
+    let session = Session {
+        id: new_id(),
+        title: format!("{} (branch)", parent.title),
+        created_at: now,
+        updated_at: now,
+        messages: parent.messages[..=message_index].to_vec(),
+        branched_from: Some(parent.id),
+        pinned: parent
+            .pinned
+            .into_iter()
+            .filter(|&i| i <= message_index)
+            .collect(),
+    };
+    write(&session)?;
+    Ok(session)
+}
+
+/// Pin or unpin one message of a saved session.
+pub fn set_pinned(id: &str, message_index: usize, pinned: bool) -> Result<()> {
+    let mut session = load(id)?;
+    if message_index >= session.messages.len() {
+        return Err(anyhow!(
+            "Message {} is out of range; the conversation has {} messages",
+            message_index,
+            session.messages.len()
+        ));
+    }
+    session.pinned.retain(|&i| i != message_index);
+    if pinned {
+        session.pinned.push(message_index);
+        session.pinned.sort_unstable();
+    }
+    write(&session)
+}
+
+/// A pinned message, with the session it lives in
+#[derive(Debug, Clone, Serialize)]
+pub struct PinnedMessage {
+    pub session_id: String,
+    pub session_title: String,
+    pub message_index: usize,
+    pub role: String,
+    pub content: String,
+}
+
+/// Every pinned message across all sessions, most recently updated session first
+pub fn pinned() -> Result<Vec<PinnedMessage>> {
+    let mut pins = Vec::new();
+    for session in load_all()? {
+        for &index in &session.pinned {
+            let Some(message) = session.messages.get(index) else {
+                continue;
+            };
+            pins.push(PinnedMessage {
+                session_id: session.id.clone(),
+                session_title: session.title.clone(),
+                message_index: index,
+                role: message.role.clone(),
+                content: message.content.clone(),
+            });
+        }
+    }
+    Ok(pins)
+}
+
+/// Pinned messages as standing context for the system prompt, newest sessions first and
+/// cut off at `PINNED_CONTEXT_CHARS`. `None` when nothing is pinned.
+pub fn pinned_context() -> Option<String> {
+    let pins = pinned().unwrap_or_else(|e| {
+        tracing::warn!("Failed to read pinned messages: {}", e);
+        Vec::new()
+    });
+    let mut context = String::from("Notes the user pinned from earlier conversations:");
+    let mut budget = PINNED_CONTEXT_CHARS;
+    let mut any = false;
+    for pin in pins {
+        let line = format!("\n- {}", pin.content.trim());
+        let len = line.chars().count();
+        if len > budget {
+            break;
+        }
+        budget -= len;
+        context.push_str(&line);
+        any = true;
+    }
+    any.then_some(context)
+}
+
+/// Every saved session, most recently updated first. Unreadable files are skipped.
+fn load_all() -> Result<Vec<Session>> {
+    let mut sessions: Vec<Session> = std::fs::read_dir(sessions_dir()?)?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|e| e == "json"))
+        .filter_map(|path| {
+            let content = std::fs::read_to_string(&path).ok()?;
+            match serde_json::from_str::<Session>(&content) {
+                Ok(session) => Some(session),
+                Err(e) => {
+                    tracing::warn!("Skipping session {:?}: {}", path, e);
+                    None
+                }
+            }
+        })
+        .collect();
+    sessions.sort_by_key(|s| std::cmp::Reverse(s.updated_at));
+    Ok(sessions)
+}
+
+pub fn delete(id: &str) -> Result<()> {
+    std::fs::remove_file(session_path(id)?)?;
+    Ok(())
+}
+
+fn file_size(id: &str) -> u64 {
+    session_path(id)
+        .and_then(|path| Ok(std::fs::metadata(path)?.len()))
+        .unwrap_or(0)
+}
+
+/// Enforce the retention limits in `policy`, deleting the oldest sessions first.
+/// Sessions with pinned messages are always kept. Returns how many were deleted.
+pub fn prune(policy: &HistoryConfig) -> Result<usize> {
+    if policy.max_conversations.is_none()
+        && policy.max_age_days.is_none()
+        && policy.max_disk_mb.is_none()
+    {
+        return Ok(0);
+    }
+    let cutoff = policy
+        .max_age_days
+        .map(|days| chrono::Utc::now().timestamp() - i64::from(days) * 86_400);
+    let max_bytes = policy.max_disk_mb.map(|mb| mb * 1024 * 1024);
+
+    let mut kept = 0;
+    let mut kept_bytes = 0;
+    let mut deleted = 0;
+    for session in load_all()? {
+        let size = file_size(&session.id);
+        let expired = cutoff.is_some_and(|cutoff| session.updated_at < cutoff)
+            || policy.max_conversations.is_some_and(|max| kept >= max)
+            || max_bytes.is_some_and(|max| kept_bytes + size > max);
+        if expired && session.pinned.is_empty() {
+            delete(&session.id)?;
+            deleted += 1;
+        } else {
+            kept += 1;
+            kept_bytes += size;
+        }
+    }
+    if deleted > 0 {
+        tracing::info!("Pruned {} old conversations", deleted);
+    }
+    Ok(deleted)
+}
+
+/// Delete every session last updated more than `days` days ago, pinned or not.
+/// Returns how many were deleted.
+pub fn purge(days: u32) -> Result<usize> {
+    let cutoff = chrono::Utc::now().timestamp() - i64::from(days) * 86_400;
+    let mut deleted = 0;
+    for session in load_all()? {
+        if session.updated_at <= cutoff {
+            delete(&session.id)?;
+            deleted += 1;
+        }
+    }
+    tracing::info!("Purged {} conversations older than {} days", deleted, days);
+    Ok(deleted)
+}
+
+/// Summaries of every saved session, most recently updated first
+pub fn list() -> Result<Vec<SessionSummary>> {
+    Ok(load_all()?.iter().map(SessionSummary::from).collect())
+}
+
+/// The `count` most recently updated sessions
+pub fn recent(count: usize) -> Vec<SessionSummary> {
+    let mut sessions = list().unwrap_or_else(|e| {
+        tracing::warn!("Failed to list sessions: {}", e);
+        Vec::new()
+    });
+    sessions.truncate(count);
+    sessions
+}
@@ -0,0 +1,174 @@
+use crate::config::{Config, Secrets};
+use anyhow::{anyhow, Result};
+use argon2::Argon2;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+const BUNDLE_FORMAT: &str = "rusty-clippy-settings";
+const BUNDLE_VERSION: u32 = 1;
+
+/// Everything needed to replicate a setup on another machine
+#[derive(Serialize, Deserialize)]
+struct SettingsBundle {
+    format: String,
+    bundle_version: u32,
+    /// The active config, without credentials
+    config: Config,
+    /// Saved profiles by name, without credentials
+    #[serde(default)]
+    profiles: HashMap<String, Config>,
+    /// Credentials, present only when exported with a passphrase
+    #[serde(default)]
+    secrets: Option<EncryptedSecrets>,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct BundleSecrets {
+    config: Secrets,
+    profiles: HashMap<String, Secrets>,
+}
+
+/// ChaCha20-Poly1305 ciphertext keyed by Argon2id(passphrase, salt); all fields base64
+#[derive(Serialize, Deserialize)]
+struct EncryptedSecrets {
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportSummary {
+    pub profiles: usize,
+    pub secrets_imported: bool,
+}
+
+/// Write the current config and all profiles to `path`. Credentials are
+/// included (encrypted) only when a passphrase is given.
+pub fn export(path: &Path, passphrase: Option<&str>) -> Result<()> {
+    // The saved settings, not this run's environment overrides
+    let mut config = Config::load_file()?;
+    let mut secrets = BundleSecrets {
+        config: config.take_secrets(),
+        ..Default::default()
+    };
+
+    let mut profiles = HashMap::new();
+    for (name, mut profile) in crate::profiles::load_all()? {
+        let profile_secrets = profile.take_secrets();
+        if !profile_secrets.is_empty() {
+            secrets.profiles.insert(name.clone(), profile_secrets);
+        }
+        profiles.insert(name, profile);
+    }
+
+    let secrets = match passphrase.filter(|p| !p.is_empty()) {
+        Some(passphrase) => Some(encrypt(&serde_json::to_vec(&secrets)?, passphrase)?),
+        None => None,
+    };
+
+    let bundle = SettingsBundle {
+        format: BUNDLE_FORMAT.to_string(),
+        bundle_version: BUNDLE_VERSION,
+        config,
+        profiles,
+        secrets,
+    };
+    std::fs::write(path, serde_json::to_string_pretty(&bundle)?)?;
+    Ok(())
+}
+
+/// Replace the current config and profiles with those in the bundle at `path`.
+/// Credentials already on this machine are kept unless the bundle provides new ones.
+pub fn import(path: &Path, passphrase: Option<&str>) -> Result<(Config, ImportSummary)> {
+    let content = std::fs::read_to_string(path)?;
+    let bundle: SettingsBundle =
+        serde_json::from_str(&content).map_err(|e| anyhow!("Not a valid settings file: {}", e))?;
+    if bundle.format != BUNDLE_FORMAT {
+        return Err(anyhow!("Not a Rusty Clippy settings file"));
+    }
+    if bundle.bundle_version > BUNDLE_VERSION {
+        return Err(anyhow!(
+            "Settings file is from a newer version of Rusty Clippy (bundle v{})",
+            bundle.bundle_version
+        ));
+    }
+
+    let secrets: Option<BundleSecrets> = match (&bundle.secrets, passphrase) {
+        (Some(encrypted), Some(passphrase)) if !passphrase.is_empty() => {
+            Some(serde_json::from_slice(&decrypt(encrypted, passphrase)?)?)
+        }
+        (Some(_), _) => {
+            tracing::info!(
+                "Settings bundle has encrypted secrets but no passphrase was given; skipping them"
+            );
+            None
+        }
+        (None, _) => None,
+    };
+    let secrets_imported = secrets.is_some();
+    let mut secrets = secrets.unwrap_or_default();
+
+    // Keep this machine's existing credentials where the bundle has none
+    let current = Config::load()?;
+    let mut config = bundle.config;
+    config.restore_secrets(current.clone().take_secrets());
+    config.restore_secrets(secrets.config);
+
+    for (name, mut profile) in bundle.profiles {
+        if let Some(profile_secrets) = secrets.profiles.remove(&name) {
+            profile.restore_secrets(profile_secrets);
+        }
+        crate::profiles::write_profile(&name, &profile)?;
+    }
+
+    config.save()?;
+    let summary = ImportSummary {
+        profiles: crate::profiles::list()?.profiles.len(),
+        secrets_imported,
+    };
+    Ok((config, summary))
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<Key> {
+    let mut key = Key::default();
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| anyhow!("Key derivation failed: {}", e))?;
+    Ok(key)
+}
+
+fn encrypt(plaintext: &[u8], passphrase: &str) -> Result<EncryptedSecrets> {
+    let mut salt = [0u8; 16];
+    OsRng.fill_bytes(&mut salt);
+    let cipher = ChaCha20Poly1305::new(&derive_key(passphrase, &salt)?);
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext)
+        .map_err(|_| anyhow!("Failed to encrypt secrets"))?;
+
+    Ok(EncryptedSecrets {
+        salt: BASE64.encode(salt),
+        nonce: BASE64.encode(nonce),
+        ciphertext: BASE64.encode(ciphertext),
+    })
+}
+
+fn decrypt(encrypted: &EncryptedSecrets, passphrase: &str) -> Result<Vec<u8>> {
+    let salt = BASE64.decode(&encrypted.salt)?;
+    let nonce = BASE64.decode(&encrypted.nonce)?;
+    let ciphertext = BASE64.decode(&encrypted.ciphertext)?;
+    if nonce.len() != 12 {
+        return Err(anyhow!("Corrupt secrets block in settings file"));
+    }
+
+    let cipher = ChaCha20Poly1305::new(&derive_key(passphrase, &salt)?);
+    cipher
+        .decrypt(Nonce::from_slice(&nonce), ciphertext.as_ref())
+        .map_err(|_| anyhow!("Wrong passphrase or corrupted settings file"))
+}
@@ -0,0 +1,169 @@
+use crate::config::{Config, LlmProviderType, ModelChoice, TtsEngineType};
+use crate::ConversationState;
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use std::path::Path;
+use tauri::AppHandle;
+use tokio::sync::RwLock;
+
+const HELP: &str = "Commands I understand:\n\
+/reset - start a new conversation\n\
+/model <name> - chat with another model, e.g. /model gpt-4o\n\
+/voice <name> - speak with another voice, e.g. /voice en_GB-alan-low\n\
+/temp <number> - how adventurous my replies are, e.g. /temp 0.3\n\
+/help - this list";
+
+/// What a slash command did, sent instead of a reply as `slash-command`
+#[derive(Debug, Clone, Serialize)]
+pub struct SlashCommandEvent {
+    /// The command without its slash, e.g. "model"
+    pub command: String,
+    pub text: String,
+    pub ok: bool,
+}
+
+/// The command at the start of `message`, and the rest of the line. Only a slash followed
+/// by letters counts, so a message starting with a path still goes to the model.
+fn split(message: &str) -> Option<(String, &str)> {
+    let rest = message.trim().strip_prefix('/')?;
+    let (name, args) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphabetic()) {
+        return None;
+    }
+    Some((name.to_lowercase(), args.trim()))
+}
+
+fn required<'a>(args: &'a str, usage: &str) -> Result<&'a str> {
+    if args.is_empty() {
+        Err(anyhow!("Usage: {}", usage))
+    } else {
+        Ok(args)
+    }
+}
+
+/// Save `config`, unless the change broke a setting that was fine in `before`.
+fn save(before: &Config, config: &Config) -> Result<()> {
+    let broken: Vec<String> = before.validate().into_iter().map(|e| e.field).collect();
+    if let Some(error) = config
+        .validate()
+        .into_iter()
+        .find(|e| !broken.contains(&e.field))
+    {
+        return Err(anyhow!(error.message));
+    }
+    config.save()
+}
+
+/// A model the tray offers whose name, menu label or file name is `name`, or else `name` on
+/// the current provider when it can run any model by name.
+fn find_model(config: &Config, name: &str) -> Result<ModelChoice> {
+    let wanted = name.to_lowercase();
+    let known = config.model_choices().into_iter().find(|choice| {
+        let file_stem = Path::new(&choice.model)
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_lowercase());
+        choice.model.to_lowercase() == wanted
+            || choice.label().to_lowercase() == wanted
+            || (choice.provider == LlmProviderType::BuiltIn
+                && file_stem.as_deref() == Some(&wanted))
+    });
+    if let Some(choice) = known {
+        return Ok(choice);
+    }
+    match config.llm_provider {
+        LlmProviderType::BuiltIn | LlmProviderType::Mock => Err(anyhow!(
+            "I don't know a model called '{}'. Try one from the tray's model menu.",
+            name
+        )),
+        _ => Ok(ModelChoice {
+            provider: config.llm_provider.clone(),
+            model: name.to_string(),
+        }),
+    }
+}
+
+async fn run(
+    app: &AppHandle,
+    state: &RwLock<ConversationState>,
+    name: &str,
+    args: &str,
+) -> Result<String> {
+    match name {
+        "reset" => {
+            crate::commands::start_new_conversation(app, state)
+                .await
+                .map_err(|e| anyhow!(e))?;
+            Ok("Fresh start! What would you like to talk about?".to_string())
+        }
+        "model" => {
+            let name = required(args, "/model <name>")?;
+            let before = Config::load()?;
+            let choice = find_model(&before, name)?;
+            let mut config = before.clone();
+            config.use_model(&choice);
+            save(&before, &config)?;
+            Ok(format!("Switched to {}.", choice.label()))
+        }
+        "voice" => {
+            let name = required(args, "/voice <name>")?;
+            let before = Config::load()?;
+            let mut config = before.clone();
+            match config.tts_engine {
+                TtsEngineType::Piper => {
+                    let voice = crate::tts::resolve_local(name).ok_or_else(|| {
+                        anyhow!(
+                            "The voice '{}' isn't downloaded. You can get it in Settings → \
+                             Text-to-Speech.",
+                            name
+                        )
+                    })?;
+                    config.tts_voice = Some(voice);
+                }
+                TtsEngineType::OpenAI => config.openai_tts_voice = name.to_string(),
+                TtsEngineType::ElevenLabs => config.elevenlabs_voice_id = name.to_string(),
+            }
+            save(&before, &config)?;
+            Ok(format!("I'll speak as {} from now on.", name))
+        }
+        "temp" => {
+            let value = required(args, "/temp <number>")?;
+            let temperature: f32 = value
+                .parse()
+                .map_err(|_| anyhow!("'{}' isn't a number. Try something like /temp 0.3", value))?;
+            let before = Config::load()?;
+            let mut config = before.clone();
+            config.temperature = temperature;
+            save(&before, &config)?;
+            Ok(format!("Temperature set to {}.", temperature))
+        }
+        "help" => Ok(HELP.to_string()),
+        _ => Err(anyhow!(
+            "I don't know /{}. Type /help to see what I can do.",
+            name
+        )),
+    }
+}
+
+/// Carry out `message` if it's a slash command, instead of sending it to the model.
+/// Returns `None` for ordinary messages.
+pub async fn handle(
+    app: &AppHandle,
+    state: &RwLock<ConversationState>,
+    message: &str,
+) -> Option<SlashCommandEvent> {
+    let (name, args) = split(message)?;
+    let (text, ok) = match run(app, state, &name, args).await {
+        Ok(text) => (text, true),
+        Err(e) => (e.to_string(), false),
+    };
+    tracing::info!(
+        "Slash command /{}: {}",
+        name,
+        if ok { "done" } else { "failed" }
+    );
+    Some(SlashCommandEvent {
+        command: name,
+        text,
+        ok,
+    })
+}
@@ -0,0 +1,170 @@
+use crate::config::Config;
+use anyhow::{anyhow, Result};
+use rodio::{buffer::SamplesBuffer, Decoder, OutputStream, Sink, Source};
+use std::f32::consts::TAU;
+use std::path::PathBuf;
+
+const SAMPLE_RATE: u32 = 22050;
+/// Formats an override file in the sounds folder may use, tried in this order
+const EXTENSIONS: [&str; 4] = ["wav", "ogg", "mp3", "flac"];
+
+/// One of Clippy's short classic sound effects
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Effect {
+    /// Clippy was summoned
+    Appear,
+    /// Clippy has something to say unprompted
+    Knock,
+    /// A reply finished
+    Tada,
+}
+
+impl Effect {
+    /// File name, without extension, of a replacement for this effect in the sounds folder
+    pub fn name(self) -> &'static str {
+        match self {
+            Effect::Appear => "appear",
+            Effect::Knock => "knock",
+            Effect::Tada => "tada",
+        }
+    }
+
+    /// The built-in version of this effect.
+    fn synthesize(self) -> Vec<f32> {
+        let mut out = Vec::new();
+        match self {
+            // A quick rising two-note chime
+            Effect::Appear => {
+                mix(&mut out, 0.0, &tone(659.3, 0.09, 30.0));
+                mix(&mut out, 0.07, &tone(987.8, 0.18, 18.0));
+            }
+            // Two soft knocks on the screen
+            Effect::Knock => {
+                for start in [0.0, 0.16] {
+                    mix(&mut out, start, &thump(0.08));
+                }
+            }
+            // An arpeggio up to a held chord
+            Effect::Tada => {
+                for (i, freq) in [523.3, 659.3, 784.0].into_iter().enumerate() {
+                    mix(&mut out, i as f32 * 0.06, &tone(freq, 0.1, 25.0));
+                }
+                for freq in [523.3, 659.3, 784.0, 1046.5] {
+                    mix(&mut out, 0.2, &tone(freq, 0.45, 6.0));
+                }
+            }
+        }
+        normalize(&mut out);
+        out
+    }
+}
+
+/// A decaying sine with a touch of its octave, so it rings rather than beeps.
+fn tone(freq: f32, seconds: f32, decay: f32) -> Vec<f32> {
+    let len = (seconds * SAMPLE_RATE as f32) as usize;
+    (0..len)
+        .map(|i| {
+            let t = i as f32 / SAMPLE_RATE as f32;
+            let wave = (TAU * freq * t).sin() + 0.3 * (TAU * 2.0 * freq * t).sin();
+            wave * (-decay * t).exp() * attack(t)
+        })
+        .collect()
+}
+
+/// A short low knock: a sine that drops in pitch as it dies away.
+fn thump(seconds: f32) -> Vec<f32> {
+    let len = (seconds * SAMPLE_RATE as f32) as usize;
+    let mut phase = 0.0;
+    (0..len)
+        .map(|i| {
+            let t = i as f32 / SAMPLE_RATE as f32;
+            phase += TAU * (180.0 - 900.0 * t).max(70.0) / SAMPLE_RATE as f32;
+            phase.sin() * (-45.0 * t).exp() * attack(t)
+        })
+        .collect()
+}
+
+/// A few milliseconds of fade-in so notes don't click.
+fn attack(t: f32) -> f32 {
+    (t / 0.004).min(1.0)
+}
+
+/// Add `samples` into `out` starting `at` seconds in, growing `out` as needed.
+fn mix(out: &mut Vec<f32>, at: f32, samples: &[f32]) {
+    let start = (at * SAMPLE_RATE as f32) as usize;
+    if out.len() < start + samples.len() {
+        out.resize(start + samples.len(), 0.0);
+    }
+    for (o, s) in out[start..].iter_mut().zip(samples) {
+        *o += s;
+    }
+}
+
+/// Scale to a comfortable peak so mixed notes don't clip.
+fn normalize(samples: &mut [f32]) {
+    let peak = samples.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+    if peak > 0.0 {
+        for s in samples.iter_mut() {
+            *s *= 0.8 / peak;
+        }
+    }
+}
+
+/// The sounds folder in the data dir, where `appear.wav` and friends replace the built-in
+/// effects (e.g. with ones from an imported character).
+pub fn sounds_dir() -> Result<PathBuf> {
+    Ok(Config::data_dir()?.join("sounds"))
+}
+
+/// A replacement file for `effect` in the sounds folder, if there is one.
+fn override_for(effect: Effect) -> Option<PathBuf> {
+    let dir = sounds_dir().ok()?;
+    EXTENSIONS
+        .iter()
+        .map(|ext| dir.join(format!("{}.{}", effect.name(), ext)))
+        .find(|path| path.is_file())
+}
+
+fn play_blocking(effect: Effect, volume: f32) -> Result<()> {
+    let (_stream, stream_handle) =
+        OutputStream::try_default().map_err(|e| anyhow!("Failed to open audio output: {}", e))?;
+    let sink =
+        Sink::try_new(&stream_handle).map_err(|e| anyhow!("Failed to create audio sink: {}", e))?;
+    match override_for(effect) {
+        Some(path) => {
+            let file = std::fs::File::open(&path)
+                .map_err(|e| anyhow!("Failed to open {}: {}", path.display(), e))?;
+            let source = Decoder::new(std::io::BufReader::new(file))
+                .map_err(|e| anyhow!("Failed to decode {}: {}", path.display(), e))?;
+            sink.append(source.amplify(volume));
+        }
+        None => {
+            let source = SamplesBuffer::new(1, SAMPLE_RATE, effect.synthesize());
+            sink.append(source.amplify(volume));
+        }
+    }
+    // Effects aren't speech, so `tts::stop` leaves them to finish on their own
+    sink.sleep_until_end();
+    Ok(())
+}
+
+/// Play `effect` in the background, unless sound effects are turned off. Failures are only
+/// logged.
+pub fn play(effect: Effect) {
+    let Ok(config) = Config::load() else {
+        return;
+    };
+    if !config.sound_effects
+        || config.sound_volume == 0
+        || crate::app_rules::silenced()
+        || crate::dnd::active()
+    {
+        return;
+    }
+    let volume = config.sound_volume.min(100) as f32 / 100.0;
+    tauri::async_runtime::spawn_blocking(move || {
+        if let Err(e) = play_blocking(effect, volume) {
+            tracing::warn!("Failed to play {} sound: {}", effect.name(), e);
+        }
+    });
+}
@@ -0,0 +1,383 @@
+use anyhow::Result;
+use chrono::{Days, Local};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+/// How long counter bumps gather in memory before stats.json is rewritten
+const SAVE_DELAY: Duration = Duration::from_secs(5);
+/// How many days back the dashboard charts
+const DASHBOARD_DAYS: u64 = 30;
+
+/// Window `get_stats` aggregates over, counted back from today
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StatsPeriod {
+    Day,
+    Week,
+    Month,
+    Year,
+    All,
+}
+
+impl StatsPeriod {
+    fn days(self) -> Option<u64> {
+        match self {
+            Self::Day => Some(1),
+            Self::Week => Some(7),
+            Self::Month => Some(30),
+            Self::Year => Some(365),
+            Self::All => None,
+        }
+    }
+}
+
+/// Counters for a single local calendar day
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct DayStats {
+    #[serde(default)]
+    messages: u64,
+    #[serde(default)]
+    providers: BTreeMap<String, u64>,
+    /// Estimated tokens sent and received, by provider
+    #[serde(default)]
+    provider_tokens: BTreeMap<String, u64>,
+    /// Estimated spend in US dollars, by provider
+    #[serde(default)]
+    provider_costs: BTreeMap<String, f64>,
+    #[serde(default)]
+    tts_utterances: u64,
+    #[serde(default)]
+    responses: u64,
+    #[serde(default)]
+    response_ms_total: u64,
+    #[serde(default)]
+    pomodoros: u64,
+    #[serde(default)]
+    focus_minutes: u64,
+}
+
+/// Everything in stats.json, keyed by `YYYY-MM-DD` so keys sort chronologically
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct StatsStore {
+    #[serde(default)]
+    days: BTreeMap<String, DayStats>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UsageStats {
+    pub period: StatsPeriod,
+    pub messages: u64,
+    pub by_provider: BTreeMap<String, u64>,
+    pub tokens: u64,
+    pub cost_usd: f64,
+    pub tts_utterances: u64,
+    pub avg_response_ms: Option<u64>,
+    pub pomodoros: u64,
+    pub focus_minutes: u64,
+    pub active_days: usize,
+    /// First day with any recorded activity in the period
+    pub since: Option<String>,
+}
+
+/// One day's bar in the dashboard charts
+#[derive(Debug, Clone, Serialize)]
+pub struct DailyUsage {
+    pub date: String,
+    pub messages: u64,
+    pub tokens: u64,
+    pub cost_usd: f64,
+}
+
+/// One provider's share of the dashboard period
+#[derive(Debug, Clone, Serialize)]
+pub struct ProviderUsage {
+    pub provider: String,
+    pub messages: u64,
+    pub tokens: u64,
+    pub cost_usd: f64,
+}
+
+/// Everything the usage dashboard shows, in one call
+#[derive(Debug, Clone, Serialize)]
+pub struct DashboardData {
+    /// The last `DASHBOARD_DAYS` days, oldest first, including days without activity
+    pub daily: Vec<DailyUsage>,
+    /// Over the same days, busiest first
+    pub providers: Vec<ProviderUsage>,
+    pub month: UsageStats,
+    pub all_time: UsageStats,
+    /// Spend since the first of this calendar month
+    pub cost_this_month_usd: f64,
+    /// `None` if the data directory couldn't be measured
+    pub storage: Option<crate::models::StorageUsage>,
+}
+
+/// Loaded on first use and written back shortly after it changes (see `schedule_save`)
+static STORE: Mutex<Option<StatsStore>> = Mutex::new(None);
+/// Whether changes are waiting for a save already on its way
+static SAVE_PENDING: AtomicBool = AtomicBool::new(false);
+
+fn stats_path() -> Result<PathBuf> {
+    let data_dir =
+        dirs::data_dir().ok_or_else(|| anyhow::anyhow!("Could not find data directory"))?;
+    let dir = data_dir.join("rusty-clippy");
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir.join("stats.json"))
+}
+
+fn load_store() -> StatsStore {
+    stats_path()
+        .ok()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// Apply `f` to today's counters, to be persisted shortly.
+fn update_today(f: impl FnOnce(&mut DayStats)) {
+    let Ok(mut guard) = STORE.lock() else {
+        return;
+    };
+    let store = guard.get_or_insert_with(load_store);
+    let today = Local::now().format(DATE_FORMAT).to_string();
+    f(store.days.entry(today).or_default());
+    drop(guard);
+    schedule_save();
+}
+
+/// Save the store after `SAVE_DELAY`, on a blocking thread. Counters bumped in the meantime,
+/// such as a reply's message, response and usage, go out with that one write.
+fn schedule_save() {
+    if SAVE_PENDING.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    tauri::async_runtime::spawn(async {
+        tokio::time::sleep(SAVE_DELAY).await;
+        let _ = tauri::async_runtime::spawn_blocking(flush).await;
+    });
+}
+
+/// Write unsaved counters to stats.json now, e.g. as the app exits. Stats are best-effort, so
+/// failures are only logged.
+pub fn flush() {
+    if !SAVE_PENDING.swap(false, Ordering::SeqCst) {
+        return;
+    }
+    let content = {
+        let Ok(guard) = STORE.lock() else {
+            return;
+        };
+        let Some(store) = guard.as_ref() else {
+            return;
+        };
+        serde_json::to_string(store)
+    };
+    let saved = content
+        .map_err(anyhow::Error::from)
+        .and_then(|content| crate::config::write_atomic(&stats_path()?, content.as_bytes()));
+    if let Err(e) = saved {
+        tracing::warn!("Failed to save usage stats: {}", e);
+    }
+}
+
+/// Count a message the user sent to `provider`.
+pub fn record_message(provider: &str) {
+    update_today(|day| {
+        day.messages += 1;
+        *day.providers.entry(provider.to_string()).or_default() += 1;
+    });
+}
+
+/// Count a completed reply and how long it took from send to the last token.
+pub fn record_response(elapsed: Duration) {
+    update_today(|day| {
+        day.responses += 1;
+        day.response_ms_total += elapsed.as_millis() as u64;
+    });
+}
+
+/// Count the tokens and cost of a completion from `provider`.
+pub fn record_usage(provider: &str, usage: crate::pricing::Usage) {
+    update_today(|day| {
+        *day.provider_tokens.entry(provider.to_string()).or_default() += usage.tokens;
+        *day.provider_costs.entry(provider.to_string()).or_default() += usage.cost_usd;
+    });
+}
+
+/// Estimated tokens used with `provider` so far today.
+pub fn tokens_today(provider: &str) -> u64 {
+    let Ok(mut guard) = STORE.lock() else {
+        return 0;
+    };
+    let today = Local::now().format(DATE_FORMAT).to_string();
+    guard
+        .get_or_insert_with(load_store)
+        .days
+        .get(&today)
+        .and_then(|day| day.provider_tokens.get(provider).copied())
+        .unwrap_or(0)
+}
+
+pub fn record_tts_utterance() {
+    update_today(|day| day.tts_utterances += 1);
+}
+
+/// Count a finished focus session of `minutes`.
+pub fn record_pomodoro(minutes: u32) {
+    update_today(|day| {
+        day.pomodoros += 1;
+        day.focus_minutes += minutes as u64;
+    });
+}
+
+/// Totals for `period`, ending today.
+pub fn summary(period: StatsPeriod) -> UsageStats {
+    let Ok(mut guard) = STORE.lock() else {
+        return summarize(period, &StatsStore::default());
+    };
+    summarize(period, guard.get_or_insert_with(load_store))
+}
+
+fn summarize(period: StatsPeriod, store: &StatsStore) -> UsageStats {
+    let start = period.days().and_then(|days| {
+        Local::now()
+            .date_naive()
+            .checked_sub_days(Days::new(days - 1))
+            .map(|date| date.format(DATE_FORMAT).to_string())
+    });
+    let days = match &start {
+        Some(start) => store.days.range(start.clone()..),
+        None => store.days.range(String::new()..),
+    };
+
+    let mut stats = UsageStats {
+        period,
+        messages: 0,
+        by_provider: BTreeMap::new(),
+        tokens: 0,
+        cost_usd: 0.0,
+        tts_utterances: 0,
+        avg_response_ms: None,
+        pomodoros: 0,
+        focus_minutes: 0,
+        active_days: 0,
+        since: None,
+    };
+    let (mut responses, mut response_ms) = (0u64, 0u64);
+    for (date, day) in days {
+        stats.since.get_or_insert_with(|| date.clone());
+        stats.active_days += 1;
+        stats.messages += day.messages;
+        stats.tts_utterances += day.tts_utterances;
+        stats.pomodoros += day.pomodoros;
+        stats.focus_minutes += day.focus_minutes;
+        for (provider, count) in &day.providers {
+            *stats.by_provider.entry(provider.clone()).or_default() += count;
+        }
+        stats.tokens += day.provider_tokens.values().sum::<u64>();
+        stats.cost_usd += day.provider_costs.values().sum::<f64>();
+        responses += day.responses;
+        response_ms += day.response_ms_total;
+    }
+    stats.avg_response_ms = response_ms.checked_div(responses);
+    stats
+}
+
+fn month_start() -> String {
+    Local::now().format("%Y-%m-01").to_string()
+}
+
+/// Spend with `provider` since the first of this calendar month.
+pub fn cost_this_month(provider: &str) -> f64 {
+    let Ok(mut guard) = STORE.lock() else {
+        return 0.0;
+    };
+    guard
+        .get_or_insert_with(load_store)
+        .days
+        .range(month_start()..)
+        .filter_map(|(_, day)| day.provider_costs.get(provider))
+        .sum()
+}
+
+/// Spend since the first of this calendar month, across providers.
+fn month_cost(store: &StatsStore) -> f64 {
+    store
+        .days
+        .range(month_start()..)
+        .map(|(_, day)| day.provider_costs.values().sum::<f64>())
+        .sum()
+}
+
+/// Usage over the last `DASHBOARD_DAYS` days, all-time totals and disk usage.
+pub fn dashboard() -> DashboardData {
+    let storage = crate::models::storage_usage()
+        .inspect_err(|e| tracing::warn!("Failed to compute storage usage: {}", e))
+        .ok();
+    let Ok(mut guard) = STORE.lock() else {
+        return dashboard_from(&StatsStore::default(), storage);
+    };
+    dashboard_from(guard.get_or_insert_with(load_store), storage)
+}
+
+fn provider_entry<'a>(
+    providers: &'a mut BTreeMap<String, ProviderUsage>,
+    name: &str,
+) -> &'a mut ProviderUsage {
+    providers
+        .entry(name.to_string())
+        .or_insert_with(|| ProviderUsage {
+            provider: name.to_string(),
+            messages: 0,
+            tokens: 0,
+            cost_usd: 0.0,
+        })
+}
+
+fn dashboard_from(
+    store: &StatsStore,
+    storage: Option<crate::models::StorageUsage>,
+) -> DashboardData {
+    let today = Local::now().date_naive();
+    let mut daily = Vec::new();
+    let mut providers: BTreeMap<String, ProviderUsage> = BTreeMap::new();
+    for back in (0..DASHBOARD_DAYS).rev() {
+        let Some(date) = today.checked_sub_days(Days::new(back)) else {
+            continue;
+        };
+        let date = date.format(DATE_FORMAT).to_string();
+        let day = store.days.get(&date).cloned().unwrap_or_default();
+        daily.push(DailyUsage {
+            messages: day.messages,
+            tokens: day.provider_tokens.values().sum(),
+            cost_usd: day.provider_costs.values().sum(),
+            date,
+        });
+
+        for (name, count) in &day.providers {
+            provider_entry(&mut providers, name).messages += count;
+        }
+        for (name, tokens) in &day.provider_tokens {
+            provider_entry(&mut providers, name).tokens += tokens;
+        }
+        for (name, cost) in &day.provider_costs {
+            provider_entry(&mut providers, name).cost_usd += cost;
+        }
+    }
+    let mut providers: Vec<ProviderUsage> = providers.into_values().collect();
+    providers.sort_by_key(|p| std::cmp::Reverse(p.messages));
+
+    DashboardData {
+        daily,
+        providers,
+        month: summarize(StatsPeriod::Month, store),
+        all_time: summarize(StatsPeriod::All, store),
+        cost_this_month_usd: month_cost(store),
+        storage,
+    }
+}
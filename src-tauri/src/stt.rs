@@ -0,0 +1,113 @@
+use crate::config::{Config, SttBackend};
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+use std::time::Duration;
+
+const OPENAI_TRANSCRIPTIONS_URL: &str = "https://api.openai.com/v1/audio/transcriptions";
+/// A minute of speech uploads and transcribes in a few seconds; this leaves room for slow links
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(120);
+/// OpenAI turns away uploads over 25 MB
+const MAX_AUDIO_BYTES: usize = 25 * 1024 * 1024;
+
+#[derive(Deserialize)]
+struct Transcription {
+    text: String,
+}
+
+/// The file name OpenAI is told, whose extension is how it knows the format
+fn file_name(mime_type: &str) -> &'static str {
+    let essence = mime_type.split(';').next().unwrap_or_default().trim();
+    match essence {
+        "audio/ogg" => "speech.ogg",
+        "audio/mp4" | "audio/m4a" | "audio/x-m4a" => "speech.m4a",
+        "audio/mpeg" | "audio/mp3" => "speech.mp3",
+        "audio/wav" | "audio/x-wav" | "audio/wave" => "speech.wav",
+        _ => "speech.webm",
+    }
+}
+
+/// A `multipart/form-data` body of `fields` followed by the audio as `file`
+fn form(boundary: &str, fields: &[(&str, &str)], mime_type: &str, audio: &[u8]) -> Vec<u8> {
+    let mut body = Vec::with_capacity(audio.len() + 512);
+    for (name, value) in fields {
+        body.extend_from_slice(
+            format!(
+                "--{}\r\nContent-Disposition: form-data; name=\"{}\"\r\n\r\n{}\r\n",
+                boundary, name, value
+            )
+            .as_bytes(),
+        );
+    }
+    body.extend_from_slice(
+        format!(
+            "--{}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"{}\"\r\n\
+             Content-Type: {}\r\n\r\n",
+            boundary,
+            file_name(mime_type),
+            mime_type
+        )
+        .as_bytes(),
+    );
+    body.extend_from_slice(audio);
+    body.extend_from_slice(format!("\r\n--{}--\r\n", boundary).as_bytes());
+    body
+}
+
+/// What was said in `audio`, a recording from the chat's microphone button
+pub async fn transcribe(config: &Config, audio: &[u8], mime_type: &str) -> Result<String> {
+    if audio.is_empty() {
+        return Err(anyhow!("Nothing was recorded"));
+    }
+    if audio.len() > MAX_AUDIO_BYTES {
+        return Err(anyhow!(
+            "That recording is too long to transcribe; keep it under a few minutes"
+        ));
+    }
+    match config.stt.backend {
+        SttBackend::Off => Err(anyhow!("Speech input is turned off in Settings")),
+        SttBackend::OpenAI => openai(config, audio, mime_type).await,
+    }
+}
+
+async fn openai(config: &Config, audio: &[u8], mime_type: &str) -> Result<String> {
+    let key = config
+        .openai_api_key
+        .as_deref()
+        .filter(|k| !k.is_empty())
+        .ok_or_else(|| anyhow!("Transcribing needs an OpenAI API key; add one in Settings"))?;
+    let mut fields = vec![
+        ("model", config.stt.model.trim()),
+        ("response_format", "json"),
+    ];
+    if let Some(language) = config.stt.language.as_deref().map(str::trim) {
+        fields.push(("language", language));
+    }
+    let boundary = format!(
+        "rusty-clippy-{}",
+        chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default()
+    );
+    let response = reqwest::Client::new()
+        .post(OPENAI_TRANSCRIPTIONS_URL)
+        .bearer_auth(key)
+        .header(
+            reqwest::header::CONTENT_TYPE,
+            format!("multipart/form-data; boundary={}", boundary),
+        )
+        .body(form(&boundary, &fields, mime_type, audio))
+        .timeout(REQUEST_TIMEOUT)
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let text = response.text().await.unwrap_or_default();
+        return Err(anyhow!(
+            "OpenAI transcription answered {}: {}",
+            status,
+            crate::redact::redact(&text)
+        ));
+    }
+    let transcription: Transcription = response.json().await?;
+    let text = transcription.text.trim().to_string();
+    tracing::info!("Transcribed {} bytes of speech", audio.len());
+    Ok(text)
+}
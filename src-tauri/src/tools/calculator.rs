@@ -0,0 +1,446 @@
+use super::Tool;
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use regex::Regex;
+use std::sync::LazyLock;
+use tauri::AppHandle;
+
+/// Deepest nesting of parentheses, signs and exponents the parser follows. The model writes
+/// the expression, so this keeps a pathological one from overflowing the stack.
+const MAX_DEPTH: usize = 64;
+
+/// "<amount> <unit> to|in|into <unit>"
+static CONVERSION: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?i)^\s*(.+?)\s*([a-z°µ][a-z°µ²/0-9]*)\s+(?:to|in|into|as)\s+([a-z°µ][a-z°µ²/0-9]*)\s*$",
+    )
+    .unwrap()
+});
+
+/// Deterministic arithmetic and unit conversion, so Clippy stops doing sums by feel
+pub struct Calculator;
+
+#[async_trait]
+impl Tool for Calculator {
+    fn name(&self) -> &'static str {
+        "calculator"
+    }
+
+    fn description(&self) -> &'static str {
+        "Evaluates arithmetic exactly and converts units. Supports + - * / % ^, parentheses, \
+         sqrt, abs, round, floor, ceil, ln, log, sin, cos, tan, min, max, pi and e, and \
+         conversions such as \"3.5 km to mi\", \"180 lb in kg\" or \"72 f to c\"."
+    }
+
+    fn label(&self) -> &'static str {
+        "Computed"
+    }
+
+    fn parameters(&self) -> &'static str {
+        r#"{"expression": "the calculation or conversion, e.g. \"(17 * 3) / 4\""}"#
+    }
+
+    async fn call(&self, _app: &AppHandle, arguments: serde_json::Value) -> Result<String> {
+        let expression = arguments
+            .get("expression")
+            .and_then(|e| e.as_str())
+            .ok_or_else(|| anyhow!("Missing 'expression'"))?;
+        calculate(expression)
+    }
+}
+
+/// Evaluate `input` as a unit conversion or a plain expression, as "input = result"
+pub fn calculate(input: &str) -> Result<String> {
+    if let Some(caps) = CONVERSION.captures(input) {
+        if let (Some(from), Some(to)) = (unit(&caps[2]), unit(&caps[3])) {
+            let amount = evaluate(&caps[1])?;
+            if from.dimension != to.dimension {
+                return Err(anyhow!(
+                    "Can't convert {} to {}: one is a {} and the other a {}",
+                    from.symbol,
+                    to.symbol,
+                    from.dimension,
+                    to.dimension
+                ));
+            }
+            let base = amount * from.scale + from.offset;
+            let converted = (base - to.offset) / to.scale;
+            return Ok(format!(
+                "{} {} = {} {}",
+                format_number(amount),
+                from.symbol,
+                format_number(converted),
+                to.symbol
+            ));
+        }
+    }
+    let value = evaluate(input)?;
+    Ok(format!("{} = {}", input.trim(), format_number(value)))
+}
+
+/// Up to 12 significant digits, without trailing zeros
+fn format_number(value: f64) -> String {
+    if value == value.trunc() && value.abs() < 1e15 {
+        return format!("{}", value as i64);
+    }
+    if value.abs() >= 1e15 || value.abs() < 1e-6 {
+        return format!("{:e}", value);
+    }
+    let digits_before = value.abs().log10().floor().max(0.0) as usize + 1;
+    let decimals = 12usize.saturating_sub(digits_before);
+    let text = format!("{:.*}", decimals, value);
+    text.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+struct Unit {
+    symbol: &'static str,
+    dimension: &'static str,
+    /// value in the dimension's base unit = value * scale + offset
+    scale: f64,
+    offset: f64,
+}
+
+/// (aliases, dimension, scale to the dimension's base unit); the first alias is the symbol
+const UNITS: &[(&[&str], &str, f64)] = &[
+    // Length, in metres
+    (
+        &[
+            "mm",
+            "millimeter",
+            "millimeters",
+            "millimetre",
+            "millimetres",
+        ],
+        "length",
+        0.001,
+    ),
+    (
+        &[
+            "cm",
+            "centimeter",
+            "centimeters",
+            "centimetre",
+            "centimetres",
+        ],
+        "length",
+        0.01,
+    ),
+    (&["m", "meter", "meters", "metre", "metres"], "length", 1.0),
+    (
+        &["km", "kilometer", "kilometers", "kilometre", "kilometres"],
+        "length",
+        1000.0,
+    ),
+    (&["in", "inch", "inches"], "length", 0.0254),
+    (&["ft", "foot", "feet"], "length", 0.3048),
+    (&["yd", "yard", "yards"], "length", 0.9144),
+    (&["mi", "mile", "miles"], "length", 1609.344),
+    (&["nmi", "nauticalmile", "nauticalmiles"], "length", 1852.0),
+    // Mass, in kilograms
+    (&["mg", "milligram", "milligrams"], "mass", 1e-6),
+    (&["g", "gram", "grams"], "mass", 0.001),
+    (
+        &["kg", "kilogram", "kilograms", "kilo", "kilos"],
+        "mass",
+        1.0,
+    ),
+    (&["t", "tonne", "tonnes"], "mass", 1000.0),
+    (&["oz", "ounce", "ounces"], "mass", 0.028_349_523_125),
+    (&["lb", "lbs", "pound", "pounds"], "mass", 0.453_592_37),
+    (&["st", "stone", "stones"], "mass", 6.350_293_18),
+    // Volume, in litres
+    (
+        &[
+            "ml",
+            "milliliter",
+            "milliliters",
+            "millilitre",
+            "millilitres",
+        ],
+        "volume",
+        0.001,
+    ),
+    (&["l", "liter", "liters", "litre", "litres"], "volume", 1.0),
+    (
+        &["tsp", "teaspoon", "teaspoons"],
+        "volume",
+        0.004_928_921_593_75,
+    ),
+    (
+        &["tbsp", "tablespoon", "tablespoons"],
+        "volume",
+        0.014_786_764_781_25,
+    ),
+    (
+        &["floz", "fluidounce", "fluidounces"],
+        "volume",
+        0.029_573_529_562_5,
+    ),
+    (&["cup", "cups"], "volume", 0.236_588_236_5),
+    (&["pt", "pint", "pints"], "volume", 0.473_176_473),
+    (&["qt", "quart", "quarts"], "volume", 0.946_352_946),
+    (&["gal", "gallon", "gallons"], "volume", 3.785_411_784),
+    // Time, in seconds
+    (&["ms", "millisecond", "milliseconds"], "time", 0.001),
+    (&["s", "sec", "secs", "second", "seconds"], "time", 1.0),
+    (&["min", "mins", "minute", "minutes"], "time", 60.0),
+    (&["h", "hr", "hrs", "hour", "hours"], "time", 3600.0),
+    (&["d", "day", "days"], "time", 86_400.0),
+    (&["wk", "week", "weeks"], "time", 604_800.0),
+    (&["yr", "year", "years"], "time", 31_557_600.0),
+    // Speed, in metres per second
+    (&["m/s", "mps"], "speed", 1.0),
+    (&["km/h", "kph", "kmh"], "speed", 1.0 / 3.6),
+    (&["mph"], "speed", 0.447_04),
+    (&["kn", "knot", "knots"], "speed", 0.514_444_444_444),
+    // Area, in square metres
+    (&["m²", "m2", "sqm"], "area", 1.0),
+    (&["km²", "km2"], "area", 1e6),
+    (&["ft²", "ft2", "sqft"], "area", 0.092_903_04),
+    (&["ha", "hectare", "hectares"], "area", 10_000.0),
+    (&["acre", "acres"], "area", 4_046.856_422_4),
+    // Data, in bytes
+    (&["bit", "bits"], "data size", 0.125),
+    (&["B", "byte", "bytes"], "data size", 1.0),
+    (&["KB", "kilobyte", "kilobytes"], "data size", 1e3),
+    (&["MB", "megabyte", "megabytes"], "data size", 1e6),
+    (&["GB", "gigabyte", "gigabytes"], "data size", 1e9),
+    (&["TB", "terabyte", "terabytes"], "data size", 1e12),
+    (&["KiB", "kibibyte", "kibibytes"], "data size", 1024.0),
+    (&["MiB", "mebibyte", "mebibytes"], "data size", 1_048_576.0),
+    (
+        &["GiB", "gibibyte", "gibibytes"],
+        "data size",
+        1_073_741_824.0,
+    ),
+];
+
+fn unit(name: &str) -> Option<Unit> {
+    let lowered = name.to_lowercase();
+    // Temperatures need an offset as well as a scale; their base unit is the kelvin
+    let temperature = |symbol, scale, offset| Unit {
+        symbol,
+        dimension: "temperature",
+        scale,
+        offset,
+    };
+    match lowered.trim_start_matches('°') {
+        "c" | "celsius" => return Some(temperature("°C", 1.0, 273.15)),
+        "f" | "fahrenheit" => return Some(temperature("°F", 5.0 / 9.0, 273.15 - 32.0 * 5.0 / 9.0)),
+        "k" | "kelvin" => return Some(temperature("K", 1.0, 0.0)),
+        _ => {}
+    }
+    UNITS
+        .iter()
+        .find(|(aliases, _, _)| aliases.iter().any(|a| a.to_lowercase() == lowered))
+        .map(|(aliases, dimension, scale)| Unit {
+            symbol: aliases[0],
+            dimension,
+            scale: *scale,
+            offset: 0.0,
+        })
+}
+
+/// Evaluate an arithmetic expression
+pub fn evaluate(expression: &str) -> Result<f64> {
+    let mut parser = Parser {
+        chars: expression.chars().filter(|c| !c.is_whitespace()).collect(),
+        pos: 0,
+        depth: 0,
+    };
+    let value = parser.expression()?;
+    if let Some(c) = parser.peek() {
+        return Err(anyhow!("Unexpected '{}' in the expression", c));
+    }
+    if !value.is_finite() {
+        return Err(anyhow!("The result isn't a finite number"));
+    }
+    Ok(value)
+}
+
+/// Recursive-descent parser: expression = term (+|- term)*, term = unary (*|/|% unary)*,
+/// unary = -unary | power, power = primary (^ unary)?
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+    /// How many `unary` calls are open; every level of nesting goes through one
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expression(&mut self) -> Result<f64> {
+        let mut value = self.term()?;
+        loop {
+            if self.eat('+') {
+                value += self.term()?;
+            } else if self.eat('-') || self.eat('−') {
+                value -= self.term()?;
+            } else {
+                return Ok(value);
+            }
+        }
+    }
+
+    fn term(&mut self) -> Result<f64> {
+        let mut value = self.unary()?;
+        loop {
+            if self.eat('*') || self.eat('×') || self.eat('x') {
+                value *= self.unary()?;
+            } else if self.eat('/') || self.eat('÷') {
+                let divisor = self.unary()?;
+                if divisor == 0.0 {
+                    return Err(anyhow!("Division by zero"));
+                }
+                value /= divisor;
+            } else if self.eat('%') {
+                value %= self.unary()?;
+            } else {
+                return Ok(value);
+            }
+        }
+    }
+
+    fn unary(&mut self) -> Result<f64> {
+        if self.depth == MAX_DEPTH {
+            return Err(anyhow!("The expression is nested too deeply"));
+        }
+        self.depth += 1;
+        let value = if self.eat('-') || self.eat('−') {
+            self.unary().map(|value| -value)
+        } else if self.eat('+') {
+            self.unary()
+        } else {
+            self.power()
+        };
+        self.depth -= 1;
+        value
+    }
+
+    fn power(&mut self) -> Result<f64> {
+        let base = self.primary()?;
+        if self.eat('^') {
+            // Right-associative, and binding tighter than a leading minus: -2^2 is -4
+            return Ok(base.powf(self.unary()?));
+        }
+        Ok(base)
+    }
+
+    fn primary(&mut self) -> Result<f64> {
+        match self.peek() {
+            Some('(') => {
+                self.pos += 1;
+                let value = self.expression()?;
+                if !self.eat(')') {
+                    return Err(anyhow!("Missing ')'"));
+                }
+                Ok(value)
+            }
+            Some(c) if c.is_ascii_digit() || c == '.' => self.number(),
+            Some(c) if c.is_alphabetic() || c == 'π' => self.identifier(),
+            Some(c) => Err(anyhow!("Unexpected '{}' in the expression", c)),
+            None => Err(anyhow!("The expression ended too early")),
+        }
+    }
+
+    fn number(&mut self) -> Result<f64> {
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|c| c.is_ascii_digit() || c == '.' || c == '_')
+        {
+            self.pos += 1;
+        }
+        // Exponent, as in 6.02e23
+        if matches!(self.peek(), Some('e' | 'E'))
+            && self
+                .chars
+                .get(self.pos + 1)
+                .is_some_and(|c| c.is_ascii_digit() || *c == '-' || *c == '+')
+        {
+            self.pos += 2;
+            while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+                self.pos += 1;
+            }
+        }
+        let text: String = self.chars[start..self.pos]
+            .iter()
+            .filter(|c| **c != '_')
+            .collect();
+        text.parse()
+            .map_err(|_| anyhow!("'{}' isn't a number", text))
+    }
+
+    fn identifier(&mut self) -> Result<f64> {
+        let start = self.pos;
+        while self.peek().is_some_and(|c| c.is_alphanumeric() || c == 'π') {
+            self.pos += 1;
+        }
+        let name: String = self.chars[start..self.pos]
+            .iter()
+            .collect::<String>()
+            .to_lowercase();
+
+        match name.as_str() {
+            "pi" | "π" => return Ok(std::f64::consts::PI),
+            "e" => return Ok(std::f64::consts::E),
+            "tau" => return Ok(std::f64::consts::TAU),
+            _ => {}
+        }
+
+        if !self.eat('(') {
+            return Err(anyhow!("Unknown name '{}'", name));
+        }
+        let mut args = vec![self.expression()?];
+        while self.eat(',') {
+            args.push(self.expression()?);
+        }
+        if !self.eat(')') {
+            return Err(anyhow!("Missing ')' after {}(", name));
+        }
+
+        let one = |f: fn(f64) -> f64| -> Result<f64> {
+            match args.as_slice() {
+                [x] => Ok(f(*x)),
+                _ => Err(anyhow!("{}() takes one argument", name)),
+            }
+        };
+        match name.as_str() {
+            "sqrt" => one(f64::sqrt),
+            "cbrt" => one(f64::cbrt),
+            "abs" => one(f64::abs),
+            "round" => one(f64::round),
+            "floor" => one(f64::floor),
+            "ceil" => one(f64::ceil),
+            "ln" => one(f64::ln),
+            "log" | "log10" => one(f64::log10),
+            "log2" => one(f64::log2),
+            "exp" => one(f64::exp),
+            "sin" => one(f64::sin),
+            "cos" => one(f64::cos),
+            "tan" => one(f64::tan),
+            "asin" => one(f64::asin),
+            "acos" => one(f64::acos),
+            "atan" => one(f64::atan),
+            "min" => Ok(args.iter().copied().fold(f64::INFINITY, f64::min)),
+            "max" => Ok(args.iter().copied().fold(f64::NEG_INFINITY, f64::max)),
+            "pow" => match args.as_slice() {
+                [base, exponent] => Ok(base.powf(*exponent)),
+                _ => Err(anyhow!("pow() takes two arguments")),
+            },
+            _ => Err(anyhow!("Unknown function '{}'", name)),
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use super::Tool;
+use crate::config::Config;
+use anyhow::Result;
+use async_trait::async_trait;
+use tauri::AppHandle;
+
+/// Longest look ahead the model can ask for
+const MAX_DAYS: i64 = 14;
+
+/// Upcoming events from the user's ICS calendars
+pub struct Calendar;
+
+#[async_trait]
+impl Tool for Calendar {
+    fn name(&self) -> &'static str {
+        "calendar"
+    }
+
+    fn description(&self) -> &'static str {
+        "Lists the user's upcoming calendar events. Use it for any question about their \
+         meetings, appointments or schedule."
+    }
+
+    fn parameters(&self) -> &'static str {
+        r#"{"days": "optional number of days ahead to look, 1 to 14; defaults to 1"}"#
+    }
+
+    fn label(&self) -> &'static str {
+        "Calendar"
+    }
+
+    async fn call(&self, _app: &AppHandle, arguments: serde_json::Value) -> Result<String> {
+        let days = arguments
+            .get("days")
+            .and_then(|d| d.as_i64().or_else(|| d.as_str()?.trim().parse().ok()))
+            .unwrap_or(1)
+            .clamp(1, MAX_DAYS);
+        let config = Config::load().unwrap_or_default();
+        let until = chrono::Local::now() + chrono::Duration::days(days);
+        let events = crate::calendar::upcoming(&config.calendar, until).await?;
+        let period = if days == 1 {
+            "the next 24 hours".to_string()
+        } else {
+            format!("the next {} days", days)
+        };
+        if events.is_empty() {
+            return Ok(format!("Nothing on the calendar in {}.", period));
+        }
+        let lines: Vec<String> = events
+            .iter()
+            .map(|e| format!("- {}", e.describe()))
+            .collect();
+        Ok(format!("Events in {}:\n{}", period, lines.join("\n")))
+    }
+}
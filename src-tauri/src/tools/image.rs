@@ -0,0 +1,223 @@
+use super::Tool;
+use crate::config::{Config, ImageBackend};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+const OPENAI_IMAGES_URL: &str = "https://api.openai.com/v1/images/generations";
+/// Generating takes a while, especially on a local GPU
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(180);
+
+/// Sent as `image-generated` once a picture is saved, so the chat can show it
+#[derive(Debug, Clone, Serialize)]
+pub struct ImageGeneratedEvent {
+    pub path: String,
+    pub prompt: String,
+}
+
+/// Pictures from OpenAI Images or a local Stable Diffusion web UI
+pub struct GenerateImage;
+
+#[async_trait]
+impl Tool for GenerateImage {
+    fn name(&self) -> &'static str {
+        "generate_image"
+    }
+
+    fn description(&self) -> &'static str {
+        "Draws a picture from a description and shows it in the chat. Use it when the user \
+         asks you to draw, paint or generate an image."
+    }
+
+    fn parameters(&self) -> &'static str {
+        r#"{"prompt": "a detailed description of the picture"}"#
+    }
+
+    fn label(&self) -> &'static str {
+        "Image"
+    }
+
+    /// Each picture can cost money, so the user says yes first
+    fn requires_approval(&self) -> bool {
+        true
+    }
+
+    fn describe(&self, arguments: &serde_json::Value) -> String {
+        format!(
+            "Draw \"{}\"",
+            arguments
+                .get("prompt")
+                .and_then(|p| p.as_str())
+                .unwrap_or_default()
+        )
+    }
+
+    fn options(&self) -> &'static [&'static str] {
+        &["api_key"]
+    }
+
+    async fn call(&self, app: &AppHandle, arguments: serde_json::Value) -> Result<String> {
+        let prompt = arguments
+            .get("prompt")
+            .and_then(|p| p.as_str())
+            .map(str::trim)
+            .filter(|p| !p.is_empty())
+            .ok_or_else(|| anyhow!("Missing 'prompt'"))?;
+        let config = Config::load().unwrap_or_default();
+        let png = match config.image.backend {
+            ImageBackend::OpenAI => openai(&config, prompt).await?,
+            ImageBackend::StableDiffusion => stable_diffusion(&config, prompt).await?,
+        };
+        let path = save(&png)?;
+        let _ = app.emit(
+            "image-generated",
+            ImageGeneratedEvent {
+                path: path.to_string_lossy().to_string(),
+                prompt: prompt.to_string(),
+            },
+        );
+        Ok(format!(
+            "Drew the picture and saved it to {}",
+            path.display()
+        ))
+    }
+}
+
+/// Where generated pictures are kept
+pub fn images_dir() -> Result<PathBuf> {
+    let data_dir = dirs::data_dir().ok_or_else(|| anyhow!("Could not find data directory"))?;
+    let dir = data_dir.join("rusty-clippy").join("images");
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+fn save(png: &[u8]) -> Result<PathBuf> {
+    let name = format!(
+        "image-{}.png",
+        chrono::Local::now().format("%Y%m%d-%H%M%S-%3f")
+    );
+    let path = images_dir()?.join(name);
+    std::fs::write(&path, png)?;
+    tracing::info!("Saved generated image to {}", path.display());
+    Ok(path)
+}
+
+/// A saved picture as a `data:` URL the chat can show. Only files in `images_dir` are read.
+pub fn data_url(path: &str) -> Result<String> {
+    let dir = images_dir()?.canonicalize()?;
+    let path = std::path::Path::new(path).canonicalize()?;
+    if !path.starts_with(&dir) {
+        return Err(anyhow!("{} isn't a generated image", path.display()));
+    }
+    let png = std::fs::read(&path)?;
+    Ok(format!(
+        "data:image/png;base64,{}",
+        base64::engine::general_purpose::STANDARD.encode(png)
+    ))
+}
+
+fn decode(b64: &str) -> Result<Vec<u8>> {
+    base64::engine::general_purpose::STANDARD
+        .decode(b64)
+        .map_err(|e| anyhow!("The image came back garbled: {}", e))
+}
+
+fn client() -> Result<reqwest::Client> {
+    Ok(reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()?)
+}
+
+#[derive(Deserialize)]
+struct OpenAIImages {
+    data: Vec<OpenAIImage>,
+}
+
+#[derive(Deserialize)]
+struct OpenAIImage {
+    b64_json: Option<String>,
+}
+
+async fn openai(config: &Config, prompt: &str) -> Result<Vec<u8>> {
+    let key = config
+        .tools
+        .get("generate_image")
+        .and_then(|t| t.api_key.clone())
+        .or_else(|| config.openai_api_key.clone())
+        .ok_or_else(|| anyhow!("Drawing needs an OpenAI API key; add one in Settings"))?;
+    let model = &config.image.model;
+    let mut body = serde_json::json!({
+        "model": model,
+        "prompt": prompt,
+        "size": config.image.size,
+        "n": 1,
+    });
+    // DALL·E answers with a URL unless asked for the bytes; gpt-image models always send them
+    if model.starts_with("dall-e") {
+        body["response_format"] = "b64_json".into();
+    }
+    let response = client()?
+        .post(OPENAI_IMAGES_URL)
+        .bearer_auth(key)
+        .json(&body)
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let text = response.text().await.unwrap_or_default();
+        return Err(anyhow!(
+            "OpenAI Images answered {}: {}",
+            status,
+            crate::redact::redact(&text)
+        ));
+    }
+    let images: OpenAIImages = response.json().await?;
+    let b64 = images
+        .data
+        .into_iter()
+        .find_map(|image| image.b64_json)
+        .ok_or_else(|| anyhow!("OpenAI Images sent no picture"))?;
+    decode(&b64)
+}
+
+#[derive(Deserialize)]
+struct Txt2ImgResponse {
+    images: Vec<String>,
+}
+
+/// A Stable Diffusion web UI (AUTOMATIC1111 or Forge) started with `--api`
+async fn stable_diffusion(config: &Config, prompt: &str) -> Result<Vec<u8>> {
+    let base = config
+        .image
+        .stable_diffusion_url
+        .as_deref()
+        .ok_or_else(|| anyhow!("Set the Stable Diffusion URL in image settings"))?;
+    let (width, height) = config
+        .image
+        .dimensions()
+        .ok_or_else(|| anyhow!("Image size '{}' isn't WIDTHxHEIGHT", config.image.size))?;
+    let response = client()?
+        .post(format!("{}/sdapi/v1/txt2img", base.trim_end_matches('/')))
+        .json(&serde_json::json!({
+            "prompt": prompt,
+            "width": width,
+            "height": height,
+        }))
+        .send()
+        .await
+        .map_err(|e| anyhow!("Couldn't reach Stable Diffusion at {}: {}", base, e))?;
+    if !response.status().is_success() {
+        return Err(anyhow!("Stable Diffusion answered {}", response.status()));
+    }
+    let result: Txt2ImgResponse = response.json().await?;
+    let b64 = result
+        .images
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow!("Stable Diffusion sent no picture"))?;
+    decode(&b64)
+}
@@ -0,0 +1,401 @@
+pub mod calculator;
+pub mod calendar;
+pub mod image;
+pub mod open;
+pub mod timer;
+pub mod weather;
+
+use crate::config::{Config, ToolSettings};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::oneshot;
+
+/// Most tool calls answered before a reply, so a confused model can't loop forever
+pub const MAX_ROUNDS: usize = 3;
+
+/// An approval request nobody answers counts as denied after this long
+const APPROVAL_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Approval requests waiting for the user, by request id
+static PENDING_APPROVALS: Mutex<Option<HashMap<u64, oneshot::Sender<ApprovalDecision>>>> =
+    Mutex::new(None);
+static NEXT_APPROVAL_ID: AtomicU64 = AtomicU64::new(1);
+
+const CALL_OPEN: &str = "<tool_call>";
+const CALL_CLOSE: &str = "</tool_call>";
+
+/// Something the model can call mid-reply for things it shouldn't guess at.
+///
+/// Calls are made in the reply text rather than through an API's function calling, so every
+/// provider, the built-in model included, can use them: the model answers with a single
+/// `<tool_call>{"name": ..., "arguments": {...}}</tool_call>`, `send_message` runs it and
+/// asks again with the result.
+#[async_trait]
+pub trait Tool: Send + Sync {
+    fn name(&self) -> &'static str;
+    /// What the tool does and when to use it, for the system prompt
+    fn description(&self) -> &'static str;
+    /// The arguments object, described for the system prompt, e.g. `{"expression": "..."}`
+    fn parameters(&self) -> &'static str;
+    /// How the chat labels the tool's output, e.g. "Computed"
+    fn label(&self) -> &'static str {
+        "Tool result"
+    }
+    /// Tools that act outside the chat ask the user before every call
+    fn requires_approval(&self) -> bool {
+        false
+    }
+    /// How much harm a mistaken call could do, shown when asking for approval
+    fn risk(&self) -> Risk {
+        Risk::Medium
+    }
+    /// The call as the approval prompt shows it
+    fn describe(&self, arguments: &serde_json::Value) -> String {
+        format!("Run {} with {}", self.name(), arguments)
+    }
+    /// Which `ToolSettings` options besides `enabled` apply, e.g. `["allowed_dirs"]`, so
+    /// the settings window only offers those
+    fn options(&self) -> &'static [&'static str] {
+        &[]
+    }
+    /// Files and folders the call would touch, checked against `allowed_dirs` before it runs
+    fn paths(&self, _arguments: &serde_json::Value) -> Vec<PathBuf> {
+        Vec::new()
+    }
+    /// Run the tool. The text returned is shown to the user and handed back to the model.
+    async fn call(&self, app: &AppHandle, arguments: serde_json::Value) -> Result<String>;
+}
+
+/// A call parsed out of a reply
+#[derive(Debug, Clone, Deserialize)]
+pub struct ToolCall {
+    pub name: String,
+    #[serde(default)]
+    pub arguments: serde_json::Value,
+}
+
+/// A finished call, as sent to the chat in the `tool-result` event
+#[derive(Debug, Clone, Serialize)]
+pub struct ToolResult {
+    pub tool: String,
+    pub label: String,
+    pub output: String,
+    /// False when the tool failed and `output` is the error
+    pub ok: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Risk {
+    /// Only shows the user something
+    Low,
+    /// Opens or changes something outside Clippy
+    Medium,
+}
+
+/// Sent to the chat in the `tool-approval-request` event before a call that needs approval;
+/// answered with `respond_tool_approval`
+#[derive(Debug, Clone, Serialize)]
+pub struct ApprovalRequest {
+    pub id: u64,
+    pub tool: String,
+    /// The call in words, e.g. "Open https://example.com in your browser"
+    pub description: String,
+    /// The arguments as pretty-printed JSON
+    pub arguments: String,
+    pub risk: Risk,
+}
+
+/// The user's answer to an `ApprovalRequest`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalDecision {
+    AllowOnce,
+    /// Allow this call and every later call of the tool, saved in its settings
+    AlwaysAllow,
+    Deny,
+}
+
+/// A tool and its settings, as `list_tools` reports it to the settings window
+#[derive(Debug, Clone, Serialize)]
+pub struct ToolInfo {
+    pub name: String,
+    pub description: String,
+    pub label: String,
+    pub requires_approval: bool,
+    pub risk: Risk,
+    pub options: Vec<String>,
+    pub settings: ToolSettings,
+}
+
+/// Expand a leading `~` to the home directory
+pub(crate) fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix('~'), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest.trim_start_matches(['/', '\\'])),
+        _ => PathBuf::from(path),
+    }
+}
+
+/// Whether `path` is inside one of `dirs`, after resolving `..` and symlinks
+fn is_allowed(path: &Path, dirs: &[String]) -> bool {
+    let Ok(path) = path.canonicalize() else {
+        return false;
+    };
+    dirs.iter()
+        .filter_map(|dir| expand_home(dir.trim()).canonicalize().ok())
+        .any(|dir| path.starts_with(dir))
+}
+
+pub struct ToolRegistry {
+    tools: Vec<Box<dyn Tool>>,
+    settings: BTreeMap<String, ToolSettings>,
+}
+
+impl ToolRegistry {
+    /// Every built-in tool, with the settings from `config`
+    pub fn new(config: &Config) -> Self {
+        Self {
+            tools: vec![
+                Box::new(calculator::Calculator),
+                Box::new(open::OpenUrl),
+                Box::new(open::RevealFile),
+                Box::new(weather::Weather),
+                Box::new(calendar::Calendar),
+                Box::new(timer::Timer),
+                Box::new(image::GenerateImage),
+            ],
+            settings: config.tools.clone(),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&dyn Tool> {
+        self.tools
+            .iter()
+            .find(|t| t.name() == name)
+            .map(|t| t.as_ref())
+    }
+
+    fn settings(&self, name: &str) -> ToolSettings {
+        self.settings.get(name).cloned().unwrap_or_default()
+    }
+
+    /// Every tool with its settings, for the settings window
+    pub fn list(&self) -> Vec<ToolInfo> {
+        self.tools
+            .iter()
+            .map(|tool| ToolInfo {
+                name: tool.name().to_string(),
+                description: tool.description().to_string(),
+                label: tool.label().to_string(),
+                requires_approval: tool.requires_approval(),
+                risk: tool.risk(),
+                options: tool.options().iter().map(|o| o.to_string()).collect(),
+                settings: self.settings(tool.name()),
+            })
+            .collect()
+    }
+
+    /// Instructions for the system prompt describing the enabled tools and how to call them;
+    /// empty when they're all turned off
+    pub fn prompt(&self) -> String {
+        let enabled: Vec<&dyn Tool> = self
+            .tools
+            .iter()
+            .map(|t| t.as_ref())
+            .filter(|t| self.settings(t.name()).enabled)
+            .collect();
+        if enabled.is_empty() {
+            return String::new();
+        }
+        let mut prompt = format!(
+            "You can use tools. To call one, reply with only {}{{\"name\": \"<tool>\", \
+             \"arguments\": {{...}}}}{} and nothing else; you'll get the result and can then \
+             answer. Use a tool whenever it can give an exact answer instead of a guess. Tools:",
+            CALL_OPEN, CALL_CLOSE
+        );
+        for tool in enabled {
+            prompt.push_str(&format!(
+                "\n- {}: {} Arguments: {}",
+                tool.name(),
+                tool.description(),
+                tool.parameters()
+            ));
+        }
+        prompt
+    }
+
+    /// Whether the call is allowed by the tool's settings
+    fn check(&self, tool: &dyn Tool, arguments: &serde_json::Value) -> Result<()> {
+        let settings = self.settings(tool.name());
+        if !settings.enabled {
+            return Err(anyhow!(
+                "The {} tool is turned off in Settings",
+                tool.name()
+            ));
+        }
+        if settings.allowed_dirs.is_empty() {
+            return Ok(());
+        }
+        match tool
+            .paths(arguments)
+            .into_iter()
+            .find(|path| !is_allowed(path, &settings.allowed_dirs))
+        {
+            Some(path) => Err(anyhow!(
+                "{} is outside the folders the {} tool may use",
+                path.display(),
+                tool.name()
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Run `call`, turning a failure into a result the model can read and apologise for.
+    /// Calls the tool's settings forbid are refused before the user is asked to approve them.
+    pub async fn dispatch(&self, app: &AppHandle, call: &ToolCall) -> ToolResult {
+        let Some(tool) = self.get(&call.name) else {
+            return ToolResult {
+                tool: call.name.clone(),
+                label: "Tool result".to_string(),
+                output: format!("There is no tool called '{}'", call.name),
+                ok: false,
+            };
+        };
+        let result = if let Err(e) = self.check(tool, &call.arguments) {
+            Err(e)
+        } else if tool.requires_approval()
+            && !self.settings(tool.name()).always_allow
+            && !approve(app, tool, &call.arguments).await
+        {
+            Err(anyhow!("The user declined"))
+        } else {
+            tool.call(app, call.arguments.clone()).await
+        };
+        match result {
+            Ok(output) => {
+                tracing::info!("Tool {} succeeded", call.name);
+                ToolResult {
+                    tool: call.name.clone(),
+                    label: tool.label().to_string(),
+                    output,
+                    ok: true,
+                }
+            }
+            Err(e) => {
+                tracing::warn!("Tool {} failed: {}", call.name, e);
+                ToolResult {
+                    tool: call.name.clone(),
+                    label: tool.label().to_string(),
+                    output: e.to_string(),
+                    ok: false,
+                }
+            }
+        }
+    }
+}
+
+/// Ask the user in the chat whether the model may make this call, and wait for the answer.
+/// "Always allow" is saved to the tool's settings so later calls go ahead without asking.
+async fn approve(app: &AppHandle, tool: &dyn Tool, arguments: &serde_json::Value) -> bool {
+    let id = NEXT_APPROVAL_ID.fetch_add(1, Ordering::Relaxed);
+    let (tx, rx) = oneshot::channel();
+    if let Ok(mut pending) = PENDING_APPROVALS.lock() {
+        pending.get_or_insert_with(HashMap::new).insert(id, tx);
+    }
+
+    let request = ApprovalRequest {
+        id,
+        tool: tool.name().to_string(),
+        description: tool.describe(arguments),
+        arguments: serde_json::to_string_pretty(arguments).unwrap_or_default(),
+        risk: tool.risk(),
+    };
+    crate::notifications::notify(
+        app,
+        "Clippy wants to do something",
+        &format!("{}?", request.description),
+        crate::notifications::Target::Chat,
+    );
+    let _ = app.emit("tool-approval-request", request);
+
+    let decision = match tokio::time::timeout(APPROVAL_TIMEOUT, rx).await {
+        Ok(Ok(decision)) => decision,
+        _ => {
+            tracing::info!("Approval request {} went unanswered", id);
+            if let Ok(mut pending) = PENDING_APPROVALS.lock() {
+                pending.get_or_insert_with(HashMap::new).remove(&id);
+            }
+            ApprovalDecision::Deny
+        }
+    };
+    if decision == ApprovalDecision::AlwaysAllow {
+        if let Err(e) = always_allow(tool.name()) {
+            tracing::warn!("Failed to save approval for {}: {}", tool.name(), e);
+        }
+    }
+    decision != ApprovalDecision::Deny
+}
+
+fn always_allow(name: &str) -> Result<()> {
+    let mut config = Config::load()?;
+    config
+        .tools
+        .entry(name.to_string())
+        .or_default()
+        .always_allow = true;
+    config.save()
+}
+
+/// Answer a pending `tool-approval-request`
+pub fn answer(id: u64, decision: ApprovalDecision) -> Result<()> {
+    let sender = PENDING_APPROVALS
+        .lock()
+        .ok()
+        .and_then(|mut pending| pending.as_mut()?.remove(&id))
+        .ok_or_else(|| anyhow!("No approval request {} is waiting", id))?;
+    sender
+        .send(decision)
+        .map_err(|_| anyhow!("The tool call was abandoned"))
+}
+
+/// Whether a reply streamed so far could still turn out to be a tool call, in which case
+/// it's held back from the chat until it's complete.
+pub fn may_be_call(reply: &str) -> bool {
+    let reply = reply.trim_start();
+    CALL_OPEN.starts_with(reply) || reply.starts_with(CALL_OPEN)
+}
+
+/// The tool call a finished reply consists of, if it is one
+pub fn parse_call(reply: &str) -> Option<ToolCall> {
+    let body = reply.trim().strip_prefix(CALL_OPEN)?;
+    let body = body.split(CALL_CLOSE).next()?.trim();
+    // Models like to wrap JSON in a code fence
+    let body = body
+        .trim_start_matches("```json")
+        .trim_start_matches("```")
+        .trim_end_matches("```")
+        .trim();
+    serde_json::from_str(body).ok()
+}
+
+/// The message handing a tool's result back to the model
+pub fn result_message(result: &ToolResult) -> String {
+    if result.ok {
+        format!(
+            "Result of the {} tool (real output, not generated; report it as given): {}",
+            result.tool, result.output
+        )
+    } else {
+        format!(
+            "The {} tool failed: {}. Tell the user briefly, without making up a result.",
+            result.tool, result.output
+        )
+    }
+}
@@ -0,0 +1,125 @@
+use super::{expand_home, Risk, Tool};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use std::path::PathBuf;
+use tauri::AppHandle;
+use tauri_plugin_opener::OpenerExt;
+
+fn argument<'a>(arguments: &'a serde_json::Value, name: &str) -> Result<&'a str> {
+    arguments
+        .get(name)
+        .and_then(|v| v.as_str())
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .ok_or_else(|| anyhow!("Missing '{}'", name))
+}
+
+/// Only web pages; other schemes could launch arbitrary handlers
+fn web_url(text: &str) -> Result<reqwest::Url> {
+    let url =
+        reqwest::Url::parse(text).map_err(|e| anyhow!("'{}' isn't a valid URL: {}", text, e))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(anyhow!("Only http and https links can be opened"));
+    }
+    Ok(url)
+}
+
+/// Opens a web page in the default browser
+pub struct OpenUrl;
+
+#[async_trait]
+impl Tool for OpenUrl {
+    fn name(&self) -> &'static str {
+        "open_url"
+    }
+
+    fn description(&self) -> &'static str {
+        "Opens a web page in the user's browser, when they ask to open or go to a site. \
+         Only use URLs you are sure exist."
+    }
+
+    fn parameters(&self) -> &'static str {
+        r#"{"url": "full http(s) URL, e.g. \"https://doc.rust-lang.org/book/\""}"#
+    }
+
+    fn label(&self) -> &'static str {
+        "Opened"
+    }
+
+    fn requires_approval(&self) -> bool {
+        true
+    }
+
+    fn describe(&self, arguments: &serde_json::Value) -> String {
+        let url = arguments.get("url").and_then(|v| v.as_str()).unwrap_or("?");
+        format!("Open {} in your browser", url)
+    }
+
+    async fn call(&self, app: &AppHandle, arguments: serde_json::Value) -> Result<String> {
+        let url = web_url(argument(&arguments, "url")?)?;
+        app.opener()
+            .open_url(url.as_str(), None::<&str>)
+            .map_err(|e| anyhow!("Failed to open the browser: {}", e))?;
+        Ok(url.to_string())
+    }
+}
+
+/// Shows a file or folder in the system file manager
+pub struct RevealFile;
+
+#[async_trait]
+impl Tool for RevealFile {
+    fn name(&self) -> &'static str {
+        "reveal_file"
+    }
+
+    fn description(&self) -> &'static str {
+        "Shows a file or folder in the user's file manager (Finder, Explorer...), selected. \
+         Nothing is opened or run."
+    }
+
+    fn parameters(&self) -> &'static str {
+        r#"{"path": "absolute path, or one starting with ~, e.g. \"~/Downloads/report.pdf\""}"#
+    }
+
+    fn label(&self) -> &'static str {
+        "Revealed"
+    }
+
+    fn requires_approval(&self) -> bool {
+        true
+    }
+
+    fn describe(&self, arguments: &serde_json::Value) -> String {
+        let path = arguments
+            .get("path")
+            .and_then(|v| v.as_str())
+            .unwrap_or("?");
+        format!("Show {} in your file manager", path)
+    }
+
+    fn risk(&self) -> Risk {
+        Risk::Low
+    }
+
+    fn options(&self) -> &'static [&'static str] {
+        &["allowed_dirs"]
+    }
+
+    fn paths(&self, arguments: &serde_json::Value) -> Vec<PathBuf> {
+        argument(arguments, "path")
+            .map(|path| vec![expand_home(path)])
+            .unwrap_or_default()
+    }
+
+    async fn call(&self, app: &AppHandle, arguments: serde_json::Value) -> Result<String> {
+        let path = expand_home(argument(&arguments, "path")?);
+        if !path.exists() {
+            return Err(anyhow!("{} doesn't exist", path.display()));
+        }
+        app.opener()
+            .reveal_item_in_dir(&path)
+            .map_err(|e| anyhow!("Failed to open the file manager: {}", e))?;
+        Ok(path.display().to_string())
+    }
+}
@@ -0,0 +1,51 @@
+use super::Tool;
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{Local, TimeZone};
+use tauri::AppHandle;
+
+/// Countdown timers, kept and fired by the reminder scheduler
+pub struct Timer;
+
+#[async_trait]
+impl Tool for Timer {
+    fn name(&self) -> &'static str {
+        "set_timer"
+    }
+
+    fn description(&self) -> &'static str {
+        "Starts a countdown timer that alerts the user when it runs out. Use it whenever the \
+         user asks for a timer or alarm after a length of time."
+    }
+
+    fn parameters(&self) -> &'static str {
+        r#"{"duration": "e.g. \"20 minutes\", \"1h30m\" or \"90 seconds\"", "label": "optional"}"#
+    }
+
+    fn label(&self) -> &'static str {
+        "Timer set"
+    }
+
+    async fn call(&self, _app: &AppHandle, arguments: serde_json::Value) -> Result<String> {
+        let duration = match arguments.get("duration") {
+            Some(serde_json::Value::String(d)) => d.clone(),
+            // A bare number is minutes
+            Some(serde_json::Value::Number(n)) => n.to_string(),
+            _ => anyhow::bail!("Missing 'duration'"),
+        };
+        let label = arguments.get("label").and_then(|l| l.as_str());
+        let timer = crate::reminders::add_timer(&duration, label)?;
+
+        let length = crate::reminders::describe_duration(timer.due_at - timer.created_at);
+        let ends = Local
+            .timestamp_opt(timer.due_at, 0)
+            .single()
+            .map(|t| t.format("%H:%M:%S").to_string())
+            .unwrap_or_default();
+        Ok(if timer.text.is_empty() {
+            format!("{} timer running, done at {}", length, ends)
+        } else {
+            format!("{} {} timer running, done at {}", length, timer.text, ends)
+        })
+    }
+}
@@ -0,0 +1,304 @@
+use super::Tool;
+use crate::config::{Config, WeatherUnits};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::AppHandle;
+
+const GEOCODING_URL: &str = "https://geocoding-api.open-meteo.com/v1/search";
+const FORECAST_URL: &str = "https://api.open-meteo.com/v1/forecast";
+/// With an API key, requests go to the commercial servers instead
+const CUSTOMER_GEOCODING_URL: &str = "https://customer-geocoding-api.open-meteo.com/v1/search";
+const CUSTOMER_FORECAST_URL: &str = "https://customer-api.open-meteo.com/v1/forecast";
+/// Approximate location from the IP address, used when no location is configured
+const IP_LOCATION_URL: &str = "https://ipapi.co/json/";
+/// Forecasts are reused for this long, so a chatty session doesn't hammer Open-Meteo
+const CACHE_TTL: Duration = Duration::from_secs(10 * 60);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Reports keyed by location and units
+static CACHE: Mutex<Option<HashMap<String, (Instant, String)>>> = Mutex::new(None);
+
+/// Current conditions and the next two days' forecast from Open-Meteo
+pub struct Weather;
+
+#[async_trait]
+impl Tool for Weather {
+    fn name(&self) -> &'static str {
+        "weather"
+    }
+
+    fn description(&self) -> &'static str {
+        "Gets the real current weather and today's and tomorrow's forecast. Use it for any \
+         question about weather, temperature, rain or what to wear."
+    }
+
+    fn parameters(&self) -> &'static str {
+        r#"{"location": "optional place name, e.g. \"Lisbon\"; omit for the user's own location"}"#
+    }
+
+    fn label(&self) -> &'static str {
+        "Open-Meteo"
+    }
+
+    fn options(&self) -> &'static [&'static str] {
+        &["api_key"]
+    }
+
+    async fn call(&self, _app: &AppHandle, arguments: serde_json::Value) -> Result<String> {
+        let location = arguments
+            .get("location")
+            .and_then(|l| l.as_str())
+            .map(str::trim)
+            .filter(|l| !l.is_empty());
+        report(&Config::load().unwrap_or_default(), location).await
+    }
+}
+
+struct Place {
+    name: String,
+    latitude: f64,
+    longitude: f64,
+}
+
+#[derive(Deserialize)]
+struct GeocodingResponse {
+    #[serde(default)]
+    results: Vec<GeocodingResult>,
+}
+
+#[derive(Deserialize)]
+struct GeocodingResult {
+    name: String,
+    latitude: f64,
+    longitude: f64,
+    country: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct IpLocation {
+    city: Option<String>,
+    latitude: f64,
+    longitude: f64,
+}
+
+#[derive(Deserialize)]
+struct Forecast {
+    current: Current,
+    daily: Daily,
+}
+
+#[derive(Deserialize)]
+struct Current {
+    temperature_2m: f64,
+    apparent_temperature: f64,
+    weather_code: u32,
+    wind_speed_10m: f64,
+}
+
+#[derive(Deserialize)]
+struct Daily {
+    weather_code: Vec<u32>,
+    temperature_2m_max: Vec<f64>,
+    temperature_2m_min: Vec<f64>,
+    precipitation_probability_max: Vec<Option<f64>>,
+}
+
+fn client() -> Result<reqwest::Client> {
+    Ok(reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()?)
+}
+
+/// A GET to the free endpoint, or the commercial one with the key when there is one
+fn request(free: &str, customer: &str, api_key: Option<&str>) -> Result<reqwest::RequestBuilder> {
+    Ok(match api_key {
+        Some(key) => client()?.get(customer).query(&[("apikey", key)]),
+        None => client()?.get(free),
+    })
+}
+
+async fn geocode(name: &str, api_key: Option<&str>) -> Result<Place> {
+    let response: GeocodingResponse = request(GEOCODING_URL, CUSTOMER_GEOCODING_URL, api_key)?
+        .query(&[("name", name), ("count", "1")])
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    let place = response
+        .results
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow!("Couldn't find a place called '{}'", name))?;
+    Ok(Place {
+        name: match place.country {
+            Some(country) => format!("{}, {}", place.name, country),
+            None => place.name,
+        },
+        latitude: place.latitude,
+        longitude: place.longitude,
+    })
+}
+
+async fn locate_by_ip() -> Result<Place> {
+    let location: IpLocation = client()?
+        .get(IP_LOCATION_URL)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await
+        .map_err(|e| {
+            anyhow!(
+                "Couldn't work out your location; set one in Settings ({})",
+                e
+            )
+        })?;
+    Ok(Place {
+        name: location.city.unwrap_or_else(|| "your area".to_string()),
+        latitude: location.latitude,
+        longitude: location.longitude,
+    })
+}
+
+/// Units from the config, else the locale's: Fahrenheit and mph in the US
+fn units(config: &Config) -> WeatherUnits {
+    config.weather.units.unwrap_or_else(|| {
+        let locale = crate::personality::locale();
+        if locale.ends_with("-US") || locale.ends_with("_US") {
+            WeatherUnits::Imperial
+        } else {
+            WeatherUnits::Metric
+        }
+    })
+}
+
+/// WMO weather interpretation codes, as used by Open-Meteo
+fn describe(code: u32) -> &'static str {
+    match code {
+        0 => "clear sky",
+        1 => "mainly clear",
+        2 => "partly cloudy",
+        3 => "overcast",
+        45 | 48 => "fog",
+        51 | 53 | 55 => "drizzle",
+        56 | 57 => "freezing drizzle",
+        61 => "light rain",
+        63 => "rain",
+        65 => "heavy rain",
+        66 | 67 => "freezing rain",
+        71 => "light snow",
+        73 => "snow",
+        75 => "heavy snow",
+        77 => "snow grains",
+        80..=82 => "rain showers",
+        85 | 86 => "snow showers",
+        95 => "thunderstorm",
+        96 | 99 => "thunderstorm with hail",
+        _ => "unknown conditions",
+    }
+}
+
+/// A plain-text weather report for `location`, or the configured or IP-derived one.
+pub async fn report(config: &Config, location: Option<&str>) -> Result<String> {
+    let configured = config
+        .weather
+        .location
+        .as_deref()
+        .map(str::trim)
+        .filter(|l| !l.is_empty());
+    let location = location.or(configured);
+    let units = units(config);
+    let api_key = config
+        .tools
+        .get("weather")
+        .and_then(|t| t.api_key.as_deref())
+        .map(str::trim)
+        .filter(|k| !k.is_empty());
+    let key = format!("{}|{:?}", location.unwrap_or("").to_lowercase(), units);
+
+    if let Some(cached) = CACHE.lock().ok().and_then(|cache| {
+        let (at, report) = cache.as_ref()?.get(&key)?;
+        (at.elapsed() < CACHE_TTL).then(|| report.clone())
+    }) {
+        return Ok(cached);
+    }
+
+    let place = match location {
+        Some(name) => geocode(name, api_key).await?,
+        None => locate_by_ip().await?,
+    };
+    let (temperature_unit, wind_unit, degree, speed) = match units {
+        WeatherUnits::Metric => ("celsius", "kmh", "°C", "km/h"),
+        WeatherUnits::Imperial => ("fahrenheit", "mph", "°F", "mph"),
+    };
+    let forecast: Forecast = request(FORECAST_URL, CUSTOMER_FORECAST_URL, api_key)?
+        .query(&[
+            ("latitude", place.latitude.to_string().as_str()),
+            ("longitude", place.longitude.to_string().as_str()),
+            (
+                "current",
+                "temperature_2m,apparent_temperature,weather_code,wind_speed_10m",
+            ),
+            (
+                "daily",
+                "weather_code,temperature_2m_max,temperature_2m_min,precipitation_probability_max",
+            ),
+            ("temperature_unit", temperature_unit),
+            ("wind_speed_unit", wind_unit),
+            ("timezone", "auto"),
+            ("forecast_days", "2"),
+        ])
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await
+        .map_err(|e| anyhow!("Unexpected forecast from Open-Meteo: {}", e))?;
+
+    let now = &forecast.current;
+    let mut report = format!(
+        "{}: {}, {:.0}{} (feels like {:.0}{}), wind {:.0} {}.",
+        place.name,
+        describe(now.weather_code),
+        now.temperature_2m,
+        degree,
+        now.apparent_temperature,
+        degree,
+        now.wind_speed_10m,
+        speed
+    );
+    let daily = &forecast.daily;
+    for (day, label) in ["Today", "Tomorrow"].iter().enumerate() {
+        let (Some(code), Some(high), Some(low)) = (
+            daily.weather_code.get(day),
+            daily.temperature_2m_max.get(day),
+            daily.temperature_2m_min.get(day),
+        ) else {
+            continue;
+        };
+        report.push_str(&format!(
+            " {}: {}, {:.0}–{:.0}{}",
+            label,
+            describe(*code),
+            low,
+            high,
+            degree
+        ));
+        if let Some(Some(rain)) = daily.precipitation_probability_max.get(day) {
+            report.push_str(&format!(", {:.0}% chance of rain", rain));
+        }
+        report.push('.');
+    }
+
+    if let Ok(mut cache) = CACHE.lock() {
+        let cache = cache.get_or_insert_with(HashMap::new);
+        cache.retain(|_, (at, _)| at.elapsed() < CACHE_TTL);
+        cache.insert(key, (Instant::now(), report.clone()));
+    }
+    Ok(report)
+}
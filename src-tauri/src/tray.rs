@@ -0,0 +1,450 @@
+use crate::config::Config;
+use crate::profiles;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::image::Image;
+use tauri::menu::{CheckMenuItem, IsMenuItem, Menu, MenuEvent, MenuItem, Submenu};
+use tauri::tray::TrayIconBuilder;
+use tauri::{AppHandle, Emitter, Manager, Wry};
+
+const TRAY_ID: &str = "clippy-tray";
+const PROFILE_PREFIX: &str = "profile:";
+const MODEL_PREFIX: &str = "model:";
+const SESSION_PREFIX: &str = "session:";
+/// How many conversations the Recent Chats submenu lists
+const RECENT_SESSIONS: usize = 5;
+
+/// How fast the status dot blinks while work is in progress
+const BLINK_INTERVAL: Duration = Duration::from_millis(600);
+
+/// What Clippy is busy with, shown as a colored dot on the tray icon
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrayStatus {
+    Idle,
+    Thinking,
+    Speaking,
+    Downloading,
+    Error,
+}
+
+impl TrayStatus {
+    fn tooltip(self) -> &'static str {
+        match self {
+            Self::Idle => "Rusty Clippy",
+            Self::Thinking => "Rusty Clippy — thinking…",
+            Self::Speaking => "Rusty Clippy — speaking",
+            Self::Downloading => "Rusty Clippy — downloading…",
+            Self::Error => "Rusty Clippy — something went wrong",
+        }
+    }
+
+    /// RGBA of the status dot; idle shows the plain icon
+    fn color(self) -> Option<[u8; 4]> {
+        match self {
+            Self::Idle => None,
+            Self::Thinking => Some([255, 193, 7, 255]),
+            Self::Speaking => Some([76, 175, 80, 255]),
+            Self::Downloading => Some([33, 150, 243, 255]),
+            Self::Error => Some([229, 57, 53, 255]),
+        }
+    }
+
+    /// Ongoing work blinks so a stuck generation is easy to spot
+    fn blinks(self) -> bool {
+        matches!(self, Self::Thinking | Self::Downloading)
+    }
+}
+
+static STATUS: Mutex<TrayStatus> = Mutex::new(TrayStatus::Idle);
+/// Bumped on every status change so an old blink loop knows to stop
+static STATUS_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+pub fn setup(app: &tauri::App) -> Result<(), Box<dyn std::error::Error>> {
+    let menu = build_menu(app.handle())?;
+
+    let _tray = TrayIconBuilder::with_id(TRAY_ID)
+        .menu(&menu)
+        .on_menu_event(handle_menu_event)
+        .build(app)?;
+
+    Ok(())
+}
+
+/// Rebuild the tray menu so it reflects the current config and profiles.
+pub fn refresh(app: &AppHandle) {
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        return;
+    };
+    match build_menu(app) {
+        Ok(menu) => {
+            let _ = tray.set_menu(Some(menu));
+        }
+        Err(e) => tracing::warn!("Failed to rebuild tray menu: {}", e),
+    }
+}
+
+fn build_menu(app: &AppHandle) -> tauri::Result<Menu<Wry>> {
+    let show_i = MenuItem::with_id(app, "show", "Show Clippy", true, None::<&str>)?;
+    let profiles_i = build_profiles_menu(app)?;
+    let config = Config::load().unwrap_or_default();
+    let models_i = build_models_menu(app, &config)?;
+    let sessions_i = build_sessions_menu(app)?;
+    let focus_i = build_focus_menu(app)?;
+    let mute_i = CheckMenuItem::with_id(
+        app,
+        "mute-voice",
+        "Mute Voice",
+        true,
+        !config.tts_enabled,
+        None::<&str>,
+    )?;
+    let dnd_i = CheckMenuItem::with_id(
+        app,
+        "dnd",
+        "Do Not Disturb",
+        true,
+        crate::dnd::active(),
+        None::<&str>,
+    )?;
+    let click_through_i = CheckMenuItem::with_id(
+        app,
+        "click-through",
+        "Click-Through When Idle",
+        true,
+        config.click_through_idle,
+        None::<&str>,
+    )?;
+    let settings_i = MenuItem::with_id(app, "settings", "Settings", true, None::<&str>)?;
+    let quit_i = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
+
+    Menu::with_items(
+        app,
+        &[
+            &show_i,
+            &sessions_i,
+            &models_i,
+            &profiles_i,
+            &focus_i,
+            &dnd_i,
+            &mute_i,
+            &click_through_i,
+            &settings_i,
+            &quit_i,
+        ],
+    )
+}
+
+fn build_profiles_menu(app: &AppHandle) -> tauri::Result<Submenu<Wry>> {
+    let list = profiles::list().unwrap_or_else(|e| {
+        tracing::warn!("Failed to list profiles: {}", e);
+        profiles::ProfileList {
+            active: profiles::DEFAULT_PROFILE.to_string(),
+            profiles: vec![profiles::DEFAULT_PROFILE.to_string()],
+        }
+    });
+
+    let items = list
+        .profiles
+        .iter()
+        .map(|name| {
+            CheckMenuItem::with_id(
+                app,
+                format!("{}{}", PROFILE_PREFIX, name),
+                name,
+                true,
+                *name == list.active,
+                None::<&str>,
+            )
+        })
+        .collect::<tauri::Result<Vec<_>>>()?;
+    let items: Vec<&dyn IsMenuItem<Wry>> =
+        items.iter().map(|i| i as &dyn IsMenuItem<Wry>).collect();
+
+    Submenu::with_items(app, "Profiles", true, &items)
+}
+
+fn build_sessions_menu(app: &AppHandle) -> tauri::Result<Submenu<Wry>> {
+    let sessions = crate::sessions::recent(RECENT_SESSIONS);
+    if sessions.is_empty() {
+        let empty = MenuItem::with_id(
+            app,
+            "no-sessions",
+            "No conversations yet",
+            false,
+            None::<&str>,
+        )?;
+        return Submenu::with_items(app, "Recent Chats", true, &[&empty]);
+    }
+
+    let items = sessions
+        .iter()
+        .map(|session| {
+            MenuItem::with_id(
+                app,
+                format!("{}{}", SESSION_PREFIX, session.id),
+                &session.title,
+                true,
+                None::<&str>,
+            )
+        })
+        .collect::<tauri::Result<Vec<_>>>()?;
+    let items: Vec<&dyn IsMenuItem<Wry>> =
+        items.iter().map(|i| i as &dyn IsMenuItem<Wry>).collect();
+
+    Submenu::with_items(app, "Recent Chats", true, &items)
+}
+
+/// Start or stop the pomodoro timer, with the current phase shown while it runs
+fn build_focus_menu(app: &AppHandle) -> tauri::Result<Submenu<Wry>> {
+    let status = crate::pomodoro::status();
+    let Some(phase) = status.phase else {
+        let start = MenuItem::with_id(app, "focus-start", "Start", true, None::<&str>)?;
+        return Submenu::with_items(app, "Focus Timer", true, &[&start]);
+    };
+
+    let until = status
+        .ends_at
+        .and_then(|t| chrono::DateTime::from_timestamp(t, 0))
+        .map(|t| {
+            t.with_timezone(&chrono::Local)
+                .format(" until %H:%M")
+                .to_string()
+        })
+        .unwrap_or_default();
+    let current = MenuItem::with_id(
+        app,
+        "focus-status",
+        format!("{}{}", phase.label(), until),
+        false,
+        None::<&str>,
+    )?;
+    let restart = MenuItem::with_id(app, "focus-start", "Restart", true, None::<&str>)?;
+    let stop = MenuItem::with_id(app, "focus-stop", "Stop", true, None::<&str>)?;
+    Submenu::with_items(app, "Focus Timer", true, &[&current, &restart, &stop])
+}
+
+/// Quick switcher over recent and configured models; ids index into `Config::model_choices`.
+fn build_models_menu(app: &AppHandle, config: &Config) -> tauri::Result<Submenu<Wry>> {
+    let current = config.current_model();
+    let items = config
+        .model_choices()
+        .iter()
+        .enumerate()
+        .map(|(index, choice)| {
+            CheckMenuItem::with_id(
+                app,
+                format!("{}{}", MODEL_PREFIX, index),
+                choice.label(),
+                true,
+                *choice == current,
+                None::<&str>,
+            )
+        })
+        .collect::<tauri::Result<Vec<_>>>()?;
+    let items: Vec<&dyn IsMenuItem<Wry>> =
+        items.iter().map(|i| i as &dyn IsMenuItem<Wry>).collect();
+
+    Submenu::with_items(app, "Model", true, &items)
+}
+
+fn handle_menu_event(app: &AppHandle, event: MenuEvent) {
+    let id = event.id().as_ref();
+
+    if let Some(session_id) = id.strip_prefix(SESSION_PREFIX) {
+        let app = app.clone();
+        let session_id = session_id.to_string();
+        tauri::async_runtime::spawn(async move {
+            let state = app.state::<crate::Conversations>().get("clippy").await;
+            if let Err(e) =
+                crate::commands::open_session(&app, "clippy", &session_id, &state).await
+            {
+                tracing::warn!("{}", e);
+            }
+        });
+        return;
+    }
+
+    if let Some(index) = id.strip_prefix(MODEL_PREFIX) {
+        if let Ok(index) = index.parse::<usize>() {
+            switch_model(app, index);
+        }
+        return;
+    }
+
+    if let Some(name) = id.strip_prefix(PROFILE_PREFIX) {
+        let app = app.clone();
+        let name = name.to_string();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = crate::commands::activate_profile(&app, &name).await {
+                tracing::warn!("Failed to switch profile from tray: {}", e);
+                // Undo the check mark the click toggled
+                refresh(&app);
+            }
+        });
+        return;
+    }
+
+    match id {
+        "show" => {
+            crate::ghost::wake(app);
+            if crate::peek::summon(app) {
+                return;
+            }
+            if let Some(window) = app.get_webview_window("clippy") {
+                crate::window_state::move_to_cursor_monitor(&window);
+                crate::sounds::play(crate::sounds::Effect::Appear);
+                let _ = window.show();
+                let _ = window.set_focus();
+            }
+            crate::notifications::open_pending(app);
+        }
+        "focus-start" => {
+            crate::pomodoro::start(app);
+        }
+        "focus-stop" => crate::pomodoro::stop(app),
+        "dnd" => {
+            crate::dnd::set(app, !crate::dnd::active());
+        }
+        "mute-voice" => toggle_mute(app),
+        "click-through" => toggle_click_through(app),
+        "settings" => {
+            if let Some(window) = app.get_webview_window("clippy") {
+                let _ = window.emit("open-settings", ());
+            }
+        }
+        "quit" => {
+            app.exit(0);
+        }
+        _ => {}
+    }
+}
+
+/// Flip the click-through idle mode; config_watch picks up the change.
+fn toggle_click_through(app: &AppHandle) {
+    let mut config = match Config::load() {
+        Ok(config) => config,
+        Err(e) => {
+            tracing::warn!("Failed to load config: {}", e);
+            return;
+        }
+    };
+    config.click_through_idle = !config.click_through_idle;
+    if let Err(e) = config.save() {
+        tracing::warn!("Failed to save click-through setting: {}", e);
+        refresh(app);
+    }
+}
+
+/// Flip `tts_enabled`, cutting off any speech in progress when muting.
+/// config_watch unloads or reloads the voice once the change is saved.
+pub(crate) fn toggle_mute(app: &AppHandle) {
+    let mut config = match Config::load() {
+        Ok(config) => config,
+        Err(e) => {
+            tracing::warn!("Failed to load config: {}", e);
+            return;
+        }
+    };
+    config.tts_enabled = !config.tts_enabled;
+    if !config.tts_enabled {
+        crate::tts::stop();
+    }
+    if let Err(e) = config.save() {
+        tracing::warn!("Failed to save voice setting: {}", e);
+        refresh(app);
+        return;
+    }
+    let _ = app.emit(
+        "voice-muted",
+        crate::commands::VoiceMutedEvent {
+            muted: !config.tts_enabled,
+        },
+    );
+}
+
+/// Switch to the `index`th model choice; config_watch unloads the old model and rebuilds the menu.
+fn switch_model(app: &AppHandle, index: usize) {
+    let mut config = match Config::load() {
+        Ok(config) => config,
+        Err(e) => {
+            tracing::warn!("Failed to load config: {}", e);
+            return;
+        }
+    };
+    let Some(choice) = config.model_choices().into_iter().nth(index) else {
+        refresh(app);
+        return;
+    };
+    config.use_model(&choice);
+    if let Err(e) = config.save() {
+        tracing::warn!("Failed to switch model: {}", e);
+        refresh(app);
+        return;
+    }
+    tracing::info!("Switched to {} from the tray", choice.label());
+}
+
+/// Show `status` on the tray icon.
+pub fn set_status(app: &AppHandle, status: TrayStatus) {
+    match STATUS.lock() {
+        Ok(mut current) if *current != status => *current = status,
+        _ => return,
+    }
+    let generation = STATUS_GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+    render_status(app, status, true);
+
+    if status.blinks() {
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            let mut dot_visible = true;
+            loop {
+                tokio::time::sleep(BLINK_INTERVAL).await;
+                if STATUS_GENERATION.load(Ordering::SeqCst) != generation {
+                    break;
+                }
+                dot_visible = !dot_visible;
+                render_status(&app, status, dot_visible);
+            }
+        });
+    }
+}
+
+/// Go back to idle, unless something else has taken over the icon since `status` was set.
+pub fn clear_status(app: &AppHandle, status: TrayStatus) {
+    if STATUS.lock().is_ok_and(|current| *current == status) {
+        set_status(app, TrayStatus::Idle);
+    }
+}
+
+fn render_status(app: &AppHandle, status: TrayStatus, dot_visible: bool) {
+    let (Some(tray), Some(base)) = (app.tray_by_id(TRAY_ID), app.default_window_icon()) else {
+        return;
+    };
+    let icon = match status.color().filter(|_| dot_visible) {
+        Some(color) => with_status_dot(base, color),
+        None => base.clone().to_owned(),
+    };
+    let _ = tray.set_icon(Some(icon));
+    let _ = tray.set_tooltip(Some(status.tooltip()));
+}
+
+/// Copy of `base` with a filled circle in the bottom-right corner
+fn with_status_dot(base: &Image<'_>, color: [u8; 4]) -> Image<'static> {
+    let (width, height) = (base.width(), base.height());
+    let mut rgba = base.rgba().to_vec();
+    let radius = width.min(height) as f32 / 5.0;
+    let (center_x, center_y) = (width as f32 - radius, height as f32 - radius);
+
+    for y in 0..height {
+        for x in 0..width {
+            let dx = x as f32 + 0.5 - center_x;
+            let dy = y as f32 + 0.5 - center_y;
+            if dx * dx + dy * dy <= radius * radius {
+                let i = ((y * width + x) * 4) as usize;
+                rgba[i..i + 4].copy_from_slice(&color);
+            }
+        }
+    }
+    Image::new_owned(rgba, width, height)
+}
@@ -1,26 +1,93 @@
 use anyhow::{anyhow, Result};
 use piper_rs::synth::PiperSpeechSynthesizer;
-use rodio::{buffer::SamplesBuffer, OutputStream, Sink};
+use piper_rs::{PiperModel, SynthesisConfig};
+use rodio::{buffer::SamplesBuffer, Decoder, OutputStream, Sink};
+use crate::config::TtsQueuePolicy;
+use std::collections::{HashMap, VecDeque};
 use std::path::{Path, PathBuf};
-use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
 use tracing::{error, info, warn};
 
 /// Default voice model to download from HuggingFace
 const DEFAULT_VOICE_MODEL: &str = "en_US-amy-medium";
 const DEFAULT_SAMPLE_RATE: u32 = 22050;
+/// The Piper voice repo on HuggingFace
+const VOICES_URL: &str = "https://huggingface.co/rhasspy/piper-voices/resolve/main";
+/// Piper's quality tiers, best first, tried in turn when a voice's own isn't available
+const QUALITIES: [&str; 3] = ["high", "medium", "low"];
+/// Pre-rendered clips of every Piper voice, laid out like the voice repo
+const SAMPLES_URL: &str = "https://rhasspy.github.io/piper-samples/samples";
+
+/// OpenAI's API, for the cloud speech engine
+const OPENAI_URL: &str = "https://api.openai.com/v1";
+/// ElevenLabs' API, for the streaming cloud speech engine
+const ELEVENLABS_URL: &str = "https://api.elevenlabs.io/v1";
+/// ElevenLabs is asked for raw 16-bit mono PCM at this rate, which plays as it arrives
+const ELEVENLABS_SAMPLE_RATE: u32 = 22050;
 
 /// Managed Tauri state for TTS — uses Arc so we can clone a handle for blocking threads
-pub struct TtsState(pub Mutex<Option<Arc<PiperTTSEngine>>>);
+pub struct TtsState(pub Mutex<Option<Arc<dyn TtsEngine>>>);
+
+/// A sentence made ready to play by `TtsEngine::prepare`
+pub enum Utterance {
+    /// Synthesized mono audio
+    Samples { samples: Arc<Vec<f32>>, sample_rate: u32 },
+    /// An encoded clip in any format `play_bytes` takes
+    Encoded(Vec<u8>),
+    /// Text for an engine that only synthesizes while it plays
+    Text(String),
+}
+
+/// Something that can speak Clippy's replies aloud.
+pub trait TtsEngine: Send + Sync {
+    /// Synthesize text and play it through the default audio output.
+    /// This is fully synchronous — call from a blocking thread.
+    fn speak(&self, text: &str) -> Result<()>;
+
+    /// Get `text` ready to play, so it can wait in the speech queue while an earlier
+    /// sentence plays. Engines that synthesize as they play hand the text back as it is.
+    fn prepare(&self, text: &str) -> Result<Utterance> {
+        Ok(Utterance::Text(text.to_string()))
+    }
+
+    /// Play what `prepare` made of a sentence. Blocking, like `speak`.
+    fn play(&self, utterance: Utterance) -> Result<()> {
+        match utterance {
+            Utterance::Samples { samples, .. } if samples.is_empty() => Ok(()),
+            Utterance::Samples {
+                samples,
+                sample_rate,
+            } => play_audio(&samples, sample_rate),
+            Utterance::Encoded(bytes) => play_bytes(bytes),
+            Utterance::Text(text) => self.speak(&text),
+        }
+    }
+
+    /// Synthesize ahead of time any of `phrases` not already cached, so speaking them later
+    /// skips synthesis. Returns how many were added. Blocking, like `speak`.
+    fn warm(&self, _phrases: &[String]) -> usize {
+        0
+    }
+}
 
 /// Piper TTS engine wrapper — cross-platform, offline, fast neural TTS.
 pub struct PiperTTSEngine {
     synth: PiperSpeechSynthesizer,
+    model: Arc<dyn PiperModel + Send + Sync>,
+    /// The voice's own synthesis settings, which each sentence's emotion scales
+    base_config: Option<SynthesisConfig>,
     sample_rate: u32,
     _speaker_id: Option<i64>,
+    /// Audio for phrases synthesized ahead of time by `warm`, keyed by their text
+    cache: Mutex<HashMap<String, Arc<Vec<f32>>>>,
+    /// Held across each synthesis, since every sentence's emotion is set on the shared model
+    synthesizing: Mutex<()>,
 }
 
-// PiperSpeechSynthesizer doesn't implement Send by default, but we only
-// access it from one thread at a time (behind a Mutex + spawn_blocking).
+// PiperSpeechSynthesizer doesn't implement Send by default. The engine is shared between
+// blocking threads (speaking, the speech queue and cache warming), but the synthesizer and
+// the model's settings are only touched while `synthesizing` is held.
 unsafe impl Send for PiperTTSEngine {}
 
 impl PiperTTSEngine {
@@ -35,56 +102,570 @@ impl PiperTTSEngine {
             model.set_speaker(sid);
         }
 
-        let synth = PiperSpeechSynthesizer::new(model)
+        let base_config = model
+            .get_fallback_synthesis_config()
+            .ok()
+            .and_then(|config| config.downcast::<SynthesisConfig>().ok())
+            .map(|config| *config);
+        if base_config.is_none() {
+            warn!("Piper TTS: voice settings unavailable, speaking without emotion");
+        }
+
+        let synth = PiperSpeechSynthesizer::new(model.clone())
             .map_err(|e| anyhow!("Failed to create Piper synthesizer: {:?}", e))?;
 
         info!("Piper TTS: model loaded successfully");
         Ok(Self {
             synth,
+            model,
+            base_config,
             sample_rate: DEFAULT_SAMPLE_RATE,
             _speaker_id: speaker_id,
+            cache: Mutex::new(HashMap::new()),
+            synthesizing: Mutex::new(()),
         })
     }
+}
 
-    /// Synthesize text and play it through the default audio output.
-    /// This is fully synchronous — call from a blocking thread.
-    pub fn speak(&self, text: &str) -> Result<()> {
-        info!("Piper TTS: synthesizing \"{}\" ({} chars)", text, text.len());
+impl TtsEngine for PiperTTSEngine {
+    fn speak(&self, text: &str) -> Result<()> {
+        let generation = GENERATION.load(Ordering::SeqCst);
+        let utterance = self.prepare(text)?;
+        // Stopped while this was being synthesized, e.g. by a new message
+        if GENERATION.load(Ordering::SeqCst) != generation {
+            info!("Piper TTS: dropping speech that was interrupted");
+            return Ok(());
+        }
+        self.play(utterance)?;
+        info!("Piper TTS: playback finished");
+        Ok(())
+    }
+
+    fn prepare(&self, text: &str) -> Result<Utterance> {
+        let cached = self
+            .cache
+            .lock()
+            .ok()
+            .and_then(|cache| cache.get(text.trim()).cloned());
+        let samples = match cached {
+            Some(samples) => {
+                info!("Piper TTS: using cached \"{}\"", text);
+                samples
+            }
+            None => Arc::new(self.synthesize(text)?),
+        };
+        info!(
+            "Piper TTS: {} samples ({:.1}s at {} Hz) ready",
+            samples.len(),
+            samples.len() as f64 / self.sample_rate as f64,
+            self.sample_rate
+        );
+        Ok(Utterance::Samples {
+            samples,
+            sample_rate: self.sample_rate,
+        })
+    }
 
-        let audio = self
-            .synth
-            .synthesize_parallel(text.to_string(), None)
-            .map_err(|e| anyhow!("Piper synthesis failed: {:?}", e))?;
+    fn warm(&self, phrases: &[String]) -> usize {
+        let mut added = 0;
+        for phrase in phrases.iter().map(|p| p.trim()).filter(|p| !p.is_empty()) {
+            let cached = self
+                .cache
+                .lock()
+                .map(|cache| cache.contains_key(phrase))
+                .unwrap_or(true);
+            if cached {
+                continue;
+            }
+            match self.synthesize(phrase) {
+                Ok(samples) => {
+                    if let Ok(mut cache) = self.cache.lock() {
+                        cache.insert(phrase.to_string(), Arc::new(samples));
+                        added += 1;
+                    }
+                }
+                Err(e) => warn!("Piper TTS: failed to pre-synthesize \"{}\": {}", phrase, e),
+            }
+        }
+        added
+    }
+}
+
+impl PiperTTSEngine {
+    /// Raw audio for `text`, with trailing silence so playback isn't cut short. Each run of
+    /// sentences is delivered in the emotion its wording suggests, given Clippy's mood.
+    fn synthesize(&self, text: &str) -> Result<Vec<f32>> {
+        let text = crate::normalize::for_speech(&crate::pronunciation::apply(text));
+        info!("Piper TTS: synthesizing \"{}\" ({} chars)", text, text.len());
 
+        let guard = self
+            .synthesizing
+            .lock()
+            .map_err(|e| anyhow!("Piper lock error: {}", e))?;
         let mut samples: Vec<f32> = Vec::new();
-        for result in audio {
-            let chunk = result.map_err(|e| anyhow!("Piper audio chunk error: {:?}", e))?;
-            let raw: Vec<f32> = chunk.into_vec();
-            samples.extend_from_slice(&raw);
+        for (emotion, part) in crate::emotion::segments(&text, crate::mood::current()) {
+            self.set_emotion(emotion);
+            let audio = self
+                .synth
+                .synthesize_parallel(part, None)
+                .map_err(|e| anyhow!("Piper synthesis failed: {:?}", e))?;
+            for result in audio {
+                let chunk = result.map_err(|e| anyhow!("Piper audio chunk error: {:?}", e))?;
+                let raw: Vec<f32> = chunk.into_vec();
+                samples.extend_from_slice(&raw);
+            }
         }
+        self.set_emotion(crate::emotion::Emotion::Neutral);
+        drop(guard);
 
         if samples.is_empty() {
             warn!("Piper TTS: synthesis returned empty audio");
-            return Ok(());
+            return Ok(samples);
         }
 
         // Append 250ms of silence to prevent the audio from being cut off too early
         let silence_samples = (self.sample_rate as f32 * 0.25) as usize;
         samples.extend(std::iter::repeat(0.0f32).take(silence_samples));
+        Ok(samples)
+    }
 
-        info!(
-            "Piper TTS: synthesized {} samples ({:.1}s at {} Hz), playing...",
-            samples.len(),
-            samples.len() as f64 / self.sample_rate as f64,
-            self.sample_rate
+    /// Scale the voice's own settings for `emotion` ahead of the next synthesis. Only call
+    /// with `synthesizing` held.
+    fn set_emotion(&self, emotion: crate::emotion::Emotion) {
+        let Some(base) = &self.base_config else {
+            return;
+        };
+        let prosody = emotion.prosody();
+        let config = SynthesisConfig {
+            speaker: base.speaker.clone(),
+            length_scale: base.length_scale * prosody.length_scale,
+            noise_scale: base.noise_scale * prosody.noise_scale,
+            noise_w: base.noise_w * prosody.noise_w,
+        };
+        if let Err(e) = self.model.set_fallback_synthesis_config(&config) {
+            warn!("Piper TTS: failed to apply {:?} delivery: {:?}", emotion, e);
+        }
+    }
+}
+
+/// Speech from OpenAI's `/audio/speech` endpoint, for users who'd rather pay for a more
+/// natural voice than run Piper locally.
+pub struct OpenAITtsEngine {
+    api_key: String,
+    model: String,
+    voice: String,
+}
+
+impl OpenAITtsEngine {
+    pub fn new(api_key: String, model: String, voice: String) -> Self {
+        Self {
+            api_key,
+            model,
+            voice,
+        }
+    }
+}
+
+#[derive(serde::Serialize)]
+struct SpeechRequest<'a> {
+    model: &'a str,
+    voice: &'a str,
+    input: &'a str,
+    response_format: &'static str,
+}
+
+impl TtsEngine for OpenAITtsEngine {
+    fn speak(&self, text: &str) -> Result<()> {
+        let generation = GENERATION.load(Ordering::SeqCst);
+        let utterance = self.prepare(text)?;
+        // Stopped while this was being synthesized, e.g. by a new message
+        if GENERATION.load(Ordering::SeqCst) != generation {
+            info!("OpenAI TTS: dropping speech that was interrupted");
+            return Ok(());
+        }
+        self.play(utterance)?;
+        info!("OpenAI TTS: playback finished");
+        Ok(())
+    }
+
+    fn prepare(&self, text: &str) -> Result<Utterance> {
+        // OpenAI reads numbers and links well on its own; respellings still apply
+        let text = crate::pronunciation::apply(text);
+        info!("OpenAI TTS: synthesizing {} chars with {}", text.len(), self.voice);
+
+        // Built here rather than in `new`: the blocking client can't be created on an
+        // async runtime thread, and `speak` always runs on a blocking one
+        let client = reqwest::blocking::Client::builder()
+            .timeout(std::time::Duration::from_secs(60))
+            .build()?;
+        let response = client
+            .post(format!("{}/audio/speech", OPENAI_URL))
+            .bearer_auth(&self.api_key)
+            .json(&SpeechRequest {
+                model: &self.model,
+                voice: &self.voice,
+                input: &text,
+                response_format: "mp3",
+            })
+            .send()?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().unwrap_or_default();
+            return Err(anyhow!("OpenAI API error {}: {}", status, error_text));
+        }
+        Ok(Utterance::Encoded(response.bytes()?.to_vec()))
+    }
+}
+
+/// Speech from ElevenLabs, played while it's still streaming in so the first words are
+/// heard before the rest has been generated.
+pub struct ElevenLabsTtsEngine {
+    api_key: String,
+    voice_id: String,
+    model: String,
+}
+
+impl ElevenLabsTtsEngine {
+    pub fn new(api_key: String, voice_id: String, model: String) -> Self {
+        Self {
+            api_key,
+            voice_id,
+            model,
+        }
+    }
+}
+
+#[derive(serde::Serialize)]
+struct ElevenLabsRequest<'a> {
+    text: &'a str,
+    model_id: &'a str,
+}
+
+impl TtsEngine for ElevenLabsTtsEngine {
+    fn speak(&self, text: &str) -> Result<()> {
+        use std::io::Read;
+
+        let generation = GENERATION.load(Ordering::SeqCst);
+        let text = crate::pronunciation::apply(text);
+        info!("ElevenLabs TTS: streaming {} chars with voice {}", text.len(), self.voice_id);
+
+        // Built here rather than in `new`, like the OpenAI engine's
+        let client = reqwest::blocking::Client::builder()
+            .timeout(std::time::Duration::from_secs(120))
+            .build()?;
+        let mut response = client
+            .post(format!("{}/text-to-speech/{}/stream", ELEVENLABS_URL, self.voice_id))
+            .query(&[
+                ("output_format", format!("pcm_{}", ELEVENLABS_SAMPLE_RATE)),
+                ("optimize_streaming_latency", "3".to_string()),
+            ])
+            .header("xi-api-key", &self.api_key)
+            .json(&ElevenLabsRequest {
+                text: &text,
+                model_id: &self.model,
+            })
+            .send()?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().unwrap_or_default();
+            return Err(anyhow!("ElevenLabs API error {}: {}", status, error_text));
+        }
+
+        let (_stream, stream_handle) =
+            OutputStream::try_default().map_err(|e| anyhow!("Failed to open audio output: {}", e))?;
+        let sink = Arc::new(
+            Sink::try_new(&stream_handle)
+                .map_err(|e| anyhow!("Failed to create audio sink: {}", e))?,
         );
+        set_playing(&sink);
 
-        play_audio(&samples, self.sample_rate)?;
-        info!("Piper TTS: playback finished");
+        // Queue each chunk as it lands; an odd trailing byte waits for the next one
+        let mut chunk = [0u8; 8192];
+        let mut pending: Vec<u8> = Vec::new();
+        loop {
+            let read = response.read(&mut chunk)?;
+            if read == 0 {
+                break;
+            }
+            if GENERATION.load(Ordering::SeqCst) != generation {
+                info!("ElevenLabs TTS: dropping speech that was interrupted");
+                break;
+            }
+            pending.extend_from_slice(&chunk[..read]);
+            let whole = pending.len() - pending.len() % 2;
+            let samples: Vec<f32> = pending[..whole]
+                .chunks_exact(2)
+                .map(|b| i16::from_le_bytes([b[0], b[1]]) as f32 / i16::MAX as f32)
+                .collect();
+            pending.drain(..whole);
+            sink.append(SamplesBuffer::new(1, ELEVENLABS_SAMPLE_RATE, samples));
+        }
+
+        sink.sleep_until_end();
+        clear_playing(&sink);
+        info!("ElevenLabs TTS: playback finished");
         Ok(())
     }
 }
 
+/// Whether Piper is the chosen engine, so a voice that was just downloaded or installed
+/// should start speaking right away.
+pub fn piper_selected() -> bool {
+    crate::config::Config::load()
+        .map(|c| c.tts_engine == crate::config::TtsEngineType::Piper)
+        .unwrap_or(true)
+}
+
+/// `text` cut down to its first `max` sentences, with a pointer to the rest, so a long
+/// answer isn't synthesized into minutes of audio. `None` leaves it whole.
+pub fn limit_sentences(text: &str, max: Option<u32>) -> String {
+    let sentences = crate::emotion::sentences(text);
+    match max {
+        Some(max) if sentences.len() > max as usize => {
+            let spoken = sentences[..max as usize].concat();
+            format!("{} The rest is in the chat.", spoken.trim_end())
+        }
+        _ => text.to_string(),
+    }
+}
+
+/// Sentences of a reply made ready to play, waiting their turn. It holds at most `size`,
+/// so a long answer synthesized faster than it's spoken can't pile up minutes of audio.
+struct SpeechQueue {
+    state: Mutex<QueueState>,
+    changed: Condvar,
+    size: usize,
+    policy: TtsQueuePolicy,
+}
+
+#[derive(Default)]
+struct QueueState {
+    waiting: VecDeque<Utterance>,
+    /// Every sentence has been added
+    finished: bool,
+    /// Playback stopped early, so nothing more will be taken
+    abandoned: bool,
+}
+
+impl SpeechQueue {
+    fn new(size: usize, policy: TtsQueuePolicy) -> Self {
+        Self {
+            state: Mutex::new(QueueState::default()),
+            changed: Condvar::new(),
+            size: size.max(1),
+            policy,
+        }
+    }
+
+    /// Add the next sentence, applying the policy when the queue is full. Returns false
+    /// once playback has stopped and no more should be prepared.
+    fn push(&self, utterance: Utterance) -> bool {
+        let Ok(mut state) = self.state.lock() else {
+            return false;
+        };
+        if self.policy == TtsQueuePolicy::Block {
+            while state.waiting.len() >= self.size && !state.abandoned {
+                state = match self.changed.wait(state) {
+                    Ok(state) => state,
+                    Err(_) => return false,
+                };
+            }
+        }
+        if state.abandoned {
+            return false;
+        }
+        if state.waiting.len() >= self.size {
+            if self.policy == TtsQueuePolicy::DropNewest {
+                info!("Speech queue full, skipping a sentence");
+                return true;
+            }
+            state.waiting.pop_front();
+            info!("Speech queue full, skipping the oldest waiting sentence");
+        }
+        state.waiting.push_back(utterance);
+        self.changed.notify_all();
+        true
+    }
+
+    /// The next sentence to play, waiting for one to be ready; `None` once all have played.
+    fn pop(&self) -> Option<Utterance> {
+        let mut state = self.state.lock().ok()?;
+        loop {
+            if let Some(utterance) = state.waiting.pop_front() {
+                self.changed.notify_all();
+                return Some(utterance);
+            }
+            if state.finished {
+                return None;
+            }
+            state = self.changed.wait(state).ok()?;
+        }
+    }
+
+    fn finish(&self) {
+        if let Ok(mut state) = self.state.lock() {
+            state.finished = true;
+            self.changed.notify_all();
+        }
+    }
+
+    fn abandon(&self) {
+        if let Ok(mut state) = self.state.lock() {
+            state.abandoned = true;
+            state.waiting.clear();
+            self.changed.notify_all();
+        }
+    }
+}
+
+/// Speak `text` a sentence at a time: each is prepared on a thread of its own while the one
+/// before plays here, with at most `queue_size` waiting in between and `policy` deciding what
+/// happens past that. Blocking, like `TtsEngine::speak`.
+pub fn speak_queued(
+    engine: Arc<dyn TtsEngine>,
+    text: &str,
+    queue_size: u32,
+    policy: TtsQueuePolicy,
+) -> Result<()> {
+    let generation = GENERATION.load(Ordering::SeqCst);
+    let sentences: Vec<String> = crate::emotion::sentences(text)
+        .into_iter()
+        .map(|s| s.trim().to_string())
+        .collect();
+    let queue = Arc::new(SpeechQueue::new(queue_size as usize, policy));
+
+    let preparer = {
+        let engine = Arc::clone(&engine);
+        let queue = Arc::clone(&queue);
+        std::thread::spawn(move || -> Result<()> {
+            let mut result = Ok(());
+            for sentence in sentences {
+                if GENERATION.load(Ordering::SeqCst) != generation {
+                    break;
+                }
+                match engine.prepare(&sentence) {
+                    Ok(utterance) => {
+                        if !queue.push(utterance) {
+                            break;
+                        }
+                    }
+                    Err(e) => {
+                        result = Err(e);
+                        break;
+                    }
+                }
+            }
+            queue.finish();
+            result
+        })
+    };
+
+    let mut played = Ok(());
+    while let Some(utterance) = queue.pop() {
+        // Stopped while this was waiting, e.g. by a new message
+        if GENERATION.load(Ordering::SeqCst) != generation {
+            info!("Dropping speech that was interrupted");
+            break;
+        }
+        if let Err(e) = engine.play(utterance) {
+            played = Err(e);
+            break;
+        }
+    }
+    queue.abandon();
+    let prepared = preparer
+        .join()
+        .map_err(|_| anyhow!("Speech preparation thread panicked"))?;
+    played.and(prepared)
+}
+
+/// The phrases worth having ready for `config`: its configured ones plus the greetings of
+/// the active personality.
+pub fn phrases_to_cache(config: &crate::config::Config) -> Vec<String> {
+    let mut phrases = config.tts_cached_phrases.clone();
+    phrases.extend(crate::personality::get(&config.personality).greetings);
+    phrases
+}
+
+/// Warm a freshly loaded engine's cache in the background; failures are only logged.
+pub fn warm_in_background(engine: Arc<PiperTTSEngine>, config: &crate::config::Config) {
+    let phrases = phrases_to_cache(config);
+    tauri::async_runtime::spawn_blocking(move || {
+        let added = engine.warm(&phrases);
+        info!("Piper TTS: pre-synthesized {} phrases", added);
+    });
+}
+
+/// Speak `text` with the loaded voice, if there is one, for Clippy's unprompted messages.
+/// Failures are only logged.
+pub async fn say(app: &tauri::AppHandle, text: String) {
+    use tauri::Manager;
+
+    let engine = app.try_state::<TtsState>().and_then(|tts| {
+        tts.0
+            .lock()
+            .ok()
+            .and_then(|guard| guard.as_ref().map(Arc::clone))
+    });
+    let Some(engine) = engine else {
+        return;
+    };
+    if crate::app_rules::silenced() || crate::dnd::active() {
+        return;
+    }
+    crate::tray::set_status(app, crate::tray::TrayStatus::Speaking);
+    crate::animation::enter(app, crate::animation::AppState::Speaking);
+    let result = tokio::task::spawn_blocking(move || engine.speak(&text)).await;
+    crate::tray::clear_status(app, crate::tray::TrayStatus::Speaking);
+    crate::animation::leave(app, crate::animation::AppState::Speaking);
+    match result {
+        Ok(Ok(())) => crate::stats::record_tts_utterance(),
+        Ok(Err(e)) => warn!("Failed to speak: {}", e),
+        Err(e) => warn!("TTS task error: {}", e),
+    }
+}
+
+/// The sink currently playing, so speech can be cut off (e.g. when muted)
+static PLAYING: Mutex<Option<Arc<Sink>>> = Mutex::new(None);
+/// Bumped by `stop`, so speech still being synthesized knows not to play
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// Block until `sink` finishes or is stopped by `stop`.
+fn play_until_end(sink: Sink) {
+    let sink = Arc::new(sink);
+    set_playing(&sink);
+    sink.sleep_until_end();
+    clear_playing(&sink);
+}
+
+/// Make `sink` the one `stop` cuts off.
+fn set_playing(sink: &Arc<Sink>) {
+    if let Ok(mut playing) = PLAYING.lock() {
+        *playing = Some(Arc::clone(sink));
+    }
+}
+
+/// Forget `sink` once it's done, unless something else has started playing since.
+fn clear_playing(sink: &Arc<Sink>) {
+    if let Ok(mut playing) = PLAYING.lock() {
+        if playing.as_ref().is_some_and(|p| Arc::ptr_eq(p, sink)) {
+            *playing = None;
+        }
+    }
+}
+
+/// Cut off whatever is being spoken or played right now, and drop any speech still being
+/// synthesized.
+pub fn stop() {
+    GENERATION.fetch_add(1, Ordering::SeqCst);
+    if let Ok(mut playing) = PLAYING.lock() {
+        if let Some(sink) = playing.take() {
+            sink.stop();
+            info!("Audio playback stopped");
+        }
+    }
+}
+
 /// Play f32 audio samples through the default output device.
 fn play_audio(samples: &[f32], sample_rate: u32) -> Result<()> {
     let (_stream, stream_handle) = OutputStream::try_default().map_err(|e| {
@@ -99,7 +680,38 @@ fn play_audio(samples: &[f32], sample_rate: u32) -> Result<()> {
 
     let source = SamplesBuffer::new(1, sample_rate, samples.to_vec());
     sink.append(source);
-    sink.sleep_until_end();
+    play_until_end(sink);
+    Ok(())
+}
+
+/// Play an audio file (WAV, MP3, FLAC or Ogg Vorbis) through the default output device.
+pub fn play_file(path: &Path) -> Result<()> {
+    let file = std::fs::File::open(path)
+        .map_err(|e| anyhow!("Failed to open {}: {}", path.display(), e))?;
+    let source = Decoder::new(std::io::BufReader::new(file))
+        .map_err(|e| anyhow!("Failed to decode {}: {}", path.display(), e))?;
+    play_source(source)
+}
+
+/// Play an in-memory audio clip in any format `play_file` takes.
+pub fn play_bytes(bytes: Vec<u8>) -> Result<()> {
+    let source = Decoder::new(std::io::Cursor::new(bytes))
+        .map_err(|e| anyhow!("Failed to decode audio: {}", e))?;
+    play_source(source)
+}
+
+fn play_source<S>(source: S) -> Result<()>
+where
+    S: rodio::Source + Send + 'static,
+    S::Item: rodio::Sample + Send,
+    f32: rodio::cpal::FromSample<S::Item>,
+{
+    let (_stream, stream_handle) =
+        OutputStream::try_default().map_err(|e| anyhow!("Failed to open audio output: {}", e))?;
+    let sink =
+        Sink::try_new(&stream_handle).map_err(|e| anyhow!("Failed to create audio sink: {}", e))?;
+    sink.append(source);
+    play_until_end(sink);
     Ok(())
 }
 
@@ -122,13 +734,12 @@ pub fn default_voice_ready() -> bool {
     }
 }
 
-/// Check if a specific voice model is ready.
+/// Check if a specific voice model is ready: both its model and its config are on disk.
 pub fn voice_ready(voice_name: &str) -> bool {
     if let Ok(dir) = voices_dir() {
-        let config = dir
-            .join(voice_name)
-            .join(format!("{}.onnx.json", voice_name));
-        config.exists()
+        let dir = dir.join(voice_name);
+        dir.join(format!("{}.onnx", voice_name)).exists()
+            && dir.join(format!("{}.onnx.json", voice_name)).exists()
     } else {
         false
     }
@@ -150,19 +761,25 @@ pub fn voice_config(voice_name: &str) -> Result<PathBuf> {
         .join(format!("{}.onnx.json", voice_name)))
 }
 
-/// Download a Piper voice model from HuggingFace.
-/// Returns the path to the config JSON file.
-pub fn download_voice(voice_name: &str, data_dir: &Path) -> Result<PathBuf, String> {
-    use std::io::Write;
-
+/// Resolve the HuggingFace URLs and local destinations for a Piper voice.
+/// Returns `(url, path)` pairs for the ONNX model and its config JSON, in that order.
+pub fn voice_download_files(voice_name: &str, data_dir: &Path) -> Result<Vec<(String, PathBuf)>, String> {
     let voice_dir = data_dir.join("piper-voices").join(voice_name);
-    std::fs::create_dir_all(&voice_dir).map_err(|e| format!("Failed to create dir: {}", e))?;
 
     let onnx_file = format!("{}.onnx", voice_name);
     let config_file = format!("{}.onnx.json", voice_name);
 
-    // Determine the HuggingFace path based on voice name convention
-    // e.g. en_US-amy-medium -> en/en_US/amy/medium/en_US-amy-medium.onnx
+    let base_url = format!("{}/{}/", VOICES_URL, voice_repo_path(voice_name)?);
+
+    Ok(vec![
+        (format!("{}{}", base_url, onnx_file), voice_dir.join(&onnx_file)),
+        (format!("{}{}", base_url, config_file), voice_dir.join(&config_file)),
+    ])
+}
+
+/// Where a voice lives in the Piper repos, following the voice name convention,
+/// e.g. en_US-amy-medium -> en/en_US/amy/medium
+fn voice_repo_path(voice_name: &str) -> Result<String, String> {
     let parts: Vec<&str> = voice_name.splitn(3, '-').collect();
     if parts.len() < 3 {
         return Err(format!(
@@ -175,50 +792,71 @@ pub fn download_voice(voice_name: &str, data_dir: &Path) -> Result<PathBuf, Stri
     let name = parts[1]; // e.g. "amy"
     let quality = parts[2]; // e.g. "medium"
 
-    let base_url = format!(
-        "https://huggingface.co/rhasspy/piper-voices/resolve/main/{}/{}/{}/{}/",
-        lang, lang_region, name, quality
-    );
+    Ok(format!("{}/{}/{}/{}", lang, lang_region, name, quality))
+}
 
-    // Download ONNX model
-    let onnx_path = voice_dir.join(&onnx_file);
-    if !onnx_path.exists() {
-        info!("Downloading Piper voice model: {}", onnx_file);
-        let url = format!("{}{}", base_url, onnx_file);
-        let response = reqwest::blocking::get(&url)
-            .map_err(|e| format!("Failed to download model: {}", e))?;
-        if !response.status().is_success() {
-            return Err(format!("Download failed: HTTP {}", response.status()));
-        }
-        let bytes = response
-            .bytes()
-            .map_err(|e| format!("Failed to read model bytes: {}", e))?;
-        let mut file =
-            std::fs::File::create(&onnx_path).map_err(|e| format!("Failed to create file: {}", e))?;
-        file.write_all(&bytes)
-            .map_err(|e| format!("Failed to write model: {}", e))?;
-        info!("Downloaded {} ({} bytes)", onnx_file, bytes.len());
-    }
-
-    // Download config JSON
-    let config_path = voice_dir.join(&config_file);
-    if !config_path.exists() {
-        info!("Downloading Piper voice config: {}", config_file);
-        let url = format!("{}{}", base_url, config_file);
-        let response = reqwest::blocking::get(&url)
-            .map_err(|e| format!("Failed to download config: {}", e))?;
-        if !response.status().is_success() {
-            return Err(format!("Config download failed: HTTP {}", response.status()));
+/// `voice_name` followed by the same speaker at the other qualities, best first.
+fn quality_fallbacks(voice_name: &str) -> Vec<String> {
+    let mut voices = vec![voice_name.to_string()];
+    if let Some((speaker, quality)) = voice_name.rsplit_once('-') {
+        voices.extend(
+            QUALITIES
+                .iter()
+                .filter(|q| **q != quality)
+                .map(|q| format!("{}-{}", speaker, q)),
+        );
+    }
+    voices
+}
+
+/// The downloaded voice closest to `voice_name`: itself, or the same speaker at another quality.
+pub fn resolve_local(voice_name: &str) -> Option<String> {
+    let voice = quality_fallbacks(voice_name)
+        .into_iter()
+        .find(|v| voice_ready(v))?;
+    if voice != voice_name {
+        info!("Piper TTS: {} isn't downloaded; using {}", voice_name, voice);
+    }
+    Some(voice)
+}
+
+/// The voice to download for `voice_name`: itself if HuggingFace has it, else the same speaker
+/// at the best quality that's published.
+pub async fn resolve_remote(voice_name: &str) -> Result<String, String> {
+    let client = reqwest::Client::new();
+    for voice in quality_fallbacks(voice_name) {
+        let url = format!(
+            "{}/{}/{}.onnx.json",
+            VOICES_URL,
+            voice_repo_path(&voice)?,
+            voice
+        );
+        match client.head(&url).send().await {
+            Ok(response) if response.status() == reqwest::StatusCode::NOT_FOUND => {
+                info!("Piper TTS: {} isn't published", voice);
+            }
+            Ok(response) if response.status().is_success() => return Ok(voice),
+            // Offline or a server hiccup: let the download itself report it
+            _ => return Ok(voice_name.to_string()),
         }
-        let bytes = response
-            .bytes()
-            .map_err(|e| format!("Failed to read config bytes: {}", e))?;
-        let mut file = std::fs::File::create(&config_path)
-            .map_err(|e| format!("Failed to create config file: {}", e))?;
-        file.write_all(&bytes)
-            .map_err(|e| format!("Failed to write config: {}", e))?;
-        info!("Downloaded {}", config_file);
     }
+    Err(format!("Voice '{}' isn't available at any quality", voice_name))
+}
 
-    Ok(config_path)
+/// Play the published sample clip of a voice that isn't downloaded, so it can be heard
+/// without fetching the whole model. Multi-speaker voices play their first speaker.
+pub async fn play_sample(voice_name: &str) -> Result<()> {
+    let url = format!(
+        "{}/{}/speaker_0.mp3",
+        SAMPLES_URL,
+        voice_repo_path(voice_name).map_err(|e| anyhow!(e))?
+    );
+    info!("Piper TTS: fetching sample clip for {}", voice_name);
+    let bytes = reqwest::get(&url)
+        .await?
+        .error_for_status()
+        .map_err(|e| anyhow!("No sample clip for voice '{}': {}", voice_name, e))?
+        .bytes()
+        .await?;
+    tokio::task::spawn_blocking(move || play_bytes(bytes.to_vec())).await?
 }
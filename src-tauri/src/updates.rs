@@ -0,0 +1,105 @@
+use crate::commands::{UpdateEvent, UpdateProgressEvent};
+use crate::config::{Config, UpdateChannel};
+use anyhow::{anyhow, Result};
+use tauri::{AppHandle, Emitter, Url};
+use tauri_plugin_updater::{Update, UpdaterExt};
+
+/// Key release builds are signed with, baked in by the release workflow.
+/// Local builds have none and can't update themselves.
+const UPDATE_PUBKEY: Option<&str> = option_env!("RUSTY_CLIPPY_UPDATE_PUBKEY");
+const RELEASES_URL: &str = "https://github.com/LisanAlGa-build/RustyClippy/releases";
+
+/// Update manifest for `channel`. Stable follows GitHub's latest release, which skips
+/// pre-releases; beta reads the manifest the release workflow keeps on the `beta` release.
+fn manifest_url(channel: UpdateChannel) -> Result<Url> {
+    let url = match channel {
+        UpdateChannel::Stable => format!("{}/latest/download/latest.json", RELEASES_URL),
+        UpdateChannel::Beta => format!("{}/download/beta/latest.json", RELEASES_URL),
+    };
+    Ok(Url::parse(&url)?)
+}
+
+/// The newest release on `channel`, if it's newer than this build.
+pub async fn check(app: &AppHandle, channel: UpdateChannel) -> Result<Option<Update>> {
+    let pubkey = UPDATE_PUBKEY.filter(|key| !key.is_empty()).ok_or_else(|| {
+        anyhow!(
+            "This build wasn't signed for updates; download new versions from {}",
+            RELEASES_URL
+        )
+    })?;
+    let updater = app
+        .updater_builder()
+        .pubkey(pubkey)
+        .endpoints(vec![manifest_url(channel)?])?
+        .build()?;
+    Ok(updater.check().await?)
+}
+
+/// Download, verify and install the newest release on `channel`.
+pub async fn install(app: &AppHandle, channel: UpdateChannel) -> Result<()> {
+    let update = check(app, channel)
+        .await?
+        .ok_or_else(|| anyhow!("Already up to date"))?;
+    tracing::info!(
+        "Installing update {} (from {})",
+        update.version,
+        update.current_version
+    );
+
+    let mut downloaded = 0u64;
+    update
+        .download_and_install(
+            |chunk, total| {
+                downloaded += chunk as u64;
+                let _ = app.emit("update-progress", UpdateProgressEvent { downloaded, total });
+            },
+            || tracing::info!("Update downloaded; installing"),
+        )
+        .await?;
+
+    let _ = app.emit(
+        "update-ready",
+        UpdateEvent {
+            version: update.version.clone(),
+            notes: update.body.clone(),
+        },
+    );
+    Ok(())
+}
+
+/// Check once at launch and announce a new release, if the user hasn't turned that off.
+pub fn spawn_startup_check(app: &AppHandle) {
+    let Ok(config) = Config::load() else {
+        return;
+    };
+    if !config.check_for_updates || UPDATE_PUBKEY.is_none() {
+        return;
+    }
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        match check(&app, config.update_channel).await {
+            Ok(Some(update)) => {
+                tracing::info!("Update available: {}", update.version);
+                let body = format!(
+                    "Version {} is ready to install from Settings.",
+                    update.version
+                );
+                crate::notifications::notify(
+                    &app,
+                    "Rusty Clippy update available",
+                    &body,
+                    crate::notifications::Target::Settings,
+                );
+                let _ = app.emit(
+                    "update-available",
+                    UpdateEvent {
+                        version: update.version,
+                        notes: update.body,
+                    },
+                );
+            }
+            Ok(None) => tracing::debug!("No update available"),
+            Err(e) => tracing::warn!("Update check failed: {}", e),
+        }
+    });
+}
@@ -0,0 +1,274 @@
+use crate::config::WindowGeometry;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{
+    AppHandle, Manager, Monitor, PhysicalPosition, PhysicalSize, WebviewWindow, WindowEvent,
+};
+
+/// Dragging fires a move event per frame; only write once the window stops moving
+const SAVE_DELAY: Duration = Duration::from_millis(500);
+/// How often to look for monitors being plugged in or out; there is no event for it
+const MONITOR_POLL_INTERVAL: Duration = Duration::from_secs(3);
+
+/// Geometry captured since the last save, keyed by window label
+static PENDING: Mutex<BTreeMap<String, WindowGeometry>> = Mutex::new(BTreeMap::new());
+/// Bumped on every move/resize so a delayed save can tell it has been superseded
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// Put `window` back where it was last time and remember where it goes from now on.
+pub fn manage(window: &WebviewWindow) {
+    restore(window);
+    let tracked = window.clone();
+    window.on_window_event(move |event| {
+        if matches!(event, WindowEvent::Moved(_) | WindowEvent::Resized(_)) {
+            record(&tracked);
+        }
+    });
+}
+
+/// Move and size `window` to its saved geometry, kept on a connected monitor.
+pub fn restore(window: &WebviewWindow) {
+    let Some(saved) = crate::runtime_state::load()
+        .windows
+        .get(window.label())
+        .copied()
+    else {
+        return;
+    };
+    let monitors = window.available_monitors().unwrap_or_default();
+    let fallback = window.primary_monitor().ok().flatten();
+    let Some(geometry) = clamp_to_monitors(saved, &monitors, fallback.as_ref()) else {
+        return;
+    };
+
+    if window.is_resizable().unwrap_or(false) {
+        let _ = window.set_size(PhysicalSize::new(geometry.width, geometry.height));
+    }
+    let _ = window.set_position(PhysicalPosition::new(geometry.x, geometry.y));
+}
+
+/// Hide Clippy from the taskbar and alt-tab (and the app from the Dock on macOS), or show it there.
+pub fn set_taskbar_hidden(app: &AppHandle, hidden: bool) {
+    if let Some(window) = app.get_webview_window("clippy") {
+        if let Err(e) = window.set_skip_taskbar(hidden) {
+            tracing::warn!("Failed to change taskbar visibility: {}", e);
+        }
+    }
+
+    // An accessory app has no Dock icon or app switcher entry but can still show windows
+    #[cfg(target_os = "macos")]
+    {
+        let policy = if hidden {
+            tauri::ActivationPolicy::Accessory
+        } else {
+            tauri::ActivationPolicy::Regular
+        };
+        if let Err(e) = app.set_activation_policy(policy) {
+            tracing::warn!("Failed to change activation policy: {}", e);
+        }
+    }
+}
+
+/// Move `window` to the monitor under the mouse cursor, keeping its place relative to the screen.
+pub fn move_to_cursor_monitor(window: &WebviewWindow) {
+    let Some(target) = window
+        .cursor_position()
+        .ok()
+        .and_then(|cursor| window.monitor_from_point(cursor.x, cursor.y).ok().flatten())
+    else {
+        return;
+    };
+    let (Ok(Some(current)), Ok(position), Ok(size)) = (
+        window.current_monitor(),
+        window.outer_position(),
+        window.outer_size(),
+    ) else {
+        return;
+    };
+    if current.position() == target.position() && current.size() == target.size() {
+        return;
+    }
+    let _ = window.set_position(carry_over(position, size, &current, &target));
+}
+
+/// Watch for monitors being unplugged or rearranged and pull any window left stranded
+/// off-screen back onto a connected monitor.
+pub fn watch_monitors(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut layout = monitor_layout(&app);
+        loop {
+            tokio::time::sleep(MONITOR_POLL_INTERVAL).await;
+            let current = monitor_layout(&app);
+            if current == layout {
+                continue;
+            }
+            layout = current;
+            tracing::info!("Monitor layout changed; checking window positions");
+            for window in app.webview_windows().values() {
+                rescue(window);
+            }
+        }
+    });
+}
+
+/// Position and size of every connected monitor, to notice when that changes
+fn monitor_layout(app: &AppHandle) -> Vec<(i32, i32, u32, u32)> {
+    app.available_monitors()
+        .unwrap_or_default()
+        .iter()
+        .map(|m| {
+            (
+                m.position().x,
+                m.position().y,
+                m.size().width,
+                m.size().height,
+            )
+        })
+        .collect()
+}
+
+/// Bring `window` back onto a monitor if none of it is visible any more.
+fn rescue(window: &WebviewWindow) {
+    let (Ok(position), Ok(size)) = (window.outer_position(), window.outer_size()) else {
+        return;
+    };
+    let geometry = WindowGeometry {
+        x: position.x,
+        y: position.y,
+        width: size.width,
+        height: size.height,
+    };
+    let monitors = window.available_monitors().unwrap_or_default();
+    if monitors.iter().any(|m| overlap(&geometry, m) > 0) {
+        return;
+    }
+    let fallback = window.primary_monitor().ok().flatten();
+    if let Some(fitted) = clamp_to_monitors(geometry, &monitors, fallback.as_ref()) {
+        tracing::info!("Moving window '{}' back on screen", window.label());
+        let _ = window.set_position(PhysicalPosition::new(fitted.x, fitted.y));
+    }
+}
+
+/// Where a window at `position` on `from` lands on `to`: the same relative spot in the work
+/// area, so a window in a corner or against an edge stays there.
+fn carry_over(
+    position: PhysicalPosition<i32>,
+    size: PhysicalSize<u32>,
+    from: &Monitor,
+    to: &Monitor,
+) -> PhysicalPosition<i32> {
+    let (from, to) = (from.work_area(), to.work_area());
+    let axis = |offset: i32, length: u32, from_length: u32, to_length: u32| {
+        let from_room = from_length.saturating_sub(length) as f64;
+        let to_room = to_length.saturating_sub(length) as f64;
+        let fraction = if from_room > 0.0 {
+            (offset as f64 / from_room).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        (fraction * to_room).round() as i32
+    };
+    let x = axis(
+        position.x - from.position.x,
+        size.width,
+        from.size.width,
+        to.size.width,
+    );
+    let y = axis(
+        position.y - from.position.y,
+        size.height,
+        from.size.height,
+        to.size.height,
+    );
+    PhysicalPosition::new(to.position.x + x, to.position.y + y)
+}
+
+/// Capture the window's current geometry and schedule a save.
+fn record(window: &WebviewWindow) {
+    // Minimized windows report bogus positions (e.g. -32000 on Windows), and a peeking
+    // one is half off screen
+    if window.is_minimized().unwrap_or(false) || crate::peek::active() {
+        return;
+    }
+    let (Ok(position), Ok(size)) = (window.outer_position(), window.inner_size()) else {
+        return;
+    };
+    if let Ok(mut pending) = PENDING.lock() {
+        pending.insert(
+            window.label().to_string(),
+            WindowGeometry {
+                x: position.x,
+                y: position.y,
+                width: size.width,
+                height: size.height,
+            },
+        );
+    }
+
+    let generation = GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(SAVE_DELAY).await;
+        if GENERATION.load(Ordering::SeqCst) == generation {
+            save_pending();
+        }
+    });
+}
+
+fn save_pending() {
+    let pending = match PENDING.lock() {
+        Ok(mut pending) => std::mem::take(&mut *pending),
+        Err(_) => return,
+    };
+    if pending.is_empty() {
+        return;
+    }
+    if let Err(e) = crate::runtime_state::update(|state| state.windows.extend(pending)) {
+        tracing::warn!("Failed to save window positions: {}", e);
+    }
+}
+
+/// Fit `saved` onto the monitor it overlaps most, or onto `fallback` if it is
+/// entirely off-screen (e.g. a monitor was unplugged). Shrinks it if it no longer fits.
+fn clamp_to_monitors(
+    saved: WindowGeometry,
+    monitors: &[Monitor],
+    fallback: Option<&Monitor>,
+) -> Option<WindowGeometry> {
+    let monitor = monitors
+        .iter()
+        .map(|m| (overlap(&saved, m), m))
+        .filter(|(area, _)| *area > 0)
+        .max_by_key(|(area, _)| *area)
+        .map(|(_, m)| m)
+        .or(fallback)
+        .or(monitors.first())?;
+
+    let origin = monitor.position();
+    let bounds = monitor.size();
+    let width = saved.width.min(bounds.width);
+    let height = saved.height.min(bounds.height);
+    let max_x = origin.x + (bounds.width - width) as i32;
+    let max_y = origin.y + (bounds.height - height) as i32;
+    Some(WindowGeometry {
+        x: saved.x.clamp(origin.x, max_x),
+        y: saved.y.clamp(origin.y, max_y),
+        width,
+        height,
+    })
+}
+
+/// Area, in physical pixels, of `geometry` that lies on `monitor`
+fn overlap(geometry: &WindowGeometry, monitor: &Monitor) -> i64 {
+    let origin = monitor.position();
+    let size = monitor.size();
+    let left = geometry.x.max(origin.x) as i64;
+    let top = geometry.y.max(origin.y) as i64;
+    let right =
+        (geometry.x as i64 + geometry.width as i64).min(origin.x as i64 + size.width as i64);
+    let bottom =
+        (geometry.y as i64 + geometry.height as i64).min(origin.y as i64 + size.height as i64);
+    (right - left).max(0) * (bottom - top).max(0)
+}
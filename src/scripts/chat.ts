@@ -1,21 +1,38 @@
 import { invoke } from '@tauri-apps/api/core';
-import { listen } from '@tauri-apps/api/event';
+import { getCurrentWindow } from '@tauri-apps/api/window';
+import { open, save } from '@tauri-apps/plugin-dialog';
 
 interface Message {
   role: 'user' | 'assistant';
   content: string;
 }
 
+// A fenced code block in a finished reply, from the `chat-done` event
+interface CodeBlockInfo {
+  index: number;
+  language: string | null;
+  file_name: string;
+}
+
+interface DoneEvent {
+  message_id: number | null;
+  code_blocks: CodeBlockInfo[];
+}
+
 const messages: Message[] = [];
 let isStreaming = false;
 let currentAssistantMessage = '';
+// Text files to send with the next message
+let attachments: string[] = [];
 
 async function init() {
   const sendButton = document.getElementById('send-button') as HTMLButtonElement;
   const inputField = document.getElementById('message-input') as HTMLInputElement;
   const clearButton = document.getElementById('clear-button') as HTMLButtonElement;
+  const attachButton = document.getElementById('attach-button') as HTMLButtonElement;
 
   sendButton.addEventListener('click', sendMessage);
+  attachButton.addEventListener('click', pickAttachments);
 
   inputField.addEventListener('keypress', (e) => {
     if (e.key === 'Enter' && !e.shiftKey) {
@@ -26,19 +43,46 @@ async function init() {
 
   clearButton.addEventListener('click', clearChat);
 
-  // Listen for streaming events
+  // Listen for streaming events; replies come only to the window that asked
+  const chatWindow = getCurrentWindow();
   try {
-    await listen('chat-token', (event: any) => {
+    await chatWindow.listen('chat-token', (event: any) => {
       handleToken(event.payload.token);
     });
 
-    await listen('chat-error', (event: any) => {
+    await chatWindow.listen('chat-reasoning', (event: any) => {
+      handleReasoning(event.payload.token);
+    });
+
+    await chatWindow.listen('chat-error', (event: any) => {
       handleError(event.payload.error);
     });
 
-    await listen('chat-done', () => {
+    await chatWindow.listen('chat-done', (event: any) => {
+      handleDone(event.payload);
+    });
+
+    // Over a spending cap: Clippy says so instead of answering
+    await chatWindow.listen('rate-limited', (event: any) => {
+      currentAssistantMessage = event.payload.text;
+      updateStreamingMessage(currentAssistantMessage);
       handleDone();
     });
+
+    // "/reset", "/model gpt-4o" and friends: the backend says how it went instead of replying
+    await chatWindow.listen('slash-command', (event: any) => {
+      const { command, text, ok } = event.payload;
+      document.getElementById('streaming-message')?.remove();
+      if (command === 'reset' && ok) {
+        messages.length = 0;
+        (document.getElementById('messages') as HTMLDivElement).innerHTML = '';
+      }
+      addMessage('assistant', text);
+      const reply = (document.getElementById('messages') as HTMLDivElement).lastElementChild!;
+      reply.classList.add('slash-reply');
+      if (!ok) reply.querySelector('.message-content')!.classList.add('error');
+      finishStreaming();
+    });
     
     console.log('Chat listeners ready');
   } catch (error) {
@@ -49,14 +93,52 @@ async function init() {
   addMessage('assistant', "Hi! I'm Clippy! It looks like you're trying to chat with an AI assistant. I'm here to help! What can I do for you today?");
 }
 
+function fileName(path: string): string {
+  return path.split(/[\\/]/).pop() || path;
+}
+
+// Attached files go into the message itself; the backend reads them when it's sent
+async function pickAttachments() {
+  const selected = await open({ multiple: true, directory: false });
+  if (!selected) return;
+  for (const path of Array.isArray(selected) ? selected : [selected]) {
+    if (!attachments.includes(path)) attachments.push(path);
+  }
+  showAttachments();
+}
+
+function showAttachments() {
+  const list = document.getElementById('attachment-list') as HTMLDivElement;
+  list.innerHTML = '';
+  for (const path of attachments) {
+    const chip = document.createElement('span');
+    chip.className = 'attachment-chip';
+    chip.textContent = `📎 ${fileName(path)}`;
+    chip.title = path;
+    const remove = document.createElement('button');
+    remove.textContent = '✕';
+    remove.title = 'Remove';
+    remove.addEventListener('click', () => {
+      attachments = attachments.filter((p) => p !== path);
+      showAttachments();
+    });
+    chip.appendChild(remove);
+    list.appendChild(chip);
+  }
+}
+
 async function sendMessage() {
   const inputField = document.getElementById('message-input') as HTMLInputElement;
   const sendButton = document.getElementById('send-button') as HTMLButtonElement;
   
   const userMessage = inputField.value.trim();
-  if (!userMessage || isStreaming) return;
+  if ((!userMessage && attachments.length === 0) || isStreaming) return;
 
-  addMessage('user', userMessage);
+  const attached = attachments;
+  attachments = [];
+  showAttachments();
+  const shown = [userMessage, ...attached.map((path) => `📎 ${fileName(path)}`)];
+  addMessage('user', shown.filter(Boolean).join('\n'));
   inputField.value = '';
 
   isStreaming = true;
@@ -67,7 +149,7 @@ async function sendMessage() {
   addStreamingMessage();
 
   try {
-    await invoke('send_message', { message: userMessage });
+    await invoke('send_message', { message: userMessage, attachments: attached });
   } catch (error) {
     handleError(`${error}`);
   }
@@ -78,6 +160,27 @@ function handleToken(token: string) {
   updateStreamingMessage(currentAssistantMessage);
 }
 
+// A reasoning model's thoughts, shown folded above the reply as they stream in. They never
+// reach the reply text, so they aren't spoken or kept in the conversation
+function handleReasoning(token: string) {
+  const streamingEl = document.getElementById('streaming-message');
+  if (!streamingEl) return;
+  let details = streamingEl.querySelector('.message-reasoning');
+  if (!details) {
+    details = document.createElement('details');
+    details.className = 'message-reasoning';
+    details.innerHTML = '<summary>Thinking…</summary><p></p>';
+    streamingEl.insertBefore(details, streamingEl.firstChild);
+  }
+  details.querySelector('p')!.textContent += token;
+}
+
+// Once the reply is in, the thoughts stop being "in progress"
+function finishReasoning(messageEl: Element) {
+  const summary = messageEl.querySelector('.message-reasoning summary');
+  if (summary) summary.textContent = 'Thoughts';
+}
+
 function handleError(error: string) {
   const streamingEl = document.getElementById('streaming-message');
   if (streamingEl) {
@@ -88,15 +191,18 @@ function handleError(error: string) {
   finishStreaming();
 }
 
-function handleDone() {
+function handleDone(done?: DoneEvent) {
   const streamingEl = document.getElementById('streaming-message');
   if (streamingEl) {
     streamingEl.classList.remove('streaming');
     streamingEl.removeAttribute('id');
 
+    finishReasoning(streamingEl);
+
     // Add speak button to the completed message
     if (currentAssistantMessage) {
       addSpeakButton(streamingEl, currentAssistantMessage);
+      addCodeBlockButtons(streamingEl, done);
     }
   }
   
@@ -154,6 +260,51 @@ function addSpeakButton(messageEl: Element, text: string) {
   messageEl.appendChild(speakBtn);
 }
 
+// Copy and Save buttons for each code block in a reply. The backend keeps the blocks as the
+// model wrote them, so nothing is lost to how the message is displayed
+function addCodeBlockButtons(messageEl: Element, done?: DoneEvent) {
+  if (!done || done.message_id === null) return;
+  const messageId = done.message_id;
+  for (const block of done.code_blocks) {
+    const row = document.createElement('div');
+    row.className = 'code-block-actions';
+    const name = document.createElement('span');
+    name.textContent = `${block.language || 'Code'} #${block.index + 1}`;
+    row.appendChild(name);
+
+    const copyBtn = document.createElement('button');
+    copyBtn.className = 'code-block-btn';
+    copyBtn.textContent = 'Copy';
+    copyBtn.addEventListener('click', async () => {
+      try {
+        const code = await invoke('copy_code_block', { messageId, index: block.index }) as { code: string };
+        await navigator.clipboard.writeText(code.code);
+        copyBtn.textContent = 'Copied!';
+      } catch (error) {
+        console.error('Failed to copy code block:', error);
+        copyBtn.textContent = 'Failed';
+      }
+      setTimeout(() => { copyBtn.textContent = 'Copy'; }, 1500);
+    });
+    row.appendChild(copyBtn);
+
+    const saveBtn = document.createElement('button');
+    saveBtn.className = 'code-block-btn';
+    saveBtn.textContent = 'Save…';
+    saveBtn.addEventListener('click', async () => {
+      const path = await save({ defaultPath: block.file_name });
+      if (!path) return;
+      try {
+        await invoke('save_code_block', { messageId, index: block.index, path });
+      } catch (error) {
+        console.error('Failed to save code block:', error);
+      }
+    });
+    row.appendChild(saveBtn);
+    messageEl.appendChild(row);
+  }
+}
+
 function addStreamingMessage() {
   const container = document.getElementById('messages') as HTMLDivElement;
   const messageEl = document.createElement('div');
@@ -30,6 +30,9 @@ export class ClippyAgent {
   private currentFrame: number = 0;
   private frameTimeout: number | null = null;
   private onAnimationComplete?: () => void;
+  // Frame sound id -> URL, and the volume (0-1) to play them at
+  private sounds: Record<string, string> = {};
+  private soundVolume: number = 0;
 
   constructor(canvas: HTMLCanvasElement) {
     this.canvas = canvas;
@@ -69,6 +72,12 @@ export class ClippyAgent {
     }
   }
 
+  // Sounds the character's frames refer to; a volume of 0 keeps it quiet
+  setSounds(sounds: Record<string, string>, volume: number): void {
+    this.sounds = sounds;
+    this.soundVolume = volume;
+  }
+
   play(animationName: string, onComplete?: () => void): void {
     if (!this.animationMap) {
       console.error('Animation map not loaded');
@@ -78,6 +87,9 @@ export class ClippyAgent {
     const animation = this.animationMap.animations[animationName];
     if (!animation) {
       console.error(`Animation "${animationName}" not found`);
+      // Imported characters lack some of Clippy's animations; carry on after a pause
+      // rather than stalling whatever loop asked for it
+      if (onComplete) setTimeout(onComplete, 1000);
       return;
     }
 
@@ -107,6 +119,11 @@ export class ClippyAgent {
     }
 
     const frame = animation.frames[this.currentFrame];
+    if (frame.sound && this.soundVolume > 0 && this.sounds[frame.sound]) {
+      const audio = new Audio(this.sounds[frame.sound]);
+      audio.volume = this.soundVolume;
+      audio.play().catch(() => {});
+    }
 
     try {
       // Clear canvas with explicit fill to ensure complete clearing on all platforms
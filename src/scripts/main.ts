@@ -2,6 +2,7 @@ import { ClippyAgent } from './clippy-agent';
 import { invoke } from '@tauri-apps/api/core';
 import { listen } from '@tauri-apps/api/event';
 import { getCurrentWindow } from '@tauri-apps/api/window';
+import { ask, open, save } from '@tauri-apps/plugin-dialog';
 
 let agent: ClippyAgent;
 
@@ -13,12 +14,99 @@ let nativeDragStarted = false;
 interface Message {
   role: 'user' | 'assistant';
   content: string;
+  // A reply a stream error cut off, which can be continued
+  interrupted?: boolean;
+}
+
+// A knowledge-folder passage, from the `chat-citations` event
+interface Citation {
+  path: string;
+  name: string;
+}
+
+// A tool the model called, from the `tool-result` event
+interface ToolResult {
+  tool: string;
+  label: string;
+  output: string;
+  ok: boolean;
+}
+
+// One model's answer when several were asked, from the `ensemble-answers` event
+// A fenced code block in a finished reply, from the `chat-done` event
+interface CodeBlockInfo {
+  index: number;
+  language: string | null;
+  file_name: string;
+}
+
+interface DoneEvent {
+  message_id: number | null;
+  code_blocks: CodeBlockInfo[];
+}
+
+interface GeneratedImage {
+  path: string;
+  prompt: string;
+}
+
+interface EnsembleAnswer {
+  model: string;
+  text: string;
+  error: string | null;
+  total_ms: number;
+}
+
+// A tool call waiting for the user's go-ahead, from the `tool-approval-request` event
+interface PlayAnimationEvent {
+  state: string;
+  animation: string;
+  priority: number;
+  duration_ms: number | null;
+}
+
+interface DocumentOffer {
+  path: string;
+  name: string;
+  text: string;
+  can_proofread: boolean;
+}
+
+// A saved prompt with {placeholders}, from `list_prompt_templates` or the `template-picker` event
+interface PromptTemplate {
+  id: string;
+  name: string;
+  text: string;
+  variables: string[];
+}
+
+interface ApprovalRequest {
+  id: number;
+  tool: string;
+  description: string;
+  arguments: string;
+  risk: 'low' | 'medium';
 }
 
 const chatMessages: Message[] = [];
 let isStreaming = false;
+// Knowledge-folder passages the reply being streamed was given
+let pendingCitations: Citation[] = [];
+// Tools the model called for the reply being streamed
+let pendingToolResults: ToolResult[] = [];
+// Older messages left out of the prompt for the reply being streamed
+let pendingDropped = 0;
+// What each ensemble member said, when the reply being streamed was put together from them
+let pendingEnsemble: EnsembleAnswer[] = [];
+// Pictures the image tool drew for the reply being streamed
+let pendingImages: GeneratedImage[] = [];
 let currentAssistantMessage = '';
+// Text files to send with the next message
+let attachments: string[] = [];
 let isChatOpen = false;
+// What the backend says Clippy is doing (see animation.rs), and whether we told it we're typing
+let currentAnimation: PlayAnimationEvent | null = null;
+let listening = false;
 
 // Window is always 420x600 — transparent areas pass through clicks on macOS.
 // No dynamic resizing needed.
@@ -30,10 +118,7 @@ async function initClippy() {
   agent = new ClippyAgent(canvas);
 
   try {
-    await agent.load(
-      '/assets/agents/Clippy/agent.png',
-      '/assets/agents/Clippy/map.json'
-    );
+    await loadAgent();
 
     playIdleLoop();
 
@@ -83,11 +168,25 @@ async function initClippy() {
       e.stopPropagation();
     });
 
+    // Right-click menu is built by the backend (context_menu.rs)
+    canvas.addEventListener('contextmenu', (e) => {
+      e.preventDefault();
+      invoke('show_context_menu').catch(() => {});
+    });
+
     console.log('Clippy ready!', agent.getAvailableAnimations().length, 'animations');
 
     // ─── Chat UI setup ───
     setupChat();
     setupSettingsListener();
+    setupKonamiCode();
+    setupDocking();
+    setupClickThrough();
+    setupVoiceMute();
+    setupSpeechInput();
+    setupDnd();
+    setupAgentReload();
+    checkCrashReport();
   } catch (error) {
     console.error('Failed to load Clippy:', error);
   }
@@ -101,13 +200,22 @@ function setupChat() {
   const bubble = document.getElementById('chat-bubble')!;
   const closeBtn = document.getElementById('close-chat-btn')!;
   const clearBtn = document.getElementById('clear-chat-btn')!;
+  const templatesBtn = document.getElementById('templates-btn')!;
+  const attachBtn = document.getElementById('attach-button')!;
+  const micBtn = document.getElementById('mic-button')!;
   const sendBtn = document.getElementById('send-button') as HTMLButtonElement;
   const inputField = document.getElementById('message-input') as HTMLInputElement;
 
   closeBtn.addEventListener('click', () => toggleChat(false));
   clearBtn.addEventListener('click', clearChat);
+  templatesBtn.addEventListener('click', showTemplatePicker);
+  attachBtn.addEventListener('click', pickAttachments);
+  micBtn.addEventListener('click', toggleRecording);
   sendBtn.addEventListener('click', sendMessage);
 
+  inputField.addEventListener('input', () => setListening(inputField.value.trim() !== ''));
+  inputField.addEventListener('blur', () => setListening(false));
+
   inputField.addEventListener('keypress', (e) => {
     if (e.key === 'Enter' && !e.shiftKey) {
       e.preventDefault();
@@ -124,10 +232,31 @@ function setupChat() {
 }
 
 async function setupChatListeners() {
+  // Replies are sent only to the window that asked, so listen on this one
+  const chatWindow = getCurrentWindow();
   try {
-    await listen('chat-token', (event: any) => handleToken(event.payload.token));
-    await listen('chat-error', (event: any) => handleError(event.payload.error));
-    await listen('chat-done', () => handleDone());
+    await chatWindow.listen('chat-token', (event: any) => handleToken(event.payload.token));
+    await chatWindow.listen('chat-reasoning', (event: any) => handleReasoning(event.payload.token));
+    await chatWindow.listen('chat-error', (event: any) => handleError(event.payload.error, event.payload.partial));
+    await chatWindow.listen('chat-done', (event: any) => handleDone(event.payload));
+    await chatWindow.listen('chat-citations', (event: any) => { pendingCitations = event.payload.citations; });
+    await chatWindow.listen('tool-result', (event: any) => { pendingToolResults.push(event.payload); });
+    await chatWindow.listen('context-trimmed', (event: any) => { pendingDropped = event.payload.dropped_messages; });
+    await chatWindow.listen('ensemble-answers', (event: any) => { pendingEnsemble = event.payload.answers; });
+    // The image tool announces to every window; only the one waiting on a reply shows it
+    await listen('image-generated', (event: any) => { if (isStreaming) pendingImages.push(event.payload); });
+    await listen('tool-approval-request', (event: any) => showApprovalRequest(event.payload));
+    await listen('clippy-proactive', (event: any) => handleProactive(event.payload.text));
+    await listen('document-offer', (event: any) => showDocumentOffer(event.payload));
+    await listen('reminder', (event: any) => handleProactive(event.payload.text));
+    await listen('pomodoro', (event: any) => handlePomodoro(event.payload.text, event.payload.animation));
+    await listen('mood-changed', (event: any) => handleMoodChanged(event.payload.mood));
+    await listen('play-animation', (event: any) => handlePlayAnimation(event.payload));
+    await listen('budget-warning', (event: any) => handleBudgetWarning(event.payload.text));
+    await chatWindow.listen('rate-limited', (event: any) => handleRateLimited(event.payload.text));
+    await chatWindow.listen('slash-command', (event: any) => handleSlashCommand(event.payload));
+    await chatWindow.listen('easter-egg', (event: any) => playEggAnimation(event.payload.animation));
+    await chatWindow.listen('session-loaded', (event: any) => showSession(event.payload.messages));
     console.log('Chat listeners ready');
   } catch (error) {
     console.error('Failed to setup chat listeners:', error);
@@ -141,7 +270,7 @@ function toggleChat(open?: boolean) {
   if (isChatOpen) {
     bubble.classList.remove('hidden');
     if (chatMessages.length === 0) {
-      addMessage('assistant', "Hi! I'm Clippy! It looks like you're trying to chat with an AI assistant. I'm here to help! What can I do for you today?");
+      addGreeting();
     }
     setTimeout(() => {
       (document.getElementById('message-input') as HTMLInputElement)?.focus();
@@ -151,15 +280,112 @@ function toggleChat(open?: boolean) {
   }
 }
 
+function fileName(path: string): string {
+  return path.split(/[\\/]/).pop() || path;
+}
+
+// Attached files go into the message itself; the backend reads them when it's sent
+async function pickAttachments() {
+  const selected = await open({ multiple: true, directory: false });
+  if (!selected) return;
+  for (const path of Array.isArray(selected) ? selected : [selected]) {
+    if (!attachments.includes(path)) attachments.push(path);
+  }
+  showAttachments();
+}
+
+function showAttachments() {
+  const list = document.getElementById('attachment-list')!;
+  list.innerHTML = '';
+  for (const path of attachments) {
+    const chip = document.createElement('span');
+    chip.className = 'attachment-chip';
+    chip.textContent = `📎 ${fileName(path)}`;
+    chip.title = path;
+    const remove = document.createElement('button');
+    remove.textContent = '✕';
+    remove.title = 'Remove';
+    remove.addEventListener('click', () => {
+      attachments = attachments.filter((p) => p !== path);
+      showAttachments();
+    });
+    chip.appendChild(remove);
+    list.appendChild(chip);
+  }
+}
+
+// Speech input: the mic button records until clicked again, then the backend transcribes
+let recorder: MediaRecorder | null = null;
+
+async function toggleRecording() {
+  if (recorder) {
+    recorder.stop();
+    return;
+  }
+  const micBtn = document.getElementById('mic-button')!;
+  let stream: MediaStream;
+  try {
+    stream = await navigator.mediaDevices.getUserMedia({ audio: true });
+  } catch (error) {
+    console.error('Microphone unavailable:', error);
+    micBtn.title = 'No microphone access';
+    return;
+  }
+  const chunks: Blob[] = [];
+  recorder = new MediaRecorder(stream);
+  recorder.addEventListener('dataavailable', (e) => chunks.push(e.data));
+  recorder.addEventListener('stop', async () => {
+    const mimeType = recorder?.mimeType || 'audio/webm';
+    recorder = null;
+    stream.getTracks().forEach((track) => track.stop());
+    micBtn.title = 'Talk instead of typing';
+    micBtn.classList.remove('recording');
+    micBtn.classList.add('transcribing');
+    try {
+      const blob = new Blob(chunks, { type: mimeType });
+      const text = await invoke('transcribe_audio', {
+        audio: toBase64(new Uint8Array(await blob.arrayBuffer())),
+        mimeType,
+      }) as string;
+      const inputField = document.getElementById('message-input') as HTMLInputElement;
+      inputField.value = [inputField.value.trim(), text].filter(Boolean).join(' ');
+      inputField.focus();
+      setListening(inputField.value.trim() !== '');
+    } catch (error) {
+      console.error('Failed to transcribe:', error);
+      micBtn.title = String(error);
+    } finally {
+      micBtn.classList.remove('transcribing');
+    }
+  });
+  recorder.start();
+  micBtn.title = 'Stop and transcribe';
+  micBtn.classList.add('recording');
+  setListening(true);
+}
+
+function toBase64(bytes: Uint8Array): string {
+  let binary = '';
+  for (let i = 0; i < bytes.length; i += 0x8000) {
+    binary += String.fromCharCode(...bytes.subarray(i, i + 0x8000));
+  }
+  return btoa(binary);
+}
+
 async function sendMessage() {
   const inputField = document.getElementById('message-input') as HTMLInputElement;
   const sendBtn = document.getElementById('send-button') as HTMLButtonElement;
 
   const userMessage = inputField.value.trim();
-  if (!userMessage || isStreaming) return;
+  if ((!userMessage && attachments.length === 0) || isStreaming) return;
 
-  addMessage('user', userMessage);
+  const attached = attachments;
+  attachments = [];
+  showAttachments();
+  const shown = [userMessage, ...attached.map((path) => `📎 ${fileName(path)}`)];
+  addMessage('user', shown.filter(Boolean).join('\n'));
   inputField.value = '';
+  setListening(false);
 
   isStreaming = true;
   sendBtn.disabled = true;
@@ -168,46 +394,485 @@ async function sendMessage() {
   currentAssistantMessage = '';
   addStreamingMessage();
 
-  // Play a thinking animation
-  agent.play('Processing', () => {});
-
   try {
-    await invoke('send_message', { message: userMessage });
+    await invoke('send_message', { message: userMessage, attachments: attached });
   } catch (error) {
     handleError(`${error}`);
   }
 }
 
+// Unprompted tip after the user has been idle
+function handleProactive(text: string) {
+  if (isStreaming) return;
+  if (!isChatOpen) toggleChat(true);
+  addMessage('assistant', text);
+  agent.play('GetAttention', () => playIdleLoop());
+}
+
+// Spend passed 50, 80 or 100% of a monthly budget, usually with the reply being streamed;
+// that one gets to finish first
+let pendingBudgetWarning: string | null = null;
+
+function handleBudgetWarning(text: string) {
+  if (isStreaming) {
+    pendingBudgetWarning = text;
+    return;
+  }
+  handleProactive(text);
+}
+
+// Over a spending cap: Clippy says so in place of the reply
+function handleRateLimited(text: string) {
+  currentAssistantMessage = text;
+  updateStreamingMessage(text);
+  handleDone();
+  agent.play('Wave', () => playIdleLoop());
+}
+
+// "/model gpt-4o" and friends are carried out by the backend, which says how it went
+function handleSlashCommand(result: { command: string; text: string; ok: boolean }) {
+  document.getElementById('streaming-message')?.remove();
+  if (result.command === 'reset' && result.ok) {
+    chatMessages.length = 0;
+    document.getElementById('messages')!.innerHTML = '';
+  }
+  addMessage('assistant', result.text);
+  const reply = document.getElementById('messages')!.lastElementChild!;
+  reply.classList.add('slash-reply');
+  if (!result.ok) reply.querySelector('.message-content')!.classList.add('error');
+  finishStreaming();
+}
+
+// Focus timer phase changes: say it and act it out
+function handlePomodoro(text: string, animation: string) {
+  if (isStreaming) return;
+  if (!isChatOpen) toggleChat(true);
+  addMessage('assistant', text);
+  agent.play(animation, () => playIdleLoop());
+}
+
+// Act out the backend's mood; streaming already has its own animation
+const moodAnimations: Record<string, string> = {
+  happy: 'Congratulate',
+  confused: 'GetAttention',
+  sleepy: 'IdleSnooze',
+};
+
+function handleMoodChanged(mood: string) {
+  const anim = moodAnimations[mood];
+  if (!anim || isStreaming) return;
+  agent.play(anim, () => playIdleLoop());
+}
+
+// Loop the backend's animation for what Clippy is doing until it says otherwise
+function handlePlayAnimation(event: PlayAnimationEvent) {
+  currentAnimation = event;
+  if (event.state === 'idle') {
+    playIdleLoop();
+    return;
+  }
+  const loop = () => {
+    if (currentAnimation === event) agent.play(event.animation, loop);
+  };
+  loop();
+}
+
+// Only tell the backend when typing starts or stops, not on every key
+function setListening(value: boolean) {
+  if (value === listening) return;
+  listening = value;
+  invoke('set_listening', { listening: value }).catch(() => {});
+}
+
 function handleToken(token: string) {
   currentAssistantMessage += token;
   updateStreamingMessage(currentAssistantMessage);
 }
 
-function handleError(error: string) {
+// A reasoning model's thoughts, shown folded above the reply as they stream in. They never
+// reach the reply text, so they aren't spoken or kept in the conversation
+function handleReasoning(token: string) {
+  const streamingEl = document.getElementById('streaming-message');
+  if (!streamingEl) return;
+  let details = streamingEl.querySelector('.message-reasoning');
+  if (!details) {
+    details = document.createElement('details');
+    details.className = 'message-reasoning';
+    details.innerHTML = '<summary>Thinking…</summary><p></p>';
+    streamingEl.insertBefore(details, streamingEl.firstChild);
+  }
+  details.querySelector('p')!.textContent += token;
+}
+
+// Once the reply is in, the thoughts stop being "in progress"
+function finishReasoning(messageEl: Element) {
+  const summary = messageEl.querySelector('.message-reasoning summary');
+  if (summary) summary.textContent = 'Thoughts';
+}
+
+// Number the passages the way the prompt did, so "[2]" in the reply points at the right file
+function addSources(messageEl: Element, citations: Citation[]) {
+  if (citations.length === 0) return;
+  const sources = document.createElement('div');
+  sources.className = 'message-sources';
+  sources.textContent = 'Sources: ' + citations.map((c, i) => `[${i + 1}] ${c.name}`).join(', ');
+  sources.title = citations.map((c, i) => `[${i + 1}] ${c.path}`).join('\n');
+  messageEl.appendChild(sources);
+}
+
+// Show tool output verbatim, so a computed answer can be told apart from a generated one
+function addToolResults(messageEl: Element, results: ToolResult[]) {
+  for (const result of results) {
+    const note = document.createElement('div');
+    note.className = 'message-tool';
+    note.textContent = result.ok ? `${result.label}: ${result.output}` : `${result.tool} failed: ${result.output}`;
+    messageEl.appendChild(note);
+  }
+}
+
+// Show the pictures the image tool drew; they're read through the backend, which only
+// hands out files from its images folder
+function addImages(messageEl: Element, images: GeneratedImage[]) {
+  for (const image of images) {
+    const img = document.createElement('img');
+    img.className = 'message-image';
+    img.alt = image.prompt;
+    img.title = image.prompt;
+    messageEl.appendChild(img);
+    invoke('load_generated_image', { path: image.path })
+      .then((url) => { img.src = url as string; })
+      .catch((error) => {
+        console.error('Failed to load image:', error);
+        img.remove();
+      });
+  }
+}
+
+// Copy and Save buttons for each code block in a reply. The backend keeps the blocks as the
+// model wrote them, so nothing is lost to how the message is displayed
+function addCodeBlockButtons(messageEl: Element, done?: DoneEvent) {
+  if (!done || done.message_id === null) return;
+  const messageId = done.message_id;
+  for (const block of done.code_blocks) {
+    const row = document.createElement('div');
+    row.className = 'code-block-actions';
+    const name = document.createElement('span');
+    name.textContent = `${block.language || 'Code'} #${block.index + 1}`;
+    row.appendChild(name);
+
+    const copyBtn = document.createElement('button');
+    copyBtn.className = 'code-block-btn';
+    copyBtn.textContent = 'Copy';
+    copyBtn.addEventListener('click', async () => {
+      try {
+        const code = await invoke('copy_code_block', { messageId, index: block.index }) as { code: string };
+        await navigator.clipboard.writeText(code.code);
+        copyBtn.textContent = 'Copied!';
+      } catch (error) {
+        console.error('Failed to copy code block:', error);
+        copyBtn.textContent = 'Failed';
+      }
+      setTimeout(() => { copyBtn.textContent = 'Copy'; }, 1500);
+    });
+    row.appendChild(copyBtn);
+
+    const saveBtn = document.createElement('button');
+    saveBtn.className = 'code-block-btn';
+    saveBtn.textContent = 'Save…';
+    saveBtn.addEventListener('click', async () => {
+      const path = await save({ defaultPath: block.file_name });
+      if (!path) return;
+      try {
+        await invoke('save_code_block', { messageId, index: block.index, path });
+      } catch (error) {
+        console.error('Failed to save code block:', error);
+      }
+    });
+    row.appendChild(saveBtn);
+    messageEl.appendChild(row);
+  }
+}
+
+// Ask above the reply being streamed, which waits until the user answers
+function showApprovalRequest(request: ApprovalRequest) {
+  if (!isChatOpen) toggleChat(true);
+  const container = document.getElementById('messages')!;
+  const card = document.createElement('div');
+  card.className = `message assistant-message approval-message risk-${request.risk}`;
+  card.innerHTML = `<div class="message-content">${escapeHtml(request.description)}?</div>`;
+  card.title = request.arguments;
+
+  const buttons = document.createElement('div');
+  buttons.className = 'approval-buttons';
+  const choices: [string, string][] = [
+    ['allow_once', 'Allow once'],
+    ['always_allow', 'Always allow'],
+    ['deny', 'Deny'],
+  ];
+  for (const [decision, text] of choices) {
+    const btn = document.createElement('button');
+    btn.className = 'approval-btn';
+    btn.textContent = text;
+    btn.addEventListener('click', async () => {
+      buttons.textContent = decision === 'deny' ? 'Denied' : 'Allowed';
+      try {
+        await invoke('respond_tool_approval', { id: request.id, decision });
+      } catch (error) {
+        buttons.textContent = `${error}`;
+      }
+    });
+    buttons.appendChild(btn);
+  }
+  card.appendChild(buttons);
+
+  container.insertBefore(card, document.getElementById('streaming-message'));
+  container.scrollTop = container.scrollHeight;
+  agent.play('GetAttention', () => playIdleLoop());
+}
+
+// Offer the saved prompt templates; picking one asks for its placeholders, if any
+async function showTemplatePicker() {
+  if (isStreaming) return;
+  let templates: PromptTemplate[];
+  try {
+    templates = await invoke('list_prompt_templates') as PromptTemplate[];
+  } catch (error) {
+    addMessage('assistant', `${error}`);
+    return;
+  }
+  const container = document.getElementById('messages')!;
+  const card = document.createElement('div');
+  card.className = 'message assistant-message template-picker';
+  const text = templates.length === 0
+    ? 'No prompt templates yet. You can add some in Settings.'
+    : 'Which template?';
+  card.innerHTML = `<div class="message-content">${text}</div>`;
+
+  const buttons = document.createElement('div');
+  buttons.className = 'approval-buttons';
+  for (const template of templates) {
+    const btn = document.createElement('button');
+    btn.className = 'approval-btn';
+    btn.textContent = template.name;
+    btn.title = template.text;
+    btn.addEventListener('click', () => {
+      card.remove();
+      showTemplateForm(template);
+    });
+    buttons.appendChild(btn);
+  }
+  card.appendChild(buttons);
+  container.appendChild(card);
+  container.scrollTop = container.scrollHeight;
+}
+
+// Ask for a template's placeholders, then send it like a typed message
+function showTemplateForm(template: PromptTemplate) {
+  if (isStreaming) return;
+  const input = document.getElementById('message-input') as HTMLInputElement;
+  if (template.variables.length === 0) {
+    input.value = template.text;
+    sendMessage();
+    return;
+  }
+  const container = document.getElementById('messages')!;
+  const card = document.createElement('div');
+  card.className = 'message assistant-message template-picker';
+  card.innerHTML = `<div class="message-content">${escapeHtml(template.text)}</div>`;
+
+  const fields: Record<string, HTMLInputElement> = {};
+  for (const variable of template.variables) {
+    const row = document.createElement('label');
+    row.className = 'template-field';
+    row.textContent = variable;
+    const field = document.createElement('input');
+    field.type = 'text';
+    field.autocomplete = 'off';
+    row.appendChild(field);
+    card.appendChild(row);
+    fields[variable] = field;
+  }
+
+  const buttons = document.createElement('div');
+  buttons.className = 'approval-buttons';
+  const send = document.createElement('button');
+  send.className = 'approval-btn';
+  send.textContent = 'Send';
+  const cancel = document.createElement('button');
+  cancel.className = 'approval-btn';
+  cancel.textContent = 'Cancel';
+  const submit = async () => {
+    if (isStreaming) return;
+    const values: Record<string, string> = {};
+    for (const [variable, field] of Object.entries(fields)) values[variable] = field.value;
+    const missing = template.variables.find((v) => !values[v].trim());
+    if (missing) {
+      fields[missing].focus();
+      return;
+    }
+    try {
+      input.value = await invoke('fill_prompt_template', { id: template.id, values }) as string;
+    } catch (error) {
+      addMessage('assistant', `${error}`);
+      return;
+    }
+    card.remove();
+    sendMessage();
+  };
+  send.addEventListener('click', submit);
+  cancel.addEventListener('click', () => card.remove());
+  for (const field of Object.values(fields)) {
+    field.addEventListener('keydown', (e) => { if (e.key === 'Enter') submit(); });
+  }
+  buttons.append(send, cancel);
+  card.appendChild(buttons);
+  container.appendChild(card);
+  container.scrollTop = container.scrollHeight;
+  fields[template.variables[0]].focus();
+}
+
+// "It looks like you're writing a letter": accepting sends the request like a quick action
+function showDocumentOffer(offer: DocumentOffer) {
+  if (isStreaming) return;
+  if (!isChatOpen) toggleChat(true);
+  const container = document.getElementById('messages')!;
+  const card = document.createElement('div');
+  card.className = 'message assistant-message';
+  card.innerHTML = `<div class="message-content">${escapeHtml(offer.text)}</div>`;
+  card.title = offer.path;
+
+  const buttons = document.createElement('div');
+  buttons.className = 'approval-buttons';
+  const choices: [string | null, string][] = [['draft', 'Help me write it']];
+  if (offer.can_proofread) choices.push(['proofread', 'Proofread it']);
+  choices.push([null, 'Just type it myself']);
+  for (const [action, text] of choices) {
+    const btn = document.createElement('button');
+    btn.className = 'approval-btn';
+    btn.textContent = text;
+    btn.addEventListener('click', async () => {
+      buttons.remove();
+      if (!action) return;
+      try {
+        await invoke('document_action', { path: offer.path, action });
+      } catch (error) {
+        addMessage('assistant', `${error}`);
+      }
+    });
+    buttons.appendChild(btn);
+  }
+  card.appendChild(buttons);
+
+  container.appendChild(card);
+  container.scrollTop = container.scrollHeight;
+  agent.play('GetAttention', () => playIdleLoop());
+}
+
+// Say when the model couldn't see the start of the conversation
+function addDroppedNote(messageEl: Element, dropped: number) {
+  if (dropped === 0) return;
+  const note = document.createElement('div');
+  note.className = 'message-sources';
+  note.textContent = `Couldn't fit the ${dropped} oldest message${dropped === 1 ? '' : 's'} in my memory for this answer`;
+  messageEl.appendChild(note);
+}
+
+// The answers the reply was put together from, folded away for the curious
+function addEnsembleAnswers(messageEl: Element, answers: EnsembleAnswer[]) {
+  if (answers.length === 0) return;
+  const details = document.createElement('details');
+  details.className = 'message-ensemble';
+  const summary = document.createElement('summary');
+  summary.textContent = `Put together from ${answers.length} answer${answers.length === 1 ? '' : 's'}`;
+  details.appendChild(summary);
+  for (const answer of answers) {
+    const p = document.createElement('p');
+    const seconds = (answer.total_ms / 1000).toFixed(1);
+    p.textContent = answer.error
+      ? `${answer.model} failed: ${answer.error}`
+      : `${answer.model} (${seconds}s): ${answer.text}`;
+    details.appendChild(p);
+  }
+  messageEl.appendChild(details);
+}
+
+function handleError(error: string, partial?: string) {
+  pendingCitations = [];
+  pendingToolResults = [];
+  pendingDropped = 0;
+  pendingEnsemble = [];
+  pendingImages = [];
   const streamingEl = document.getElementById('streaming-message');
   if (streamingEl) {
     streamingEl.classList.remove('streaming');
     streamingEl.removeAttribute('id');
-    streamingEl.innerHTML = `<div class="message-content error">Error: ${error}</div>`;
+    if (partial) {
+      // Keep what arrived; it's saved in the history and can be picked up again
+      streamingEl.innerHTML = `<div class="message-content">${escapeHtml(partial)}</div>`
+        + `<div class="message-content error">Error: ${error}</div>`;
+      addContinueButton(streamingEl, partial);
+    } else {
+      streamingEl.innerHTML = `<div class="message-content error">Error: ${error}</div>`;
+    }
   }
   finishStreaming();
 }
 
-function handleDone() {
+// Resume a reply a stream error cut off, streaming the rest into the same message
+function addContinueButton(messageEl: HTMLElement, partial: string) {
+  const buttons = document.createElement('div');
+  buttons.className = 'approval-buttons';
+  const continueBtn = document.createElement('button');
+  continueBtn.className = 'approval-btn';
+  continueBtn.textContent = 'Continue';
+  continueBtn.addEventListener('click', async () => {
+    if (isStreaming) return;
+    buttons.remove();
+    messageEl.querySelector('.error')?.remove();
+    messageEl.classList.add('streaming');
+    messageEl.id = 'streaming-message';
+
+    isStreaming = true;
+    (document.getElementById('send-button') as HTMLButtonElement).disabled = true;
+    (document.getElementById('message-input') as HTMLInputElement).disabled = true;
+    currentAssistantMessage = partial;
+
+    try {
+      await invoke('continue_response');
+    } catch (error) {
+      // The interrupted reply is still in the history, so it can be tried again
+      handleError(`${error}`, partial);
+    }
+  });
+  buttons.appendChild(continueBtn);
+  messageEl.appendChild(buttons);
+}
+
+function handleDone(done?: DoneEvent) {
   const streamingEl = document.getElementById('streaming-message');
   if (streamingEl) {
     streamingEl.classList.remove('streaming');
     streamingEl.removeAttribute('id');
+    finishReasoning(streamingEl);
     if (currentAssistantMessage) {
       addSpeakButton(streamingEl, currentAssistantMessage);
+      addToolResults(streamingEl, pendingToolResults);
+      addImages(streamingEl, pendingImages);
+      addCodeBlockButtons(streamingEl, done);
+      addSources(streamingEl, pendingCitations);
+      addDroppedNote(streamingEl, pendingDropped);
+      addEnsembleAnswers(streamingEl, pendingEnsemble);
     }
   }
+  pendingCitations = [];
+  pendingToolResults = [];
+  pendingDropped = 0;
+  pendingEnsemble = [];
+  pendingImages = [];
   if (currentAssistantMessage) {
     chatMessages.push({ role: 'assistant', content: currentAssistantMessage });
   }
   finishStreaming();
-  // Return to idle
-  playIdleLoop();
 }
 
 function finishStreaming() {
@@ -217,6 +882,11 @@ function finishStreaming() {
   sendBtn.disabled = false;
   inputField.disabled = false;
   inputField.focus();
+  if (pendingBudgetWarning) {
+    const warning = pendingBudgetWarning;
+    pendingBudgetWarning = null;
+    handleProactive(warning);
+  }
 }
 
 function addMessage(role: 'user' | 'assistant', content: string) {
@@ -276,13 +946,44 @@ function updateStreamingMessage(content: string) {
   }
 }
 
-function clearChat() {
+async function clearChat() {
+  if (isStreaming) return;
+  try {
+    await invoke('new_conversation');
+  } catch (error) {
+    console.error('Failed to start a new conversation:', error);
+  }
   chatMessages.length = 0;
   const container = document.getElementById('messages')!;
   container.innerHTML = '';
   addMessage('assistant', "Chat cleared! What would you like to talk about?");
 }
 
+// Replace the bubble's contents with a saved conversation (e.g. picked from the tray)
+function showSession(messages: Message[]) {
+  if (isStreaming) return;
+  chatMessages.length = 0;
+  document.getElementById('messages')!.innerHTML = '';
+  for (const { role, content } of messages) {
+    addMessage(role, content);
+  }
+  const last = messages[messages.length - 1];
+  const lastEl = document.getElementById('messages')!.lastElementChild as HTMLElement | null;
+  if (last?.interrupted && lastEl) {
+    addContinueButton(lastEl, last.content);
+  }
+  toggleChat(true);
+}
+
+// Opening line from the active personality
+async function addGreeting() {
+  let greeting = "Hi! I'm Clippy! It looks like you're trying to chat with an AI assistant. I'm here to help! What can I do for you today?";
+  try {
+    greeting = await invoke('get_greeting') as string;
+  } catch {}
+  addMessage('assistant', greeting);
+}
+
 function escapeHtml(text: string): string {
   const div = document.createElement('div');
   div.textContent = text;
@@ -296,10 +997,220 @@ function escapeHtml(text: string): string {
 async function setupSettingsListener() {
   try {
     await listen('open-settings', () => openSettingsDialog());
+    // Global shortcut: open the chat ready to type
+    await listen('focus-chat', () => toggleChat(true));
+    // A quick action from the right-click menu: send it as if typed
+    await listen('quick-prompt', (event: any) => {
+      if (isStreaming) return;
+      toggleChat(true);
+      (document.getElementById('message-input') as HTMLInputElement).value = event.payload.prompt;
+      sendMessage();
+    });
+    // A prompt template from the right-click menu that needs its placeholders filled in
+    await listen('template-picker', (event: any) => {
+      toggleChat(true);
+      showTemplateForm(event.payload.template);
+    });
+  } catch {}
+}
+
+interface EasterEgg {
+  id: string;
+  text: string;
+  animation: string | null;
+}
+
+function playEggAnimation(animation: string | null) {
+  if (animation) agent.play(animation, () => playIdleLoop());
+}
+
+// ↑ ↑ ↓ ↓ ← → ← → B A
+const KONAMI_CODE = ['ArrowUp', 'ArrowUp', 'ArrowDown', 'ArrowDown', 'ArrowLeft', 'ArrowRight', 'ArrowLeft', 'ArrowRight', 'b', 'a'];
+
+function setupKonamiCode() {
+  let progress = 0;
+  document.addEventListener('keydown', async (e) => {
+    const key = e.key.length === 1 ? e.key.toLowerCase() : e.key;
+    progress = key === KONAMI_CODE[progress] ? progress + 1 : (key === KONAMI_CODE[0] ? 1 : 0);
+    if (progress < KONAMI_CODE.length) return;
+    progress = 0;
+    if (isStreaming) return;
+    try {
+      const egg = await invoke('trigger_easter_egg', { id: 'konami' }) as EasterEgg;
+      if (!isChatOpen) toggleChat(true);
+      const input = document.getElementById('message-input') as HTMLInputElement;
+      input.value = input.value.replace(/ba$/i, '');
+      addMessage('assistant', egg.text);
+      playEggAnimation(egg.animation);
+    } catch (error) {
+      console.error('Easter egg failed:', error);
+    }
+  });
+}
+
+interface Dock {
+  horizontal: 'left' | 'right' | null;
+  vertical: 'top' | 'bottom' | null;
+}
+
+// Lay out Clippy and the bubble so the bubble opens away from the docked edges
+function applyDock(dock: Dock) {
+  document.body.classList.toggle('dock-left', dock.horizontal === 'left');
+  document.body.classList.toggle('dock-right', dock.horizontal === 'right');
+  document.body.classList.toggle('dock-top', dock.vertical === 'top');
+}
+
+async function setupDocking() {
+  try {
+    applyDock(await invoke('get_dock') as Dock);
+    await listen('dock-changed', (event: any) => applyDock(event.payload));
+  } catch {}
+}
+
+// Click-through idle mode: fade out and let clicks reach the windows underneath
+const GHOST_AFTER_MS = 30_000;
+let clickThroughIdle = false;
+let ghosted = false;
+let ghostTimer: number | null = null;
+
+function resetGhostTimer() {
+  if (ghostTimer !== null) clearTimeout(ghostTimer);
+  ghostTimer = null;
+  if (!clickThroughIdle || ghosted) return;
+  ghostTimer = window.setTimeout(() => {
+    if (isChatOpen || isStreaming) {
+      resetGhostTimer();
+      return;
+    }
+    invoke('set_click_through', { enabled: true }).catch(() => {});
+  }, GHOST_AFTER_MS);
+}
+
+async function setupClickThrough() {
+  try {
+    const config = await invoke('get_config') as any;
+    clickThroughIdle = config.click_through_idle || false;
+    await listen('config-changed', (event: any) => {
+      clickThroughIdle = event.payload.click_through_idle || false;
+      resetGhostTimer();
+    });
+    // Woken by the hotkey or tray; the window can't see clicks while ghosted
+    await listen('ghost-changed', (event: any) => {
+      ghosted = event.payload;
+      document.body.classList.toggle('ghost', ghosted);
+      resetGhostTimer();
+    });
+  } catch {}
+  for (const type of ['mousemove', 'mousedown', 'keydown']) {
+    document.addEventListener(type, resetGhostTimer);
+  }
+  resetGhostTimer();
+}
+
+// Hide the read-aloud buttons while speech is turned off
+function applyVoiceMuted(muted: boolean) {
+  document.body.classList.toggle('voice-muted', muted);
+}
+
+async function setupVoiceMute() {
+  try {
+    const config = await invoke('get_config') as any;
+    applyVoiceMuted(!config.tts_enabled);
+    await listen('voice-muted', (event: any) => applyVoiceMuted(event.payload.muted));
+    await listen('config-changed', (event: any) => applyVoiceMuted(!event.payload.tts_enabled));
+  } catch {}
+}
+
+// Hide the mic button unless a speech input backend is chosen
+function applySpeechInput(config: any) {
+  document.body.classList.toggle('no-speech-input', (config.stt?.backend || 'Off') === 'Off');
+}
+
+async function setupSpeechInput() {
+  try {
+    applySpeechInput(await invoke('get_config'));
+    await listen('config-changed', (event: any) => applySpeechInput(event.payload));
+  } catch {}
+}
+
+// Do not disturb: the backend plays the sleeping animation, this dims Clippy to match
+function applyDnd(active: boolean) {
+  document.body.classList.toggle('dnd', active);
+}
+
+async function setupDnd() {
+  try {
+    applyDnd((await invoke('get_dnd') as { active: boolean }).active);
+    // Asleep since before this page loaded
+    handlePlayAnimation(await invoke('get_animation') as PlayAnimationEvent);
+    await listen('dnd-changed', (event: any) => applyDnd(event.payload.active));
+  } catch {}
+}
+
+// Offer the dump from a crash in the previous run, once
+async function checkCrashReport() {
+  try {
+    const report = await invoke('get_pending_crash_report') as { path: string } | null;
+    if (!report) return;
+    const reveal = await ask(
+      'Clippy crashed last time it ran. A crash report was saved — would you like to see it?',
+      { title: 'Rusty Clippy', kind: 'warning' }
+    );
+    await invoke('acknowledge_crash_report', { path: report.path, reveal });
+  } catch (error) {
+    console.error('Failed to check for crash reports:', error);
+  }
+}
+
+interface AgentData {
+  name: string;
+  sprite: string;
+  map: string;
+  sounds: Record<string, string>;
+}
+
+// Which character is showing, so config changes only reload it when it changes
+let loadedAgent: string | null = null;
+
+// The configured character (see get_agent), or the bundled Clippy
+async function loadAgent() {
+  let data: AgentData | null = null;
+  try {
+    data = await invoke('get_agent') as AgentData | null;
+  } catch (error) {
+    console.error('Failed to load character, using Clippy:', error);
+  }
+  const name = data ? data.name : 'Clippy';
+  if (name !== loadedAgent) {
+    if (data) {
+      await agent.load(data.sprite, data.map);
+    } else {
+      await agent.load('/assets/agents/Clippy/agent.png', '/assets/agents/Clippy/map.json');
+    }
+    loadedAgent = name;
+  }
+  try {
+    const config = await invoke('get_config') as any;
+    agent.setSounds(data ? data.sounds : {}, config.sound_effects ? config.sound_volume / 100 : 0);
+  } catch {}
+}
+
+async function setupAgentReload() {
+  try {
+    await listen('config-changed', async () => {
+      const before = loadedAgent;
+      await loadAgent();
+      if (loadedAgent !== before) playIdleLoop();
+    });
   } catch {}
 }
 
 function playIdleLoop() {
+  // One-off animations end here; go back to whatever Clippy is busy with
+  if (currentAnimation && currentAnimation.state !== 'idle') {
+    handlePlayAnimation(currentAnimation);
+    return;
+  }
   const idleAnims = ['RestPose', 'Idle1_1', 'IdleFingerTap', 'IdleHeadScratch', 'IdleSideToSide'];
   const randomIdle = idleAnims[Math.floor(Math.random() * idleAnims.length)];
 
@@ -315,6 +1226,7 @@ function playIdleLoop() {
 }
 
 function handleClippyClick() {
+  invoke('report_activity').catch(() => {});
   toggleChat();
   agent.play('Greeting', () => playIdleLoop());
 }
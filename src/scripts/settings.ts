@@ -1,7 +1,7 @@
 import { invoke } from '@tauri-apps/api/core';
 import { listen } from '@tauri-apps/api/event';
 import { getCurrentWindow } from '@tauri-apps/api/window';
-import { open } from '@tauri-apps/plugin-dialog';
+import { ask, open } from '@tauri-apps/plugin-dialog';
 
 // Elements
 const providerSelect = document.getElementById('provider') as HTMLSelectElement;
@@ -15,25 +15,138 @@ const builtinModelPath = document.getElementById('builtin-model-path') as HTMLIn
 const browseModelBtn = document.getElementById('browse-model-btn') as HTMLButtonElement;
 const downloadModelBtn = document.getElementById('download-model-btn') as HTMLButtonElement;
 const modelDownloadStatus = document.getElementById('model-download-status') as HTMLDivElement;
+const installModelBtn = document.getElementById('install-model-btn') as HTMLButtonElement;
+const userNameInput = document.getElementById('user-name') as HTMLInputElement;
+const personalitySelect = document.getElementById('personality') as HTMLSelectElement;
+const agentSelect = document.getElementById('agent') as HTMLSelectElement;
+const importAgentBtn = document.getElementById('import-agent-btn') as HTMLButtonElement;
+const responseLanguageSelect = document.getElementById('response-language') as HTMLSelectElement;
+const globalHotkeyInput = document.getElementById('global-hotkey') as HTMLInputElement;
+const snapToEdgesCheckbox = document.getElementById('snap-to-edges') as HTMLInputElement;
+const clickThroughCheckbox = document.getElementById('click-through-idle') as HTMLInputElement;
+const alwaysOnTopCheckbox = document.getElementById('always-on-top') as HTMLInputElement;
+const startHiddenCheckbox = document.getElementById('start-hidden') as HTMLInputElement;
+const skipTaskbarCheckbox = document.getElementById('skip-taskbar') as HTMLInputElement;
+const nativeNotificationsCheckbox = document.getElementById('native-notifications') as HTMLInputElement;
+const tuningSliders = Array.from(document.querySelectorAll('.tuning-slider')) as HTMLInputElement[];
+const customPromptInput = document.getElementById('custom-prompt') as HTMLTextAreaElement;
+const replacePromptCheckbox = document.getElementById('replace-prompt') as HTMLInputElement;
 const tempSlider = document.getElementById('temperature') as HTMLInputElement;
 const tempValue = document.getElementById('temp-value') as HTMLSpanElement;
+const seedInput = document.getElementById('seed') as HTMLInputElement;
+const reasoningSelect = document.getElementById('reasoning') as HTMLSelectElement;
+const contextTokensInput = document.getElementById('context-tokens') as HTMLInputElement;
+const pinsInContextCheckbox = document.getElementById('pins-in-context') as HTMLInputElement;
+const proactiveEnabled = document.getElementById('proactive-enabled') as HTMLInputElement;
+const proactiveIdle = document.getElementById('proactive-idle') as HTMLInputElement;
+const proactiveMax = document.getElementById('proactive-max') as HTMLInputElement;
+const proactiveQuietStart = document.getElementById('proactive-quiet-start') as HTMLInputElement;
+const proactiveQuietEnd = document.getElementById('proactive-quiet-end') as HTMLInputElement;
+const proactiveSpeak = document.getElementById('proactive-speak') as HTMLInputElement;
+const proactivePeek = document.getElementById('proactive-peek') as HTMLInputElement;
+const proactivePeekMinutes = document.getElementById('proactive-peek-minutes') as HTMLInputElement;
+const briefingEnabled = document.getElementById('briefing-enabled') as HTMLInputElement;
+const briefingTime = document.getElementById('briefing-time') as HTMLInputElement;
+const briefingSpeak = document.getElementById('briefing-speak') as HTMLInputElement;
+const briefingNowBtn = document.getElementById('briefing-now-btn') as HTMLButtonElement;
+const weatherLocation = document.getElementById('weather-location') as HTMLInputElement;
+const weatherUnits = document.getElementById('weather-units') as HTMLSelectElement;
+const imageBackend = document.getElementById('image-backend') as HTMLSelectElement;
+const imageModel = document.getElementById('image-model') as HTMLInputElement;
+const imageSize = document.getElementById('image-size') as HTMLInputElement;
+const imageSdUrl = document.getElementById('image-sd-url') as HTMLInputElement;
+const sttBackend = document.getElementById('stt-backend') as HTMLSelectElement;
+const sttModel = document.getElementById('stt-model') as HTMLInputElement;
+const sttLanguage = document.getElementById('stt-language') as HTMLInputElement;
+const calendarSources = document.getElementById('calendar-sources') as HTMLTextAreaElement;
+const addCalendarFileBtn = document.getElementById('add-calendar-file-btn') as HTMLButtonElement;
+const calendarAlertMinutes = document.getElementById('calendar-alert-minutes') as HTMLInputElement;
+const knowledgeEnabled = document.getElementById('knowledge-enabled') as HTMLInputElement;
+const knowledgeFolders = document.getElementById('knowledge-folders') as HTMLTextAreaElement;
+const addKnowledgeFolderBtn = document.getElementById('add-knowledge-folder-btn') as HTMLButtonElement;
+const knowledgeMaxChunks = document.getElementById('knowledge-max-chunks') as HTMLInputElement;
+const documentsEnabled = document.getElementById('documents-enabled') as HTMLInputElement;
+const documentsFolders = document.getElementById('documents-folders') as HTMLTextAreaElement;
+const addDocumentsFolderBtn = document.getElementById('add-documents-folder-btn') as HTMLButtonElement;
+const reindexBtn = document.getElementById('reindex-btn') as HTMLButtonElement;
+const knowledgeStatus = document.getElementById('knowledge-status') as HTMLDivElement;
+const ratePerMinute = document.getElementById('rate-per-minute') as HTMLInputElement;
+const ratePerHour = document.getElementById('rate-per-hour') as HTMLInputElement;
+const rateTokensPerDay = document.getElementById('rate-tokens-per-day') as HTMLInputElement;
+const budgetOpenai = document.getElementById('budget-openai') as HTMLInputElement;
+const budgetCustom = document.getElementById('budget-custom') as HTMLInputElement;
+const budgetFallback = document.getElementById('budget-fallback') as HTMLInputElement;
+const historyMaxConversations = document.getElementById('history-max-conversations') as HTMLInputElement;
+const historyMaxAge = document.getElementById('history-max-age') as HTMLInputElement;
+const historyMaxDisk = document.getElementById('history-max-disk') as HTMLInputElement;
+const purgeHistoryBtn = document.getElementById('purge-history-btn') as HTMLButtonElement;
+const pomodoroWork = document.getElementById('pomodoro-work') as HTMLInputElement;
+const pomodoroShortBreak = document.getElementById('pomodoro-short-break') as HTMLInputElement;
+const pomodoroLongBreak = document.getElementById('pomodoro-long-break') as HTMLInputElement;
+const pomodoroLongEvery = document.getElementById('pomodoro-long-every') as HTMLInputElement;
 const ttsEnabledCheckbox = document.getElementById('tts-enabled') as HTMLInputElement;
 const downloadTtsBtn = document.getElementById('download-tts-btn') as HTMLButtonElement;
 const ttsDownloadStatus = document.getElementById('tts-download-status') as HTMLDivElement;
 const testTtsBtn = document.getElementById('test-tts-btn') as HTMLButtonElement;
+const installVoiceBtn = document.getElementById('install-voice-btn') as HTMLButtonElement;
+const pronunciationWord = document.getElementById('pronunciation-word') as HTMLInputElement;
+const pronunciationSay = document.getElementById('pronunciation-say') as HTMLInputElement;
+const addPronunciationBtn = document.getElementById('add-pronunciation-btn') as HTMLButtonElement;
+const templateName = document.getElementById('template-name') as HTMLInputElement;
+const templateText = document.getElementById('template-text') as HTMLTextAreaElement;
+const saveTemplateBtn = document.getElementById('save-template-btn') as HTMLButtonElement;
+const ttsCachedPhrases = document.getElementById('tts-cached-phrases') as HTMLTextAreaElement;
+const ttsMaxSentences = document.getElementById('tts-max-sentences') as HTMLInputElement;
+const ttsQueueSize = document.getElementById('tts-queue-size') as HTMLInputElement;
+const ttsQueuePolicy = document.getElementById('tts-queue-policy') as HTMLSelectElement;
+const soundEffectsCheckbox = document.getElementById('sound-effects') as HTMLInputElement;
+const soundVolume = document.getElementById('sound-volume') as HTMLInputElement;
 const saveBtn = document.getElementById('save-btn') as HTMLButtonElement;
 const cancelBtn = document.getElementById('cancel-btn') as HTMLButtonElement;
 const statusEl = document.getElementById('status') as HTMLDivElement;
+const crashReportsCheckbox = document.getElementById('crash-reports') as HTMLInputElement;
+const copyDiagnosticsBtn = document.getElementById('copy-diagnostics-btn') as HTMLButtonElement;
+const copyLogsBtn = document.getElementById('copy-logs-btn') as HTMLButtonElement;
+const openLogsBtn = document.getElementById('open-logs-btn') as HTMLButtonElement;
+const requestLogCheckbox = document.getElementById('request-log') as HTMLInputElement;
+const openRequestLogBtn = document.getElementById('open-request-log-btn') as HTMLButtonElement;
+const updateChannelSelect = document.getElementById('update-channel') as HTMLSelectElement;
+const checkForUpdatesCheckbox = document.getElementById('check-for-updates') as HTMLInputElement;
+const checkUpdatesBtn = document.getElementById('check-updates-btn') as HTMLButtonElement;
+const updateStatus = document.getElementById('update-status') as HTMLDivElement;
 
 // Sections
 const openaiSection = document.getElementById('openai-section') as HTMLDivElement;
 const localApiSection = document.getElementById('localapi-section') as HTMLDivElement;
+const lmstudioModels = document.getElementById('lmstudio-models') as HTMLDivElement;
+const lmstudioStatus = document.getElementById('lmstudio-status') as HTMLDivElement;
+const lmstudioModelList = document.getElementById('lmstudio-model-list') as HTMLDivElement;
+const lmstudioRefreshBtn = document.getElementById('lmstudio-refresh-btn') as HTMLButtonElement;
+const lmstudioLoadField = document.getElementById('lmstudio-load-field') as HTMLDivElement;
+const lmstudioLoadOnChat = document.getElementById('lmstudio-load-on-chat') as HTMLInputElement;
+const ollamaModels = document.getElementById('ollama-models') as HTMLDivElement;
+const ollamaModelList = document.getElementById('ollama-model-list') as HTMLDivElement;
+const ollamaPullName = document.getElementById('ollama-pull-name') as HTMLInputElement;
+const ollamaPullBtn = document.getElementById('ollama-pull-btn') as HTMLButtonElement;
+const ollamaPullStatus = document.getElementById('ollama-pull-status') as HTMLDivElement;
 const builtinSection = document.getElementById('builtin-section') as HTMLDivElement;
 const ttsOptions = document.getElementById('tts-options') as HTMLDivElement;
+const ttsEngineSelect = document.getElementById('tts-engine') as HTMLSelectElement;
+const openaiTtsOptions = document.getElementById('openai-tts-options') as HTMLDivElement;
+const piperTtsOptions = document.getElementById('piper-tts-options') as HTMLDivElement;
+const openaiTtsModel = document.getElementById('openai-tts-model') as HTMLSelectElement;
+const openaiTtsVoice = document.getElementById('openai-tts-voice') as HTMLSelectElement;
+const elevenlabsTtsOptions = document.getElementById('elevenlabs-tts-options') as HTMLDivElement;
+const elevenlabsApiKey = document.getElementById('elevenlabs-api-key') as HTMLInputElement;
+const elevenlabsVoiceId = document.getElementById('elevenlabs-voice-id') as HTMLInputElement;
+const elevenlabsModel = document.getElementById('elevenlabs-model') as HTMLSelectElement;
 
 // Voice selection element (created dynamically)
 let voiceSelect: HTMLSelectElement;
 
+// Last loaded config, so fields without a control here survive a save
+let loadedConfig: any = {};
+
 async function checkVoiceStatus() {
   if (!voiceSelect) return;
   const voice = voiceSelect.value;
@@ -57,7 +170,7 @@ async function checkVoiceStatus() {
 }
 
 function initVoiceSelector() {
-  if (!ttsOptions) return;
+  if (!piperTtsOptions) return;
 
   const container = document.createElement('div');
   container.style.marginBottom = '10px';
@@ -92,14 +205,24 @@ function initVoiceSelector() {
   container.appendChild(label);
   container.appendChild(voiceSelect);
 
-  // Insert at the top of ttsOptions
-  if (ttsOptions.firstChild) {
-    ttsOptions.insertBefore(container, ttsOptions.firstChild);
+  // Insert at the top of the Piper options
+  if (piperTtsOptions.firstChild) {
+    piperTtsOptions.insertBefore(container, piperTtsOptions.firstChild);
   } else {
-    ttsOptions.appendChild(container);
+    piperTtsOptions.appendChild(container);
   }
 }
 
+// Piper's voices and downloads don't apply to cloud speech, and vice versa
+function updateTtsEngineSections() {
+  const engine = ttsEngineSelect.value;
+  openaiTtsOptions.style.display = engine === 'OpenAI' ? '' : 'none';
+  elevenlabsTtsOptions.style.display = engine === 'ElevenLabs' ? '' : 'none';
+  piperTtsOptions.style.display = engine === 'Piper' ? '' : 'none';
+}
+
+ttsEngineSelect.addEventListener('change', updateTtsEngineSections);
+
 // Default URLs for each provider
 const PROVIDER_DEFAULTS: Record<string, { url: string; model: string }> = {
   LMStudio: { url: 'http://localhost:1234/v1', model: 'default' },
@@ -113,6 +236,11 @@ function updateProviderSections() {
   openaiSection.style.display = provider === 'OpenAI' ? '' : 'none';
   localApiSection.style.display = ['LMStudio', 'Ollama', 'CustomAPI'].includes(provider) ? '' : 'none';
   builtinSection.style.display = provider === 'BuiltIn' ? '' : 'none';
+  lmstudioModels.style.display = provider === 'LMStudio' ? '' : 'none';
+  lmstudioLoadField.style.display = provider === 'LMStudio' ? '' : 'none';
+  if (provider === 'LMStudio') loadLmStudioModels();
+  ollamaModels.style.display = provider === 'Ollama' ? '' : 'none';
+  if (provider === 'Ollama') loadOllamaModels();
 
   if (PROVIDER_DEFAULTS[provider]) {
     if (!customApiUrl.value) customApiUrl.value = PROVIDER_DEFAULTS[provider].url;
@@ -120,6 +248,153 @@ function updateProviderSections() {
   }
 }
 
+interface LmStudioModel {
+  id: string;
+  kind: string;
+  loaded: boolean;
+  quantization: string | null;
+  max_context_length: number | null;
+}
+
+interface LmStudioStatus {
+  running: boolean;
+  url: string;
+  models: LmStudioModel[];
+  error: string | null;
+}
+
+// Whether LM Studio's server is up, and its models with buttons to chat with or load them
+async function loadLmStudioModels() {
+  lmstudioStatus.style.display = 'block';
+  lmstudioStatus.className = 'progress-status';
+  lmstudioStatus.textContent = 'Looking for LM Studio...';
+  lmstudioModelList.innerHTML = '';
+  let status: LmStudioStatus;
+  try {
+    status = await invoke('lmstudio_status') as LmStudioStatus;
+  } catch (error) {
+    lmstudioStatus.textContent = `${error}`;
+    lmstudioStatus.className = 'progress-status error';
+    return;
+  }
+  if (status.error) {
+    lmstudioStatus.textContent = status.error;
+    lmstudioStatus.className = 'progress-status error';
+    return;
+  }
+  const loaded = status.models.filter((m) => m.loaded).length;
+  lmstudioStatus.textContent = `Running at ${status.url}: ${status.models.length} models, ${loaded} loaded`;
+  lmstudioStatus.className = 'progress-status success';
+  for (const model of status.models.filter((m) => m.kind !== 'embeddings')) {
+    const row = document.createElement('div');
+    row.className = 'input-with-btn';
+    const text = document.createElement('span');
+    const details = [model.quantization, model.loaded ? 'loaded' : null].filter((d) => d).join(', ');
+    text.textContent = details ? `${model.id} (${details})` : model.id;
+    const use = document.createElement('button');
+    use.className = 'icon-btn';
+    use.textContent = 'Use';
+    use.addEventListener('click', () => { customModel.value = model.id; });
+    row.append(text, use);
+    if (!model.loaded) {
+      const load = document.createElement('button');
+      load.className = 'icon-btn';
+      load.textContent = 'Load';
+      load.addEventListener('click', async () => {
+        load.disabled = true;
+        load.textContent = 'Loading...';
+        try {
+          await invoke('lmstudio_load_model', { model: model.id });
+        } catch (error) {
+          showStatus(`${error}`, 'error');
+        }
+        await loadLmStudioModels();
+      });
+      row.appendChild(load);
+    }
+    lmstudioModelList.appendChild(row);
+  }
+}
+
+lmstudioRefreshBtn.addEventListener('click', loadLmStudioModels);
+
+interface OllamaModel {
+  name: string;
+  size_bytes: number;
+  modified_at: string;
+  parameter_size: string | null;
+  quantization: string | null;
+}
+
+// What Ollama has pulled, each with buttons to chat with it or delete it
+async function loadOllamaModels() {
+  let models: OllamaModel[];
+  try {
+    models = await invoke('ollama_list_models') as OllamaModel[];
+  } catch (error) {
+    ollamaModelList.textContent = `${error}`;
+    return;
+  }
+  ollamaModelList.innerHTML = '';
+  if (models.length === 0) {
+    ollamaModelList.textContent = 'No models yet. Pull one below.';
+  }
+  for (const model of models) {
+    const row = document.createElement('div');
+    row.className = 'input-with-btn';
+    const text = document.createElement('span');
+    const details = [model.parameter_size, model.quantization].filter((d) => d).join(', ');
+    const size = `${(model.size_bytes / 1_000_000_000).toFixed(1)} GB`;
+    text.textContent = `${model.name} (${details ? `${details}, ` : ''}${size})`;
+    const use = document.createElement('button');
+    use.className = 'icon-btn';
+    use.textContent = 'Use';
+    use.addEventListener('click', () => { customModel.value = model.name; });
+    const remove = document.createElement('button');
+    remove.className = 'icon-btn';
+    remove.textContent = 'Delete';
+    remove.addEventListener('click', async () => {
+      if (!await ask(`Delete ${model.name} from Ollama?`, { title: 'Delete model', kind: 'warning' })) return;
+      try {
+        await invoke('ollama_delete_model', { name: model.name });
+      } catch (error) {
+        showStatus(`${error}`, 'error');
+      }
+      await loadOllamaModels();
+    });
+    row.append(text, use, remove);
+    ollamaModelList.appendChild(row);
+  }
+}
+
+ollamaPullBtn.addEventListener('click', async () => {
+  const name = ollamaPullName.value.trim();
+  if (!name) return;
+  ollamaPullBtn.disabled = true;
+  ollamaPullStatus.textContent = `Pulling ${name}...`;
+  ollamaPullStatus.className = 'progress-status';
+  ollamaPullStatus.style.display = 'block';
+  try {
+    await invoke('ollama_pull_model', { name });
+    ollamaPullStatus.textContent = `${name} is ready`;
+    ollamaPullStatus.className = 'progress-status success';
+    ollamaPullName.value = '';
+    await loadOllamaModels();
+  } catch (error) {
+    ollamaPullStatus.textContent = `Error: ${error}`;
+    ollamaPullStatus.className = 'progress-status error';
+  } finally {
+    ollamaPullBtn.disabled = false;
+  }
+});
+
+listen('ollama-pull-progress', (event: any) => {
+  const { model, status, percent } = event.payload;
+  ollamaPullStatus.textContent = percent == null
+    ? `${model}: ${status}`
+    : `${model}: ${status} (${percent.toFixed(0)}%)`;
+});
+
 providerSelect.addEventListener('change', () => {
   customApiUrl.value = '';
   customApiKey.value = '';
@@ -163,6 +438,75 @@ browseModelBtn.addEventListener('click', async () => {
   }
 });
 
+addCalendarFileBtn.addEventListener('click', async () => {
+  try {
+    const selected = await open({
+      filters: [{ name: 'Calendars', extensions: ['ics'] }],
+      multiple: false,
+    });
+    if (selected) {
+      const sources = calendarSources.value.trim();
+      calendarSources.value = sources ? `${sources}\n${selected}` : (selected as string);
+    }
+  } catch (error) {
+    showStatus(`Browse failed: ${error}`, 'error');
+  }
+});
+
+addKnowledgeFolderBtn.addEventListener('click', async () => {
+  try {
+    const selected = await open({ directory: true, multiple: false });
+    if (selected) {
+      const folders = knowledgeFolders.value.trim();
+      knowledgeFolders.value = folders ? `${folders}\n${selected}` : (selected as string);
+    }
+  } catch (error) {
+    showStatus(`Browse failed: ${error}`, 'error');
+  }
+});
+
+addDocumentsFolderBtn.addEventListener('click', async () => {
+  try {
+    const selected = await open({ directory: true, multiple: false });
+    if (selected) {
+      const folders = documentsFolders.value.trim();
+      documentsFolders.value = folders ? `${folders}\n${selected}` : (selected as string);
+    }
+  } catch (error) {
+    showStatus(`Browse failed: ${error}`, 'error');
+  }
+});
+
+interface IndexStatus {
+  indexing: boolean;
+  files: number;
+  chunks: number;
+  error: string | null;
+}
+
+function showIndexStatus(status: IndexStatus) {
+  knowledgeStatus.style.display = 'block';
+  if (status.indexing) {
+    knowledgeStatus.textContent = 'Indexing...';
+  } else if (status.error) {
+    knowledgeStatus.textContent = `Indexing failed: ${status.error}`;
+  } else {
+    knowledgeStatus.textContent = `${status.files} files, ${status.chunks} passages indexed`;
+  }
+  reindexBtn.disabled = status.indexing;
+}
+
+listen('knowledge-status', (event: any) => showIndexStatus(event.payload));
+invoke('index_status').then((status) => showIndexStatus(status as IndexStatus)).catch(() => {});
+
+reindexBtn.addEventListener('click', async () => {
+  try {
+    await invoke('reindex');
+  } catch (error) {
+    showStatus(`${error}`, 'error');
+  }
+});
+
 // Download default LLM model
 downloadModelBtn.addEventListener('click', async () => {
   downloadModelBtn.disabled = true;
@@ -182,6 +526,58 @@ downloadModelBtn.addEventListener('click', async () => {
   }
 });
 
+// Copy a GGUF from disk into the data folder, for machines that can't download one
+installModelBtn.addEventListener('click', async () => {
+  try {
+    const selected = await open({
+      filters: [{ name: 'GGUF Models', extensions: ['gguf'] }],
+      multiple: false,
+    });
+    if (!selected) return;
+    installModelBtn.disabled = true;
+    modelDownloadStatus.textContent = 'Copying model...';
+    modelDownloadStatus.style.display = 'block';
+    builtinModelPath.value = await invoke('use_local_model', { path: selected }) as string;
+    modelDownloadStatus.textContent = 'Model installed!';
+    modelDownloadStatus.className = 'progress-status success';
+  } catch (error) {
+    modelDownloadStatus.textContent = `Error: ${error}`;
+    modelDownloadStatus.className = 'progress-status error';
+  } finally {
+    installModelBtn.disabled = false;
+  }
+});
+
+// Install a Piper voice from its .onnx and .onnx.json files
+installVoiceBtn.addEventListener('click', async () => {
+  try {
+    const selected = await open({
+      filters: [{ name: 'Piper Voice (.onnx and .onnx.json)', extensions: ['onnx', 'json'] }],
+      multiple: true,
+    });
+    if (!selected) return;
+    const files = selected as string[];
+    const onnxPath = files.find((f) => f.endsWith('.onnx'));
+    const jsonPath = files.find((f) => f.endsWith('.onnx.json'));
+    ttsDownloadStatus.style.display = 'block';
+    if (!onnxPath || !jsonPath) {
+      ttsDownloadStatus.textContent = 'Select both the voice\'s .onnx and .onnx.json files';
+      ttsDownloadStatus.className = 'progress-status error';
+      return;
+    }
+    installVoiceBtn.disabled = true;
+    ttsDownloadStatus.textContent = 'Installing voice...';
+    const voice = await invoke('install_voice_from_file', { onnxPath, jsonPath }) as string;
+    ttsDownloadStatus.textContent = `Voice ${voice} installed!`;
+    ttsDownloadStatus.className = 'progress-status success';
+  } catch (error) {
+    ttsDownloadStatus.textContent = `Error: ${error}`;
+    ttsDownloadStatus.className = 'progress-status error';
+  } finally {
+    installVoiceBtn.disabled = false;
+  }
+});
+
 // Download TTS voice model (Piper)
 downloadTtsBtn.addEventListener('click', async () => {
   downloadTtsBtn.disabled = true;
@@ -190,8 +586,10 @@ downloadTtsBtn.addEventListener('click', async () => {
 
   try {
     const voice = voiceSelect ? voiceSelect.value : 'en_US-amy-medium';
-    await invoke('download_tts_model', { voice });
-    ttsDownloadStatus.textContent = 'Voice model ready!';
+    const used = await invoke('download_tts_model', { voice }) as string;
+    ttsDownloadStatus.textContent = used === voice
+      ? 'Voice model ready!'
+      : `Voice model ready! ${voice} isn't available, so ${used} was downloaded.`;
     ttsDownloadStatus.className = 'progress-status success';
     downloadTtsBtn.textContent = 'Voice Downloaded';
     downloadTtsBtn.disabled = true;
@@ -205,11 +603,13 @@ downloadTtsBtn.addEventListener('click', async () => {
 // Test TTS voice
 testTtsBtn.addEventListener('click', async () => {
   testTtsBtn.disabled = true;
-  ttsDownloadStatus.textContent = 'Speaking...';
   ttsDownloadStatus.style.display = 'block';
 
   try {
     const voice = voiceSelect ? voiceSelect.value : 'en_US-amy-medium';
+    // Voices that aren't downloaded yet play their published sample instead
+    const downloaded = await invoke('is_voice_downloaded', { voice }) as boolean;
+    ttsDownloadStatus.textContent = downloaded ? 'Speaking...' : 'Playing a sample of this voice...';
     await invoke('preview_voice', { text: "Hi! I'm Clippy, your helpful assistant!", voice });
     ttsDownloadStatus.textContent = 'Voice test complete!';
     ttsDownloadStatus.className = 'progress-status success';
@@ -232,10 +632,282 @@ listen('model-download-progress', (event: any) => {
   }
 });
 
+// Pick up edits made to config.json outside this window
+listen('config-changed', () => {
+  loadConfig();
+});
+
+// Fill the character dropdown once
+async function loadPersonalities() {
+  if (personalitySelect.options.length > 0) return;
+  const personalities = await invoke('list_personalities') as { id: string; name: string; description: string }[];
+  for (const p of personalities) {
+    const option = document.createElement('option');
+    option.value = p.id;
+    option.textContent = p.name;
+    option.title = p.description;
+    personalitySelect.appendChild(option);
+  }
+}
+
+// Fill the character art dropdown; refilled after an import
+async function loadAgents() {
+  const agents = await invoke('list_agents') as string[];
+  const selected = agentSelect.value;
+  agentSelect.innerHTML = '';
+  const personalityOption = document.createElement('option');
+  personalityOption.value = '';
+  personalityOption.textContent = "Personality's own";
+  agentSelect.appendChild(personalityOption);
+  for (const name of agents) {
+    const option = document.createElement('option');
+    option.value = name;
+    option.textContent = name;
+    agentSelect.appendChild(option);
+  }
+  agentSelect.value = selected;
+}
+
+importAgentBtn.addEventListener('click', async () => {
+  try {
+    const selected = await open({ directory: true, multiple: false });
+    if (!selected) return;
+    importAgentBtn.disabled = true;
+    const name = await invoke('import_agent', { path: selected as string }) as string;
+    await loadAgents();
+    agentSelect.value = name;
+    showStatus(`Imported ${name}. Save to use it.`, 'success');
+  } catch (error) {
+    showStatus(`Import failed: ${error}`, 'error');
+  } finally {
+    importAgentBtn.disabled = false;
+  }
+});
+
+// Piper voice for each reply language, used to keep speech in step with replies
+const languageVoices: Record<string, string | null> = {};
+
+// Fill the reply-language dropdown once
+async function loadLanguages() {
+  if (responseLanguageSelect.options.length > 1) return;
+  const languages = await invoke('list_languages') as { code: string; name: string; voice: string | null }[];
+  for (const lang of languages) {
+    languageVoices[lang.code] = lang.voice;
+    const option = document.createElement('option');
+    option.value = lang.code;
+    option.textContent = lang.name;
+    responseLanguageSelect.appendChild(option);
+  }
+}
+
+// Picking a reply language switches to a voice that can speak it
+responseLanguageSelect.addEventListener('change', () => {
+  const voice = languageVoices[responseLanguageSelect.value];
+  if (!voice || !voiceSelect || voiceSelect.value.startsWith(`${responseLanguageSelect.value}_`)) return;
+  selectVoice(voice);
+  checkVoiceStatus();
+});
+
+// Select a voice, adding it to the dropdown if it isn't one of the presets
+function selectVoice(voice: string) {
+  if (!Array.from(voiceSelect.options).some((opt) => opt.value === voice)) {
+    const opt = document.createElement('option');
+    opt.value = voice;
+    opt.textContent = voice;
+    voiceSelect.appendChild(opt);
+  }
+  voiceSelect.value = voice;
+}
+
+interface ToolInfo {
+  name: string;
+  description: string;
+  requires_approval: boolean;
+  options: string[];
+}
+
+interface ToolInputs {
+  enabled: HTMLInputElement;
+  alwaysAllow?: HTMLInputElement;
+  allowedDirs?: HTMLTextAreaElement;
+  apiKey?: HTMLInputElement;
+}
+
+// Inputs for each tool's settings, by tool name
+const toolInputs: Record<string, ToolInputs> = {};
+// "Always allow" as loaded, to tell the user's unticking from approvals granted since
+const loadedAlwaysAllow: Record<string, boolean> = {};
+
+// Build a row of settings for every tool once
+async function loadTools() {
+  const list = document.getElementById('tools-list') as HTMLDivElement;
+  if (list.childElementCount > 0) return;
+  const tools = await invoke('list_tools') as ToolInfo[];
+  for (const tool of tools) {
+    const row = document.createElement('div');
+    row.className = 'field checkbox-field';
+    const enabled = document.createElement('input');
+    enabled.type = 'checkbox';
+    enabled.id = `tool-${tool.name}-enabled`;
+    const label = document.createElement('label');
+    label.htmlFor = enabled.id;
+    label.textContent = tool.requires_approval ? `${tool.name} (asks first)` : tool.name;
+    label.title = tool.description;
+    row.append(enabled, label);
+    list.appendChild(row);
+    toolInputs[tool.name] = { enabled };
+
+    if (tool.requires_approval) {
+      const field = document.createElement('div');
+      field.className = 'field checkbox-field';
+      const alwaysAllow = document.createElement('input');
+      alwaysAllow.type = 'checkbox';
+      alwaysAllow.id = `tool-${tool.name}-always-allow`;
+      const allowLabel = document.createElement('label');
+      allowLabel.htmlFor = alwaysAllow.id;
+      allowLabel.textContent = "Don't ask before each use";
+      field.append(alwaysAllow, allowLabel);
+      list.appendChild(field);
+      toolInputs[tool.name].alwaysAllow = alwaysAllow;
+    }
+
+    if (tool.options.includes('allowed_dirs')) {
+      const field = document.createElement('div');
+      field.className = 'field';
+      const dirsLabel = document.createElement('label');
+      dirsLabel.textContent = 'Only these folders, one per line (empty allows any)';
+      const allowedDirs = document.createElement('textarea');
+      allowedDirs.rows = 2;
+      allowedDirs.placeholder = '~/Documents';
+      field.append(dirsLabel, allowedDirs);
+      list.appendChild(field);
+      toolInputs[tool.name].allowedDirs = allowedDirs;
+    }
+    if (tool.options.includes('api_key')) {
+      const field = document.createElement('div');
+      field.className = 'field';
+      const keyLabel = document.createElement('label');
+      keyLabel.textContent = 'API key (optional)';
+      const apiKey = document.createElement('input');
+      apiKey.type = 'password';
+      field.append(keyLabel, apiKey);
+      list.appendChild(field);
+      toolInputs[tool.name].apiKey = apiKey;
+    }
+  }
+}
+
+// Respelled words take effect straight away rather than on Save
+async function loadPronunciations() {
+  const list = document.getElementById('pronunciation-list') as HTMLDivElement;
+  const words = await invoke('list_pronunciations') as Record<string, string>;
+  list.innerHTML = '';
+  for (const [word, say] of Object.entries(words)) {
+    const row = document.createElement('div');
+    row.className = 'input-with-btn';
+    const text = document.createElement('span');
+    text.textContent = `${word} → ${say}`;
+    const remove = document.createElement('button');
+    remove.className = 'icon-btn';
+    remove.textContent = 'Remove';
+    remove.addEventListener('click', async () => {
+      await invoke('remove_pronunciation', { word });
+      await loadPronunciations();
+    });
+    row.append(text, remove);
+    list.appendChild(row);
+  }
+}
+
+addPronunciationBtn.addEventListener('click', async () => {
+  try {
+    await invoke('add_pronunciation', {
+      word: pronunciationWord.value,
+      replacement: pronunciationSay.value,
+    });
+    pronunciationWord.value = '';
+    pronunciationSay.value = '';
+    await loadPronunciations();
+  } catch (error) {
+    showStatus(`${error}`, 'error');
+  }
+});
+
+// Prompt templates, like pronunciations, are saved straight away rather than on Save
+interface PromptTemplate {
+  id: string;
+  name: string;
+  text: string;
+}
+
+// The template being edited, or null when the form adds a new one
+let editingTemplateId: string | null = null;
+
+function resetTemplateForm() {
+  editingTemplateId = null;
+  templateName.value = '';
+  templateText.value = '';
+  saveTemplateBtn.textContent = 'Add Template';
+}
+
+async function loadTemplates() {
+  const list = document.getElementById('template-list') as HTMLDivElement;
+  const templates = await invoke('list_prompt_templates') as PromptTemplate[];
+  list.innerHTML = '';
+  for (const template of templates) {
+    const row = document.createElement('div');
+    row.className = 'input-with-btn';
+    const text = document.createElement('span');
+    text.textContent = template.name;
+    text.title = template.text;
+    const edit = document.createElement('button');
+    edit.className = 'icon-btn';
+    edit.textContent = 'Edit';
+    edit.addEventListener('click', () => {
+      editingTemplateId = template.id;
+      templateName.value = template.name;
+      templateText.value = template.text;
+      saveTemplateBtn.textContent = 'Save Template';
+      templateName.focus();
+    });
+    const remove = document.createElement('button');
+    remove.className = 'icon-btn';
+    remove.textContent = 'Remove';
+    remove.addEventListener('click', async () => {
+      await invoke('delete_prompt_template', { id: template.id });
+      if (editingTemplateId === template.id) resetTemplateForm();
+      await loadTemplates();
+    });
+    row.append(text, edit, remove);
+    list.appendChild(row);
+  }
+}
+
+saveTemplateBtn.addEventListener('click', async () => {
+  try {
+    await invoke('save_prompt_template', {
+      id: editingTemplateId,
+      name: templateName.value,
+      text: templateText.value,
+    });
+    resetTemplateForm();
+    await loadTemplates();
+  } catch (error) {
+    showStatus(`${error}`, 'error');
+  }
+});
+
 // Load current config
 async function loadConfig() {
   try {
+    await loadPersonalities();
+    await loadAgents();
+    await loadLanguages();
+    await loadTools();
+    await loadPronunciations();
+    await loadTemplates();
     const config = await invoke('get_config') as any;
+    loadedConfig = config;
 
     providerSelect.value = config.llm_provider || 'BuiltIn';
     apiKeyInput.value = config.openai_api_key || '';
@@ -243,15 +915,114 @@ async function loadConfig() {
     customApiUrl.value = config.custom_api_url || '';
     customApiKey.value = config.custom_api_key || '';
     customModel.value = config.custom_model || '';
+    lmstudioLoadOnChat.checked = config.lmstudio_load_on_chat || false;
     builtinModelPath.value = config.builtin_model_path || '';
+    userNameInput.value = config.user_name || '';
+    personalitySelect.value = config.personality || 'clippy';
+    agentSelect.value = config.agent || '';
+    responseLanguageSelect.value = config.response_language || '';
+    globalHotkeyInput.value = config.global_hotkey || '';
+    snapToEdgesCheckbox.checked = config.snap_to_edges ?? true;
+    clickThroughCheckbox.checked = config.click_through_idle || false;
+    alwaysOnTopCheckbox.checked = config.always_on_top ?? true;
+    startHiddenCheckbox.checked = config.start_hidden ?? false;
+    skipTaskbarCheckbox.checked = config.skip_taskbar ?? true;
+    updateChannelSelect.value = config.update_channel || 'stable';
+    checkForUpdatesCheckbox.checked = config.check_for_updates ?? true;
+    nativeNotificationsCheckbox.checked = config.native_notifications ?? true;
+    for (const slider of tuningSliders) {
+      slider.value = String(config.personality_tuning?.[slider.dataset.dial!] ?? 5);
+    }
+    customPromptInput.value = config.custom_system_prompt || '';
+    replacePromptCheckbox.checked = config.system_prompt_mode === 'Replace';
     tempSlider.value = String(config.temperature ?? 0.9);
     tempValue.textContent = tempSlider.value;
+    seedInput.value = config.seed == null ? '' : String(config.seed);
+    reasoningSelect.value = config.reasoning || 'collapsed';
+    contextTokensInput.value = config.context_tokens == null ? '' : String(config.context_tokens);
+    pinsInContextCheckbox.checked = config.pins_in_context ?? false;
+    const proactive = config.proactive || {};
+    proactiveEnabled.checked = proactive.enabled || false;
+    proactiveIdle.value = String(proactive.idle_minutes ?? 15);
+    proactiveMax.value = String(proactive.max_per_hour ?? 2);
+    proactiveQuietStart.value = proactive.quiet_hours_start != null ? String(proactive.quiet_hours_start) : '';
+    proactiveQuietEnd.value = proactive.quiet_hours_end != null ? String(proactive.quiet_hours_end) : '';
+    proactiveSpeak.checked = proactive.speak || false;
+    proactivePeek.checked = proactive.peek || false;
+    proactivePeekMinutes.value = String(proactive.peek_minutes ?? 30);
+    const briefing = config.briefing || {};
+    briefingEnabled.checked = briefing.enabled || false;
+    briefingTime.value = briefing.time || '08:00';
+    briefingSpeak.checked = briefing.speak ?? true;
+    for (const [name, inputs] of Object.entries(toolInputs)) {
+      const tool = (config.tools || {})[name] || {};
+      inputs.enabled.checked = tool.enabled ?? true;
+      if (inputs.alwaysAllow) inputs.alwaysAllow.checked = tool.always_allow || false;
+      loadedAlwaysAllow[name] = tool.always_allow || false;
+      if (inputs.allowedDirs) inputs.allowedDirs.value = (tool.allowed_dirs || []).join('\n');
+      if (inputs.apiKey) inputs.apiKey.value = tool.api_key || '';
+    }
+    const weather = config.weather || {};
+    weatherLocation.value = weather.location || '';
+    weatherUnits.value = weather.units || '';
+    const image = config.image || {};
+    imageBackend.value = image.backend || 'OpenAI';
+    imageModel.value = image.model || 'gpt-image-1';
+    imageSize.value = image.size || '1024x1024';
+    imageSdUrl.value = image.stable_diffusion_url || '';
+    const stt = config.stt || {};
+    sttBackend.value = stt.backend || 'Off';
+    sttModel.value = stt.model || 'whisper-1';
+    sttLanguage.value = stt.language || '';
+    const calendar = config.calendar || {};
+    calendarSources.value = (calendar.sources || []).join('\n');
+    calendarAlertMinutes.value = calendar.alert_minutes != null ? String(calendar.alert_minutes) : '';
+    const knowledge = config.knowledge || {};
+    knowledgeEnabled.checked = knowledge.enabled || false;
+    knowledgeFolders.value = (knowledge.folders || []).join('\n');
+    knowledgeMaxChunks.value = String(knowledge.max_chunks ?? 4);
+    const documents = config.documents || {};
+    documentsEnabled.checked = documents.enabled || false;
+    documentsFolders.value = (documents.folders || []).join('\n');
+    const rateLimits = config.rate_limits || {};
+    ratePerMinute.value = rateLimits.requests_per_minute != null ? String(rateLimits.requests_per_minute) : '';
+    ratePerHour.value = rateLimits.requests_per_hour != null ? String(rateLimits.requests_per_hour) : '';
+    rateTokensPerDay.value = rateLimits.tokens_per_day != null ? String(rateLimits.tokens_per_day) : '';
+    const budget = config.budget || {};
+    const monthly = budget.monthly_usd || {};
+    budgetOpenai.value = monthly.OpenAI != null ? String(monthly.OpenAI) : '';
+    budgetCustom.value = monthly.CustomAPI != null ? String(monthly.CustomAPI) : '';
+    budgetFallback.checked = budget.fallback_to_local ?? true;
+    const history = config.history || {};
+    historyMaxConversations.value = history.max_conversations != null ? String(history.max_conversations) : '';
+    historyMaxAge.value = history.max_age_days != null ? String(history.max_age_days) : '';
+    historyMaxDisk.value = history.max_disk_mb != null ? String(history.max_disk_mb) : '';
+    const pomodoro = config.pomodoro || {};
+    pomodoroWork.value = String(pomodoro.work_minutes ?? 25);
+    pomodoroShortBreak.value = String(pomodoro.short_break_minutes ?? 5);
+    pomodoroLongBreak.value = String(pomodoro.long_break_minutes ?? 15);
+    pomodoroLongEvery.value = String(pomodoro.long_break_every ?? 4);
     ttsEnabledCheckbox.checked = config.tts_enabled || false;
+    ttsEngineSelect.value = config.tts_engine || 'Piper';
+    openaiTtsModel.value = config.openai_tts_model || 'gpt-4o-mini-tts';
+    openaiTtsVoice.value = config.openai_tts_voice || 'alloy';
+    elevenlabsApiKey.value = config.elevenlabs_api_key || '';
+    elevenlabsVoiceId.value = config.elevenlabs_voice_id || '';
+    elevenlabsModel.value = config.elevenlabs_model || 'eleven_flash_v2_5';
+    ttsCachedPhrases.value = (config.tts_cached_phrases || []).join('\n');
+    ttsMaxSentences.value = config.tts_max_sentences == null ? '' : String(config.tts_max_sentences);
+    ttsQueueSize.value = String(config.tts_queue_size ?? 3);
+    ttsQueuePolicy.value = config.tts_queue_policy || 'block';
+    soundEffectsCheckbox.checked = config.sound_effects ?? true;
+    soundVolume.value = String(config.sound_volume ?? 60);
+    crashReportsCheckbox.checked = config.crash_reports || false;
+    requestLogCheckbox.checked = config.request_log || false;
     if (config.tts_voice && voiceSelect) {
-      voiceSelect.value = config.tts_voice;
+      selectVoice(config.tts_voice);
     }
 
     updateProviderSections();
+    updateTtsEngineSections();
     ttsOptions.style.display = ttsEnabledCheckbox.checked ? '' : 'none';
 
     await checkVoiceStatus();
@@ -263,34 +1034,145 @@ async function loadConfig() {
 // Save
 saveBtn.addEventListener('click', async () => {
   const provider = providerSelect.value;
-
-  if (provider === 'OpenAI' && !apiKeyInput.value.trim()) {
-    showStatus('Please enter an OpenAI API key', 'error');
-    return;
-  }
-  if (provider === 'CustomAPI' && !customApiUrl.value.trim()) {
-    showStatus('Please enter a custom API URL', 'error');
-    return;
-  }
-  if (provider === 'BuiltIn' && !builtinModelPath.value.trim()) {
-    showStatus('Please select or download a model file', 'error');
-    return;
-  }
+  const config: any = {
+    ...loadedConfig,
+    llm_provider: provider,
+    openai_api_key: apiKeyInput.value.trim() || null,
+    openai_model: modelSelect.value,
+    custom_api_url: customApiUrl.value.trim() || null,
+    custom_api_key: customApiKey.value.trim() || null,
+    custom_model: customModel.value.trim() || null,
+    lmstudio_load_on_chat: lmstudioLoadOnChat.checked,
+    builtin_model_path: builtinModelPath.value.trim() || null,
+    user_name: userNameInput.value.trim() || null,
+    personality: personalitySelect.value,
+    agent: agentSelect.value || null,
+    response_language: responseLanguageSelect.value || null,
+    global_hotkey: globalHotkeyInput.value.trim() || null,
+    snap_to_edges: snapToEdgesCheckbox.checked,
+    click_through_idle: clickThroughCheckbox.checked,
+    always_on_top: alwaysOnTopCheckbox.checked,
+    start_hidden: startHiddenCheckbox.checked,
+    skip_taskbar: skipTaskbarCheckbox.checked,
+    update_channel: updateChannelSelect.value,
+    check_for_updates: checkForUpdatesCheckbox.checked,
+    native_notifications: nativeNotificationsCheckbox.checked,
+    personality_tuning: Object.fromEntries(
+      tuningSliders.map((slider) => [slider.dataset.dial!, parseInt(slider.value, 10)])
+    ),
+    custom_system_prompt: customPromptInput.value.trim() || null,
+    system_prompt_mode: replacePromptCheckbox.checked ? 'Replace' : 'Augment',
+    temperature: parseFloat(tempSlider.value),
+    seed: seedInput.value === '' ? null : parseInt(seedInput.value, 10),
+    reasoning: reasoningSelect.value,
+    context_tokens: contextTokensInput.value === '' ? null : parseInt(contextTokensInput.value, 10),
+    pins_in_context: pinsInContextCheckbox.checked,
+    proactive: {
+      enabled: proactiveEnabled.checked,
+      idle_minutes: parseInt(proactiveIdle.value, 10) || 0,
+      max_per_hour: parseInt(proactiveMax.value, 10) || 0,
+      quiet_hours_start: proactiveQuietStart.value === '' ? null : parseInt(proactiveQuietStart.value, 10),
+      quiet_hours_end: proactiveQuietEnd.value === '' ? null : parseInt(proactiveQuietEnd.value, 10),
+      speak: proactiveSpeak.checked,
+      peek: proactivePeek.checked,
+      peek_minutes: parseInt(proactivePeekMinutes.value, 10) || 0,
+    },
+    briefing: {
+      enabled: briefingEnabled.checked,
+      time: briefingTime.value,
+      speak: briefingSpeak.checked,
+    },
+    tools: Object.fromEntries(
+      Object.entries(toolInputs).map(([name, inputs]) => [name, {
+        enabled: inputs.enabled.checked,
+        always_allow: inputs.alwaysAllow?.checked || false,
+        allowed_dirs: (inputs.allowedDirs?.value || '').split('\n').map((d) => d.trim()).filter((d) => d),
+        api_key: inputs.apiKey?.value.trim() || null,
+      }])
+    ),
+    weather: {
+      location: weatherLocation.value.trim() || null,
+      units: weatherUnits.value || null,
+    },
+    image: {
+      backend: imageBackend.value,
+      model: imageModel.value.trim() || 'gpt-image-1',
+      size: imageSize.value.trim(),
+      stable_diffusion_url: imageSdUrl.value.trim() || null,
+    },
+    stt: {
+      backend: sttBackend.value,
+      model: sttModel.value.trim() || 'whisper-1',
+      language: sttLanguage.value.trim().toLowerCase() || null,
+    },
+    calendar: {
+      sources: calendarSources.value.split('\n').map((s) => s.trim()).filter((s) => s),
+      alert_minutes: calendarAlertMinutes.value === '' ? null : parseInt(calendarAlertMinutes.value, 10),
+    },
+    knowledge: {
+      enabled: knowledgeEnabled.checked,
+      folders: knowledgeFolders.value.split('\n').map((f) => f.trim()).filter((f) => f),
+      max_chunks: parseInt(knowledgeMaxChunks.value, 10) || 0,
+    },
+    documents: {
+      enabled: documentsEnabled.checked,
+      folders: documentsFolders.value.split('\n').map((f) => f.trim()).filter((f) => f),
+    },
+    rate_limits: {
+      requests_per_minute: ratePerMinute.value === '' ? null : parseInt(ratePerMinute.value, 10),
+      requests_per_hour: ratePerHour.value === '' ? null : parseInt(ratePerHour.value, 10),
+      tokens_per_day: rateTokensPerDay.value === '' ? null : parseInt(rateTokensPerDay.value, 10),
+    },
+    budget: {
+      monthly_usd: Object.fromEntries(
+        [['OpenAI', budgetOpenai.value], ['CustomAPI', budgetCustom.value]]
+          .filter(([, value]) => value !== '')
+          .map(([provider, value]) => [provider, parseFloat(value)])
+      ),
+      fallback_to_local: budgetFallback.checked,
+    },
+    history: {
+      max_conversations: historyMaxConversations.value === '' ? null : parseInt(historyMaxConversations.value, 10),
+      max_age_days: historyMaxAge.value === '' ? null : parseInt(historyMaxAge.value, 10),
+      max_disk_mb: historyMaxDisk.value === '' ? null : parseInt(historyMaxDisk.value, 10),
+    },
+    pomodoro: {
+      work_minutes: parseInt(pomodoroWork.value, 10) || 0,
+      short_break_minutes: parseInt(pomodoroShortBreak.value, 10) || 0,
+      long_break_minutes: parseInt(pomodoroLongBreak.value, 10) || 0,
+      long_break_every: parseInt(pomodoroLongEvery.value, 10) || 0,
+    },
+    tts_enabled: ttsEnabledCheckbox.checked,
+    tts_voice: voiceSelect ? voiceSelect.value : null,
+    tts_engine: ttsEngineSelect.value,
+    openai_tts_model: openaiTtsModel.value,
+    openai_tts_voice: openaiTtsVoice.value,
+    elevenlabs_api_key: elevenlabsApiKey.value.trim() || null,
+    elevenlabs_voice_id: elevenlabsVoiceId.value.trim(),
+    elevenlabs_model: elevenlabsModel.value,
+    tts_cached_phrases: ttsCachedPhrases.value.split('\n').map((p) => p.trim()).filter((p) => p),
+    tts_max_sentences: ttsMaxSentences.value === '' ? null : parseInt(ttsMaxSentences.value, 10),
+    tts_queue_size: parseInt(ttsQueueSize.value, 10) || 3,
+    tts_queue_policy: ttsQueuePolicy.value,
+    sound_effects: soundEffectsCheckbox.checked,
+    sound_volume: parseInt(soundVolume.value, 10),
+    crash_reports: crashReportsCheckbox.checked,
+    request_log: requestLogCheckbox.checked,
+  };
 
   try {
-    const config: any = {
-      llm_provider: provider,
-      openai_api_key: apiKeyInput.value.trim() || null,
-      openai_model: modelSelect.value,
-      custom_api_url: customApiUrl.value.trim() || null,
-      custom_api_key: customApiKey.value.trim() || null,
-      custom_model: customModel.value.trim() || null,
-      builtin_model_path: builtinModelPath.value.trim() || null,
-      temperature: parseFloat(tempSlider.value),
-      tts_enabled: ttsEnabledCheckbox.checked,
-      tts_voice: voiceSelect ? voiceSelect.value : null,
-    };
-    await invoke('save_config', { config });
+    const errors = await invoke('validate_config', { config }) as FieldError[];
+    showFieldErrors(errors);
+    if (errors.length > 0) {
+      showStatus(errors[0].message, 'error');
+      return;
+    }
+
+    // Only what the user unticked here is revoked; approvals granted meanwhile stay
+    const revokedTools = Object.entries(toolInputs)
+      .filter(([name, inputs]) => loadedAlwaysAllow[name] && inputs.alwaysAllow && !inputs.alwaysAllow.checked)
+      .map(([name]) => name);
+    await invoke('save_config', { config, revokedTools });
     showStatus('Settings saved! Clippy is ready to chat.', 'success');
 
     setTimeout(async () => {
@@ -305,6 +1187,114 @@ saveBtn.addEventListener('click', async () => {
   }
 });
 
+// Diagnostics and logs for bug reports
+copyDiagnosticsBtn.addEventListener('click', async () => {
+  try {
+    const diagnostics = await invoke('get_diagnostics');
+    await navigator.clipboard.writeText(JSON.stringify(diagnostics, null, 2));
+    showStatus('Diagnostics copied to clipboard', 'success');
+  } catch (error) {
+    showStatus(`Failed to collect diagnostics: ${error}`, 'error');
+  }
+});
+
+copyLogsBtn.addEventListener('click', async () => {
+  try {
+    const lines = await invoke('get_recent_logs', { lines: 500 }) as string[];
+    await navigator.clipboard.writeText(lines.join('\n'));
+    showStatus('Recent logs copied to clipboard', 'success');
+  } catch (error) {
+    showStatus(`Failed to copy logs: ${error}`, 'error');
+  }
+});
+
+openLogsBtn.addEventListener('click', async () => {
+  try {
+    await invoke('open_log_folder');
+  } catch (error) {
+    showStatus(`${error}`, 'error');
+  }
+});
+
+openRequestLogBtn.addEventListener('click', async () => {
+  try {
+    await invoke('open_request_log');
+  } catch (error) {
+    showStatus(`${error}`, 'error');
+  }
+});
+
+briefingNowBtn.addEventListener('click', async () => {
+  briefingNowBtn.disabled = true;
+  try {
+    await invoke('run_briefing');
+    showStatus('Briefing delivered', 'success');
+  } catch (error) {
+    showStatus(`Briefing failed: ${error}`, 'error');
+  } finally {
+    briefingNowBtn.disabled = false;
+  }
+});
+
+purgeHistoryBtn.addEventListener('click', async () => {
+  if (!confirm('Delete every saved conversation? This cannot be undone.')) return;
+  purgeHistoryBtn.disabled = true;
+  try {
+    const deleted = await invoke('purge_history', { olderThan: 0 }) as number;
+    showStatus(`Deleted ${deleted} conversation${deleted === 1 ? '' : 's'}`, 'success');
+  } catch (error) {
+    showStatus(`${error}`, 'error');
+  } finally {
+    purgeHistoryBtn.disabled = false;
+  }
+});
+
+// Updates: check, then install what was found, then restart to finish
+let updateStep: 'check' | 'install' | 'restart' = 'check';
+
+checkUpdatesBtn.addEventListener('click', async () => {
+  if (updateStep === 'restart') {
+    await invoke('restart_app');
+    return;
+  }
+  checkUpdatesBtn.disabled = true;
+  try {
+    if (updateStep === 'check') {
+      updateStatus.textContent = 'Checking...';
+      const update = await invoke('check_for_updates') as { version: string; notes: string | null } | null;
+      if (update) {
+        updateStep = 'install';
+        updateStatus.textContent = `Version ${update.version} is available`;
+        checkUpdatesBtn.textContent = `Install ${update.version}`;
+      } else {
+        updateStatus.textContent = 'Clippy is up to date';
+      }
+    } else {
+      updateStatus.textContent = 'Downloading...';
+      await invoke('install_update');
+    }
+  } catch (error) {
+    updateStatus.textContent = '';
+    showStatus(`${error}`, 'error');
+  } finally {
+    checkUpdatesBtn.disabled = false;
+  }
+});
+
+listen('update-progress', (event: any) => {
+  const { downloaded, total } = event.payload;
+  const mb = (bytes: number) => (bytes / 1_000_000).toFixed(1);
+  updateStatus.textContent = total
+    ? `Downloading... ${mb(downloaded)} / ${mb(total)} MB`
+    : `Downloading... ${mb(downloaded)} MB`;
+});
+
+listen('update-ready', (event: any) => {
+  updateStep = 'restart';
+  updateStatus.textContent = `Version ${event.payload.version} installed`;
+  checkUpdatesBtn.textContent = 'Restart to Finish';
+});
+
 // Cancel
 cancelBtn.addEventListener('click', async () => {
   try {
@@ -314,6 +1304,76 @@ cancelBtn.addEventListener('click', async () => {
   }
 });
 
+interface FieldError {
+  field: string;
+  message: string;
+}
+
+// Show each validation error under the input for its config field
+function showFieldErrors(errors: FieldError[]) {
+  document.querySelectorAll('.field-error').forEach((el) => el.remove());
+  const inputs: Record<string, HTMLElement | undefined> = {
+    openai_api_key: apiKeyInput,
+    openai_model: modelSelect,
+    custom_api_url: customApiUrl,
+    builtin_model_path: builtinModelPath,
+    personality: personalitySelect,
+    agent: agentSelect,
+    response_language: responseLanguageSelect,
+    global_hotkey: globalHotkeyInput,
+    temperature: tempSlider,
+    context_tokens: contextTokensInput,
+    'proactive.idle_minutes': proactiveIdle,
+    'proactive.quiet_hours_start': proactiveQuietStart,
+    'proactive.quiet_hours_end': proactiveQuietEnd,
+    'proactive.peek_minutes': proactivePeekMinutes,
+    'briefing.time': briefingTime,
+    'image.size': imageSize,
+    'image.stable_diffusion_url': imageSdUrl,
+    'stt.model': sttModel,
+    'stt.language': sttLanguage,
+    'calendar.sources': calendarSources,
+    'calendar.alert_minutes': calendarAlertMinutes,
+    'knowledge.folders': knowledgeFolders,
+    'knowledge.max_chunks': knowledgeMaxChunks,
+    'documents.folders': documentsFolders,
+    'rate_limits.requests_per_minute': ratePerMinute,
+    'rate_limits.requests_per_hour': ratePerHour,
+    'rate_limits.tokens_per_day': rateTokensPerDay,
+    'budget.monthly_usd.OpenAI': budgetOpenai,
+    'budget.monthly_usd.CustomAPI': budgetCustom,
+    'history.max_conversations': historyMaxConversations,
+    'history.max_age_days': historyMaxAge,
+    'history.max_disk_mb': historyMaxDisk,
+    'pomodoro.work_minutes': pomodoroWork,
+    'pomodoro.short_break_minutes': pomodoroShortBreak,
+    'pomodoro.long_break_minutes': pomodoroLongBreak,
+    'pomodoro.long_break_every': pomodoroLongEvery,
+    tts_voice: voiceSelect,
+    tts_engine: ttsEngineSelect,
+    openai_tts_model: openaiTtsModel,
+    openai_tts_voice: openaiTtsVoice,
+    elevenlabs_api_key: elevenlabsApiKey,
+    elevenlabs_voice_id: elevenlabsVoiceId,
+    tts_cached_phrases: ttsCachedPhrases,
+    tts_max_sentences: ttsMaxSentences,
+    tts_queue_size: ttsQueueSize,
+    sound_volume: soundVolume,
+  };
+  for (const slider of tuningSliders) {
+    inputs[`personality_tuning.${slider.dataset.dial}`] = slider;
+  }
+  for (const [name, tool] of Object.entries(toolInputs)) {
+    inputs[`tools.${name}.allowed_dirs`] = tool.allowedDirs;
+  }
+  for (const { field, message } of errors) {
+    const el = document.createElement('div');
+    el.className = 'field-error';
+    el.textContent = message;
+    inputs[field]?.closest('.field')?.appendChild(el);
+  }
+}
+
 function showStatus(text: string, type: 'success' | 'error') {
   statusEl.textContent = text;
   statusEl.className = `status ${type}`;